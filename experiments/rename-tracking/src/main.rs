@@ -0,0 +1,155 @@
+use std::{convert::Infallible, time::Instant};
+
+use anyhow::anyhow;
+use gix::{
+    hash::ObjectId,
+    object::tree::diff::{change::Event, Action, Renames},
+};
+
+const GITOXIDE_CACHED_OBJECT_DATA_PER_THREAD_IN_BYTES: usize = 50 * 1024 * 1024;
+/// The `diff.renameLimit` default of gitoxide, which libgit2 is configured to use as well.
+const RENAME_LIMIT: usize = 1000;
+/// The minimal similarity of renamed blobs in percent, the default of both git and libgit2.
+const RENAME_THRESHOLD: u16 = 50;
+
+/// Track renames in the diff between each commit and its first parent along the first-parent history of a revision,
+/// once with gitoxide and once with libgit2, and print the time each took along with the amount of renames found.
+///
+/// This crate isn't part of the workspace, so run it from its own directory with `cargo run --release`.
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let repo_dir = args
+        .next()
+        .ok_or_else(|| anyhow!("First argument is the repository to work in"))?;
+    let rev_spec = args.next().unwrap_or_else(|| "HEAD".into());
+    let repo = gix::open(&repo_dir)?;
+
+    let start = Instant::now();
+    let commit_id = repo.rev_parse_single(rev_spec.as_str())?;
+    let mut tree_pairs = Vec::new();
+    for commit_id in commit_id.ancestors().first_parent_only().all()? {
+        let commit = commit_id?.object()?.into_commit();
+        let parent_id = commit.parent_ids().next();
+        if let Some(parent_id) = parent_id {
+            let parent_tree_id = parent_id.object()?.into_commit().tree_id()?.detach();
+            tree_pairs.push((parent_tree_id, commit.tree_id()?.detach()));
+        }
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "gitoxide: collected {} tree pairs in {:?} ({:0.0} commits/s)",
+        tree_pairs.len(),
+        elapsed,
+        tree_pairs.len() as f32 / elapsed.as_secs_f32()
+    );
+
+    let repo = repo.into_sync();
+    for computation in [Computation::SingleThreaded, Computation::MultiThreaded] {
+        let start = Instant::now();
+        let renames = do_gitoxide_rename_tracking(&tree_pairs, || Ok(repo.to_thread_local()), computation)?;
+        report("gitoxide", computation, &tree_pairs, renames, start);
+
+        let start = Instant::now();
+        let renames = do_libgit2_rename_tracking(
+            &tree_pairs,
+            || git2::Repository::open(&repo_dir).map_err(Into::into),
+            computation,
+        )?;
+        report("libgit2", computation, &tree_pairs, renames, start);
+    }
+    Ok(())
+}
+
+fn report(name: &str, computation: Computation, tree_pairs: &[(ObjectId, ObjectId)], renames: usize, start: Instant) {
+    let elapsed = start.elapsed();
+    println!(
+        "{name} ({computation:?}): found {renames} renames in {} tree diffs in {elapsed:?} ({:0.0} diffs/s)",
+        tree_pairs.len(),
+        tree_pairs.len() as f32 / elapsed.as_secs_f32()
+    );
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Computation {
+    SingleThreaded,
+    MultiThreaded,
+}
+
+/// Call `count` on each chunk of `tree_pairs`, one chunk per thread if `computation` allows it, and return the sum of all counts.
+fn in_parallel_if_needed(
+    tree_pairs: &[(ObjectId, ObjectId)],
+    computation: Computation,
+    count: impl Fn(&[(ObjectId, ObjectId)]) -> anyhow::Result<usize> + Sync,
+) -> anyhow::Result<usize> {
+    match computation {
+        Computation::SingleThreaded => count(tree_pairs),
+        Computation::MultiThreaded => {
+            let threads = std::thread::available_parallelism()?.get();
+            let chunk_size = (tree_pairs.len() / threads).max(1);
+            std::thread::scope(|scope| {
+                let count = &count;
+                let handles: Vec<_> = tree_pairs
+                    .chunks(chunk_size)
+                    .map(|chunk| scope.spawn(move || count(chunk)))
+                    .collect();
+                handles.into_iter().map(|handle| handle.join().expect("no panic")).sum()
+            })
+        }
+    }
+}
+
+fn do_gitoxide_rename_tracking(
+    tree_pairs: &[(ObjectId, ObjectId)],
+    repo: impl Fn() -> anyhow::Result<gix::Repository> + Sync,
+    computation: Computation,
+) -> anyhow::Result<usize> {
+    in_parallel_if_needed(tree_pairs, computation, |tree_pairs| {
+        let mut repo = repo()?;
+        repo.object_cache_size_if_unset(GITOXIDE_CACHED_OBJECT_DATA_PER_THREAD_IN_BYTES);
+        let mut renames = 0;
+        for (old, new) in tree_pairs {
+            let old = repo.find_object(*old)?.into_tree();
+            let new = repo.find_object(*new)?.into_tree();
+            old.changes()?
+                .track_renames(Some(Renames {
+                    limit: RENAME_LIMIT,
+                    percentage: Some(RENAME_THRESHOLD as f32 / 100.0),
+                    ..Default::default()
+                }))
+                .for_each_to_obtain_tree(&new, |change| {
+                    if let Event::Rename { .. } = change.event {
+                        renames += 1;
+                    }
+                    Ok::<_, Infallible>(Action::Continue)
+                })?;
+        }
+        Ok(renames)
+    })
+}
+
+fn do_libgit2_rename_tracking(
+    tree_pairs: &[(ObjectId, ObjectId)],
+    repo: impl Fn() -> anyhow::Result<git2::Repository> + Sync,
+    computation: Computation,
+) -> anyhow::Result<usize> {
+    in_parallel_if_needed(tree_pairs, computation, |tree_pairs| {
+        let repo = repo()?;
+        let mut renames = 0;
+        for (old, new) in tree_pairs {
+            let old = repo.find_tree(git2::Oid::from_bytes(old.as_bytes())?)?;
+            let new = repo.find_tree(git2::Oid::from_bytes(new.as_bytes())?)?;
+            let mut diff = repo.diff_tree_to_tree(Some(&old), Some(&new), None)?;
+            diff.find_similar(Some(
+                git2::DiffFindOptions::new()
+                    .renames(true)
+                    .rename_limit(RENAME_LIMIT)
+                    .rename_threshold(RENAME_THRESHOLD),
+            ))?;
+            renames += diff
+                .deltas()
+                .filter(|delta| delta.status() == git2::Delta::Renamed)
+                .count();
+        }
+        Ok(renames)
+    })
+}
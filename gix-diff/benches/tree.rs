@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gix_hash::ObjectId;
+use gix_object::{tree, TreeRefIter, WriteTo};
+
+/// A tree with 100 directories of 100 files each, along with all trees it contains, keyed by a made-up id.
+struct Trees {
+    root: Vec<u8>,
+    subtrees: HashMap<ObjectId, Vec<u8>>,
+}
+
+fn id(n: u32) -> ObjectId {
+    let mut bytes = [0; 20];
+    bytes[..4].copy_from_slice(&n.to_be_bytes());
+    ObjectId::from(bytes)
+}
+
+fn encode(entries: Vec<tree::Entry>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    gix_object::Tree { entries }
+        .write_to(&mut buf)
+        .expect("in-memory write");
+    buf
+}
+
+fn trees() -> Trees {
+    let mut subtrees = HashMap::new();
+    let mut root = Vec::new();
+    for dir in 0..100u32 {
+        let files = (0..100u32)
+            .map(|file| tree::Entry {
+                mode: tree::EntryMode::Blob,
+                filename: format!("file{file:03}").into(),
+                oid: id(dir * 1000 + file + 1),
+            })
+            .collect();
+        let dir_id = id(1_000_000 + dir);
+        subtrees.insert(dir_id, encode(files));
+        root.push(tree::Entry {
+            mode: tree::EntryMode::Tree,
+            filename: format!("dir{dir:03}").into(),
+            oid: dir_id,
+        });
+    }
+    Trees {
+        root: encode(root),
+        subtrees,
+    }
+}
+
+fn diff(trees: &Trees, lhs: &[u8]) -> usize {
+    let mut recorder = gix_diff::tree::Recorder::default();
+    gix_diff::tree::Changes::from(TreeRefIter::from_bytes(lhs))
+        .needed_to_obtain(
+            TreeRefIter::from_bytes(&trees.root),
+            gix_diff::tree::State::default(),
+            |oid, buf| -> Result<_, std::convert::Infallible> {
+                buf.clear();
+                buf.extend_from_slice(&trees.subtrees[&oid.to_owned()]);
+                Ok(TreeRefIter::from_bytes(buf))
+            },
+            &mut recorder,
+        )
+        .expect("all trees are present");
+    recorder.records.len()
+}
+
+fn against_empty_tree(c: &mut Criterion) {
+    let trees = trees();
+    c.bench_function("tree diff against empty tree", |b| {
+        b.iter(|| diff(&trees, black_box(&[])))
+    });
+}
+
+criterion_group!(benches, against_empty_tree);
+criterion_main!(benches);
@@ -1,3 +0,0 @@
-//! For using text diffs, please have a look at the [`imara-diff` documentation](https://docs.rs/imara-diff),
-//! maintained by [Pascal Kuthe](https://github.com/pascalkuthe).
-pub use imara_diff::*;
@@ -0,0 +1,131 @@
+use std::{hash::Hash, ops::Range};
+
+use gix_object::bstr::BString;
+
+use crate::blob::{intern::InternedInput, Sink};
+
+/// A single change within an edit script, replacing a range of lines in the base with new lines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Hunk {
+    /// The zero-based index of the first line in the base that is affected by this hunk.
+    ///
+    /// If no lines are removed, this is the line before which the `added` lines are inserted.
+    pub before_start: u32,
+    /// The lines removed from the base, including their line terminator.
+    pub removed: Vec<BString>,
+    /// The lines added in place of the `removed` ones, including their line terminator.
+    pub added: Vec<BString>,
+}
+
+/// A [`Sink`] which records all changes as [hunks][Hunk] that can be [applied][super::apply()] to the base later.
+///
+/// For the result to be accurate, the `input` should be created from lines that include their terminator, like
+/// [`byte_lines_with_terminator()`][crate::blob::sources::byte_lines_with_terminator()] does.
+pub struct EditScript<'a, T: Hash + Eq> {
+    input: &'a InternedInput<T>,
+    hunks: Vec<Hunk>,
+}
+
+impl<'a, T: Hash + Eq> EditScript<'a, T> {
+    /// Create a new instance to record changes between the tokens in `input`.
+    pub fn new(input: &'a InternedInput<T>) -> Self {
+        EditScript {
+            input,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T> Sink for EditScript<'a, T>
+where
+    T: Hash + Eq + AsRef<[u8]>,
+{
+    type Out = Vec<Hunk>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        let lines = |tokens: &[crate::blob::intern::Token]| {
+            tokens
+                .iter()
+                .map(|&token| self.input.interner[token].as_ref().into())
+                .collect()
+        };
+        self.hunks.push(Hunk {
+            before_start: before.start,
+            removed: lines(&self.input.before[before.start as usize..before.end as usize]),
+            added: lines(&self.input.after[after.start as usize..after.end as usize]),
+        });
+    }
+
+    fn finish(self) -> Self::Out {
+        self.hunks
+    }
+}
+
+/// The error returned by [`apply()`][super::apply()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Hunk starting at line {before_start} overlaps with the previous hunk or isn't sorted")]
+    Unordered { before_start: u32 },
+    #[error("Hunk starting at line {before_start} needs {needed} lines, but the base only has {actual} lines")]
+    OutOfBounds {
+        before_start: u32,
+        needed: usize,
+        actual: usize,
+    },
+    #[error("Line {line} of the base was expected to be {expected:?}, but was {actual:?}")]
+    Mismatch {
+        line: u32,
+        expected: BString,
+        actual: BString,
+    },
+}
+
+pub(super) mod function {
+    use gix_object::bstr::BString;
+
+    use super::{Error, Hunk};
+    use crate::blob::sources::byte_lines_with_terminator;
+
+    /// Apply `hunks` as produced by an [`EditScript`][super::EditScript] to `base` and return the new content.
+    ///
+    /// `hunks` must be sorted by their position in `base` and must not overlap. Lines removed by a hunk must match
+    /// the lines in `base` exactly, otherwise an error is returned.
+    pub fn apply<'a>(base: &[u8], hunks: impl IntoIterator<Item = &'a Hunk>) -> Result<BString, Error> {
+        let lines: Vec<_> = byte_lines_with_terminator(base).collect();
+        let mut out = BString::default();
+        let mut next_line = 0;
+        for hunk in hunks {
+            let start = hunk.before_start as usize;
+            if start < next_line {
+                return Err(Error::Unordered {
+                    before_start: hunk.before_start,
+                });
+            }
+            let end = start + hunk.removed.len();
+            if end > lines.len() {
+                return Err(Error::OutOfBounds {
+                    before_start: hunk.before_start,
+                    needed: end,
+                    actual: lines.len(),
+                });
+            }
+            for (line_index, (expected, actual)) in hunk.removed.iter().zip(&lines[start..end]).enumerate() {
+                if expected != actual {
+                    return Err(Error::Mismatch {
+                        line: (start + line_index) as u32,
+                        expected: expected.clone(),
+                        actual: (*actual).into(),
+                    });
+                }
+            }
+            lines[next_line..start]
+                .iter()
+                .for_each(|line| out.extend_from_slice(line));
+            hunk.added.iter().for_each(|line| out.extend_from_slice(line));
+            next_line = end;
+        }
+        lines[next_line..].iter().for_each(|line| out.extend_from_slice(line));
+        Ok(out)
+    }
+}
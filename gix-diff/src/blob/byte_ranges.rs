@@ -0,0 +1,79 @@
+//! A [`Sink`] to obtain hunks as byte ranges into the diffed data, instead of ranges of tokens.
+//!
+//! This is useful to map hunks back to the original data if tokens, like lines, vary in length.
+use std::ops::Range;
+
+use crate::blob::{sources::byte_lines_with_terminator, Sink};
+
+/// A change between two versions of data, as byte ranges into each of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The range of bytes in the old version that were replaced, which is empty for insertions.
+    pub before: Range<usize>,
+    /// The range of bytes in the new version that replace `before`, which is empty for removals.
+    pub after: Range<usize>,
+}
+
+/// A [`Sink`] which translates the token ranges of each change into [byte ranges][Hunk] and collects them.
+#[derive(Debug, Clone)]
+pub struct ByteRanges {
+    before: Vec<usize>,
+    after: Vec<usize>,
+    hunks: Vec<Hunk>,
+}
+
+/// Lifecycle
+impl ByteRanges {
+    /// Create a new instance for a diff of the lines of `before` and `after`, as tokenized by
+    /// [`byte_lines_with_terminator()`] or [`InternedInput::new()`][crate::blob::intern::InternedInput::new()]
+    /// for byte slices.
+    ///
+    /// The byte ranges of each line include its line terminator, even though line terminators aren't compared
+    /// by the latter tokenizer.
+    pub fn for_lines(before: &[u8], after: &[u8]) -> Self {
+        Self::from_token_offsets(line_offsets(before), line_offsets(after))
+    }
+
+    /// Create a new instance from the byte offsets at which each token starts in the old (`before`) and new (`after`)
+    /// version, each followed by the offset at which the last token ends, like returned by [`token_offsets()`].
+    pub fn from_token_offsets(before: Vec<usize>, after: Vec<usize>) -> Self {
+        ByteRanges {
+            before,
+            after,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+impl Sink for ByteRanges {
+    type Out = Vec<Hunk>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        self.hunks.push(Hunk {
+            before: self.before[before.start as usize]..self.before[before.end as usize],
+            after: self.after[after.start as usize]..self.after[after.end as usize],
+        });
+    }
+
+    fn finish(self) -> Self::Out {
+        self.hunks
+    }
+}
+
+/// Return the byte offset at which each of the `tokens` starts, followed by the offset at which the last one ends.
+///
+/// `tokens` must be contiguous, so each token must start where the previous one ended.
+pub fn token_offsets<'a>(tokens: impl IntoIterator<Item = &'a [u8]>) -> Vec<usize> {
+    let mut offset = 0;
+    std::iter::once(0)
+        .chain(tokens.into_iter().map(|token| {
+            offset += token.len();
+            offset
+        }))
+        .collect()
+}
+
+/// Return the [offsets of all lines][token_offsets()] in `data`, including their line terminator.
+pub fn line_offsets(data: &[u8]) -> Vec<usize> {
+    token_offsets(byte_lines_with_terminator(data))
+}
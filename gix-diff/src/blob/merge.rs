@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use gix_object::bstr::BString;
+
+/// A region of lines which were changed differently by both sides of a [three-way merge][super::merge()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConflictRegion {
+    /// The zero-based range of lines in the base that both sides changed.
+    pub base_range: Range<u32>,
+    /// The lines of the base within `base_range`, including their line terminator.
+    pub base: Vec<BString>,
+    /// The zero-based range of lines in our version that replaced the lines in `base_range`.
+    pub ours_range: Range<u32>,
+    /// The lines of our version within `ours_range`, including their line terminator.
+    pub ours: Vec<BString>,
+    /// The zero-based range of lines in their version that replaced the lines in `base_range`.
+    pub theirs_range: Range<u32>,
+    /// The lines of their version within `theirs_range`, including their line terminator.
+    pub theirs: Vec<BString>,
+}
+
+/// The outcome of a [three-way merge][super::merge()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MergeResult {
+    /// All changes could be merged without conflict, resulting in the contained content.
+    Clean(BString),
+    /// At least one region was changed differently by both sides.
+    Conflicted {
+        /// The merged content, with each conflicting region enclosed in conflict markers like `git merge` would write them.
+        merged_with_markers: BString,
+        /// The conflicting regions in the order they appear in the base.
+        regions: Vec<ConflictRegion>,
+    },
+}
+
+pub(super) mod function {
+    use std::ops::Range;
+
+    use gix_object::bstr::BString;
+
+    use super::{ConflictRegion, MergeResult};
+    use crate::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Side {
+        Ours,
+        Theirs,
+    }
+
+    struct Hunk {
+        side: Side,
+        before: Range<u32>,
+        after: Range<u32>,
+    }
+
+    /// Merge the changes from `base` to `ours` and from `base` to `theirs` line by line, using `algorithm` to diff them.
+    ///
+    /// Lines changed by only one side, or changed in exactly the same way by both, are merged cleanly. Changes of both sides
+    /// which overlap or touch each other are conflicts, which are written like `git merge` would do with the conflict
+    /// markers `<<<<<<< ours`, `=======` and `>>>>>>> theirs`, and are returned as [structured regions][ConflictRegion]
+    /// as well.
+    pub fn merge(algorithm: Algorithm, base: &[u8], ours: &[u8], theirs: &[u8]) -> MergeResult {
+        let base_lines: Vec<_> = byte_lines_with_terminator(base).collect();
+        let ours_lines: Vec<_> = byte_lines_with_terminator(ours).collect();
+        let theirs_lines: Vec<_> = byte_lines_with_terminator(theirs).collect();
+
+        let mut hunks = Vec::new();
+        for (side, other) in [(Side::Ours, ours), (Side::Theirs, theirs)] {
+            let input = InternedInput::new(byte_lines_with_terminator(base), byte_lines_with_terminator(other));
+            crate::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+                hunks.push(Hunk { side, before, after })
+            });
+        }
+        hunks.sort_by_key(|hunk| (hunk.before.start, hunk.before.end));
+
+        let mut out = BString::default();
+        let mut regions = Vec::new();
+        let mut next_base_line = 0;
+        // The difference between line numbers of each side and the base up to `next_base_line`.
+        let mut offsets = [0i64; 2];
+        let mut hunks = hunks.into_iter().peekable();
+        while let Some(first) = hunks.next() {
+            let mut chunk = vec![first];
+            let mut base_end = chunk[0].before.end;
+            while let Some(hunk) = hunks.next_if(|hunk| hunk.before.start <= base_end) {
+                base_end = base_end.max(hunk.before.end);
+                chunk.push(hunk);
+            }
+            let base_range = chunk[0].before.start..base_end;
+            extend(
+                &mut out,
+                &base_lines[next_base_line as usize..base_range.start as usize],
+            );
+            next_base_line = base_range.end;
+
+            let mut side_range = |side: Side| -> Option<Range<u32>> {
+                let mut hunks = chunk.iter().filter(|hunk| hunk.side == side).peekable();
+                hunks.peek()?;
+                let offset = &mut offsets[side as usize];
+                let start = (base_range.start as i64 + *offset) as u32;
+                let len = hunks.fold(base_range.end - base_range.start, |len, hunk| {
+                    len - (hunk.before.end - hunk.before.start) + (hunk.after.end - hunk.after.start)
+                });
+                *offset += len as i64 - (base_range.end - base_range.start) as i64;
+                Some(start..start + len)
+            };
+            let ours_range = side_range(Side::Ours);
+            let theirs_range = side_range(Side::Theirs);
+            match (ours_range, theirs_range) {
+                (Some(range), None) => extend(&mut out, &ours_lines[to_usize(range)]),
+                (None, Some(range)) => extend(&mut out, &theirs_lines[to_usize(range)]),
+                (Some(ours_range), Some(theirs_range))
+                    if ours_lines[to_usize(ours_range.clone())] == theirs_lines[to_usize(theirs_range.clone())] =>
+                {
+                    extend(&mut out, &ours_lines[to_usize(ours_range)])
+                }
+                (Some(ours_range), Some(theirs_range)) => {
+                    let region = ConflictRegion {
+                        base: to_owned(&base_lines[to_usize(base_range.clone())]),
+                        base_range,
+                        ours: to_owned(&ours_lines[to_usize(ours_range.clone())]),
+                        ours_range,
+                        theirs: to_owned(&theirs_lines[to_usize(theirs_range.clone())]),
+                        theirs_range,
+                    };
+                    write_conflict(&mut out, &region);
+                    regions.push(region);
+                }
+                (None, None) => unreachable!("every chunk has at least one hunk"),
+            }
+        }
+        extend(&mut out, &base_lines[next_base_line as usize..]);
+
+        if regions.is_empty() {
+            MergeResult::Clean(out)
+        } else {
+            MergeResult::Conflicted {
+                merged_with_markers: out,
+                regions,
+            }
+        }
+    }
+
+    fn write_conflict(out: &mut BString, region: &ConflictRegion) {
+        out.extend_from_slice(b"<<<<<<< ours\n");
+        extend_terminated(out, &region.ours);
+        out.extend_from_slice(b"=======\n");
+        extend_terminated(out, &region.theirs);
+        out.extend_from_slice(b">>>>>>> theirs\n");
+    }
+
+    /// Write `lines` and assure the last one is terminated so a conflict marker can follow.
+    fn extend_terminated(out: &mut BString, lines: &[BString]) {
+        for line in lines {
+            out.extend_from_slice(line);
+        }
+        if !lines.is_empty() && !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+
+    fn extend(out: &mut BString, lines: &[&[u8]]) {
+        for line in lines {
+            out.extend_from_slice(line);
+        }
+    }
+
+    fn to_owned(lines: &[&[u8]]) -> Vec<BString> {
+        lines.iter().map(|line| (*line).into()).collect()
+    }
+
+    fn to_usize(range: Range<u32>) -> Range<usize> {
+        range.start as usize..range.end as usize
+    }
+}
@@ -0,0 +1,79 @@
+//! For using text diffs, please have a look at the [`imara-diff` documentation](https://docs.rs/imara-diff),
+//! maintained by [Pascal Kuthe](https://github.com/pascalkuthe).
+//!
+//! Instead of the [`imara_diff::Algorithm`], [`imara_diff::diff()`] and [`imara_diff::diff_with_tokens()`],
+//! use the [`Algorithm`], [`diff()`] and [`diff_with_tokens()`] defined here which add the
+//! [patience][Algorithm::Patience] algorithm.
+pub use imara_diff::{intern, sink, sources, Sink, UnifiedDiffBuilder};
+
+use crate::blob::intern::{InternedInput, Token};
+
+///
+pub mod tokens;
+
+///
+pub mod byte_ranges;
+
+///
+pub mod unified;
+
+///
+pub mod apply;
+pub use apply::function::apply;
+
+///
+pub mod merge;
+pub use merge::function::merge;
+
+mod patience;
+
+/// The algorithms available for computing the changes between two sequences of tokens.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    /// The histogram algorithm of `imara-diff`, a faster variant of the patience algorithm which produces similarly
+    /// readable diffs. See [`imara_diff::Algorithm::Histogram`].
+    #[default]
+    Histogram,
+    /// Myers algorithm with heuristics to bound its runtime, which is what git uses by default.
+    /// See [`imara_diff::Algorithm::Myers`].
+    Myers,
+    /// Myers algorithm without heuristics to guarantee a minimal diff. See [`imara_diff::Algorithm::MyersMinimal`].
+    MyersMinimal,
+    /// The patience algorithm which anchors the diff at tokens that occur exactly once on both sides, and uses Myers
+    /// algorithm in between if there are no such tokens.
+    ///
+    /// Just like in git, this keeps unique lines like function signatures aligned, which yields much more readable hunks
+    /// for inputs with many repeated lines, like braces or empty lines, than Myers algorithm.
+    Patience,
+}
+
+/// Compute the changes that transform `input.before` into `input.after` with `algorithm`, passing each of them
+/// to `sink` in order, and return the output of the sink.
+pub fn diff<S: Sink, T>(algorithm: Algorithm, input: &InternedInput<T>, sink: S) -> S::Out {
+    diff_with_tokens(
+        algorithm,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+        sink,
+    )
+}
+
+/// Compute the changes that transform `before` into `after` with `algorithm`, passing each of them to `sink` in order,
+/// and return the output of the sink. `num_tokens` is the amount of distinct tokens known to the interner.
+pub fn diff_with_tokens<S: Sink>(
+    algorithm: Algorithm,
+    before: &[Token],
+    after: &[Token],
+    num_tokens: u32,
+    sink: S,
+) -> S::Out {
+    let algorithm = match algorithm {
+        Algorithm::Histogram => imara_diff::Algorithm::Histogram,
+        Algorithm::Myers => imara_diff::Algorithm::Myers,
+        Algorithm::MyersMinimal => imara_diff::Algorithm::MyersMinimal,
+        Algorithm::Patience => return patience::diff(before, after, num_tokens, sink),
+    };
+    imara_diff::diff_with_tokens(algorithm, before, after, num_tokens, sink)
+}
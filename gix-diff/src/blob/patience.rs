@@ -0,0 +1,118 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::blob::{intern::Token, Sink};
+
+/// Compute the changes between `before` and `after` with the patience algorithm and pass them to `sink`.
+///
+/// This works like git's `xdl_do_patience_diff()`, but processes the ranges between anchors with a stack instead of
+/// recursion so deeply nested anchors can't exhaust the call stack.
+pub(super) fn diff<S: Sink>(before: &[Token], after: &[Token], num_tokens: u32, mut sink: S) -> S::Out {
+    // The ranges of `before` and `after` which are still to be diffed, with the next one to diff on top.
+    let mut ranges = vec![(0..before.len() as u32, 0..after.len() as u32)];
+    let mut occurrences = HashMap::new();
+    while let Some((mut b, mut a)) = ranges.pop() {
+        while !b.is_empty() && !a.is_empty() && before[b.start as usize] == after[a.start as usize] {
+            b.start += 1;
+            a.start += 1;
+        }
+        while !b.is_empty() && !a.is_empty() && before[b.end as usize - 1] == after[a.end as usize - 1] {
+            b.end -= 1;
+            a.end -= 1;
+        }
+        if b.is_empty() || a.is_empty() {
+            if !(b.is_empty() && a.is_empty()) {
+                sink.process_change(b, a);
+            }
+            continue;
+        }
+
+        let anchors = unique_common_subsequence(before, after, b.clone(), a.clone(), &mut occurrences);
+        if anchors.is_empty() {
+            imara_diff::diff_with_tokens(
+                imara_diff::Algorithm::Myers,
+                &before[b.start as usize..b.end as usize],
+                &after[a.start as usize..a.end as usize],
+                num_tokens,
+                &mut |before: Range<u32>, after: Range<u32>| {
+                    sink.process_change(
+                        before.start + b.start..before.end + b.start,
+                        after.start + a.start..after.end + a.start,
+                    )
+                },
+            );
+            continue;
+        }
+
+        let (mut b_end, mut a_end) = (b.end, a.end);
+        for (b_anchor, a_anchor) in anchors.into_iter().rev() {
+            ranges.push((b_anchor + 1..b_end, a_anchor + 1..a_end));
+            b_end = b_anchor;
+            a_end = a_anchor;
+        }
+        ranges.push((b.start..b_end, a.start..a_end));
+    }
+    sink.finish()
+}
+
+/// How often a token occurs in the range of each side, along with the position of its last occurrence.
+#[derive(Default, Clone, Copy)]
+struct Occurrences {
+    before: u32,
+    before_pos: u32,
+    after: u32,
+    after_pos: u32,
+}
+
+/// Return the positions of the longest sequence of tokens which occur exactly once in each range, in the same order.
+///
+/// `occurrences` is scratch space which is cleared before it's used.
+fn unique_common_subsequence(
+    before: &[Token],
+    after: &[Token],
+    b: Range<u32>,
+    a: Range<u32>,
+    occurrences: &mut HashMap<Token, Occurrences>,
+) -> Vec<(u32, u32)> {
+    occurrences.clear();
+    for pos in b.clone() {
+        let entry = occurrences.entry(before[pos as usize]).or_default();
+        entry.before += 1;
+        entry.before_pos = pos;
+    }
+    for pos in a {
+        // Tokens that don't occur in `before` can't be anchors, so they don't need to be tracked.
+        if let Some(entry) = occurrences.get_mut(&after[pos as usize]) {
+            entry.after += 1;
+            entry.after_pos = pos;
+        }
+    }
+    // Unique common tokens in the order of `before`, as position in `before` and `after`.
+    let unique: Vec<(u32, u32)> = b
+        .filter_map(|pos| {
+            let entry = occurrences[&before[pos as usize]];
+            (entry.before == 1 && entry.after == 1).then_some((pos, entry.after_pos))
+        })
+        .collect();
+
+    // Patience sorting: the top of each pile along with the index of the top of the previous pile at the time it was placed.
+    let mut pile_tops: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(unique.len());
+    for (idx, &(_, after_pos)) in unique.iter().enumerate() {
+        let pile = pile_tops.partition_point(|&top| unique[top].1 < after_pos);
+        predecessors.push(pile.checked_sub(1).map(|prev| pile_tops[prev]));
+        if pile == pile_tops.len() {
+            pile_tops.push(idx);
+        } else {
+            pile_tops[pile] = idx;
+        }
+    }
+
+    let mut sequence = Vec::with_capacity(pile_tops.len());
+    let mut next = pile_tops.last().copied();
+    while let Some(idx) = next {
+        sequence.push(unique[idx]);
+        next = predecessors[idx];
+    }
+    sequence.reverse();
+    sequence
+}
@@ -0,0 +1,67 @@
+//! Token sources for diffs which are more fine-grained than lines.
+//!
+//! Note that these produce one token per byte or character, which makes them expensive for large inputs.
+use gix_object::bstr::ByteSlice;
+
+use crate::blob::intern::TokenSource;
+
+/// Return a [`TokenSource`] that uses each byte in `data` as token.
+pub fn bytes(data: &[u8]) -> Bytes<'_> {
+    Bytes(data)
+}
+
+/// Return a [`TokenSource`] that uses each UTF-8 encoded character in `data` as token.
+///
+/// Bytes that are not valid UTF-8 are each treated as their own token.
+pub fn chars(data: &[u8]) -> Chars<'_> {
+    Chars(data)
+}
+
+/// A [`TokenSource`] producing one token per byte, created by [`bytes()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Bytes<'a>(&'a [u8]);
+
+impl<'a> TokenSource for Bytes<'a> {
+    type Token = u8;
+    type Tokenizer = std::iter::Copied<std::slice::Iter<'a, u8>>;
+
+    fn tokenize(&self) -> Self::Tokenizer {
+        self.0.iter().copied()
+    }
+
+    fn estimate_tokens(&self) -> u32 {
+        self.0.len() as u32
+    }
+}
+
+/// A [`TokenSource`] producing one token per UTF-8 character, created by [`chars()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Chars<'a>(&'a [u8]);
+
+impl<'a> TokenSource for Chars<'a> {
+    type Token = &'a [u8];
+    type Tokenizer = CharTokens<'a>;
+
+    fn tokenize(&self) -> Self::Tokenizer {
+        CharTokens(self.0)
+    }
+
+    fn estimate_tokens(&self) -> u32 {
+        self.0.len() as u32
+    }
+}
+
+/// The iterator over all characters of a [`Chars`] token source.
+pub struct CharTokens<'a>(&'a [u8]);
+
+impl<'a> Iterator for CharTokens<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end, _char) = self.0.char_indices().next()?;
+        debug_assert_eq!(start, 0, "each character starts where the previous one ended");
+        let (token, rest) = self.0.split_at(end);
+        self.0 = rest;
+        Some(token)
+    }
+}
@@ -0,0 +1,125 @@
+//! A [`Sink`] to render line diffs as hunks of a unified diff, the way `git diff` shows them.
+use std::ops::Range;
+
+use gix_object::bstr::BString;
+
+use crate::blob::{intern::InternedInput, Sink};
+
+/// The amount of context lines around each change that `git diff` uses by default.
+pub const DEFAULT_CONTEXT_LEN: u32 = 3;
+
+/// The maximum amount of bytes of a function line shown after a hunk header, like git does.
+const MAX_FUNCTION_LINE_LEN: usize = 80;
+
+/// A [`Sink`] which renders all hunks of a diff of lines into the unified diff format, as `git diff` would show them
+/// after the `---` and `+++` lines.
+///
+/// The input must be tokenized into lines which include their line terminator, like
+/// [`byte_lines_with_terminator()`][crate::blob::sources::byte_lines_with_terminator()] does. Changes which are at most
+/// twice the amount of context lines apart are shown in the same hunk.
+///
+/// Like in git, each hunk header is followed by the closest line before the hunk which looks like the start of a function,
+/// i.e. which starts with a letter, `_` or `$`, and lines without terminator are followed by `\ No newline at end of file`.
+pub struct UnifiedDiff<'a> {
+    input: &'a InternedInput<&'a [u8]>,
+    context_len: u32,
+    changes: Vec<(Range<u32>, Range<u32>)>,
+}
+
+/// Lifecycle
+impl<'a> UnifiedDiff<'a> {
+    /// Create a new instance to render the diff of `input` with `context_len` unchanged lines around each change.
+    pub fn new(input: &'a InternedInput<&'a [u8]>, context_len: u32) -> Self {
+        UnifiedDiff {
+            input,
+            context_len,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl Sink for UnifiedDiff<'_> {
+    type Out = BString;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        self.changes.push((before, after));
+    }
+
+    fn finish(self) -> Self::Out {
+        let mut out = BString::default();
+        let mut changes = self.changes.as_slice();
+        while let Some(first) = changes.first() {
+            let hunk_len = 1 + changes
+                .windows(2)
+                .take_while(|pair| pair[1].0.start - pair[0].0.end <= 2 * self.context_len)
+                .count();
+            let (hunk, rest) = changes.split_at(hunk_len);
+            changes = rest;
+            let last = hunk.last().expect("at least one change");
+
+            let before_start = first.0.start.saturating_sub(self.context_len);
+            let after_start = first.1.start - (first.0.start - before_start);
+            let before_end = (last.0.end + self.context_len).min(self.input.before.len() as u32);
+            let after_end = last.1.end + (before_end - last.0.end);
+
+            out.extend_from_slice(b"@@ -");
+            write_range(&mut out, before_start..before_end);
+            out.extend_from_slice(b" +");
+            write_range(&mut out, after_start..after_end);
+            out.extend_from_slice(b" @@");
+            if let Some(function_line) = self.function_line_before(before_start) {
+                out.push(b' ');
+                out.extend_from_slice(function_line);
+            }
+            out.push(b'\n');
+
+            let mut pos = before_start;
+            for (before, after) in hunk {
+                self.write_lines(&mut out, b' ', &self.input.before, pos..before.start);
+                self.write_lines(&mut out, b'-', &self.input.before, before.clone());
+                self.write_lines(&mut out, b'+', &self.input.after, after.clone());
+                pos = before.end;
+            }
+            self.write_lines(&mut out, b' ', &self.input.before, pos..before_end);
+        }
+        out
+    }
+}
+
+impl UnifiedDiff<'_> {
+    fn write_lines(&self, out: &mut BString, prefix: u8, tokens: &[crate::blob::intern::Token], range: Range<u32>) {
+        for token in &tokens[range.start as usize..range.end as usize] {
+            let line = self.input.interner[*token];
+            out.push(prefix);
+            out.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                out.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    /// Return the closest line in the old version before the line at `line_index` which looks like the start of a function,
+    /// truncated and without trailing whitespace.
+    fn function_line_before(&self, line_index: u32) -> Option<&[u8]> {
+        self.input.before[..line_index as usize].iter().rev().find_map(|token| {
+            let line = self.input.interner[*token];
+            let first = *line.first()?;
+            (first.is_ascii_alphabetic() || first == b'_' || first == b'$').then(|| {
+                let line = &line[..line.len().min(MAX_FUNCTION_LINE_LEN)];
+                let len = line.len() - line.iter().rev().take_while(|b| b.is_ascii_whitespace()).count();
+                &line[..len]
+            })
+        })
+    }
+}
+
+/// Write `lines` as `start,count` like git does, with `start` being one-based unless there are no lines,
+/// and the count being omitted if it's one.
+fn write_range(out: &mut BString, lines: Range<u32>) {
+    let count = lines.end - lines.start;
+    let start = if count == 0 { lines.start } else { lines.start + 1 };
+    out.extend_from_slice(start.to_string().as_bytes());
+    if count != 1 {
+        out.extend_from_slice(format!(",{count}").as_bytes());
+    }
+}
@@ -7,3 +7,7 @@ pub mod tree;
 
 ///
 pub mod blob;
+
+///
+pub mod no_index;
+pub use no_index::function::no_index;
@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+/// Options for use in [`no_index()`][crate::no_index()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The kind of hash to use for computing the ids of blobs and trees.
+    pub object_hash: gix_hash::Kind,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            object_hash: gix_hash::Kind::Sha1,
+        }
+    }
+}
+
+/// The error returned by [`no_index()`][crate::no_index()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("Cannot compare the file at '{}' to the directory at '{}'", file.display(), directory.display())]
+    FileAgainstDirectory { file: PathBuf, directory: PathBuf },
+    #[error(transparent)]
+    Diff(#[from] crate::tree::changes::Error),
+}
+
+pub(super) mod function {
+    use std::{collections::HashMap, path::Path};
+
+    use gix_hash::ObjectId;
+    use gix_object::{
+        bstr::{BString, ByteVec},
+        tree, TreeRefIter, WriteTo,
+    };
+
+    use super::{Error, Options};
+    use crate::tree::recorder::{Change, Recorder};
+
+    /// Diff the files or directories at `old` and `new` like `git diff --no-index` would, without them having to be
+    /// part of a repository, and return all changes in the same way as [`Recorder`] would record them for a tree diff.
+    ///
+    /// Files are hashed according to `options` while they are read, and only trees are held in memory as far as needed for
+    /// the tree diff. Empty directories are ignored like git would.
+    ///
+    /// Symbolic links given as `old` or `new` are resolved, so a link to a directory is compared like that directory,
+    /// while symbolic links within directories are compared by the path they point to.
+    ///
+    /// * If both paths are files, the change is returned with an empty path.
+    /// * If one of the paths doesn't exist, all of the other side is returned as addition or deletion.
+    ///
+    /// Comparing a file to a directory is an error.
+    pub fn no_index(old: impl AsRef<Path>, new: impl AsRef<Path>, options: Options) -> Result<Vec<Change>, Error> {
+        let (old, new) = (old.as_ref(), new.as_ref());
+        let mut trees = HashMap::new();
+        let old_entry = entry(old, Symlinks::Follow, options, &mut trees)?;
+        let new_entry = entry(new, Symlinks::Follow, options, &mut trees)?;
+
+        let (old_tree, new_tree) = match (old_entry, new_entry) {
+            (None, None) => return Ok(Vec::new()),
+            (Some((tree::EntryMode::Tree, _)), Some((tree::EntryMode::Tree, _)))
+            | (Some((tree::EntryMode::Tree, _)), None)
+            | (None, Some((tree::EntryMode::Tree, _))) => (old_entry, new_entry),
+            (Some((tree::EntryMode::Tree, _)), Some(_)) => {
+                return Err(Error::FileAgainstDirectory {
+                    file: new.into(),
+                    directory: old.into(),
+                })
+            }
+            (Some(_), Some((tree::EntryMode::Tree, _))) => {
+                return Err(Error::FileAgainstDirectory {
+                    file: old.into(),
+                    directory: new.into(),
+                })
+            }
+            (old, new) => return Ok(file_change(old, new).into_iter().collect()),
+        };
+
+        let mut old_buf = Vec::new();
+        let mut new_buf = Vec::new();
+        let old_tree = tree_iter(old_tree, &trees, &mut old_buf);
+        let new_tree = tree_iter(new_tree, &trees, &mut new_buf);
+        let mut recorder = Recorder::default();
+        crate::tree::Changes::from(old_tree).needed_to_obtain(
+            new_tree,
+            crate::tree::State::default(),
+            |oid, buf| -> Result<_, std::convert::Infallible> {
+                buf.clear();
+                buf.extend_from_slice(&trees[&oid.to_owned()]);
+                Ok(TreeRefIter::from_bytes(buf))
+            },
+            &mut recorder,
+        )?;
+        Ok(recorder.records)
+    }
+
+    fn file_change(
+        old: Option<(tree::EntryMode, ObjectId)>,
+        new: Option<(tree::EntryMode, ObjectId)>,
+    ) -> Option<Change> {
+        let path = BString::default();
+        Some(match (old, new) {
+            (Some((entry_mode, oid)), None) => Change::Deletion { entry_mode, oid, path },
+            (None, Some((entry_mode, oid))) => Change::Addition { entry_mode, oid, path },
+            (Some(old), Some(new)) if old != new => Change::Modification {
+                previous_entry_mode: old.0,
+                previous_oid: old.1,
+                entry_mode: new.0,
+                oid: new.1,
+                path,
+            },
+            _ => return None,
+        })
+    }
+
+    fn tree_iter<'a>(
+        tree: Option<(tree::EntryMode, ObjectId)>,
+        trees: &HashMap<ObjectId, Vec<u8>>,
+        buf: &'a mut Vec<u8>,
+    ) -> TreeRefIter<'a> {
+        if let Some((_, id)) = tree {
+            buf.extend_from_slice(&trees[&id]);
+        }
+        TreeRefIter::from_bytes(buf)
+    }
+
+    /// How to treat the symbolic link at a path.
+    #[derive(Clone, Copy)]
+    enum Symlinks {
+        /// Use what the link points to.
+        Follow,
+        /// Use the link itself, which is hashed as blob containing the path it points to.
+        Keep,
+    }
+
+    /// Return the mode and id of the file or directory at `path`, or `None` if it doesn't exist or is an empty directory.
+    /// Encoded trees are put into `trees` for later lookup.
+    fn entry(
+        path: &Path,
+        symlinks: Symlinks,
+        options: Options,
+        trees: &mut HashMap<ObjectId, Vec<u8>>,
+    ) -> Result<Option<(tree::EntryMode, ObjectId)>, Error> {
+        let io_err = |source| Error::Io {
+            source,
+            path: path.into(),
+        };
+        let meta = match symlinks {
+            Symlinks::Follow => std::fs::metadata(path),
+            Symlinks::Keep => std::fs::symlink_metadata(path),
+        };
+        let meta = match meta {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_err(err)),
+        };
+
+        if meta.file_type().is_symlink() {
+            let target = std::fs::read_link(path).map_err(io_err)?;
+            let target = Vec::from_path_lossy(&target);
+            return Ok(Some((
+                tree::EntryMode::Link,
+                hash(options, gix_object::Kind::Blob, &target),
+            )));
+        }
+        if meta.is_file() {
+            let mode = if is_executable(&meta) {
+                tree::EntryMode::BlobExecutable
+            } else {
+                tree::EntryMode::Blob
+            };
+            return Ok(Some((mode, hash_file(path, options).map_err(io_err)?)));
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in std::fs::read_dir(path).map_err(io_err)? {
+            let dir_entry = dir_entry.map_err(io_err)?;
+            if let Some((mode, oid)) = entry(&dir_entry.path(), Symlinks::Keep, options, trees)? {
+                entries.push(tree::Entry {
+                    mode,
+                    filename: Vec::from_os_str_lossy(&dir_entry.file_name()).into_owned().into(),
+                    oid,
+                });
+            }
+        }
+        if entries.is_empty() {
+            return Ok(None);
+        }
+        // Sort like git, which compares directories as if their name ended with a slash.
+        entries.sort_by(|a, b| sort_key(a).cmp(sort_key(b)));
+        let mut data = Vec::new();
+        gix_object::Tree { entries }
+            .write_to(&mut data)
+            .expect("writing to memory never fails");
+        let id = hash(options, gix_object::Kind::Tree, &data);
+        trees.insert(id, data);
+        Ok(Some((tree::EntryMode::Tree, id)))
+    }
+
+    fn sort_key(entry: &tree::Entry) -> impl Iterator<Item = u8> + '_ {
+        let slash = (entry.mode == tree::EntryMode::Tree).then_some(b'/');
+        entry.filename.iter().copied().chain(slash)
+    }
+
+    fn hash(options: Options, kind: gix_object::Kind, data: &[u8]) -> ObjectId {
+        let mut hasher = gix_features::hash::hasher(options.object_hash);
+        hasher.update(&gix_object::encode::loose_header(kind, data.len()));
+        hasher.update(data);
+        ObjectId::from(hasher.digest())
+    }
+
+    /// Hash the file at `path` as blob while reading it, without holding its content in memory.
+    fn hash_file(path: &Path, options: Options) -> std::io::Result<ObjectId> {
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut hasher = gix_features::hash::Write::new(std::io::sink(), options.object_hash);
+        hasher.hash.update(&gix_object::encode::loose_header(
+            gix_object::Kind::Blob,
+            len.try_into()
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::Other, "file too large"))?,
+        ));
+        let num_hashed = std::io::copy(&mut std::io::Read::take(file, len), &mut hasher)?;
+        if num_hashed != len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "file was truncated while reading it",
+            ));
+        }
+        Ok(ObjectId::from(hasher.hash.digest()))
+    }
+
+    #[cfg(unix)]
+    fn is_executable(meta: &std::fs::Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        meta.permissions().mode() & 0o111 != 0
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(_meta: &std::fs::Metadata) -> bool {
+        false
+    }
+}
@@ -1,6 +1,7 @@
 use std::{borrow::BorrowMut, collections::VecDeque};
 
 use gix_hash::{oid, ObjectId};
+use gix_object::bstr::BStr;
 
 use crate::{
     tree,
@@ -22,6 +23,14 @@ pub enum Error {
     EntriesDecode(#[from] gix_object::decode::Error),
 }
 
+/// The outcome of [tree::Changes::needed_to_obtain()].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of trees which had the same id on both sides, and which were skipped along with all trees they contain
+    /// without ever being looked up.
+    pub pruned_subtrees: usize,
+}
+
 impl<'a> tree::Changes<'a> {
     /// Calculate the changes that would need to be applied to `self` to get `other`.
     ///
@@ -37,10 +46,17 @@ impl<'a> tree::Changes<'a> {
     /// * To obtain progress, implement it within the `delegate`.
     /// * Tree entries are expected to be ordered using [`tree-entry-comparison`][git_cmp_c] (the same [in Rust][git_cmp_rs])
     /// * it does a breadth first iteration as buffer space only fits two trees, the current one on the one we compare with.
+    /// * trees with the same id on both sides are never looked up or descended into, which is what makes diffs of large trees
+    ///   with few changes fast. The amount of trees skipped this way is returned as [`Outcome::pruned_subtrees`].
     /// * does not do rename tracking but attempts to reduce allocations to zero (so performance is mostly determined
     ///   by the delegate implementation which should be as specific as possible. Rename tracking can be computed on top of the changes
     ///   received by the `delegate`.
     /// * cycle checking is not performed, but can be performed in the delegate which can return [`tree::visit::Action::Cancel`] to stop the traversal.
+    /// * the entries of each changed tree are compared between calls to [`enter_tree()`][tree::Visit::enter_tree()] and
+    ///   [`leave_tree()`][tree::Visit::leave_tree()], which allows the `delegate` to skip trees, along with all trees they contain.
+    ///   Wrap the `delegate` into a [`Filter`][tree::Filter] to skip trees by path, like when limiting a diff to a directory.
+    /// * returning [`Action::Skip`][tree::visit::Action::Skip] when visiting the change of a tree skips its entries as well,
+    ///   without even looking up the tree.
     /// * [std::mem::ManuallyDrop] is used because `Peekable` is needed. When using it as wrapper around our no-drop iterators, all of the sudden
     ///   borrowcheck complains as Drop is present (even though it's not)
     ///
@@ -52,7 +68,7 @@ impl<'a> tree::Changes<'a> {
         mut state: StateMut,
         mut find: FindFn,
         delegate: &mut R,
-    ) -> Result<(), Error>
+    ) -> Result<Outcome, Error>
     where
         FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::TreeRefIter<'b>, E>,
         E: std::error::Error + Send + Sync + 'static,
@@ -64,6 +80,8 @@ impl<'a> tree::Changes<'a> {
         let mut lhs_entries = peekable(self.0.take().unwrap_or_default());
         let mut rhs_entries = peekable(other);
         let mut pop_path = false;
+        let mut entered_tree = false;
+        let mut outcome = Outcome::default();
 
         loop {
             if pop_path {
@@ -73,44 +91,48 @@ impl<'a> tree::Changes<'a> {
 
             match (lhs_entries.next(), rhs_entries.next()) {
                 (None, None) => {
-                    match state.trees.pop_front() {
-                        Some((None, Some(rhs))) => {
-                            delegate.pop_front_tracked_path_and_set_current();
-                            rhs_entries = peekable(find(&rhs, &mut state.buf2).map_err(|err| Error::FindExisting {
-                                oid: rhs,
-                                source: err.into(),
-                            })?);
+                    if entered_tree {
+                        delegate.leave_tree();
+                    }
+                    loop {
+                        let (lhs, rhs) = match state.trees.pop_front() {
+                            Some(pair) => pair,
+                            None => return Ok(outcome),
+                        };
+                        delegate.pop_front_tracked_path_and_set_current();
+                        if !delegate.enter_tree(&tree_change(lhs, rhs)) {
+                            continue;
                         }
-                        Some((Some(lhs), Some(rhs))) => {
-                            delegate.pop_front_tracked_path_and_set_current();
+                        entered_tree = true;
+                        if let Some(lhs) = lhs {
                             lhs_entries = peekable(find(&lhs, &mut state.buf1).map_err(|err| Error::FindExisting {
                                 oid: lhs,
                                 source: err.into(),
                             })?);
+                        }
+                        if let Some(rhs) = rhs {
                             rhs_entries = peekable(find(&rhs, &mut state.buf2).map_err(|err| Error::FindExisting {
                                 oid: rhs,
                                 source: err.into(),
                             })?);
                         }
-                        Some((Some(lhs), None)) => {
-                            delegate.pop_front_tracked_path_and_set_current();
-                            lhs_entries = peekable(find(&lhs, &mut state.buf1).map_err(|err| Error::FindExisting {
-                                oid: lhs,
-                                source: err.into(),
-                            })?);
-                        }
-                        Some((None, None)) => unreachable!("BUG: it makes no sense to fill the stack with empties"),
-                        None => return Ok(()),
-                    };
+                        break;
+                    }
                     pop_path = false;
                 }
                 (Some(lhs), Some(rhs)) => {
                     use std::cmp::Ordering::*;
                     let (lhs, rhs) = (lhs?, rhs?);
                     match lhs.filename.cmp(rhs.filename) {
-                        Equal => handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, &mut state.trees, delegate)?,
-                        Less => catchup_lhs_with_rhs(&mut lhs_entries, lhs, rhs, &mut state.trees, delegate)?,
-                        Greater => catchup_rhs_with_lhs(&mut rhs_entries, lhs, rhs, &mut state.trees, delegate)?,
+                        Equal => {
+                            handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, &mut state.trees, &mut outcome, delegate)?
+                        }
+                        Less => {
+                            catchup_lhs_with_rhs(&mut lhs_entries, lhs, rhs, &mut state.trees, &mut outcome, delegate)?
+                        }
+                        Greater => {
+                            catchup_rhs_with_lhs(&mut rhs_entries, lhs, rhs, &mut state.trees, &mut outcome, delegate)?
+                        }
                     }
                 }
                 (Some(lhs), None) => {
@@ -126,25 +148,37 @@ impl<'a> tree::Changes<'a> {
     }
 }
 
+/// Return the change that caused the trees `lhs` and `rhs` to be scheduled for comparison.
+fn tree_change(lhs: Option<ObjectId>, rhs: Option<ObjectId>) -> Change {
+    use gix_object::tree::EntryMode::Tree;
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Change::Modification {
+            previous_entry_mode: Tree,
+            previous_oid: lhs,
+            entry_mode: Tree,
+            oid: rhs,
+        },
+        (Some(oid), None) => Change::Deletion { entry_mode: Tree, oid },
+        (None, Some(oid)) => Change::Addition { entry_mode: Tree, oid },
+        (None, None) => unreachable!("BUG: it makes no sense to fill the stack with empties"),
+    }
+}
+
 fn delete_entry_schedule_recursion<R: tree::Visit>(
     entry: gix_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
     delegate: &mut R,
 ) -> Result<(), Error> {
     delegate.push_path_component(entry.filename);
-    if delegate
-        .visit(Change::Deletion {
-            entry_mode: entry.mode,
-            oid: entry.oid.to_owned(),
-        })
-        .cancelled()
-    {
+    let action = delegate.visit(Change::Deletion {
+        entry_mode: entry.mode,
+        oid: entry.oid.to_owned(),
+    });
+    if action.cancelled() {
         return Err(Error::Cancelled);
     }
-    if entry.mode.is_tree() {
-        delegate.pop_path_component();
-        delegate.push_back_tracked_path_component(entry.filename);
-        queue.push_back((Some(entry.oid.to_owned()), None));
+    if entry.mode.is_tree() && !action.skipped() {
+        schedule_recursion(entry.filename, (Some(entry.oid.to_owned()), None), queue, delegate);
     }
     Ok(())
 }
@@ -155,19 +189,15 @@ fn add_entry_schedule_recursion<R: tree::Visit>(
     delegate: &mut R,
 ) -> Result<(), Error> {
     delegate.push_path_component(entry.filename);
-    if delegate
-        .visit(Change::Addition {
-            entry_mode: entry.mode,
-            oid: entry.oid.to_owned(),
-        })
-        .cancelled()
-    {
+    let action = delegate.visit(Change::Addition {
+        entry_mode: entry.mode,
+        oid: entry.oid.to_owned(),
+    });
+    if action.cancelled() {
         return Err(Error::Cancelled);
     }
-    if entry.mode.is_tree() {
-        delegate.pop_path_component();
-        delegate.push_back_tracked_path_component(entry.filename);
-        queue.push_back((None, Some(entry.oid.to_owned())))
+    if entry.mode.is_tree() && !action.skipped() {
+        schedule_recursion(entry.filename, (None, Some(entry.oid.to_owned())), queue, delegate);
     }
     Ok(())
 }
@@ -176,6 +206,7 @@ fn catchup_rhs_with_lhs<R: tree::Visit>(
     lhs: gix_object::tree::EntryRef<'_>,
     rhs: gix_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    outcome: &mut Outcome,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -186,7 +217,7 @@ fn catchup_rhs_with_lhs<R: tree::Visit>(
                 Equal => {
                     let rhs = rhs_entries.next().transpose()?.expect("the peeked item to be present");
                     delegate.pop_path_component();
-                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, delegate)?;
+                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, outcome, delegate)?;
                     break;
                 }
                 Greater => {
@@ -216,6 +247,7 @@ fn catchup_lhs_with_rhs<R: tree::Visit>(
     lhs: gix_object::tree::EntryRef<'_>,
     rhs: gix_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    outcome: &mut Outcome,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -226,7 +258,7 @@ fn catchup_lhs_with_rhs<R: tree::Visit>(
                 Equal => {
                     let lhs = lhs_entries.next().expect("the peeked item to be present")?;
                     delegate.pop_path_component();
-                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, delegate)?;
+                    handle_lhs_and_rhs_with_equal_filenames(lhs, rhs, queue, outcome, delegate)?;
                     break;
                 }
                 Less => {
@@ -255,28 +287,37 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
     lhs: gix_object::tree::EntryRef<'_>,
     rhs: gix_object::tree::EntryRef<'_>,
     queue: &mut VecDeque<TreeInfoPair>,
+    outcome: &mut Outcome,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use gix_object::tree::EntryMode::*;
     match (lhs.mode, rhs.mode) {
+        (Tree, Tree) if lhs.oid == rhs.oid => {
+            delegate.push_path_component(lhs.filename);
+            outcome.pruned_subtrees += 1;
+        }
         (Tree, Tree) => {
-            delegate.push_back_tracked_path_component(lhs.filename);
-            if lhs.oid != rhs.oid
-                && delegate
-                    .visit(Change::Modification {
-                        previous_entry_mode: lhs.mode,
-                        previous_oid: lhs.oid.to_owned(),
-                        entry_mode: rhs.mode,
-                        oid: rhs.oid.to_owned(),
-                    })
-                    .cancelled()
-            {
+            delegate.push_path_component(lhs.filename);
+            let action = delegate.visit(Change::Modification {
+                previous_entry_mode: lhs.mode,
+                previous_oid: lhs.oid.to_owned(),
+                entry_mode: rhs.mode,
+                oid: rhs.oid.to_owned(),
+            });
+            if action.cancelled() {
                 return Err(Error::Cancelled);
             }
-            queue.push_back((Some(lhs.oid.to_owned()), Some(rhs.oid.to_owned())));
+            if !action.skipped() {
+                schedule_recursion(
+                    lhs.filename,
+                    (Some(lhs.oid.to_owned()), Some(rhs.oid.to_owned())),
+                    queue,
+                    delegate,
+                );
+            }
         }
         (lhs_mode, Tree) if lhs_mode.is_no_tree() => {
-            delegate.push_back_tracked_path_component(lhs.filename);
+            delegate.push_path_component(lhs.filename);
             if delegate
                 .visit(Change::Deletion {
                     entry_mode: lhs.mode,
@@ -286,26 +327,24 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
             {
                 return Err(Error::Cancelled);
             };
-            if delegate
-                .visit(Change::Addition {
-                    entry_mode: rhs.mode,
-                    oid: rhs.oid.to_owned(),
-                })
-                .cancelled()
-            {
+            let action = delegate.visit(Change::Addition {
+                entry_mode: rhs.mode,
+                oid: rhs.oid.to_owned(),
+            });
+            if action.cancelled() {
                 return Err(Error::Cancelled);
             };
-            queue.push_back((None, Some(rhs.oid.to_owned())));
+            if !action.skipped() {
+                schedule_recursion(lhs.filename, (None, Some(rhs.oid.to_owned())), queue, delegate);
+            }
         }
         (Tree, rhs_mode) if rhs_mode.is_no_tree() => {
-            delegate.push_back_tracked_path_component(lhs.filename);
-            if delegate
-                .visit(Change::Deletion {
-                    entry_mode: lhs.mode,
-                    oid: lhs.oid.to_owned(),
-                })
-                .cancelled()
-            {
+            delegate.push_path_component(lhs.filename);
+            let action = delegate.visit(Change::Deletion {
+                entry_mode: lhs.mode,
+                oid: lhs.oid.to_owned(),
+            });
+            if action.cancelled() {
                 return Err(Error::Cancelled);
             }
             if delegate
@@ -317,12 +356,14 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
             {
                 return Err(Error::Cancelled);
             };
-            queue.push_back((Some(lhs.oid.to_owned()), None));
+            if !action.skipped() {
+                schedule_recursion(lhs.filename, (Some(lhs.oid.to_owned()), None), queue, delegate);
+            }
         }
         (lhs_non_tree, rhs_non_tree) => {
             delegate.push_path_component(lhs.filename);
             debug_assert!(lhs_non_tree.is_no_tree() && rhs_non_tree.is_no_tree());
-            if lhs.oid != rhs.oid
+            if (lhs.oid != rhs.oid || lhs.mode != rhs.mode)
                 && delegate
                     .visit(Change::Modification {
                         previous_entry_mode: lhs.mode,
@@ -339,6 +380,18 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
     Ok(())
 }
 
+/// Schedule the comparison of the `trees` at the current path, whose last component is `filename`.
+fn schedule_recursion<R: tree::Visit>(
+    filename: &BStr,
+    trees: TreeInfoPair,
+    queue: &mut VecDeque<TreeInfoPair>,
+    delegate: &mut R,
+) {
+    delegate.pop_path_component();
+    delegate.push_back_tracked_path_component(filename);
+    queue.push_back(trees);
+}
+
 type IteratorType<I> = std::mem::ManuallyDrop<std::iter::Peekable<I>>;
 
 fn peekable<I: Iterator>(iter: I) -> IteratorType<I> {
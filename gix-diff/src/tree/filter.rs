@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+use gix_object::bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::tree::{visit, Visit};
+
+/// A [`Visit`] implementation which passes everything on to a `delegate`, but only compares the entries of trees whose path
+/// is approved by a `should_descend(path)` function, so all other trees are skipped before they are even looked up.
+///
+/// This is useful to limit a diff to certain directories, for instance to those matched by a pathspec, while the
+/// `delegate` still sees the changes of the skipped trees themselves. It keeps track of paths on its own,
+/// independently of the `delegate`, so each path passed to `should_descend` is the full path of a tree relative
+/// to the root of the compared trees.
+pub struct Filter<'a, D, F> {
+    delegate: &'a mut D,
+    should_descend: F,
+    path_deque: VecDeque<BString>,
+    path: BString,
+}
+
+/// Lifecycle
+impl<'a, D, F> Filter<'a, D, F>
+where
+    D: Visit,
+    F: FnMut(&BStr) -> bool,
+{
+    /// Create a new instance to pass all calls to `delegate`, except for trees which `should_descend` rejects
+    /// when called with their path.
+    pub fn new(delegate: &'a mut D, should_descend: F) -> Self {
+        Filter {
+            delegate,
+            should_descend,
+            path_deque: VecDeque::new(),
+            path: BString::default(),
+        }
+    }
+}
+
+impl<'a, D, F> Filter<'a, D, F> {
+    fn pop_element(&mut self) {
+        if let Some(pos) = self.path.rfind_byte(b'/') {
+            self.path.resize(pos, 0);
+        } else {
+            self.path.clear();
+        }
+    }
+
+    fn push_element(&mut self, name: &BStr) {
+        if !self.path.is_empty() {
+            self.path.push(b'/');
+        }
+        self.path.push_str(name);
+    }
+}
+
+impl<'a, D, F> Visit for Filter<'a, D, F>
+where
+    D: Visit,
+    F: FnMut(&BStr) -> bool,
+{
+    fn pop_front_tracked_path_and_set_current(&mut self) {
+        self.path = self.path_deque.pop_front().expect("every parent is set only once");
+        self.delegate.pop_front_tracked_path_and_set_current();
+    }
+
+    fn push_back_tracked_path_component(&mut self, component: &BStr) {
+        self.push_element(component);
+        self.path_deque.push_back(self.path.clone());
+        self.delegate.push_back_tracked_path_component(component);
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.push_element(component);
+        self.delegate.push_path_component(component);
+    }
+
+    fn pop_path_component(&mut self) {
+        self.pop_element();
+        self.delegate.pop_path_component();
+    }
+
+    fn visit(&mut self, change: visit::Change) -> visit::Action {
+        self.delegate.visit(change)
+    }
+
+    fn enter_tree(&mut self, change: &visit::Change) -> bool {
+        (self.should_descend)(self.path.as_ref()) && self.delegate.enter_tree(change)
+    }
+
+    fn leave_tree(&mut self) {
+        self.delegate.leave_tree()
+    }
+
+    fn visit_merge(&mut self, change: visit::MergeChange<'_>) -> visit::Action {
+        self.delegate.visit_merge(change)
+    }
+}
@@ -0,0 +1,165 @@
+use std::{borrow::BorrowMut, collections::BTreeMap};
+
+use gix_hash::oid;
+use gix_object::{bstr::BString, tree::EntryMode};
+
+use crate::tree::{
+    changes::Error,
+    recorder,
+    visit::{Change, MergeChange, MergeStatus},
+};
+
+impl<'a> crate::tree::Changes<'a> {
+    /// Calculate the changes that `ours` and `theirs` made to `self`, their common base, and pass them to
+    /// [`delegate.visit_merge()`][crate::tree::Visit::visit_merge()] classified by which side changed each entry.
+    ///
+    /// * `state` and `find` are used exactly like in [`needed_to_obtain()`][Self::needed_to_obtain()], which is used to
+    ///   compare the base to each side.
+    /// * `delegate` receives one call per changed path in the order of the paths' bytes, see [`MergeStatus`] for
+    ///   how changes are classified. None of its other methods are called.
+    ///
+    /// # Notes
+    ///
+    /// * Changes are compared by mode and object id only, the content of blobs is never looked at.
+    /// * Modifications of trees that remain trees aren't passed as they are fully described by the changes to their entries.
+    /// * Changing the type of an entry, like turning a file into a directory, is passed as single [`Change::Modification`].
+    /// * All changes of both sides are held in memory before the first call to `delegate`.
+    pub fn needed_to_merge<FindFn, R, StateMut, E>(
+        self,
+        ours: gix_object::TreeRefIter<'_>,
+        theirs: gix_object::TreeRefIter<'_>,
+        mut state: StateMut,
+        mut find: FindFn,
+        delegate: &mut R,
+    ) -> Result<(), Error>
+    where
+        FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::TreeRefIter<'b>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+        R: crate::tree::Visit,
+        StateMut: BorrowMut<crate::tree::State>,
+    {
+        let base = self.0;
+        let mut changes_by_side: [BTreeMap<BString, Change>; 2] = Default::default();
+        for (side, changes) in [ours, theirs].into_iter().zip(changes_by_side.iter_mut()) {
+            let mut recorder = recorder::Recorder::default();
+            crate::tree::Changes(base.clone()).needed_to_obtain(side, state.borrow_mut(), &mut find, &mut recorder)?;
+            for change in recorder.records {
+                let (path, change) = split_path(change);
+                let change = match (changes.remove(&path), change) {
+                    (
+                        Some(Change::Deletion {
+                            entry_mode: previous_entry_mode,
+                            oid: previous_oid,
+                        }),
+                        Change::Addition { entry_mode, oid },
+                    ) => Change::Modification {
+                        previous_entry_mode,
+                        previous_oid,
+                        entry_mode,
+                        oid,
+                    },
+                    (_, change) => change,
+                };
+                changes.insert(path, change);
+            }
+            changes.retain(|_, change| {
+                !matches!(
+                    change,
+                    Change::Modification {
+                        previous_entry_mode: EntryMode::Tree,
+                        entry_mode: EntryMode::Tree,
+                        ..
+                    }
+                )
+            });
+        }
+
+        let [ours, theirs] = changes_by_side;
+        let mut ours = ours.into_iter().peekable();
+        let mut theirs = theirs.into_iter().peekable();
+        loop {
+            use std::cmp::Ordering::*;
+            let (location, status) = match (ours.peek(), theirs.peek()) {
+                (None, None) => break,
+                (Some(_), None) => {
+                    let (location, change) = ours.next().expect("peeked");
+                    (location, MergeStatus::Ours(change))
+                }
+                (None, Some(_)) => {
+                    let (location, change) = theirs.next().expect("peeked");
+                    (location, MergeStatus::Theirs(change))
+                }
+                (Some((lhs, _)), Some((rhs, _))) => match lhs.cmp(rhs) {
+                    Less => {
+                        let (location, change) = ours.next().expect("peeked");
+                        (location, MergeStatus::Ours(change))
+                    }
+                    Greater => {
+                        let (location, change) = theirs.next().expect("peeked");
+                        (location, MergeStatus::Theirs(change))
+                    }
+                    Equal => {
+                        let (location, ours) = ours.next().expect("peeked");
+                        let (_, theirs) = theirs.next().expect("peeked");
+                        let status = if is_same_result(&ours, &theirs) {
+                            MergeStatus::Both(ours)
+                        } else {
+                            MergeStatus::Conflict { ours, theirs }
+                        };
+                        (location, status)
+                    }
+                },
+            };
+            if delegate
+                .visit_merge(MergeChange {
+                    location: location.as_ref(),
+                    status,
+                })
+                .cancelled()
+            {
+                return Err(Error::Cancelled);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn split_path(change: recorder::Change) -> (BString, Change) {
+    match change {
+        recorder::Change::Addition { entry_mode, oid, path } => (path, Change::Addition { entry_mode, oid }),
+        recorder::Change::Deletion { entry_mode, oid, path } => (path, Change::Deletion { entry_mode, oid }),
+        recorder::Change::Modification {
+            previous_entry_mode,
+            previous_oid,
+            entry_mode,
+            oid,
+            path,
+        } => (
+            path,
+            Change::Modification {
+                previous_entry_mode,
+                previous_oid,
+                entry_mode,
+                oid,
+            },
+        ),
+    }
+}
+
+/// Return `true` if `ours` and `theirs` leave the entry in the same state.
+///
+/// Trees are the same no matter their id, as differences between them are reported for their entries.
+fn is_same_result(ours: &Change, theirs: &Change) -> bool {
+    match (resulting_entry(ours), resulting_entry(theirs)) {
+        (Some((EntryMode::Tree, _)), Some((EntryMode::Tree, _))) => true,
+        (ours, theirs) => ours == theirs,
+    }
+}
+
+/// Return the mode and id of the entry after `change`, or `None` if it was deleted.
+fn resulting_entry(change: &Change) -> Option<(EntryMode, &oid)> {
+    match change {
+        Change::Addition { entry_mode, oid } | Change::Modification { entry_mode, oid, .. } => Some((*entry_mode, oid)),
+        Change::Deletion { .. } => None,
+    }
+}
@@ -36,6 +36,8 @@ where
 ///
 pub mod changes;
 
+mod merge;
+
 ///
 pub mod visit;
 #[doc(inline)]
@@ -45,3 +47,8 @@ pub use visit::Visit;
 pub mod recorder;
 #[doc(inline)]
 pub use recorder::Recorder;
+
+/// A [`Visit`] implementation to only compare the entries of selected trees.
+pub mod filter;
+#[doc(inline)]
+pub use filter::Filter;
@@ -1,4 +1,4 @@
-use gix_hash::ObjectId;
+use gix_hash::{oid, ObjectId};
 use gix_object::{bstr::BStr, tree};
 
 /// Represents any possible change in order to turn one tree into another.
@@ -33,13 +33,76 @@ pub enum Change {
     },
 }
 
+impl Change {
+    /// Return the object id of the entry before the change, or `None` if it was [added][Change::Addition].
+    pub fn previous_oid(&self) -> Option<&oid> {
+        match self {
+            Change::Addition { .. } => None,
+            Change::Deletion { oid, .. } => Some(oid),
+            Change::Modification { previous_oid, .. } => Some(previous_oid),
+        }
+    }
+
+    /// Return the object ids of the entry before and after the change as `(previous, new)`.
+    ///
+    /// Additions only have a new id, deletions only have a previous one, and modifications have both.
+    pub fn oids(&self) -> (Option<ObjectId>, Option<ObjectId>) {
+        match self {
+            Change::Addition { oid, .. } => (None, Some(*oid)),
+            Change::Deletion { oid, .. } => (Some(*oid), None),
+            Change::Modification { previous_oid, oid, .. } => (Some(*previous_oid), Some(*oid)),
+        }
+    }
+}
+
+/// A change at a single path as observed during a [three-way comparison][crate::tree::Changes::needed_to_merge()],
+/// as passed to [`Visit::visit_merge()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeChange<'a> {
+    /// The full path of the changed entry, relative to the root of the compared trees.
+    pub location: &'a BStr,
+    /// How the entry at `location` was changed by each side.
+    pub status: MergeStatus,
+}
+
+/// Describes how an entry was changed relative to the common base by the two sides of a three-way comparison.
+///
+/// Changes are compared structurally by mode and object id only, the content of blobs isn't considered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeStatus {
+    /// Only our side changed the entry.
+    Ours(Change),
+    /// Only their side changed the entry.
+    Theirs(Change),
+    /// Both sides changed the entry in the same way, resulting in the same mode and object id, or both deleted it.
+    /// Entries which became trees on both sides are considered the same, as their differences are passed for their entries.
+    ///
+    /// The change is the one of our side.
+    Both(Change),
+    /// Both sides changed the entry differently.
+    Conflict {
+        /// The change of our side.
+        ours: Change,
+        /// The change of their side.
+        theirs: Change,
+    },
+}
+
 /// What to do after a [Change] was [recorded][Visit::visit()].
+///
+/// More actions may be added in future, so matching on it needs a wildcard pattern.
 #[derive(Clone, Copy, PartialOrd, PartialEq, Ord, Eq, Hash)]
+#[non_exhaustive]
 pub enum Action {
     /// Continue the traversal of changes.
     Continue,
     /// Stop the traversal of changes, making this the last call to [visit(…)][Visit::visit()].
     Cancel,
+    /// If returned for the change of a tree, don't compare the entries of the tree but continue the traversal elsewhere.
+    ///
+    /// This is the same as [`Continue`][Action::Continue] for all other changes, and when returned from
+    /// [`visit_merge()`][Visit::visit_merge()].
+    Skip,
 }
 
 impl Default for Action {
@@ -53,6 +116,11 @@ impl Action {
     pub fn cancelled(&self) -> bool {
         matches!(self, Action::Cancel)
     }
+
+    /// Returns true if this action means to not descend into the tree whose change was just visited.
+    pub fn skipped(&self) -> bool {
+        matches!(self, Action::Skip)
+    }
 }
 
 /// A trait to allow responding to a traversal designed to figure out the [changes][Change]
@@ -70,12 +138,62 @@ pub trait Visit {
     ///
     /// The implementation may use the current path to lean where in the tree the change is located.
     fn visit(&mut self, change: Change) -> Action;
+    /// Called before the entries of a changed tree are compared, with the `change` of the tree itself as previously
+    /// passed to [`visit()`][Visit::visit()], and after its path was made current.
+    ///
+    /// Return `false` to not compare its entries, which also skips all trees within it. This allows to treat added or
+    /// deleted trees as a unit, or to ignore changes within a tree entirely.
+    /// Note that this isn't called for the root trees whose entries are always compared.
+    fn enter_tree(&mut self, change: &Change) -> bool {
+        let _ = change;
+        true
+    }
+    /// Called after all entries of a tree previously [entered][Visit::enter_tree()] were compared.
+    ///
+    /// As trees are compared breadth-first, changes to trees within it are observed after leaving it.
+    fn leave_tree(&mut self) {}
+    /// Record a `change` observed during a [three-way comparison][crate::tree::Changes::needed_to_merge()]
+    /// and return an instruction whether to continue or not.
+    ///
+    /// The path of the change is passed along with it, so path tracking isn't needed for this kind of comparison.
+    fn visit_merge(&mut self, change: MergeChange<'_>) -> Action {
+        let _ = change;
+        Action::Continue
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn oids_of_changes() {
+        let previous = ObjectId::null(gix_hash::Kind::Sha1);
+        let new = ObjectId::empty_tree(gix_hash::Kind::Sha1);
+        let addition = Change::Addition {
+            entry_mode: tree::EntryMode::Blob,
+            oid: new,
+        };
+        assert_eq!(addition.previous_oid(), None);
+        assert_eq!(addition.oids(), (None, Some(new)));
+
+        let deletion = Change::Deletion {
+            entry_mode: tree::EntryMode::Blob,
+            oid: previous,
+        };
+        assert_eq!(deletion.previous_oid(), Some(previous.as_ref()));
+        assert_eq!(deletion.oids(), (Some(previous), None));
+
+        let modification = Change::Modification {
+            previous_entry_mode: tree::EntryMode::Blob,
+            previous_oid: previous,
+            entry_mode: tree::EntryMode::Tree,
+            oid: new,
+        };
+        assert_eq!(modification.previous_oid(), Some(previous.as_ref()));
+        assert_eq!(modification.oids(), (Some(previous), Some(new)));
+    }
+
     #[test]
     fn size_of_change() {
         let actual = std::mem::size_of::<Change>();
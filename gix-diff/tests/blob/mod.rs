@@ -1,2 +1,334 @@
-#[test]
-fn currently_there_is_no_api_surface_to_test_as_it_is_reexporting_imara_diff() {}
+mod tokens {
+    use gix_diff::blob::{intern::InternedInput, sink::Counter, tokens, Algorithm};
+
+    #[test]
+    fn bytes_produce_a_minimal_character_level_diff() {
+        let input = InternedInput::new(tokens::bytes(b"value = 42"), tokens::bytes(b"value = 43"));
+        let mut hunks = Vec::new();
+        gix_diff::blob::diff(Algorithm::Myers, &input, |before, after| hunks.push((before, after)));
+        assert_eq!(hunks, vec![(9..10, 9..10)], "only the last character was replaced");
+
+        let counts = gix_diff::blob::diff(Algorithm::Histogram, &input, Counter::default());
+        assert_eq!(counts.removals, 1);
+        assert_eq!(counts.insertions, 1);
+    }
+
+    #[test]
+    fn chars_keep_multi_byte_characters_together() {
+        let input = InternedInput::new(tokens::chars("grüße".as_bytes()), tokens::chars("grüne".as_bytes()));
+        assert_eq!(input.before.len(), 5, "one token per character, not per byte");
+
+        let mut hunks = Vec::new();
+        gix_diff::blob::diff(Algorithm::Myers, &input, |before, after| hunks.push((before, after)));
+        assert_eq!(hunks, vec![(3..4, 3..4)], "'ß' was replaced with 'n'");
+    }
+
+    #[test]
+    fn chars_treat_invalid_utf8_as_individual_tokens() {
+        let input = InternedInput::new(tokens::chars(b"a\xffb"), tokens::chars(b"ab"));
+        assert_eq!(input.before.len(), 3);
+        let counts = gix_diff::blob::diff(Algorithm::Myers, &input, Counter::default());
+        assert_eq!(counts.removals, 1);
+        assert_eq!(counts.insertions, 0);
+    }
+}
+
+mod byte_ranges {
+    use gix_diff::blob::{
+        byte_ranges::{token_offsets, ByteRanges, Hunk},
+        intern::{InternedInput, TokenSource},
+        tokens, Algorithm,
+    };
+
+    #[test]
+    fn line_hunks_map_to_bytes_of_lines_of_varying_length() {
+        let before = "short\na much longer line\nx\nend".as_bytes();
+        let after = "short\nx\ninserted\r\nend\n".as_bytes();
+        let input = InternedInput::new(before, after);
+        let hunks = gix_diff::blob::diff(Algorithm::Histogram, &input, ByteRanges::for_lines(before, after));
+        assert_eq!(
+            hunks,
+            vec![
+                Hunk {
+                    before: 6..25,
+                    after: 6..6
+                },
+                Hunk {
+                    before: 27..27,
+                    after: 8..18
+                }
+            ]
+        );
+        assert_eq!(
+            &before[6..25],
+            b"a much longer line\n",
+            "removals include the line terminator"
+        );
+        assert_eq!(&after[8..18], b"inserted\r\n", "insertions include the line terminator");
+        assert_eq!(
+            &after[18..],
+            b"end\n",
+            "line terminators aren't part of the line tokens, so 'end' is unchanged"
+        );
+    }
+
+    #[test]
+    fn hunks_of_any_contiguous_tokens_map_to_bytes() {
+        let (before, after) = ("aäb".as_bytes(), "aöb".as_bytes());
+        let input = InternedInput::new(tokens::chars(before), tokens::chars(after));
+        let hunks = gix_diff::blob::diff(
+            Algorithm::Myers,
+            &input,
+            ByteRanges::from_token_offsets(
+                token_offsets(tokens::chars(before).tokenize()),
+                token_offsets(tokens::chars(after).tokenize()),
+            ),
+        );
+        assert_eq!(
+            hunks,
+            vec![Hunk {
+                before: 1..3,
+                after: 1..3
+            }],
+            "multi-byte characters span multiple bytes"
+        );
+    }
+
+    #[test]
+    fn empty_inputs_have_no_hunks() {
+        let input = InternedInput::new(&b""[..], &b""[..]);
+        assert_eq!(
+            gix_diff::blob::diff(Algorithm::Histogram, &input, ByteRanges::for_lines(b"", b"")),
+            vec![]
+        );
+    }
+}
+
+mod unified {
+    use gix_diff::blob::{
+        intern::InternedInput,
+        unified::{UnifiedDiff, DEFAULT_CONTEXT_LEN},
+        Algorithm,
+    };
+    use gix_object::bstr::BStr;
+
+    fn unified_diff(before: &[u8], after: &[u8], context_len: u32) -> gix_object::bstr::BString {
+        let input = InternedInput::new(
+            gix_diff::blob::sources::byte_lines_with_terminator(before),
+            gix_diff::blob::sources::byte_lines_with_terminator(after),
+        );
+        gix_diff::blob::diff(Algorithm::Myers, &input, UnifiedDiff::new(&input, context_len))
+    }
+
+    #[test]
+    fn hunks_match_git_diff_output() {
+        for name in [
+            "function-context",
+            "separate-hunks",
+            "merged-hunks",
+            "missing-final-newline",
+            "added-final-newline",
+            "new-file",
+            "deleted-file",
+            "replace-all",
+        ] {
+            let fixture = |ext: &str| gix_testtools::fixture_bytes_standalone(format!("unified/{name}.{ext}"));
+            let actual = unified_diff(&fixture("before"), &fixture("after"), DEFAULT_CONTEXT_LEN);
+            assert_eq!(
+                actual,
+                BStr::new(&fixture("patch")),
+                "{name}: the output of `git diff` after the `+++` line is reproduced exactly"
+            );
+        }
+    }
+
+    #[test]
+    fn context_len_controls_hunk_size_and_merging() {
+        let before = "1\n2\n3\n4\n5\n";
+        let after = "one\n2\n3\n4\nfive\n";
+        assert_eq!(
+            unified_diff(before.as_bytes(), after.as_bytes(), 0),
+            "@@ -1 +1 @@\n-1\n+one\n@@ -5 +5 @@\n-5\n+five\n"
+        );
+        assert_eq!(
+            unified_diff(before.as_bytes(), after.as_bytes(), 2),
+            "@@ -1,5 +1,5 @@\n-1\n+one\n 2\n 3\n 4\n-5\n+five\n",
+            "changes at most twice the context apart share a hunk"
+        );
+    }
+
+    #[test]
+    fn equal_inputs_have_no_hunks() {
+        assert_eq!(unified_diff(b"a\nb", b"a\nb", DEFAULT_CONTEXT_LEN), "");
+    }
+}
+
+mod patience {
+    use gix_diff::blob::{
+        intern::InternedInput,
+        sink::Counter,
+        sources::byte_lines_with_terminator,
+        unified::{UnifiedDiff, DEFAULT_CONTEXT_LEN},
+        Algorithm,
+    };
+    use gix_object::bstr::BStr;
+
+    #[test]
+    fn unique_lines_anchor_the_diff_unlike_with_myers() {
+        let fixture = |name: &str| gix_testtools::fixture_bytes_standalone(format!("unified/{name}"));
+        let (before, after) = (fixture("patience.before"), fixture("patience.after"));
+        let input = InternedInput::new(byte_lines_with_terminator(&before), byte_lines_with_terminator(&after));
+        for (algorithm, expected) in [
+            (Algorithm::Patience, "patience.patch"),
+            (Algorithm::Myers, "patience-with-myers.patch"),
+        ] {
+            assert_eq!(
+                gix_diff::blob::diff(algorithm, &input, UnifiedDiff::new(&input, DEFAULT_CONTEXT_LEN)),
+                BStr::new(&fixture(expected)),
+                "{algorithm:?} produces the same hunks as `git diff`"
+            );
+        }
+    }
+
+    #[test]
+    fn repeated_lines_fall_back_to_myers() {
+        let input = InternedInput::new("a\nb\na\nb\n", "b\na\nb\na\n");
+        let counts = gix_diff::blob::diff(Algorithm::Patience, &input, Counter::default());
+        assert_eq!(
+            (counts.removals, counts.insertions),
+            (1, 1),
+            "without unique lines the diff is still minimal"
+        );
+    }
+
+    #[test]
+    fn empty_inputs() {
+        for (before, after, expected) in [("", "", (0, 0)), ("a\n", "", (1, 0)), ("", "a\nb\n", (0, 2))] {
+            let input = InternedInput::new(before, after);
+            let counts = gix_diff::blob::diff(Algorithm::Patience, &input, Counter::default());
+            assert_eq!((counts.removals, counts.insertions), expected);
+        }
+    }
+}
+
+mod apply {
+    use gix_diff::blob::{
+        apply::{EditScript, Error, Hunk},
+        intern::InternedInput,
+        sources::byte_lines_with_terminator,
+        Algorithm,
+    };
+
+    fn edit_script(before: &str, after: &str) -> Vec<Hunk> {
+        let input = InternedInput::new(
+            byte_lines_with_terminator(before.as_bytes()),
+            byte_lines_with_terminator(after.as_bytes()),
+        );
+        gix_diff::blob::diff(Algorithm::Histogram, &input, EditScript::new(&input))
+    }
+
+    #[test]
+    fn round_trip() -> crate::Result {
+        for (before, after) in [
+            ("a\nb\nc\n", "a\nB\nc\nd\n"),
+            ("a\nb\nc\n", ""),
+            ("", "a\nb"),
+            ("a\nb\nc", "x\na\nc\r\n"),
+            ("same\n", "same\n"),
+        ] {
+            let hunks = edit_script(before, after);
+            let actual = gix_diff::blob::apply(before.as_bytes(), &hunks)?;
+            assert_eq!(actual, after, "applying the diff of {before:?} reproduces {after:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mismatching_context_is_an_error() {
+        let hunks = edit_script("a\nb\nc\n", "a\nB\nc\n");
+        let err = gix_diff::blob::apply(b"a\nX\nc\n", &hunks).unwrap_err();
+        assert!(matches!(err, Error::Mismatch { line: 1, .. }), "{err:?}");
+    }
+
+    #[test]
+    fn hunks_past_the_end_of_the_base_are_an_error() {
+        let hunks = edit_script("a\nb\nc\n", "a\n");
+        let err = gix_diff::blob::apply(b"a\n", &hunks).unwrap_err();
+        assert!(matches!(err, Error::OutOfBounds { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn unordered_hunks_are_an_error() {
+        let mut hunks = edit_script("a\nb\nc\nd\n", "A\nb\nc\nD\n");
+        hunks.reverse();
+        let err = gix_diff::blob::apply(b"a\nb\nc\nd\n", &hunks).unwrap_err();
+        assert!(matches!(err, Error::Unordered { before_start: 0 }), "{err:?}");
+    }
+}
+
+mod merge {
+    use gix_diff::blob::{
+        merge::{ConflictRegion, MergeResult},
+        Algorithm,
+    };
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> MergeResult {
+        gix_diff::blob::merge(
+            Algorithm::Histogram,
+            base.as_bytes(),
+            ours.as_bytes(),
+            theirs.as_bytes(),
+        )
+    }
+
+    #[test]
+    fn changes_of_different_lines_merge_cleanly() {
+        assert_eq!(
+            merge("a\nb\nc\nd\ne\n", "A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\nf\n"),
+            MergeResult::Clean("A\nb\nc\nd\nE\nf\n".into())
+        );
+        assert_eq!(
+            merge("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n"),
+            MergeResult::Clean("a\nB\nc\n".into()),
+            "identical changes on both sides aren't conflicting"
+        );
+        assert_eq!(merge("a\n", "a\n", "a\n"), MergeResult::Clean("a\n".into()));
+    }
+
+    #[test]
+    fn overlapping_changes_are_conflicts_with_structured_regions() {
+        let base = "1\n2\n3\n4\n5\n6\n";
+        let ours = "1\n2\nours-3\nours-4\n5\n6\n";
+        let theirs = "1\nzero\n2\n3\ntheirs-4\n5\n6\nseven\n";
+        assert_eq!(
+            merge(base, ours, theirs),
+            MergeResult::Conflicted {
+                merged_with_markers:
+                    "1\nzero\n2\n<<<<<<< ours\nours-3\nours-4\n=======\n3\ntheirs-4\n>>>>>>> theirs\n5\n6\nseven\n"
+                        .into(),
+                regions: vec![ConflictRegion {
+                    base_range: 2..4,
+                    base: vec!["3\n".into(), "4\n".into()],
+                    ours_range: 2..4,
+                    ours: vec!["ours-3\n".into(), "ours-4\n".into()],
+                    theirs_range: 3..5,
+                    theirs: vec!["3\n".into(), "theirs-4\n".into()],
+                }]
+            },
+            "the conflict spans all lines changed by either side, and line ranges account for prior changes"
+        );
+    }
+
+    #[test]
+    fn conflict_markers_are_on_their_own_line() {
+        match merge("a\n", "ours", "theirs") {
+            MergeResult::Conflicted {
+                merged_with_markers, ..
+            } => assert_eq!(
+                merged_with_markers,
+                "<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n"
+            ),
+            MergeResult::Clean(_) => unreachable!("both sides changed the only line"),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use gix_diff::{no_index, tree::recorder::Change};
+use gix_object::tree::EntryMode;
+
+use crate::hex_to_id;
+
+fn write_files(root: &Path, files: &[(&str, &str)]) -> std::io::Result<()> {
+    for (path, content) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().expect("in root"))?;
+        std::fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn directories() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (old, new) = (tmp.path().join("old"), tmp.path().join("new"));
+    write_files(&old, &[("a", "a\n"), ("dir/b", "b\n"), ("dir/c", "c\n")])?;
+    write_files(
+        &new,
+        &[("a", "b\n"), ("dir/b", "b\n"), ("dir/d", "d\n"), ("new", "c\n")],
+    )?;
+    std::fs::create_dir(old.join("empty"))?;
+
+    let (a, b, c, d) = (
+        hex_to_id("78981922613b2afb6025042ff6bd878ac1994e85"),
+        hex_to_id("61780798228d17af2d34fce4cfbdf35556832472"),
+        hex_to_id("f2ad6c76f0115a6ba5b00456a849810e7ec0af20"),
+        hex_to_id("4bcfe98e640c8284511312660fb8709b0afa888e"),
+    );
+    assert_eq!(
+        no_index(&old, &new, Default::default())?,
+        vec![
+            Change::Modification {
+                previous_entry_mode: EntryMode::Blob,
+                previous_oid: a,
+                entry_mode: EntryMode::Blob,
+                oid: b,
+                path: "a".into()
+            },
+            Change::Modification {
+                previous_entry_mode: EntryMode::Tree,
+                previous_oid: hex_to_id("e42ba3e77f66f623836b47df796932f7e5604aec"),
+                entry_mode: EntryMode::Tree,
+                oid: hex_to_id("c544b147f561699369f87b1222bd29f8fded6bbc"),
+                path: "dir".into()
+            },
+            Change::Addition {
+                entry_mode: EntryMode::Blob,
+                oid: c,
+                path: "new".into()
+            },
+            Change::Deletion {
+                entry_mode: EntryMode::Blob,
+                oid: c,
+                path: "dir/c".into()
+            },
+            Change::Addition {
+                entry_mode: EntryMode::Blob,
+                oid: d,
+                path: "dir/d".into()
+            },
+        ],
+        "ids are the same as git would compute them, and empty directories are ignored"
+    );
+
+    let missing = tmp.path().join("missing");
+    assert_eq!(
+        no_index(&missing, old.join("dir"), Default::default())?,
+        vec![
+            Change::Addition {
+                entry_mode: EntryMode::Blob,
+                oid: b,
+                path: "b".into()
+            },
+            Change::Addition {
+                entry_mode: EntryMode::Blob,
+                oid: c,
+                path: "c".into()
+            }
+        ],
+        "a missing side turns everything into additions…"
+    );
+    assert_eq!(
+        no_index(new.join("new"), &missing, Default::default())?,
+        vec![Change::Deletion {
+            entry_mode: EntryMode::Blob,
+            oid: c,
+            path: "".into()
+        }],
+        "…or deletions, with files using an empty path"
+    );
+    Ok(())
+}
+
+#[test]
+fn files() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    write_files(tmp.path(), &[("a", "a\n"), ("b", "b\n"), ("also-b", "b\n")])?;
+    let path = |name: &str| tmp.path().join(name);
+    assert_eq!(
+        no_index(path("a"), path("b"), Default::default())?,
+        vec![Change::Modification {
+            previous_entry_mode: EntryMode::Blob,
+            previous_oid: hex_to_id("78981922613b2afb6025042ff6bd878ac1994e85"),
+            entry_mode: EntryMode::Blob,
+            oid: hex_to_id("61780798228d17af2d34fce4cfbdf35556832472"),
+            path: "".into()
+        }]
+    );
+    assert_eq!(
+        no_index(path("b"), path("also-b"), Default::default())?,
+        vec![],
+        "files with the same content are unchanged"
+    );
+    assert!(matches!(
+        no_index(path("a"), tmp.path(), Default::default()),
+        Err(no_index::Error::FileAgainstDirectory { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn symlinks_are_resolved_only_if_passed_directly() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (old, new) = (tmp.path().join("old"), tmp.path().join("new"));
+    write_files(&old, &[("a", "a\n")])?;
+    write_files(&new, &[("a", "b\n")])?;
+    std::os::unix::fs::symlink(&new, tmp.path().join("link-to-new"))?;
+    std::os::unix::fs::symlink("a", old.join("link"))?;
+
+    assert_eq!(
+        no_index(&old, tmp.path().join("link-to-new"), Default::default())?,
+        no_index(&old, &new, Default::default())?,
+        "a link to a directory is compared like the directory it points to"
+    );
+    assert_eq!(
+        no_index(&old, &new, Default::default())?,
+        vec![
+            Change::Modification {
+                previous_entry_mode: EntryMode::Blob,
+                previous_oid: hex_to_id("78981922613b2afb6025042ff6bd878ac1994e85"),
+                entry_mode: EntryMode::Blob,
+                oid: hex_to_id("61780798228d17af2d34fce4cfbdf35556832472"),
+                path: "a".into()
+            },
+            Change::Deletion {
+                entry_mode: EntryMode::Link,
+                oid: hex_to_id("2e65efe2a145dda7ee51d1741299f848e5bf752e"),
+                path: "link".into()
+            }
+        ],
+        "links within directories are compared by the path they point to"
+    );
+    Ok(())
+}
@@ -9,7 +9,7 @@ mod changes {
 
         type Changes = Vec<recorder::Change>;
 
-        fn db(args: impl IntoIterator<Item = &'static str>) -> crate::Result<gix_odb::Handle> {
+        pub(super) fn db(args: impl IntoIterator<Item = &'static str>) -> crate::Result<gix_odb::Handle> {
             gix_odb::at(
                 gix_testtools::scripted_fixture_read_only_with_args_standalone("make_diff_repo.sh", args)?
                     .join(".git")
@@ -18,7 +18,7 @@ mod changes {
             .map_err(Into::into)
         }
 
-        fn locate_tree_by_commit<'a>(
+        pub(super) fn locate_tree_by_commit<'a>(
             db: &gix_odb::Handle,
             commit: &oid,
             buf: &'a mut Vec<u8>,
@@ -126,7 +126,7 @@ mod changes {
             .expect("valid hex id")
         }
 
-        fn all_commits(db: &gix_odb::Handle) -> Vec<ObjectId> {
+        pub(super) fn all_commits(db: &gix_odb::Handle) -> Vec<ObjectId> {
             use gix_traverse::commit;
 
             let head = head_of(db);
@@ -611,5 +611,665 @@ mod changes {
             );
             Ok(())
         }
+
+        #[test]
+        fn trees_with_equal_ids_are_pruned() -> crate::Result {
+            let db = db(None)?;
+            let all_commits = all_commits(&db);
+            let (mut buf, mut buf2) = (Vec::new(), Vec::new());
+            let mut recorder = gix_diff::tree::Recorder::default();
+            let mut lookups = 0;
+            let outcome = gix_diff::tree::Changes::from(locate_tree_by_commit(&db, &all_commits[5], &mut buf)?)
+                .needed_to_obtain(
+                    locate_tree_by_commit(&db, &all_commits[6], &mut buf2)?,
+                    gix_diff::tree::State::default(),
+                    |oid, buf| {
+                        use gix_odb::pack::FindExt;
+                        lookups += 1;
+                        db.find(oid, buf)
+                            .map(|obj| obj.0.try_into_tree_iter().expect("only called for trees"))
+                    },
+                    &mut recorder,
+                )?;
+            assert_eq!(
+                recorder.records,
+                vec![Addition {
+                    entry_mode: EntryMode::Blob,
+                    oid: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                    path: "a".into()
+                }]
+            );
+            assert_eq!(outcome.pruned_subtrees, 1, "the unchanged tree 'f' was skipped…");
+            assert_eq!(lookups, 0, "…without looking it up");
+            Ok(())
+        }
+
+        #[test]
+        fn mode_only_changes_are_modifications() -> crate::Result {
+            use gix_object::WriteTo;
+
+            let blob = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+            let tree = |mode: EntryMode| -> Vec<u8> {
+                let mut buf = Vec::new();
+                gix_object::Tree {
+                    entries: vec![gix_object::tree::Entry {
+                        mode,
+                        filename: "script".into(),
+                        oid: blob,
+                    }],
+                }
+                .write_to(&mut buf)
+                .expect("in-memory write succeeds");
+                buf
+            };
+            let (lhs, rhs) = (tree(EntryMode::Blob), tree(EntryMode::BlobExecutable));
+            let mut recorder = gix_diff::tree::Recorder::default();
+            gix_diff::tree::Changes::from(gix_object::TreeRefIter::from_bytes(&lhs)).needed_to_obtain(
+                gix_object::TreeRefIter::from_bytes(&rhs),
+                gix_diff::tree::State::default(),
+                |_oid, _buf| -> Result<gix_object::TreeRefIter<'_>, std::convert::Infallible> {
+                    unreachable!("there are no subtrees")
+                },
+                &mut recorder,
+            )?;
+            assert_eq!(
+                recorder.records,
+                vec![Modification {
+                    previous_entry_mode: EntryMode::Blob,
+                    previous_oid: blob,
+                    entry_mode: EntryMode::BlobExecutable,
+                    oid: blob,
+                    path: "script".into()
+                }],
+                "the content is the same, but the mode changed"
+            );
+            Ok(())
+        }
+
+        /// Trees which are kept in memory under made-up ids, as only their identity matters.
+        #[derive(Default)]
+        struct InMemoryTrees(std::collections::HashMap<ObjectId, Vec<u8>>);
+
+        impl InMemoryTrees {
+            /// The ids of the old and the new version of a blob that changed.
+            const OLD_BLOB: &'static str = "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391";
+            const NEW_BLOB: &'static str = "28ce6a8b26aa170e1de65536fe8abe1832bd3242";
+
+            fn write(&mut self, entries: Vec<(&str, EntryMode, ObjectId)>) -> ObjectId {
+                use gix_object::WriteTo;
+
+                let mut buf = Vec::new();
+                gix_object::Tree {
+                    entries: entries
+                        .into_iter()
+                        .map(|(filename, mode, oid)| gix_object::tree::Entry {
+                            mode,
+                            filename: filename.into(),
+                            oid,
+                        })
+                        .collect(),
+                }
+                .write_to(&mut buf)
+                .expect("in-memory write succeeds");
+                let id = ObjectId::from([self.0.len() as u8 + 1; 20]);
+                self.0.insert(id, buf);
+                id
+            }
+
+            /// Diff the trees with ids `lhs` and `rhs` for `delegate` and return how many trees had to be looked up.
+            fn diff(
+                &self,
+                lhs: ObjectId,
+                rhs: ObjectId,
+                delegate: &mut impl gix_diff::tree::Visit,
+            ) -> crate::Result<usize> {
+                let mut lookups = 0;
+                gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.0[&lhs])).needed_to_obtain(
+                    TreeRefIter::from_bytes(&self.0[&rhs]),
+                    gix_diff::tree::State::default(),
+                    |oid, buf| -> Result<_, std::convert::Infallible> {
+                        lookups += 1;
+                        buf.clear();
+                        buf.extend_from_slice(&self.0[&oid.to_owned()]);
+                        Ok(TreeRefIter::from_bytes(buf))
+                    },
+                    delegate,
+                )?;
+                Ok(lookups)
+            }
+        }
+
+        #[test]
+        fn filter_skips_trees_by_path_without_looking_them_up() -> crate::Result {
+            let mut trees = InMemoryTrees::default();
+            let mut root = |blob: ObjectId| {
+                let a = trees.write(vec![("file", EntryMode::Blob, blob)]);
+                let b = trees.write(vec![("file", EntryMode::Blob, blob), ("x", EntryMode::Blob, blob)]);
+                trees.write(vec![("a", EntryMode::Tree, a), ("b", EntryMode::Tree, b)])
+            };
+            let (lhs, rhs) = (
+                root(hex_to_id(InMemoryTrees::OLD_BLOB)),
+                root(hex_to_id(InMemoryTrees::NEW_BLOB)),
+            );
+
+            let mut recorder = gix_diff::tree::Recorder::default();
+            let mut descended_into = Vec::new();
+            let lookups = trees.diff(
+                lhs,
+                rhs,
+                &mut gix_diff::tree::Filter::new(&mut recorder, |path| {
+                    descended_into.push(path.to_owned());
+                    path == "a"
+                }),
+            )?;
+            assert_eq!(
+                descended_into,
+                vec!["a", "b"],
+                "each changed tree is checked by its path"
+            );
+            assert_eq!(
+                recorder
+                    .records
+                    .iter()
+                    .map(|change| match change {
+                        Modification { path, .. } => path.to_string(),
+                        _ => unreachable!("only modifications"),
+                    })
+                    .collect::<Vec<_>>(),
+                vec!["a", "b", "a/file"],
+                "the skipped tree itself is still visited, but not its entries"
+            );
+            assert_eq!(lookups, 2, "only both sides of the tree at 'a' are looked up");
+            Ok(())
+        }
+
+        #[test]
+        fn skip_action_prunes_changed_trees() -> crate::Result {
+            use gix_diff::tree::{
+                visit::{Action, Change},
+                Visit,
+            };
+            use gix_object::bstr::BStr;
+
+            struct SkipRecorder {
+                inner: gix_diff::tree::Recorder,
+                skip: &'static [&'static str],
+            }
+
+            impl Visit for SkipRecorder {
+                fn pop_front_tracked_path_and_set_current(&mut self) {
+                    self.inner.pop_front_tracked_path_and_set_current()
+                }
+
+                fn push_back_tracked_path_component(&mut self, component: &BStr) {
+                    self.inner.push_back_tracked_path_component(component)
+                }
+
+                fn push_path_component(&mut self, component: &BStr) {
+                    self.inner.push_path_component(component)
+                }
+
+                fn pop_path_component(&mut self) {
+                    self.inner.pop_path_component()
+                }
+
+                fn visit(&mut self, change: Change) -> Action {
+                    self.inner.visit(change);
+                    let path = match self.inner.records.last().expect("just recorded") {
+                        Addition { path, .. } | Deletion { path, .. } | Modification { path, .. } => path,
+                    };
+                    if self.skip.iter().any(|skip| path == skip) {
+                        Action::Skip
+                    } else {
+                        Action::Continue
+                    }
+                }
+            }
+
+            let (old_blob, new_blob) = (hex_to_id(InMemoryTrees::OLD_BLOB), hex_to_id(InMemoryTrees::NEW_BLOB));
+            let mut trees = InMemoryTrees::default();
+            let lhs = {
+                let a = trees.write(vec![("file", EntryMode::Blob, old_blob)]);
+                let b = trees.write(vec![("file", EntryMode::Blob, old_blob)]);
+                trees.write(vec![("a", EntryMode::Tree, a), ("b", EntryMode::Tree, b)])
+            };
+            let rhs = {
+                let a = trees.write(vec![("file", EntryMode::Blob, new_blob)]);
+                let b = trees.write(vec![("file", EntryMode::Blob, new_blob)]);
+                let c = trees.write(vec![("file", EntryMode::Blob, new_blob)]);
+                trees.write(vec![
+                    ("a", EntryMode::Tree, a),
+                    ("b", EntryMode::Tree, b),
+                    ("c", EntryMode::Tree, c),
+                ])
+            };
+
+            let mut recorder = SkipRecorder {
+                inner: Default::default(),
+                skip: &["b", "c"],
+            };
+            let lookups = trees.diff(lhs, rhs, &mut recorder)?;
+            assert_eq!(
+                recorder
+                    .inner
+                    .records
+                    .iter()
+                    .map(|change| match change {
+                        Addition { path, .. } => format!("+{path}"),
+                        Deletion { path, .. } => format!("-{path}"),
+                        Modification { path, .. } => format!("~{path}"),
+                    })
+                    .collect::<Vec<_>>(),
+                vec!["~a", "~b", "+c", "~a/file"],
+                "skipped trees are visited themselves, but their entries aren't, while paths elsewhere remain correct"
+            );
+            assert_eq!(lookups, 2, "only both sides of the tree at 'a' are looked up");
+            Ok(())
+        }
+
+        mod enter_and_leave_tree {
+            use gix_diff::tree::{
+                recorder,
+                visit::{Action, Change},
+                Visit,
+            };
+            use gix_object::{bstr::BStr, tree::EntryMode};
+
+            use super::{all_commits, db, locate_tree_by_commit};
+            use crate::hex_to_id;
+
+            struct TreeRecorder {
+                skip_additions: bool,
+                inner: gix_diff::tree::Recorder,
+                events: Vec<String>,
+            }
+
+            impl Visit for TreeRecorder {
+                fn pop_front_tracked_path_and_set_current(&mut self) {
+                    self.inner.pop_front_tracked_path_and_set_current()
+                }
+
+                fn push_back_tracked_path_component(&mut self, component: &BStr) {
+                    self.inner.push_back_tracked_path_component(component)
+                }
+
+                fn push_path_component(&mut self, component: &BStr) {
+                    self.inner.push_path_component(component)
+                }
+
+                fn pop_path_component(&mut self) {
+                    self.inner.pop_path_component()
+                }
+
+                fn visit(&mut self, change: Change) -> Action {
+                    self.inner.visit(change)
+                }
+
+                fn enter_tree(&mut self, change: &Change) -> bool {
+                    let is_addition = matches!(change, Change::Addition { .. });
+                    self.events.push(format!("enter addition: {is_addition}"));
+                    !(self.skip_additions && is_addition)
+                }
+
+                fn leave_tree(&mut self) {
+                    self.events.push("leave".into());
+                }
+            }
+
+            fn diff_commits(
+                skip_additions: bool,
+                lhs: usize,
+                rhs: usize,
+            ) -> crate::Result<(Vec<recorder::Change>, Vec<String>)> {
+                let db = db(None)?;
+                let all_commits = all_commits(&db);
+                let (mut buf, mut buf2) = (Vec::new(), Vec::new());
+                let mut recorder = TreeRecorder {
+                    skip_additions,
+                    inner: Default::default(),
+                    events: Vec::new(),
+                };
+                gix_diff::tree::Changes::from(locate_tree_by_commit(&db, &all_commits[lhs], &mut buf)?)
+                    .needed_to_obtain(
+                        locate_tree_by_commit(&db, &all_commits[rhs], &mut buf2)?,
+                        gix_diff::tree::State::default(),
+                        |oid, buf| {
+                            use gix_odb::pack::FindExt;
+                            db.find(oid, buf)
+                                .map(|obj| obj.0.try_into_tree_iter().expect("only called for trees"))
+                        },
+                        &mut recorder,
+                    )?;
+                Ok((recorder.inner.records, recorder.events))
+            }
+
+            #[test]
+            fn added_trees_can_be_skipped() -> crate::Result {
+                let (changes, events) = diff_commits(true, 3, 6)?;
+                assert_eq!(events, vec!["enter addition: true"], "skipped trees aren't left");
+                assert_eq!(
+                    changes,
+                    vec![
+                        recorder::Change::Addition {
+                            entry_mode: EntryMode::Blob,
+                            oid: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                            path: "a".into()
+                        },
+                        recorder::Change::Deletion {
+                            entry_mode: EntryMode::Blob,
+                            oid: hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242"),
+                            path: "f".into()
+                        },
+                        recorder::Change::Addition {
+                            entry_mode: EntryMode::Tree,
+                            oid: hex_to_id("ebbe0b3000afdfd1aed15000094b59a2800328eb"),
+                            path: "f".into()
+                        },
+                    ],
+                    "the added tree is treated as a unit"
+                );
+
+                let (changes, events) = diff_commits(false, 3, 6)?;
+                assert_eq!(events, vec!["enter addition: true", "leave"]);
+                assert_eq!(changes.len(), 4, "the entries of the added tree are visited as well");
+                Ok(())
+            }
+
+            #[test]
+            fn modified_trees_are_entered_and_left() -> crate::Result {
+                let (changes, events) = diff_commits(true, 4, 5)?;
+                assert_eq!(events, vec!["enter addition: false", "leave"]);
+                assert_eq!(changes.len(), 2, "the modified tree and its modified entry");
+                Ok(())
+            }
+        }
+
+        mod against_empty_tree {
+            use gix_diff::tree::{
+                visit::{Action, Change},
+                Visit,
+            };
+            use gix_object::{bstr::BStr, tree::EntryMode, TreeRefIter, WriteTo};
+
+            use super::{all_commits, db, locate_tree_by_commit};
+
+            /// Record every call made by the diff, to be able to compare them exactly.
+            #[derive(Default)]
+            struct CallRecorder {
+                calls: Vec<String>,
+            }
+
+            impl Visit for CallRecorder {
+                fn pop_front_tracked_path_and_set_current(&mut self) {
+                    self.calls.push("pop front".into());
+                }
+
+                fn push_back_tracked_path_component(&mut self, component: &BStr) {
+                    self.calls.push(format!("push back {component}"));
+                }
+
+                fn push_path_component(&mut self, component: &BStr) {
+                    self.calls.push(format!("push {component}"));
+                }
+
+                fn pop_path_component(&mut self) {
+                    self.calls.push("pop".into());
+                }
+
+                fn visit(&mut self, change: Change) -> Action {
+                    self.calls.push(format!("visit {change:?}"));
+                    Action::Continue
+                }
+
+                fn enter_tree(&mut self, change: &Change) -> bool {
+                    self.calls.push(format!("enter {change:?}"));
+                    true
+                }
+
+                fn leave_tree(&mut self) {
+                    self.calls.push("leave".into());
+                }
+            }
+
+            fn calls(db: &gix_odb::Handle, lhs: TreeRefIter<'_>, rhs: TreeRefIter<'_>) -> crate::Result<Vec<String>> {
+                let mut recorder = CallRecorder::default();
+                let outcome = gix_diff::tree::Changes::from(lhs).needed_to_obtain(
+                    rhs,
+                    gix_diff::tree::State::default(),
+                    |oid, buf| {
+                        use gix_odb::pack::FindExt;
+                        db.find(oid, buf)
+                            .map(|obj| obj.0.try_into_tree_iter().expect("only called for trees"))
+                    },
+                    &mut recorder,
+                )?;
+                assert_eq!(
+                    outcome.pruned_subtrees, 0,
+                    "there is nothing to prune if one side is empty"
+                );
+                Ok(recorder.calls)
+            }
+
+            #[test]
+            fn produces_the_same_calls_as_a_tree_with_an_unrelated_entry() -> crate::Result {
+                // The only entry of this tree sorts after all others, so it's deleted last at the top level,
+                // and otherwise the same comparisons are made as with an empty tree.
+                let mut unrelated = Vec::new();
+                gix_object::Tree {
+                    entries: vec![gix_object::tree::Entry {
+                        mode: EntryMode::Blob,
+                        filename: "~unrelated".into(),
+                        oid: gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1),
+                    }],
+                }
+                .write_to(&mut unrelated)?;
+
+                for args in [None, Some("a")] {
+                    let db = db(args)?;
+                    for commit in all_commits(&db) {
+                        let mut buf = Vec::new();
+                        let actual = calls(
+                            &db,
+                            TreeRefIter::from_bytes(&[]),
+                            locate_tree_by_commit(&db, &commit, &mut buf)?,
+                        )?;
+
+                        let mut expected = calls(
+                            &db,
+                            TreeRefIter::from_bytes(&unrelated),
+                            locate_tree_by_commit(&db, &commit, &mut buf)?,
+                        )?;
+                        let pos = expected
+                            .iter()
+                            .position(|call| call == "push ~unrelated")
+                            .expect("unrelated entry is always deleted");
+                        assert!(expected[pos + 1].starts_with("visit Deletion"));
+                        assert_eq!(expected[pos + 2], "pop");
+                        expected.drain(pos..pos + 3);
+
+                        assert_eq!(actual, expected, "commit {commit} in fixture {args:?}");
+                        assert!(actual.iter().all(|call| !call.contains("Deletion")));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    mod to_merge {
+        use gix_diff::tree::visit::{Action, Change::*, MergeChange, MergeStatus, MergeStatus::*};
+        use gix_hash::{oid, ObjectId};
+        use gix_object::{bstr::BString, tree::EntryMode};
+
+        use super::to_obtain_tree::{all_commits, db, locate_tree_by_commit};
+        use crate::hex_to_id;
+
+        #[derive(Default)]
+        struct MergeRecorder {
+            records: Vec<(BString, MergeStatus)>,
+        }
+
+        impl gix_diff::tree::Visit for MergeRecorder {
+            fn pop_front_tracked_path_and_set_current(&mut self) {
+                unreachable!("not called for merges")
+            }
+
+            fn push_back_tracked_path_component(&mut self, _component: &gix_object::bstr::BStr) {
+                unreachable!("not called for merges")
+            }
+
+            fn push_path_component(&mut self, _component: &gix_object::bstr::BStr) {
+                unreachable!("not called for merges")
+            }
+
+            fn pop_path_component(&mut self) {
+                unreachable!("not called for merges")
+            }
+
+            fn visit(&mut self, _change: gix_diff::tree::visit::Change) -> Action {
+                unreachable!("not called for merges")
+            }
+
+            fn visit_merge(&mut self, change: MergeChange<'_>) -> Action {
+                self.records.push((change.location.to_owned(), change.status));
+                Action::Continue
+            }
+        }
+
+        fn merge_commits(
+            db: &gix_odb::Handle,
+            base: &oid,
+            ours: &oid,
+            theirs: &oid,
+        ) -> crate::Result<Vec<(BString, MergeStatus)>> {
+            let (mut buf, mut buf2, mut buf3) = (Vec::new(), Vec::new(), Vec::new());
+            let mut recorder = MergeRecorder::default();
+            gix_diff::tree::Changes::from(locate_tree_by_commit(db, base, &mut buf)?).needed_to_merge(
+                locate_tree_by_commit(db, ours, &mut buf2)?,
+                locate_tree_by_commit(db, theirs, &mut buf3)?,
+                gix_diff::tree::State::default(),
+                |oid, buf| {
+                    use gix_odb::pack::FindExt;
+                    db.find(oid, buf)
+                        .map(|obj| obj.0.try_into_tree_iter().expect("only called for trees"))
+                },
+                &mut recorder,
+            )?;
+            Ok(recorder.records)
+        }
+
+        fn empty_blob() -> ObjectId {
+            hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")
+        }
+
+        fn blob_m() -> ObjectId {
+            hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242")
+        }
+
+        #[test]
+        fn changes_of_only_one_side() -> crate::Result {
+            let db = db(None)?;
+            let all_commits = all_commits(&db);
+            let deletion = Deletion {
+                entry_mode: EntryMode::Blob,
+                oid: blob_m(),
+            };
+            assert_eq!(
+                merge_commits(&db, &all_commits[1], &all_commits[2], &all_commits[1])?,
+                vec![("f".into(), Ours(deletion.clone()))]
+            );
+            assert_eq!(
+                merge_commits(&db, &all_commits[1], &all_commits[3], &all_commits[2])?,
+                vec![("f".into(), Theirs(deletion))],
+                "the same tree on one side is like no change"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn identical_changes_of_both_sides() -> crate::Result {
+            let db = db(None)?;
+            let all_commits = all_commits(&db);
+            assert_eq!(
+                merge_commits(&db, &all_commits[0], &all_commits[1], &all_commits[3])?,
+                vec![(
+                    "f".into(),
+                    Both(Modification {
+                        previous_entry_mode: EntryMode::Blob,
+                        previous_oid: empty_blob(),
+                        entry_mode: EntryMode::Blob,
+                        oid: blob_m(),
+                    })
+                )]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn conflicting_changes_of_both_sides() -> crate::Result {
+            let db = db(None)?;
+            let all_commits = all_commits(&db);
+            assert_eq!(
+                merge_commits(&db, &all_commits[0], &all_commits[2], &all_commits[1])?,
+                vec![(
+                    "f".into(),
+                    Conflict {
+                        ours: Deletion {
+                            entry_mode: EntryMode::Blob,
+                            oid: empty_blob(),
+                        },
+                        theirs: Modification {
+                            previous_entry_mode: EntryMode::Blob,
+                            previous_oid: empty_blob(),
+                            entry_mode: EntryMode::Blob,
+                            oid: blob_m(),
+                        }
+                    }
+                )]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn type_changes_are_single_modifications_and_entries_of_new_trees_are_compared() -> crate::Result {
+            let db = db(None)?;
+            let all_commits = all_commits(&db);
+            assert_eq!(
+                merge_commits(&db, &all_commits[3], &all_commits[4], &all_commits[6])?,
+                vec![
+                    (
+                        "a".into(),
+                        Theirs(Addition {
+                            entry_mode: EntryMode::Blob,
+                            oid: empty_blob(),
+                        })
+                    ),
+                    (
+                        "f".into(),
+                        Both(Modification {
+                            previous_entry_mode: EntryMode::Blob,
+                            previous_oid: blob_m(),
+                            entry_mode: EntryMode::Tree,
+                            oid: hex_to_id("10f2f4b82222d2b5c31985130979a91fd87410f7"),
+                        })
+                    ),
+                    (
+                        "f/f".into(),
+                        Conflict {
+                            ours: Addition {
+                                entry_mode: EntryMode::Blob,
+                                oid: blob_m(),
+                            },
+                            theirs: Addition {
+                                entry_mode: EntryMode::Blob,
+                                oid: hex_to_id("13c2aca72ab576cb5f22dc8e7f8ba8ddab553a8a"),
+                            }
+                        }
+                    ),
+                ],
+                "trees are the same on both sides as their differences show in their entries"
+            );
+            Ok(())
+        }
     }
 }
@@ -109,6 +109,22 @@ impl ObjectId {
         }
     }
 
+    /// The hash of an empty blob
+    #[inline]
+    pub const fn empty_blob(hash: Kind) -> ObjectId {
+        match hash {
+            Kind::Sha1 => {
+                ObjectId::Sha1(*b"\xe6\x9d\xe2\x9b\xb2\xd1\xd6\x43\x4b\x8b\x29\xae\x77\x5a\xd8\xc2\xe4\x8c\x53\x91")
+            }
+        }
+    }
+
+    /// Returns true if this is the hash of an empty blob
+    #[inline]
+    pub fn is_empty_blob(&self) -> bool {
+        *self == ObjectId::empty_blob(self.kind())
+    }
+
     /// Returns true if this hash consists of all null bytes
     #[inline]
     pub fn is_null(&self) -> bool {
@@ -14,7 +14,7 @@ use crate::{ObjectId, SIZE_OF_SHA1_DIGEST};
 /// hash `[`kind()`][oid::kind()]`.
 /// We expect to have quite a few bits available for such 'conflict resolution' as most hashes aren't longer
 /// than 64 bytes.
-#[derive(PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[derive(PartialEq, Eq, Ord, PartialOrd)]
 #[repr(transparent)]
 #[allow(non_camel_case_types)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize))]
@@ -22,6 +22,14 @@ pub struct oid {
     bytes: [u8],
 }
 
+// This implementation matches the `Hash` implementation of `ObjectId`, which is required for maps keyed by
+// object ids to be queried with borrowed ids.
+impl std::hash::Hash for oid {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(self.as_bytes())
+    }
+}
+
 /// A utility able to format itself with the given amount of characters in hex
 #[derive(PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct HexDisplay<'a> {
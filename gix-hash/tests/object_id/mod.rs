@@ -41,3 +41,26 @@ mod from_hex {
         }
     }
 }
+
+mod empty {
+    use gix_hash::{Kind, ObjectId};
+
+    #[test]
+    fn blob_and_tree_have_their_well_known_hashes() {
+        assert_eq!(
+            ObjectId::empty_blob(Kind::Sha1).to_string(),
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+        assert_eq!(
+            ObjectId::empty_tree(Kind::Sha1).to_string(),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+        );
+    }
+
+    #[test]
+    fn is_empty_blob() {
+        assert!(ObjectId::empty_blob(Kind::Sha1).is_empty_blob());
+        assert!(!ObjectId::empty_tree(Kind::Sha1).is_empty_blob());
+        assert!(!ObjectId::null(Kind::Sha1).is_empty_blob());
+    }
+}
@@ -13,3 +13,13 @@ mod to_hex_with_len {
         );
     }
 }
+
+#[test]
+fn hash_is_the_same_as_the_one_of_object_ids() {
+    let id = gix_hash::ObjectId::from_hex(b"0123456789abcdef123456789abcdef123456789").expect("valid input");
+    let map: std::collections::HashMap<_, _> = Some((id, ())).into_iter().collect();
+    assert!(
+        map.contains_key(id.as_ref()),
+        "maps keyed by object ids can be queried with borrowed ids"
+    );
+}
@@ -47,6 +47,71 @@ impl<'a> Data<'a> {
             _ => None,
         }
     }
+
+    /// Returns this object as tree iterator whose entries are known to decode successfully, or an error telling
+    /// whether this isn't a tree or the tree is malformed.
+    ///
+    /// Use [`try_into_tree_iter()`][Self::try_into_tree_iter()] to skip the validation and only check the kind.
+    pub fn try_into_tree_iter_validated(self) -> Result<TreeRefIter<'a>, try_into_iter_validated::Error> {
+        self.expect_kind(Kind::Tree)?;
+        validated(TreeRefIter::from_bytes(self.data))
+    }
+
+    /// Returns this object as commit iterator whose tokens are known to decode successfully, or an error telling
+    /// whether this isn't a commit or the commit is malformed.
+    ///
+    /// Use [`try_into_commit_iter()`][Self::try_into_commit_iter()] to skip the validation and only check the kind.
+    pub fn try_into_commit_iter_validated(self) -> Result<CommitRefIter<'a>, try_into_iter_validated::Error> {
+        self.expect_kind(Kind::Commit)?;
+        validated(CommitRefIter::from_bytes(self.data))
+    }
+
+    /// Returns this object as tag iterator whose tokens are known to decode successfully, or an error telling
+    /// whether this isn't a tag or the tag is malformed.
+    ///
+    /// Use [`try_into_tag_iter()`][Self::try_into_tag_iter()] to skip the validation and only check the kind.
+    pub fn try_into_tag_iter_validated(self) -> Result<TagRefIter<'a>, try_into_iter_validated::Error> {
+        self.expect_kind(Kind::Tag)?;
+        validated(TagRefIter::from_bytes(self.data))
+    }
+
+    fn expect_kind(&self, expected: Kind) -> Result<(), try_into_iter_validated::Error> {
+        if self.kind == expected {
+            Ok(())
+        } else {
+            Err(try_into_iter_validated::Error::WrongKind {
+                expected,
+                actual: self.kind,
+            })
+        }
+    }
+}
+
+/// Iterate `iter` to completion to find decode errors, and return it unchanged otherwise.
+fn validated<I, T>(iter: I) -> Result<I, try_into_iter_validated::Error>
+where
+    I: Iterator<Item = Result<T, crate::decode::Error>> + Clone,
+{
+    for item in iter.clone() {
+        item?;
+    }
+    Ok(iter)
+}
+
+/// Types returned by the validating iterator accessors of [`Data`].
+pub mod try_into_iter_validated {
+    use crate::Kind;
+
+    /// Returned by [`crate::Data::try_into_tree_iter_validated()`], [`crate::Data::try_into_commit_iter_validated()`]
+    /// and [`crate::Data::try_into_tag_iter_validated()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Expected object of kind {expected}, but got {actual}")]
+        WrongKind { expected: Kind, actual: Kind },
+        #[error("The object is of the expected kind, but could not be decoded")]
+        Decode(#[from] crate::decode::Error),
+    }
 }
 
 /// Types supporting object hash verification
@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+
+use crate::{tree, tree::EntryRef, TreeRefIter};
+
+/// An iterator over the entries of two trees in the order used by git, pairing entries with the same name and kind,
+/// as created by [`TreeRefIter::merge()`].
+pub struct Merge<'a, 'b> {
+    lhs: TreeRefIter<'a>,
+    rhs: TreeRefIter<'b>,
+    lhs_next: Option<EntryRef<'a>>,
+    rhs_next: Option<EntryRef<'b>>,
+}
+
+impl<'a> TreeRefIter<'a> {
+    /// Iterate the entries of this tree and `other` side by side, returning entries with the same name on both sides
+    /// together, and all others alone on their side, which is how a tree diff sees them.
+    ///
+    /// Entries are returned in the order git sorts tree entries in, which compares the names of trees as if they had
+    /// a trailing slash. Thus the file `foo` comes before the file `foo.txt`, which comes before the tree `foo`, because
+    /// `.` sorts before `/`. This also means that a file and a tree of the same name are never paired, as they are different
+    /// entries to git.
+    ///
+    /// Both trees are expected to be sorted correctly, which is the case for all trees written by git.
+    /// Iteration stops after the first error.
+    pub fn merge<'b>(self, other: TreeRefIter<'b>) -> Merge<'a, 'b> {
+        Merge {
+            lhs: self,
+            rhs: other,
+            lhs_next: None,
+            rhs_next: None,
+        }
+    }
+}
+
+impl<'a, 'b> Iterator for Merge<'a, 'b> {
+    type Item = Result<(Option<EntryRef<'a>>, Option<EntryRef<'b>>), crate::decode::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lhs = match self.lhs_next.take().map(Ok).or_else(|| self.lhs.next()).transpose() {
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(self.stop(err))),
+        };
+        let rhs = match self.rhs_next.take().map(Ok).or_else(|| self.rhs.next()).transpose() {
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(self.stop(err))),
+        };
+        Some(Ok(match (lhs, rhs) {
+            (None, None) => return None,
+            (Some(lhs), None) => (Some(lhs), None),
+            (None, Some(rhs)) => (None, Some(rhs)),
+            (Some(lhs), Some(rhs)) => match tree::compare_paths(lhs.filename, lhs.mode, rhs.filename, rhs.mode) {
+                Ordering::Equal => (Some(lhs), Some(rhs)),
+                Ordering::Less => {
+                    self.rhs_next = Some(rhs);
+                    (Some(lhs), None)
+                }
+                Ordering::Greater => {
+                    self.lhs_next = Some(lhs);
+                    (None, Some(rhs))
+                }
+            },
+        }))
+    }
+}
+
+impl Merge<'_, '_> {
+    fn stop(&mut self, err: crate::decode::Error) -> crate::decode::Error {
+        self.lhs = TreeRefIter::default();
+        self.rhs = TreeRefIter::default();
+        self.lhs_next = None;
+        self.rhs_next = None;
+        err
+    }
+}
@@ -5,6 +5,11 @@ use crate::{
     tree,
 };
 
+mod merge;
+pub use merge::Merge;
+///
+pub mod recursive;
+pub use recursive::Recursive;
 mod ref_iter;
 ///
 pub mod write;
@@ -44,6 +49,27 @@ impl EntryMode {
         matches!(self, EntryMode::Blob | EntryMode::BlobExecutable)
     }
 
+    /// Return true if the entry is a blob which isn't executable.
+    pub fn is_regular_file(&self) -> bool {
+        *self == EntryMode::Blob
+    }
+
+    /// Return true if the entry is an executable blob.
+    pub fn is_executable(&self) -> bool {
+        *self == EntryMode::BlobExecutable
+    }
+
+    /// Return true if the entry is a symbolic link, whose blob contains the path it points to.
+    pub fn is_symlink(&self) -> bool {
+        *self == EntryMode::Link
+    }
+
+    /// Return true if the entry is a commit, as used by submodules, which typically isn't present in the
+    /// object database of the repository containing the tree.
+    pub fn is_commit(&self) -> bool {
+        *self == EntryMode::Commit
+    }
+
     /// Represent the mode as descriptive string.
     pub fn as_str(&self) -> &'static str {
         use EntryMode::*;
@@ -57,6 +83,25 @@ impl EntryMode {
     }
 }
 
+/// Compare the names or paths `lhs` and `rhs` of entries with the respective modes like git does, which compares
+/// the names of trees as if they had a trailing slash.
+///
+/// Thus the file `foo` comes before the file `foo.txt`, which comes before the tree `foo`, because `.` sorts before `/`.
+/// This is the order in which entries are stored in trees and in which [`TreeRefIter::merge()`][crate::TreeRefIter::merge()]
+/// returns them. If `lhs` and `rhs` are paths relative to the same tree, like `dir/foo`, it's also the order in which
+/// `git diff` lists them.
+pub fn compare_paths(lhs: &[u8], lhs_mode: EntryMode, rhs: &[u8], rhs_mode: EntryMode) -> Ordering {
+    let common_len = lhs.len().min(rhs.len());
+    lhs[..common_len].cmp(&rhs[..common_len]).then_with(|| {
+        let terminator =
+            |name: &[u8], mode: EntryMode| name.get(common_len).copied().or_else(|| mode.is_tree().then_some(b'/'));
+        terminator(lhs, lhs_mode)
+            .cmp(&terminator(rhs, rhs_mode))
+            // A tree comes before the paths within it, like `foo` before `foo/bar`.
+            .then_with(|| lhs.len().cmp(&rhs.len()))
+    })
+}
+
 /// An element of a [`TreeRef`][crate::TreeRef::entries].
 #[derive(PartialEq, Eq, Debug, Hash, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -127,4 +172,57 @@ impl EntryMode {
             Commit => b"160000",
         }
     }
+
+    /// Return the mode as six octal digits, the way `git diff` shows it in its headers, like `100644` or `040000`.
+    ///
+    /// Note that trees are written as `40000` in the git internal format, see [`as_bytes()`][Self::as_bytes()].
+    pub fn as_octal_str(&self) -> &'static str {
+        use EntryMode::*;
+        match self {
+            Tree => "040000",
+            Blob => "100644",
+            BlobExecutable => "100755",
+            Link => "120000",
+            Commit => "160000",
+        }
+    }
+}
+
+/// Deserialization
+impl EntryMode {
+    /// Parse `mode` as octal number like `100644`, with leading zeroes being optional, and return the entry mode
+    /// it represents.
+    ///
+    /// Only modes which are valid in trees are accepted, just like when [converting][TryFrom] them from numbers,
+    /// so besides the standard modes as returned by [`as_octal_str()`][Self::as_octal_str()] the rare blob modes
+    /// `100664` and `100640` are accepted as [`Blob`][EntryMode::Blob] as well.
+    pub fn from_octal(mode: &[u8]) -> Result<Self, entry_mode::Error> {
+        let invalid = || entry_mode::Error::InvalidOctal { mode: mode.into() };
+        if mode.is_empty() || mode.len() > 6 {
+            return Err(invalid());
+        }
+        let mut value = 0u32;
+        for digit in mode {
+            if !(b'0'..=b'7').contains(digit) {
+                return Err(invalid());
+            }
+            value = value * 8 + u32::from(digit - b'0');
+        }
+        EntryMode::try_from(value).map_err(|mode| entry_mode::Error::UnknownMode { mode })
+    }
+}
+
+///
+pub mod entry_mode {
+    use crate::bstr::BString;
+
+    /// The error returned by [`EntryMode::from_octal()`][super::EntryMode::from_octal()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Mode {mode:?} is not an octal number of at most 6 digits")]
+        InvalidOctal { mode: BString },
+        #[error("Mode {mode:06o} is not a valid mode of a tree entry")]
+        UnknownMode { mode: u32 },
+    }
 }
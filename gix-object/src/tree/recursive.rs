@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+use bstr::{BString, ByteVec};
+use gix_hash::{oid, ObjectId};
+
+use crate::{tree, TreeRefIter};
+
+/// The error returned by [`Recursive`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[error("The tree {oid} at '{location}' could not be found")]
+    NotFound { oid: ObjectId, location: BString },
+    #[error("The tree {oid} at '{location}' could not be looked up")]
+    Find {
+        #[source]
+        err: E,
+        oid: ObjectId,
+        location: BString,
+    },
+    #[error(transparent)]
+    Decode(#[from] crate::decode::Error),
+}
+
+/// An iterator over all entries of a tree along with their path, descending into subtrees as they are encountered,
+/// as created by [`TreeRefIter::recursive()`].
+///
+/// Each subtree is looked up only once the iterator advances past its entry, with the data of all trees at the same depth
+/// being copied into the same buffer.
+pub struct Recursive<'a, Find> {
+    root: TreeRefIter<'a>,
+    find: Find,
+    /// The data of the subtree currently iterated at each depth, with buffers being kept for reuse once a subtree is done.
+    buffers: Vec<Vec<u8>>,
+    /// The buffer passed to `find`, from which the data of the found tree is copied into `buffers`.
+    find_buf: Vec<u8>,
+    /// The range of the entries left in the buffer of each entered subtree, along with the length of `path` without it.
+    levels: Vec<(Range<usize>, usize)>,
+    /// The tree to enter before the next entry is returned, along with the length of `path` without it.
+    pending: Option<(ObjectId, usize)>,
+    /// The path of the tree currently iterated, or of the pending tree if there is one.
+    path: BString,
+}
+
+impl<'a> TreeRefIter<'a> {
+    /// Return an iterator over the entries of this tree and all of its subtrees along with their path relative to this
+    /// tree, using `find` to obtain the data of subtrees, which is called only when iterating past the entry of a subtree.
+    ///
+    /// Entries are returned depth-first, with each tree being returned right before its entries. Iteration stops after the
+    /// first error, which is also returned if `find` fails or can't find a subtree.
+    pub fn recursive<Find, E>(self, find: Find) -> Recursive<'a, Find>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<TreeRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Recursive {
+            root: self,
+            find,
+            buffers: Vec::new(),
+            find_buf: Vec::new(),
+            levels: Vec::new(),
+            pending: None,
+            path: BString::default(),
+        }
+    }
+}
+
+impl<'a, Find, E> Recursive<'a, Find>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<TreeRefIter<'b>>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn enter(&mut self, id: ObjectId, parent_path_len: usize) -> Result<(), Error<E>> {
+        let depth = self.levels.len();
+        if self.buffers.len() == depth {
+            self.buffers.push(Vec::new());
+        }
+        let buf = &mut self.buffers[depth];
+        match (self.find)(&id, &mut self.find_buf) {
+            Ok(Some(tree)) => {
+                buf.clear();
+                buf.extend_from_slice(tree.data);
+            }
+            Ok(None) => {
+                return Err(Error::NotFound {
+                    oid: id,
+                    location: self.path.clone(),
+                })
+            }
+            Err(err) => {
+                return Err(Error::Find {
+                    err,
+                    oid: id,
+                    location: self.path.clone(),
+                })
+            }
+        }
+        self.levels.push((0..buf.len(), parent_path_len));
+        Ok(())
+    }
+
+    /// Return the next entry of the tree at the current depth, or `None` if it's exhausted.
+    fn next_entry(&mut self) -> Option<Result<tree::Entry, Error<E>>> {
+        let depth = self.levels.len();
+        let (range, _) = match self.levels.last_mut() {
+            Some(level) => level,
+            None => return self.root.next().map(|entry| Ok(entry?.into())),
+        };
+        let mut iter = TreeRefIter::from_bytes(&self.buffers[depth - 1][range.clone()]);
+        let entry = iter.next()?;
+        range.start = range.end - iter.data.len();
+        Some(entry.map(Into::into).map_err(Into::into))
+    }
+
+    fn stop(&mut self) {
+        self.root = TreeRefIter::default();
+        self.levels.clear();
+        self.pending = None;
+    }
+}
+
+impl<'a, Find, E> Iterator for Recursive<'a, Find>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<TreeRefIter<'b>>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Item = Result<(BString, tree::Entry), Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((id, parent_path_len)) = self.pending.take() {
+            if let Err(err) = self.enter(id, parent_path_len) {
+                self.stop();
+                return Some(Err(err));
+            }
+        }
+        loop {
+            match self.next_entry() {
+                Some(Ok(entry)) => {
+                    let parent_path_len = self.path.len();
+                    if !self.path.is_empty() {
+                        self.path.push(b'/');
+                    }
+                    self.path.push_str(&entry.filename);
+                    let path = self.path.clone();
+                    if entry.mode.is_tree() {
+                        self.pending = Some((entry.oid, parent_path_len));
+                    } else {
+                        self.path.truncate(parent_path_len);
+                    }
+                    return Some(Ok((path, entry)));
+                }
+                Some(Err(err)) => {
+                    self.stop();
+                    return Some(Err(err));
+                }
+                None => match self.levels.pop() {
+                    Some((_, parent_path_len)) => self.path.truncate(parent_path_len),
+                    None => return None,
+                },
+            }
+        }
+    }
+}
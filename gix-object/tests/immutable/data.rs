@@ -0,0 +1,80 @@
+use gix_object::{data::try_into_iter_validated::Error, Data, Kind};
+
+use crate::immutable::fixture_bytes;
+
+#[test]
+fn into_iter_accessors_validate_kind_and_data() -> crate::Result {
+    let tree = fixture_bytes("tree", "everything.tree");
+    let entries = Data::new(Kind::Tree, &tree).try_into_tree_iter_validated()?.count();
+    assert_eq!(
+        entries,
+        Data::new(Kind::Tree, &tree).try_into_tree_iter().expect("tree").count()
+    );
+
+    let commit = fixture_bytes("commit", "unsigned.txt");
+    assert!(Data::new(Kind::Commit, &commit)
+        .try_into_commit_iter_validated()?
+        .all(|token| token.is_ok()));
+
+    let tag = fixture_bytes("tag", "signed.txt");
+    assert!(Data::new(Kind::Tag, &tag)
+        .try_into_tag_iter_validated()?
+        .all(|token| token.is_ok()));
+    Ok(())
+}
+
+#[test]
+fn wrong_kind() {
+    let commit = fixture_bytes("commit", "unsigned.txt");
+    let err = Data::new(Kind::Commit, &commit)
+        .try_into_tree_iter_validated()
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::WrongKind {
+                expected: Kind::Tree,
+                actual: Kind::Commit
+            }
+        ),
+        "{err:?}"
+    );
+    assert_eq!(err.to_string(), "Expected object of kind tree, but got commit");
+    assert!(
+        Data::new(Kind::Commit, &commit).try_into_tree_iter().is_none(),
+        "the lenient version only indicates that there is no tree"
+    );
+
+    let tree = fixture_bytes("tree", "everything.tree");
+    assert!(matches!(
+        Data::new(Kind::Tree, &tree).try_into_commit_iter_validated(),
+        Err(Error::WrongKind { .. })
+    ));
+    assert!(matches!(
+        Data::new(Kind::Blob, &tree).try_into_tag_iter_validated(),
+        Err(Error::WrongKind { .. })
+    ));
+}
+
+#[test]
+fn malformed_data() {
+    let tree = fixture_bytes("tree", "everything.tree");
+    let truncated = &tree[..tree.len() / 2];
+    assert!(matches!(
+        Data::new(Kind::Tree, truncated).try_into_tree_iter_validated(),
+        Err(Error::Decode(_))
+    ));
+    assert!(
+        Data::new(Kind::Tree, truncated).try_into_tree_iter().is_some(),
+        "the lenient version doesn't look at the data"
+    );
+
+    assert!(matches!(
+        Data::new(Kind::Commit, b"tree invalid\n").try_into_commit_iter_validated(),
+        Err(Error::Decode(_))
+    ));
+    assert!(matches!(
+        Data::new(Kind::Tag, b"object invalid\n").try_into_tag_iter_validated(),
+        Err(Error::Decode(_))
+    ));
+}
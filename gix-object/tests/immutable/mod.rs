@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use gix_actor::{Sign, Time};
 
 mod commit;
+mod data;
 mod tag;
 mod tree;
 
@@ -55,6 +55,225 @@ mod iter {
     }
 }
 
+fn id(byte: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from([byte; 20])
+}
+
+/// Encode `entries` as tree in the given order, which must be the order git would use.
+fn tree(entries: &[(&str, gix_object::tree::EntryMode, u8)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (filename, mode, byte) in entries {
+        data.extend_from_slice(mode.as_bytes());
+        data.push(b' ');
+        data.extend_from_slice(filename.as_bytes());
+        data.push(0);
+        data.extend_from_slice(id(*byte).as_bytes());
+    }
+    data
+}
+
+mod merge {
+    use gix_object::{bstr::ByteSlice, tree, TreeRefIter};
+
+    use super::tree;
+
+    #[test]
+    fn trees_sort_as_if_they_had_a_trailing_slash() -> crate::Result {
+        let lhs = tree(&[
+            ("a", tree::EntryMode::Blob, 1),
+            ("foo", tree::EntryMode::Blob, 2),
+            ("foo.txt", tree::EntryMode::Blob, 3),
+        ]);
+        let rhs = tree(&[
+            ("foo.txt", tree::EntryMode::Blob, 4),
+            ("foo", tree::EntryMode::Tree, 5),
+            ("z", tree::EntryMode::Blob, 6),
+        ]);
+        let name = |entry: Option<tree::EntryRef<'_>>| {
+            entry.map(|entry| (entry.filename.to_str_lossy().into_owned(), entry.mode))
+        };
+        let actual = TreeRefIter::from_bytes(&lhs)
+            .merge(TreeRefIter::from_bytes(&rhs))
+            .map(|pair| pair.map(|(lhs, rhs)| (name(lhs), name(rhs))))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            actual,
+            vec![
+                (Some(("a".into(), tree::EntryMode::Blob)), None),
+                (Some(("foo".into(), tree::EntryMode::Blob)), None),
+                (
+                    Some(("foo.txt".into(), tree::EntryMode::Blob)),
+                    Some(("foo.txt".into(), tree::EntryMode::Blob))
+                ),
+                (None, Some(("foo".into(), tree::EntryMode::Tree))),
+                (None, Some(("z".into(), tree::EntryMode::Blob))),
+            ],
+            "the file 'foo' and the tree 'foo/' are different entries, with 'foo.txt' sorting between them"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_trees() -> crate::Result {
+        let lhs = tree(&[("a", tree::EntryMode::Blob, 1)]);
+        assert_eq!(
+            TreeRefIter::from_bytes(&[]).merge(TreeRefIter::from_bytes(&[])).count(),
+            0
+        );
+        let actual = TreeRefIter::from_bytes(&[])
+            .merge(TreeRefIter::from_bytes(&lhs))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(actual.len(), 1);
+        assert!(actual[0].0.is_none() && actual[0].1.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn errors_stop_the_iteration() {
+        let data = tree(&[("a", tree::EntryMode::Blob, 1), ("b", tree::EntryMode::Blob, 2)]);
+        let mut iter = TreeRefIter::from_bytes(&data[..data.len() - 1]).merge(TreeRefIter::from_bytes(&[]));
+        assert!(iter.next().expect("first entry").is_ok());
+        assert!(iter.next().expect("an error").is_err());
+        assert!(iter.next().is_none());
+    }
+}
+
+mod compare_paths {
+    use std::cmp::Ordering;
+
+    use gix_object::tree::{compare_paths, EntryMode};
+
+    #[test]
+    fn trees_compare_as_if_they_had_a_trailing_slash() {
+        for (lhs, lhs_mode, rhs, rhs_mode, expected) in [
+            ("a", EntryMode::Blob, "a", EntryMode::Blob, Ordering::Equal),
+            ("a", EntryMode::Tree, "a", EntryMode::Tree, Ordering::Equal),
+            ("a", EntryMode::Blob, "a", EntryMode::Tree, Ordering::Less),
+            ("a", EntryMode::Blob, "a.txt", EntryMode::Blob, Ordering::Less),
+            ("a.txt", EntryMode::Blob, "a", EntryMode::Tree, Ordering::Less),
+            ("a0", EntryMode::Blob, "a", EntryMode::Tree, Ordering::Greater),
+            ("a", EntryMode::Tree, "a/b", EntryMode::Blob, Ordering::Less),
+            ("a/b", EntryMode::Blob, "a.txt", EntryMode::Blob, Ordering::Greater),
+        ] {
+            assert_eq!(
+                compare_paths(lhs.as_bytes(), lhs_mode, rhs.as_bytes(), rhs_mode),
+                expected,
+                "{lhs} {rhs}"
+            );
+            assert_eq!(
+                compare_paths(rhs.as_bytes(), rhs_mode, lhs.as_bytes(), lhs_mode),
+                expected.reverse(),
+                "{rhs} {lhs}"
+            );
+        }
+    }
+}
+
+mod recursive {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use gix_object::{bstr::BString, tree, tree::recursive::Error, TreeRefIter};
+
+    use super::{id, tree};
+
+    #[test]
+    fn all_entries_with_full_path_depth_first() -> crate::Result {
+        let trees: HashMap<_, _> = [
+            (
+                id(10),
+                tree(&[("b", tree::EntryMode::Blob, 2), ("sub", tree::EntryMode::Tree, 11)]),
+            ),
+            (id(11), tree(&[("c", tree::EntryMode::BlobExecutable, 3)])),
+        ]
+        .into_iter()
+        .collect();
+        let root = tree(&[
+            ("a", tree::EntryMode::Blob, 1),
+            ("dir", tree::EntryMode::Tree, 10),
+            ("z", tree::EntryMode::Blob, 4),
+        ]);
+
+        let mut lookups = 0;
+        let actual = TreeRefIter::from_bytes(&root)
+            .recursive(|oid, buf| {
+                lookups += 1;
+                Ok::<_, Infallible>(trees.get(&oid.to_owned()).map(|data| {
+                    buf.clear();
+                    buf.extend_from_slice(data);
+                    TreeRefIter::from_bytes(buf)
+                }))
+            })
+            .map(|item| item.map(|(path, entry)| (path, entry.mode, entry.oid)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            actual,
+            vec![
+                (BString::from("a"), tree::EntryMode::Blob, id(1)),
+                ("dir".into(), tree::EntryMode::Tree, id(10)),
+                ("dir/b".into(), tree::EntryMode::Blob, id(2)),
+                ("dir/sub".into(), tree::EntryMode::Tree, id(11)),
+                ("dir/sub/c".into(), tree::EntryMode::BlobExecutable, id(3)),
+                ("z".into(), tree::EntryMode::Blob, id(4)),
+            ]
+        );
+        assert_eq!(lookups, 2, "each subtree is looked up once");
+        Ok(())
+    }
+
+    #[test]
+    fn subtrees_are_looked_up_lazily() {
+        let root = tree(&[("a", tree::EntryMode::Tree, 10), ("b", tree::EntryMode::Blob, 1)]);
+        let mut lookups = 0;
+        let mut iter = TreeRefIter::from_bytes(&root).recursive(|_oid, _buf| {
+            lookups += 1;
+            Ok::<_, Infallible>(None)
+        });
+        assert_eq!(iter.next().expect("an entry").expect("no error").0, "a");
+        drop(iter);
+        assert_eq!(lookups, 0, "the subtree is only needed to advance past its entry");
+    }
+
+    #[test]
+    fn missing_subtrees_are_an_error_which_stops_the_iteration() {
+        let sub = tree(&[("missing", tree::EntryMode::Tree, 11)]);
+        let root = tree(&[("dir", tree::EntryMode::Tree, 10), ("file", tree::EntryMode::Blob, 1)]);
+        let mut iter = TreeRefIter::from_bytes(&root).recursive(|oid, buf| {
+            Ok::<_, Infallible>((oid == id(10)).then(|| {
+                buf.clear();
+                buf.extend_from_slice(&sub);
+                TreeRefIter::from_bytes(buf)
+            }))
+        });
+        assert_eq!(iter.next().expect("an entry").expect("no error").0, "dir");
+        assert_eq!(iter.next().expect("an entry").expect("no error").0, "dir/missing");
+        match iter.next() {
+            Some(Err(Error::NotFound { oid, location })) => {
+                assert_eq!(oid, id(11));
+                assert_eq!(location, "dir/missing");
+            }
+            other => unreachable!("expected a missing tree, got {other:?}"),
+        }
+        assert!(iter.next().is_none(), "iteration stops after an error");
+    }
+
+    #[test]
+    fn lookup_errors_are_returned_and_stop_the_iteration() {
+        let root = tree(&[("dir", tree::EntryMode::Tree, 10), ("file", tree::EntryMode::Blob, 1)]);
+        let mut iter = TreeRefIter::from_bytes(&root)
+            .recursive(|_oid, _buf| Err(std::io::Error::new(std::io::ErrorKind::Other, "lookup failed")));
+        assert_eq!(iter.next().expect("an entry").expect("no error").0, "dir");
+        match iter.next() {
+            Some(Err(Error::Find { err, oid, location })) => {
+                assert_eq!(err.to_string(), "lookup failed");
+                assert_eq!(oid, id(10));
+                assert_eq!(location, "dir");
+            }
+            other => unreachable!("expected a lookup error, got {other:?}"),
+        }
+        assert!(iter.next().is_none(), "iteration stops after an error");
+    }
+}
+
 mod from_bytes {
     use gix_object::{bstr::ByteSlice, tree, tree::EntryRef, TreeRef};
 
@@ -141,4 +360,81 @@ mod entry_mode {
             "it should not change without notice"
         );
     }
+
+    #[test]
+    fn predicates() {
+        for (mode, is_blob, is_regular_file, is_executable, is_symlink, is_commit, is_tree) in [
+            (EntryMode::Blob, true, true, false, false, false, false),
+            (EntryMode::BlobExecutable, true, false, true, false, false, false),
+            (EntryMode::Link, false, false, false, true, false, false),
+            (EntryMode::Commit, false, false, false, false, true, false),
+            (EntryMode::Tree, false, false, false, false, false, true),
+        ] {
+            assert_eq!(mode.is_blob(), is_blob, "{mode:?}");
+            assert_eq!(mode.is_regular_file(), is_regular_file, "{mode:?}");
+            assert_eq!(mode.is_executable(), is_executable, "{mode:?}");
+            assert_eq!(mode.is_symlink(), is_symlink, "{mode:?}");
+            assert_eq!(mode.is_commit(), is_commit, "{mode:?}");
+            assert_eq!(mode.is_tree(), is_tree, "{mode:?}");
+            assert_eq!(mode.is_no_tree(), !is_tree, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn octal_round_trip() -> crate::Result {
+        for (mode, octal) in [
+            (EntryMode::Tree, "040000"),
+            (EntryMode::Blob, "100644"),
+            (EntryMode::BlobExecutable, "100755"),
+            (EntryMode::Link, "120000"),
+            (EntryMode::Commit, "160000"),
+        ] {
+            assert_eq!(mode.as_octal_str(), octal);
+            assert_eq!(EntryMode::from_octal(octal.as_bytes())?, mode);
+            assert_eq!(
+                EntryMode::from_octal(mode.as_bytes())?,
+                mode,
+                "the git internal format can be parsed as well"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_octal_rejects_invalid_modes() {
+        use gix_object::tree::entry_mode::Error;
+        for invalid in [&b""[..], b"1006448", b"10064a", b"-10064", b"100 644"] {
+            assert!(
+                matches!(EntryMode::from_octal(invalid), Err(Error::InvalidOctal { .. })),
+                "{invalid:?}"
+            );
+        }
+        for (unknown, mode) in [(&b"100600"[..], 0o100600), (b"100000", 0o100000), (b"0", 0)] {
+            assert!(
+                matches!(EntryMode::from_octal(unknown), Err(Error::UnknownMode { mode: actual }) if actual == mode),
+                "{unknown:?}"
+            );
+        }
+        assert_eq!(
+            EntryMode::from_octal(b"100600").unwrap_err().to_string(),
+            "Mode 100600 is not a valid mode of a tree entry"
+        );
+    }
+
+    #[test]
+    fn from_octal_accepts_what_trees_accept() -> crate::Result {
+        for mode in ["100664", "100640"] {
+            assert_eq!(
+                EntryMode::from_octal(mode.as_bytes())?,
+                EntryMode::Blob,
+                "rare blob modes are known as blobs"
+            );
+            assert_eq!(
+                EntryMode::try_from(u32::from_str_radix(mode, 8)?),
+                Ok(EntryMode::Blob),
+                "just like when converting numbers"
+            );
+        }
+        Ok(())
+    }
 }
@@ -41,35 +41,67 @@ pub enum Error {
 /// `./info/alternates` file into canonical paths and resolve relative paths with the help of the `current_dir`.
 /// If no alternate object database was resolved, the resulting `Vec` is empty (it is not an error
 /// if there are no alternates).
-/// It is an error once a repository is seen again as it would lead to a cycle.
+///
+/// Directories which are reachable through more than one alternate are only returned once, but it is an error
+/// if an alternate leads back to one of the directories it was reached through as it would lead to a cycle.
+/// The [error][Error::Cycle] lists the canonicalized directories forming the cycle in the order they link to each other.
 pub fn resolve(
     objects_directory: impl Into<PathBuf>,
     current_dir: impl AsRef<std::path::Path>,
 ) -> Result<Vec<PathBuf>, Error> {
     let relative_base = objects_directory.into();
-    let mut dirs = vec![(0, relative_base.clone())];
-    let mut out = Vec::new();
     let cwd = current_dir.as_ref();
-    let mut seen = vec![gix_path::realpath_opts(&relative_base, cwd, MAX_SYMLINKS)?];
-    while let Some((depth, dir)) = dirs.pop() {
-        match fs::read(dir.join("info").join("alternates")) {
-            Ok(input) => {
-                for path in parse::content(&input)?.into_iter() {
-                    let path = relative_base.join(path);
-                    let path_canonicalized = gix_path::realpath_opts(&path, cwd, MAX_SYMLINKS)?;
-                    if seen.contains(&path_canonicalized) {
-                        return Err(Error::Cycle(seen));
-                    }
-                    seen.push(path_canonicalized);
-                    dirs.push((depth + 1, path));
+    let root = gix_path::realpath_opts(&relative_base, cwd, MAX_SYMLINKS)?;
+    // All canonicalized directories seen so far, which are only explored once.
+    let mut seen = vec![root.clone()];
+    // The canonicalized directories which are currently explored, each one linking to the next.
+    let mut chain = vec![root];
+    // For each directory in `chain`, its alternates which are yet to be explored.
+    let mut pending = vec![alternates_of(&relative_base, &relative_base, cwd)?];
+    let mut out = Vec::new();
+    while let Some(alternates) = pending.last_mut() {
+        match alternates.pop() {
+            Some((path, path_canonicalized)) => {
+                if let Some(cycle_start) = chain.iter().position(|dir| *dir == path_canonicalized) {
+                    return Err(Error::Cycle(chain[cycle_start..].to_vec()));
+                }
+                if seen.contains(&path_canonicalized) {
+                    continue;
                 }
+                seen.push(path_canonicalized.clone());
+                pending.push(alternates_of(&path, &relative_base, cwd)?);
+                chain.push(path_canonicalized);
+                out.push(path);
+            }
+            None => {
+                pending.pop();
+                chain.pop();
             }
-            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
-            Err(err) => return Err(err.into()),
-        };
-        if depth != 0 {
-            out.push(dir);
         }
     }
     Ok(out)
 }
+
+/// Return the alternates listed in `dir` as pairs of their path and their canonicalized path, in reverse order
+/// so they can be popped in the order they are listed.
+fn alternates_of(
+    dir: &std::path::Path,
+    relative_base: &std::path::Path,
+    cwd: &std::path::Path,
+) -> Result<Vec<(PathBuf, PathBuf)>, Error> {
+    let input = match fs::read(dir.join("info").join("alternates")) {
+        Ok(input) => input,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut alternates = parse::content(&input)?
+        .into_iter()
+        .map(|path| {
+            let path = relative_base.join(path);
+            let path_canonicalized = gix_path::realpath_opts(&path, cwd, MAX_SYMLINKS)?;
+            Ok((path, path_canonicalized))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    alternates.reverse();
+    Ok(alternates)
+}
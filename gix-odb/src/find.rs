@@ -30,6 +30,15 @@ pub mod existing_object {
         #[error("Expected object of kind {expected}")]
         ObjectKind { expected: gix_object::Kind },
     }
+
+    impl<T: std::error::Error + 'static> From<super::existing::Error<T>> for Error<T> {
+        fn from(err: super::existing::Error<T>) -> Self {
+            match err {
+                super::existing::Error::Find(err) => Error::Find(err),
+                super::existing::Error::NotFound { oid } => Error::NotFound { oid },
+            }
+        }
+    }
 }
 
 ///
@@ -7,6 +7,14 @@ impl Store {
         &self.path
     }
 
+    /// Resolve the object directories of all linked repositories by following the alternates of [our path][Self::path()],
+    /// which is where objects are looked up in addition to our own objects.
+    ///
+    /// Note that the alternates are read from disk each time this is called.
+    pub fn alternate_dirs(&self) -> Result<Vec<std::path::PathBuf>, crate::alternate::Error> {
+        crate::alternate::resolve(&self.path, &self.current_dir)
+    }
+
     /// The kind of object hash to assume when dealing with pack indices and pack data files.
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.object_hash
@@ -2,7 +2,7 @@ use std::{convert::TryInto, ops::Deref};
 
 use gix_pack::cache::DecodeEntry;
 
-use crate::store::{handle, load_index};
+use crate::store::{handle, load_index, VerifyMode};
 
 pub(crate) mod error {
     use crate::{loose, pack};
@@ -33,6 +33,13 @@ pub(crate) mod error {
             /// The original object to lookup
             id: gix_hash::ObjectId,
         },
+        #[error("Object expected to have id {expected}, but actual id was {actual}")]
+        ChecksumMismatch {
+            /// The id the object was looked up with.
+            expected: gix_hash::ObjectId,
+            /// The id computed from the decoded object.
+            actual: gix_hash::ObjectId,
+        },
         #[error("An error occurred when looking up a ref delta base object {} to decode {}", .base_id, .id)]
         DeltaBaseLookup {
             #[source]
@@ -272,6 +279,7 @@ where
                         if idx != 0 {
                             snapshot.indices.swap(0, idx);
                         }
+                        self.verify_if_needed(id, &res.0)?;
                         return Ok(Some(res));
                     }
                 }
@@ -280,10 +288,11 @@ where
             for lodb in snapshot.loose_dbs.iter() {
                 // TODO: remove this double-lookup once the borrow checker allows it.
                 if lodb.contains(id) {
-                    return lodb
-                        .try_find(id, buffer)
-                        .map(|obj| obj.map(|obj| (obj, None)))
-                        .map_err(Into::into);
+                    let obj = lodb.try_find(id, buffer)?;
+                    if let Some(obj) = &obj {
+                        self.verify_if_needed(id, obj)?;
+                    }
+                    return Ok(obj.map(|obj| (obj, None)));
                 }
             }
 
@@ -297,6 +306,18 @@ where
         }
     }
 
+    fn verify_if_needed(&self, id: &gix_hash::oid, obj: &gix_object::Data<'_>) -> Result<(), Error> {
+        match self.verify {
+            VerifyMode::Never => Ok(()),
+            VerifyMode::OnDecode => obj.verify_checksum(id).map_err(
+                |gix_object::data::verify::Error::ChecksumMismatch { desired, actual }| Error::ChecksumMismatch {
+                    expected: desired,
+                    actual,
+                },
+            ),
+        }
+    }
+
     pub(crate) fn clear_cache(&self) {
         self.packed_object_count.borrow_mut().take();
     }
@@ -0,0 +1,58 @@
+use std::ops::Deref;
+
+use gix_hash::{oid, ObjectId};
+use gix_pack::cache::DecodeEntry;
+
+use crate::store::{find::Error, types};
+
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    /// Find all objects identified by `ids` and call `cb` with each id along with its object, or `None` if it could not be found.
+    ///
+    /// Objects are looked up in order of their location in the packs they are stored in, with objects that aren't packed
+    /// coming last. This benefits from locality, and delta bases shared by multiple objects only have to be decoded once
+    /// if `pack_cache` keeps them.
+    /// **This means that `cb` is called in order of pack locality, not in the order of `ids`.**
+    ///
+    /// Each object passed to `cb` borrows from an internal buffer which is reused for the next object.
+    pub fn find_many<E>(
+        &self,
+        ids: &[ObjectId],
+        pack_cache: &mut impl DecodeEntry,
+        mut cb: impl FnMut(&oid, Option<gix_object::Data<'_>>) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        E: From<Error>,
+    {
+        let mut ids_by_location: Vec<_> = ids.iter().map(|id| (self.pack_location(id), id)).collect();
+        ids_by_location.sort_by(|(a, _), (b, _)| (a.is_none(), a).cmp(&(b.is_none(), b)));
+
+        let mut buf = Vec::new();
+        for (_, id) in ids_by_location {
+            let obj = gix_pack::Find::try_find_cached(self, id, &mut buf, pack_cache)?.map(|(obj, _location)| obj);
+            cb(id, obj)?;
+        }
+        Ok(())
+    }
+
+    /// Return the pack and offset at which the object with `id` is stored, for sorting only, or `None` if it isn't packed.
+    fn pack_location(&self, id: &oid) -> Option<(types::IndexId, Option<gix_pack::multi_index::PackIndex>, u64)> {
+        // Assure the index containing `id` is loaded.
+        if !gix_pack::Find::contains(self, id) {
+            return None;
+        }
+        let mut snapshot = self.snapshot.borrow_mut();
+        snapshot.indices.iter_mut().find_map(|index| {
+            index.lookup(id).map(|outcome| {
+                let location = outcome.object_index;
+                (
+                    location.pack_id.index,
+                    location.pack_id.multipack_index,
+                    location.pack_offset,
+                )
+            })
+        })
+    }
+}
@@ -9,7 +9,7 @@ use std::{
 use gix_features::threading::OwnShared;
 use gix_hash::oid;
 
-use crate::store::{handle, types, RefreshMode};
+use crate::store::{handle, types, RefreshMode, VerifyMode};
 
 pub(crate) enum SingleOrMultiIndex {
     Single {
@@ -256,6 +256,7 @@ impl super::Store {
             store: self.clone(),
             refresh: RefreshMode::default(),
             ignore_replacements: false,
+            verify: Default::default(),
             token: Some(token),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
@@ -272,6 +273,7 @@ impl super::Store {
             store: self.clone(),
             refresh: Default::default(),
             ignore_replacements: false,
+            verify: Default::default(),
             token: Some(token),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
@@ -326,6 +328,14 @@ where
     pub fn refresh_mode(&mut self) -> RefreshMode {
         self.refresh
     }
+
+    /// Hash each object after it was obtained to assure it matches the id it was looked up with.
+    ///
+    /// This is useful to detect corrupted objects early, for instance when long-running operations work with packs
+    /// received over unreliable connections.
+    pub fn verify_on_decode(&mut self) {
+        self.verify = VerifyMode::OnDecode;
+    }
 }
 
 impl<S> Drop for super::Handle<S>
@@ -363,6 +373,7 @@ impl super::Handle<Rc<super::Store>> {
         let mut cache = store.to_handle_arc();
         cache.refresh = self.refresh;
         cache.max_recursion_depth = self.max_recursion_depth;
+        cache.verify = self.verify;
         Ok(cache)
     }
 }
@@ -383,6 +394,7 @@ where
             store: self.store.clone(),
             refresh: self.refresh,
             ignore_replacements: self.ignore_replacements,
+            verify: self.verify,
             token: {
                 let token = self.store.register_handle();
                 match self.token.as_ref().expect("token is always set here ") {
@@ -63,6 +63,7 @@ pub struct AllObjects {
     num_objects: usize,
     loose_dbs: Arc<Vec<loose::Store>>,
     order: Ordering,
+    seen: Option<gix_hashtable::HashSet>,
 }
 
 /// Builder
@@ -72,6 +73,29 @@ impl AllObjects {
         self.order = order;
         self
     }
+
+    /// Only return each object id once, even if it is contained in multiple packs, linked databases or as loose object.
+    ///
+    /// Note that this requires to keep all object ids seen so far in memory, which is pre-allocated according to
+    /// [`len_objects()`][AllObjects::len_objects()].
+    pub fn deduplicated(mut self) -> Self {
+        self.seen = Some(gix_hashtable::HashSet::with_capacity_and_hasher(
+            self.num_objects,
+            Default::default(),
+        ));
+        self
+    }
+}
+
+impl AllObjects {
+    /// Return the amount of objects in all packs of all linked databases as seen when this iterator was created, as a hint
+    /// for how many objects will be returned.
+    ///
+    /// Loose objects aren't counted, and objects contained in multiple packs are counted multiple times, so the amount
+    /// of objects actually returned may be higher or lower, particularly if [deduplicated][AllObjects::deduplicated()].
+    pub fn len_objects(&self) -> usize {
+        self.num_objects
+    }
 }
 
 impl AllObjects {
@@ -110,6 +134,7 @@ impl AllObjects {
             loose_dbs,
             num_objects: packed_objects,
             order,
+            seen: None,
         })
     }
 }
@@ -157,6 +182,25 @@ impl Iterator for AllObjects {
     type Item = Result<ObjectId, loose::iter::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let res = self.next_possibly_duplicate()?;
+            if let (Some(seen), Ok(id)) = (&mut self.seen, &res) {
+                if !seen.insert(*id) {
+                    continue;
+                }
+            }
+            return Some(res);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let lower_bound = if self.seen.is_some() { 0 } else { self.num_objects };
+        (lower_bound, None)
+    }
+}
+
+impl AllObjects {
+    fn next_possibly_duplicate(&mut self) -> Option<<Self as Iterator>::Item> {
         match &mut self.state {
             State::Depleted => None,
             State::Pack {
@@ -190,7 +234,7 @@ impl Iterator for AllObjects {
                             }
                         }
                     }
-                    self.next()
+                    self.next_possibly_duplicate()
                 }
             }
             State::Loose { iter, index } => match iter.next() {
@@ -200,7 +244,7 @@ impl Iterator for AllObjects {
                     match self.loose_dbs.get(*index).map(|ldb| ldb.iter()) {
                         Some(new_iter) => {
                             *iter = new_iter;
-                            self.next()
+                            self.next_possibly_duplicate()
                         }
                         None => {
                             self.state = State::Depleted;
@@ -211,10 +255,6 @@ impl Iterator for AllObjects {
             },
         }
     }
-
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.num_objects, None)
-    }
 }
 
 impl<S> super::Handle<S>
@@ -223,6 +263,9 @@ where
 {
     /// Return an iterator over all, _possibly duplicate_, objects, first the ones in all packs of all linked databases (via alternates),
     /// followed by all loose objects.
+    ///
+    /// Use [`AllObjects::deduplicated()`] to see each object only once, and [`AllObjects::len_objects()`]
+    /// for an estimate of how many objects there are.
     pub fn iter(&self) -> Result<AllObjects, dynamic::load_index::Error> {
         AllObjects::new(self.store_ref())
     }
@@ -21,6 +21,8 @@ where
 
     /// If true, replacements will not be performed even if these are available.
     pub ignore_replacements: bool,
+    /// Defines whether or not decoded objects are hashed to assure they match the id they were looked up with.
+    pub verify: VerifyMode,
 
     pub(crate) token: Option<handle::Mode>,
     snapshot: RefCell<load_index::Snapshot>,
@@ -45,6 +47,19 @@ impl Default for RefreshMode {
     }
 }
 
+/// Decide whether objects are verified after they were obtained.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VerifyMode {
+    /// Trust the object database and never verify objects, which is the fastest option and the default.
+    #[default]
+    Never,
+    /// Hash each object after it was decoded and fail with [`ChecksumMismatch`][find::Error::ChecksumMismatch]
+    /// if it doesn't match the id it was looked up with.
+    ///
+    /// This catches corruption early, at the cost of hashing every object that is read.
+    OnDecode,
+}
+
 impl RefreshMode {
     /// Set this refresh mode to never refresh.
     pub fn never(&mut self) {
@@ -60,6 +75,8 @@ pub mod prefix;
 
 mod header;
 
+mod find_many;
+
 ///
 pub mod iter;
 
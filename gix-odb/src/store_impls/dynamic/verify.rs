@@ -106,7 +106,139 @@ pub mod integrity {
     }
 }
 
+///
+pub mod packs {
+    use std::path::PathBuf;
+
+    use crate::pack;
+
+    /// The error recorded in a [`Report`] of [`Store::verify_packs()`][crate::Store::verify_packs()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        IndexOpen(#[from] pack::index::init::Error),
+        #[error(transparent)]
+        PackOpen(#[from] pack::data::init::Error),
+        #[error("The pack trailer doesn't match the pack data")]
+        Checksum(#[from] pack::verify::checksum::Error),
+        #[error("The entry of object {id} at pack offset {pack_offset} has CRC32 {actual:#010x}, but the index expects {expected:#010x}")]
+        Crc32Mismatch {
+            id: gix_hash::ObjectId,
+            pack_offset: pack::data::Offset,
+            expected: u32,
+            actual: u32,
+        },
+        #[error("The entry of object {id} at pack offset {pack_offset} lies outside of the pack data")]
+        EntryOutOfBounds {
+            id: gix_hash::ObjectId,
+            pack_offset: pack::data::Offset,
+        },
+        #[error("Interrupted by user")]
+        Interrupted,
+    }
+
+    /// The outcome of verifying a single pack with [`Store::verify_packs()`][crate::Store::verify_packs()].
+    #[derive(Debug)]
+    pub struct Report {
+        /// The path to the index of the pack, or `None` if the pack is governed by a multi-pack index
+        /// and its own index doesn't exist, in which case only the pack trailer is verified.
+        pub index_path: Option<PathBuf>,
+        /// The path to the pack data file.
+        pub pack_path: PathBuf,
+        /// The amount of entries whose CRC32 matched the one stored in the index.
+        ///
+        /// Note that version 1 indices don't store a CRC32, so their entries are only verified by the pack trailer,
+        /// just like all entries of packs without index, and are only counted if it matches.
+        pub num_objects_verified: usize,
+        /// The first error encountered, or `None` if the pack is intact.
+        pub error: Option<Error>,
+    }
+}
+
 impl super::Store {
+    /// Verify each pack like `git verify-pack` does, without decoding any object, by comparing the CRC32 of each entry
+    /// with the one stored in its index and by verifying the hash in the pack trailer, using up to `thread_limit` threads
+    /// to check multiple packs in parallel.
+    ///
+    /// All entries are checked even if some of them are corrupt, and the first error is recorded in the [report][packs::Report]
+    /// of each pack, which are returned in the order of the packs in the store. The pack trailer is always verified.
+    /// Packs of multi-pack indices are verified using their own index as named in the multi-pack index, if it exists.
+    ///
+    /// Note that this will not force loading all indices or packs permanently, as we will only use the momentarily loaded disk state.
+    /// This does, however, include all alternates.
+    pub fn verify_packs(
+        &self,
+        thread_limit: Option<usize>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<packs::Report>, integrity::Error> {
+        let mut index = self.index.load();
+        if !index.is_initialized() {
+            self.consolidate_with_disk_state(true, false)?;
+            index = self.index.load();
+            assert!(
+                index.is_initialized(),
+                "BUG: after consolidating successfully, we have an initialized index"
+            )
+        }
+
+        let mut paths = Vec::new();
+        for slot_index in &index.slot_indices {
+            let slot = &self.files[*slot_index];
+            if slot.generation.load(Ordering::SeqCst) != index.generation {
+                return Err(integrity::Error::NeedsRetryDueToChangeOnDisk);
+            }
+            let files = slot.files.load();
+            let files = Option::as_ref(&files).ok_or(integrity::Error::NeedsRetryDueToChangeOnDisk)?;
+            match files {
+                IndexAndPacks::Index(bundle) => {
+                    paths.push((Some(bundle.index.path().to_owned()), bundle.data.path().to_owned()));
+                }
+                IndexAndPacks::MultiIndex(bundle) => {
+                    let index;
+                    let index = match bundle.multi_index.loaded() {
+                        Some(index) => index.deref(),
+                        None => {
+                            index = pack::multi_index::File::at(bundle.multi_index.path())?;
+                            &index
+                        }
+                    };
+                    let index_dir = bundle.multi_index.path().parent().expect("file in a directory");
+                    paths.extend(index.index_names().iter().zip(&bundle.data).map(|(index_name, data)| {
+                        let index_path = index_dir.join(index_name);
+                        (index_path.is_file().then_some(index_path), data.path().to_owned())
+                    }));
+                }
+            }
+        }
+
+        let object_hash = self.object_hash;
+        let num_packs = paths.len();
+        let mut reports = gix_features::parallel::in_parallel_if(
+            || num_packs > 1,
+            paths.into_iter().enumerate(),
+            thread_limit,
+            |_| (),
+            |(order, (index_path, pack_path)), _| {
+                let (num_objects_verified, error) =
+                    verify_pack(index_path.as_deref(), &pack_path, object_hash, should_interrupt);
+                (
+                    order,
+                    packs::Report {
+                        index_path,
+                        pack_path,
+                        num_objects_verified,
+                        error,
+                    },
+                )
+            },
+            CollectReports::default(),
+        )
+        .unwrap_or_else(|err: std::convert::Infallible| match err {});
+        reports.sort_by_key(|(order, _)| *order);
+        Ok(reports.into_iter().map(|(_, report)| report).collect())
+    }
+
     /// Check the integrity of all objects as per the given `options`.
     ///
     /// Note that this will not not force loading all indices or packs permanently, as we will only use the momentarily loaded disk state.
@@ -265,3 +397,94 @@ impl super::Store {
         })
     }
 }
+
+/// Return the amount of entries of the pack at `pack_path` whose CRC32 matches the one in the index at `index_path`,
+/// or which are verified by the pack trailer alone, along with the first error encountered.
+fn verify_pack(
+    index_path: Option<&std::path::Path>,
+    pack_path: &std::path::Path,
+    object_hash: gix_hash::Kind,
+    should_interrupt: &AtomicBool,
+) -> (usize, Option<packs::Error>) {
+    let index = match index_path
+        .map(|path| pack::index::File::at(path, object_hash))
+        .transpose()
+    {
+        Ok(index) => index,
+        Err(err) => return (0, Some(err.into())),
+    };
+    let data = match pack::data::File::at(pack_path, object_hash) {
+        Ok(data) => data,
+        Err(err) => return (0, Some(err.into())),
+    };
+
+    let mut entries: Vec<_> = index.iter().flat_map(|index| index.iter()).collect();
+    entries.sort_by_key(|entry| entry.pack_offset);
+    let mut num_verified = 0;
+    // Entries which can only be verified by the pack trailer.
+    let mut num_unverified = if index.is_none() {
+        data.num_objects() as usize
+    } else {
+        0
+    };
+    let mut first_error = None;
+    for (idx, entry) in entries.iter().enumerate() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return (num_verified, Some(packs::Error::Interrupted));
+        }
+        let entry_end = entries
+            .get(idx + 1)
+            .map_or(data.pack_end() as u64, |next| next.pack_offset);
+        if entry.pack_offset >= entry_end || entry_end > data.pack_end() as u64 {
+            first_error.get_or_insert(packs::Error::EntryOutOfBounds {
+                id: entry.oid,
+                pack_offset: entry.pack_offset,
+            });
+            continue;
+        }
+        match entry.crc32 {
+            Some(expected) => {
+                let actual = data.entry_crc32(entry.pack_offset, (entry_end - entry.pack_offset) as usize);
+                if actual == expected {
+                    num_verified += 1;
+                } else {
+                    first_error.get_or_insert(packs::Error::Crc32Mismatch {
+                        id: entry.oid,
+                        pack_offset: entry.pack_offset,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+            None => num_unverified += 1,
+        }
+    }
+
+    match data.verify_checksum(gix_features::progress::Discard, should_interrupt) {
+        Ok(_) => num_verified += num_unverified,
+        Err(err) => {
+            first_error.get_or_insert(err.into());
+        }
+    }
+    (num_verified, first_error)
+}
+
+/// Collect the reports of all packs along with their original position.
+#[derive(Default)]
+struct CollectReports(Vec<(usize, packs::Report)>);
+
+impl gix_features::parallel::Reduce for CollectReports {
+    type Input = (usize, packs::Report);
+    type FeedProduce = ();
+    type Output = Vec<(usize, packs::Report)>;
+    type Error = std::convert::Infallible;
+
+    fn feed(&mut self, item: Self::Input) -> Result<Self::FeedProduce, Self::Error> {
+        self.0.push(item);
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.0)
+    }
+}
@@ -116,6 +116,11 @@ impl Store {
     ) -> Result<gix_hash::ObjectId, Error> {
         let id = gix_hash::ObjectId::from(hash.digest());
         let object_path = loose::hash_path(&id, self.path.clone());
+        // Objects are named by their content, so an existing object is the same one and must not be replaced,
+        // for instance to not change its permissions or modification time.
+        if object_path.is_file() {
+            return Ok(id);
+        }
         let object_dir = object_path
             .parent()
             .expect("each object path has a 1 hex-bytes directory");
@@ -82,6 +82,96 @@ fn circular_alternates_are_detected_with_relative_paths() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn self_referential_alternates_are_detected() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (from, _) = alternate(tmp.path().join("a"), tmp.path().join("a"))?;
+
+    let err = alternate::resolve(from, std::env::current_dir()?).expect_err("a cycle");
+    let message = err.to_string();
+    match err {
+        alternate::Error::Cycle(chain) => {
+            assert_eq!(chain.len(), 1, "only the directory itself forms the cycle");
+            assert_eq!(chain[0].file_name().expect("non-root"), "a");
+            let a = chain[0].display();
+            assert_eq!(message, format!("Alternates form a cycle: '{a}' -> {a}"));
+        }
+        err => unreachable!("should be a specific kind of error: {:?}", err),
+    }
+    Ok(())
+}
+
+#[test]
+fn cycles_are_reported_without_the_directories_leading_to_them() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (from, _) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+    alternate(tmp.path().join("b"), tmp.path().join("c"))?;
+    alternate(tmp.path().join("c"), tmp.path().join("b"))?;
+
+    match alternate::resolve(from, std::env::current_dir()?) {
+        Err(alternate::Error::Cycle(chain)) => {
+            assert_eq!(
+                chain
+                    .into_iter()
+                    .map(|p| p.file_name().expect("non-root").to_str().expect("utf8").to_owned())
+                    .collect::<Vec<_>>(),
+                vec!["b", "c"]
+            );
+        }
+        res => unreachable!("should be a specific kind of error: {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn cycles_between_sibling_alternates_are_detected() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (from, b) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+    let c = tmp.path().join("c");
+    std::fs::create_dir_all(&c)?;
+    std::fs::write(
+        from.join("info").join("alternates"),
+        format!("{}\n{}\n", b.display(), c.display()),
+    )?;
+    alternate(&b, &c)?;
+    alternate(&c, &b)?;
+
+    match alternate::resolve(from, std::env::current_dir()?) {
+        Err(alternate::Error::Cycle(chain)) => {
+            assert_eq!(
+                chain
+                    .into_iter()
+                    .map(|p| p.file_name().expect("non-root").to_str().expect("utf8").to_owned())
+                    .collect::<Vec<_>>(),
+                vec!["b", "c"],
+                "directories which were seen before but are still explored are part of the cycle"
+            );
+        }
+        res => unreachable!("should be a specific kind of error: {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn directories_reachable_through_multiple_alternates_are_returned_once() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (from, b) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+    let c = tmp.path().join("c");
+    let d = tmp.path().join("d");
+    std::fs::create_dir_all(&c)?;
+    std::fs::write(
+        from.join("info").join("alternates"),
+        format!("{}\n{}\n", b.display(), c.display()),
+    )?;
+    alternate(&b, &d)?;
+    alternate(&c, &d)?;
+
+    let mut alternates = alternate::resolve(from, std::env::current_dir()?)?;
+    alternates.sort();
+    assert_eq!(alternates, vec![b, c, d], "the shared alternate isn't a cycle");
+    Ok(())
+}
+
 #[test]
 fn single_link_with_comment_before_path_and_ansi_c_escape() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -39,3 +39,31 @@ fn pack_object() {
         })
     );
 }
+
+#[test]
+fn sizes_of_all_objects_including_deltas_match_their_data() -> crate::Result {
+    use gix_odb::{Find, HeaderExt};
+
+    let db = db();
+    let mut buf = Vec::new();
+    for id in db.iter()? {
+        let id = id?;
+        let expected = db.try_find(id, &mut buf)?.expect("object exists").data.len() as u64;
+        assert_eq!(db.header(id)?.size(), expected, "{id}");
+    }
+    Ok(())
+}
+
+#[test]
+fn missing_objects_have_no_header() -> crate::Result {
+    use gix_odb::HeaderExt;
+
+    let db = db();
+    let missing = gix_hash::ObjectId::null(gix_hash::Kind::Sha1);
+    assert!(gix_odb::Header::try_header(&db, missing)?.is_none());
+    assert!(matches!(
+        db.header(missing),
+        Err(gix_odb::find::existing::Error::NotFound { .. })
+    ));
+    Ok(())
+}
@@ -751,6 +751,11 @@ fn iterate_over_a_bunch_of_loose_and_packed_objects() -> crate::Result {
             (139, None),
             "we only count packs and have no upper bound"
         );
+        assert_eq!(
+            iter.len_objects() as u64,
+            db.packed_object_count()?,
+            "the hint is the amount of objects in all pack indices"
+        );
         assert_eq!(iter.count(), 146, "it sees the correct amount of objects");
         for id in db.iter()? {
             assert!(db.contains(id?), "each object exists");
@@ -759,6 +764,121 @@ fn iterate_over_a_bunch_of_loose_and_packed_objects() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn iterate_deduplicated_with_objects_that_are_loose_and_packed() -> crate::Result {
+    let (db, tmp) = db_with_all_object_sources()?;
+    let packed_id = db.iter()?.next().expect("first object is packed")?;
+    let mut buf = Vec::new();
+    let obj = db.find(packed_id, &mut buf)?;
+    let loose = gix_odb::loose::Store::at(tmp.path(), gix_hash::Kind::Sha1);
+    assert!(!loose.contains(packed_id), "the object wasn't loose yet");
+    assert_eq!(loose.write_buf(obj.kind, obj.data)?, packed_id);
+
+    for order in all_orderings() {
+        assert_eq!(db.iter()?.with_ordering(order).count(), 147, "the duplicate is seen");
+        assert_eq!(
+            db.iter()?.with_ordering(order).deduplicated().len_objects(),
+            139,
+            "the hint only counts packed objects"
+        );
+
+        let mut unique = std::collections::HashSet::new();
+        for id in db.iter()?.with_ordering(order).deduplicated() {
+            assert!(unique.insert(id?), "each object is seen only once");
+        }
+        assert_eq!(unique.len(), 146);
+    }
+    Ok(())
+}
+
+#[test]
+fn find_many_calls_back_in_pack_order_with_loose_and_missing_objects_last() -> crate::Result {
+    let (db, tmp) = db_with_all_object_sources()?;
+    let loose = gix_odb::loose::Store::at(tmp.path(), gix_hash::Kind::Sha1);
+    let missing = hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    let mut ids = vec![missing];
+    for id in db.iter()? {
+        ids.push(id?);
+    }
+    ids.reverse();
+
+    let mut seen = Vec::new();
+    let mut buf = Vec::new();
+    db.find_many(
+        &ids,
+        &mut gix_pack::cache::Never,
+        |id, obj| -> Result<_, gix_odb::store::find::Error> {
+            match obj {
+                Some(obj) => {
+                    let expected = db.find(id, &mut buf).expect("present");
+                    assert_eq!(obj.kind, expected.kind);
+                    assert_eq!(obj.data, expected.data);
+                }
+                None => assert_eq!(id, missing, "only one object is missing"),
+            }
+            seen.push(id.to_owned());
+            Ok(())
+        },
+    )?;
+
+    assert_eq!(seen.len(), ids.len(), "each object is seen once");
+    assert_eq!(seen.last(), Some(&missing), "missing objects come last");
+    let first_loose = seen
+        .iter()
+        .position(|id| loose.contains(id))
+        .expect("there are loose objects");
+    assert!(
+        seen[first_loose..seen.len() - 1].iter().all(|id| loose.contains(id)),
+        "loose objects come after all packed ones"
+    );
+    assert_ne!(seen, ids, "the callback order follows pack locality, not input order");
+    Ok(())
+}
+
+#[test]
+fn verify_on_decode_detects_checksum_mismatches() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let mut handle = gix_odb::at(tmp.path())?;
+    let actual = handle.write_buf(gix_object::Kind::Blob, b"content")?;
+    let expected = hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    let loose_path = |id: gix_hash::ObjectId| {
+        let hex = id.to_hex().to_string();
+        tmp.path().join(&hex[..2]).join(&hex[2..])
+    };
+    std::fs::create_dir_all(loose_path(expected).parent().expect("parent"))?;
+    std::fs::copy(loose_path(actual), loose_path(expected))?;
+
+    let mut buf = Vec::new();
+    assert_eq!(
+        handle.find(expected, &mut buf)?.data,
+        b"content",
+        "by default, objects aren't verified"
+    );
+
+    handle.verify_on_decode();
+    match handle.try_find(expected, &mut buf) {
+        Err(store::find::Error::ChecksumMismatch {
+            expected: err_expected,
+            actual: err_actual,
+        }) => {
+            assert_eq!(err_expected, expected);
+            assert_eq!(err_actual, actual);
+        }
+        res => unreachable!("expected checksum mismatch, got {:?}", res.map(|obj| obj.is_some())),
+    }
+    assert!(handle.try_find(actual, &mut buf)?.is_some(), "intact objects pass");
+
+    let mut handle = db();
+    handle.verify_on_decode();
+    for id in handle.iter()? {
+        assert!(
+            handle.try_find(id?, &mut buf)?.is_some(),
+            "all packed objects are intact"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn auto_refresh_with_and_without_id_stability() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -915,9 +1035,148 @@ mod verify {
     use std::sync::atomic::AtomicBool;
 
     use gix_features::progress;
+    use gix_odb::store::verify::packs;
     use gix_testtools::fixture_path;
 
-    use crate::store::dynamic::db;
+    use crate::store::dynamic::{db, db_with_all_object_sources};
+
+    #[test]
+    fn packs_of_intact_store() -> crate::Result {
+        let handle = db();
+        let reports = handle.store_ref().verify_packs(None, &AtomicBool::new(false))?;
+        assert_eq!(
+            reports
+                .iter()
+                .map(|r| (
+                    r.index_path
+                        .as_deref()
+                        .and_then(|p| p.file_name())
+                        .expect("file")
+                        .to_str()
+                        .expect("UTF-8"),
+                    r.num_objects_verified,
+                    r.error.is_none()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("pack-c0438c19fb16422b6bbcce24387b3264416d485b.idx", 67, true),
+                ("pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx", 42, true),
+                ("pack-11fdfa9e156ab73caae3b6da867192221f2089c2.idx", 30, true)
+            ],
+            "each pack is reported in order along with its amount of objects"
+        );
+        for report in &reports {
+            assert_eq!(
+                Some(report.pack_path.clone()),
+                report.index_path.as_ref().map(|p| p.with_extension("pack"))
+            );
+        }
+
+        let (handle, _tmp) = db_with_all_object_sources()?;
+        let reports = handle.store_ref().verify_packs(Some(1), &AtomicBool::new(false))?;
+        assert_eq!(reports.len(), 3, "packs of the multi-pack index are verified as well");
+        assert!(reports.iter().all(|r| r.error.is_none()));
+        Ok(())
+    }
+
+    #[test]
+    fn packs_of_multi_pack_index_without_their_own_index() -> crate::Result {
+        let (handle, tmp) = db_with_all_object_sources()?;
+        let index_path = tmp
+            .path()
+            .join("pack/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx");
+        std::fs::remove_file(&index_path)?;
+
+        let reports = handle.store_ref().verify_packs(None, &AtomicBool::new(false))?;
+        assert_eq!(reports.len(), 3);
+        assert!(
+            reports.iter().all(|r| r.error.is_none()),
+            "a missing index isn't corruption"
+        );
+        let report = reports
+            .iter()
+            .find(|r| r.pack_path == index_path.with_extension("pack"))
+            .expect("pack is still known through the multi-pack index");
+        assert_eq!(report.index_path, None, "the index isn't made up");
+        assert_eq!(
+            report.num_objects_verified, 42,
+            "all objects are verified by the pack trailer"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn packs_with_corrupt_trailer() -> crate::Result {
+        let objects_dir = gix_testtools::tempfile::tempdir()?;
+        gix_testtools::copy_recursively_into_existing_dir(fixture_path("objects"), &objects_dir)?;
+        let pack_path = objects_dir
+            .path()
+            .join("pack/pack-c0438c19fb16422b6bbcce24387b3264416d485b.pack");
+        let mut data = std::fs::read(&pack_path)?;
+        *data.last_mut().expect("non-empty") ^= 0xff;
+        std::fs::write(&pack_path, data)?;
+
+        let handle = gix_odb::at(objects_dir.path())?;
+        let reports = handle.store_ref().verify_packs(None, &AtomicBool::new(false))?;
+        let report = reports
+            .iter()
+            .find(|r| r.pack_path == pack_path)
+            .expect("corrupt pack is reported");
+        assert_eq!(
+            report.num_objects_verified, 0,
+            "entries of V1 indices without CRC32 are only verified by the trailer"
+        );
+        assert!(matches!(report.error, Some(packs::Error::Checksum(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn packs_with_corrupt_entry() -> crate::Result {
+        let objects_dir = gix_testtools::tempfile::tempdir()?;
+        gix_testtools::copy_recursively_into_existing_dir(fixture_path("objects"), &objects_dir)?;
+        let pack_path = objects_dir
+            .path()
+            .join("pack/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.pack");
+        let index = gix_odb::pack::index::File::at(pack_path.with_extension("idx"), gix_hash::Kind::Sha1)?;
+        let mut entries: Vec<_> = index.iter().collect();
+        entries.sort_by_key(|e| e.pack_offset);
+        let (corrupt, next) = (&entries[10], &entries[11]);
+
+        let mut data = std::fs::read(&pack_path)?;
+        let last_byte_of_entry = next.pack_offset as usize - 1;
+        data[last_byte_of_entry] ^= 0xff;
+        std::fs::write(&pack_path, data)?;
+
+        let handle = gix_odb::at(objects_dir.path())?;
+        let reports = handle.store_ref().verify_packs(None, &AtomicBool::new(false))?;
+        assert_eq!(reports.len(), 3);
+        for report in reports {
+            if report.pack_path != pack_path {
+                assert!(report.error.is_none(), "other packs are unaffected");
+                continue;
+            }
+            assert_eq!(
+                report.num_objects_verified,
+                index.num_objects() as usize - 1,
+                "all other entries are still verified"
+            );
+            match report.error {
+                Some(packs::Error::Crc32Mismatch {
+                    id,
+                    pack_offset,
+                    expected,
+                    actual,
+                }) => {
+                    assert_eq!(id, corrupt.oid);
+                    assert_eq!(pack_offset, corrupt.pack_offset);
+                    assert_eq!(Some(expected), corrupt.crc32);
+                    assert_ne!(actual, expected);
+                }
+                err => unreachable!("a CRC mismatch is expected, got {err:?}"),
+            }
+        }
+        Ok(())
+    }
 
     #[test]
     fn integrity() {
@@ -965,3 +1224,40 @@ mod verify {
         );
     }
 }
+
+#[test]
+fn multi_index_is_preferred_over_pack_indices_and_they_are_used_without_it() -> crate::Result {
+    let mut expected_count = None;
+    for (arg, expected_indices) in [("no", 1), ("without-multi-index", 15)] {
+        let dir = gix_testtools::scripted_fixture_read_only_with_args("make_repo_multi_index.sh", Some(arg))?;
+        let objects_dir = dir.join(".git/objects");
+        assert_eq!(
+            objects_dir.join("pack/multi-pack-index").is_file(),
+            arg == "no",
+            "the fixture only writes a multi-pack index if asked"
+        );
+        let handle = gix_odb::at(objects_dir)?;
+
+        let mut buf = Vec::new();
+        let mut count = 0;
+        for oid in handle.iter()? {
+            let oid = oid?;
+            assert!(handle.try_find(oid, &mut buf)?.is_some());
+            count += 1;
+        }
+        assert_eq!(
+            *expected_count.get_or_insert(count),
+            count,
+            "all objects can be found either way"
+        );
+
+        let metrics = handle.store_ref().metrics();
+        assert_eq!(metrics.known_packs, 15);
+        assert_eq!(
+            metrics.known_reachable_indices, expected_indices,
+            "a multi-pack index replaces all indices of the packs it covers"
+        );
+        assert_eq!(metrics.open_reachable_indices, expected_indices);
+    }
+    Ok(())
+}
@@ -55,15 +55,16 @@ mod locate {
 
 mod init {
     use gix_hash::ObjectId;
-    use gix_odb::Find;
+    use gix_odb::{Find, Header, Write};
 
     use crate::odb::{alternate::alternate, db};
 
     #[test]
     fn multiple_linked_repositories_via_alternates() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
-        let (object_path, _linked_object_path) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+        let (object_path, linked_object_path) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
         let db = gix_odb::at(object_path.clone())?;
+        assert_eq!(db.store_ref().alternate_dirs()?, vec![linked_object_path]);
         db.contains(ObjectId::null(gix_hash::Kind::Sha1)); // trigger load
 
         assert_eq!(db.store_ref().metrics().loose_dbs, 2);
@@ -72,6 +73,25 @@ mod init {
         Ok(())
     }
 
+    #[test]
+    fn contains_and_header_consult_alternates() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (object_path, linked_object_path) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+        let id = gix_odb::loose::Store::at(linked_object_path, gix_hash::Kind::Sha1)
+            .write_buf(gix_object::Kind::Blob, b"hello")?;
+
+        let db = gix_odb::at(object_path)?;
+        assert!(db.contains(id), "objects in alternates are visible");
+        let header = db.try_header(id)?.expect("present in alternate");
+        assert_eq!(header.kind(), gix_object::Kind::Blob);
+        assert_eq!(header.size(), 5);
+        assert!(
+            db.try_header(ObjectId::null(gix_hash::Kind::Sha1))?.is_none(),
+            "missing objects have no header"
+        );
+        Ok(())
+    }
+
     #[test]
     fn a_db_without_alternates() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -79,6 +99,7 @@ mod init {
         db.contains(ObjectId::null(gix_hash::Kind::Sha1)); // trigger load
         assert_eq!(db.store_ref().metrics().loose_dbs, 1);
         assert_eq!(db.store_ref().path(), tmp.path());
+        assert!(db.store_ref().alternate_dirs()?.is_empty());
         Ok(())
     }
 
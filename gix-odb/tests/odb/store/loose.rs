@@ -70,6 +70,34 @@ mod write {
         }
         Ok(())
     }
+
+    #[test]
+    fn existing_objects_are_not_overwritten() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let db = loose::Store::at(dir.path(), gix_hash::Kind::Sha1);
+        let id = db.write_buf(gix_object::Kind::Blob, b"hello")?;
+        assert_eq!(
+            id,
+            gix_hash::ObjectId::from_hex(b"b6fc4c620b67d95f953a5c1c1230aaab5db5a1b0")?
+        );
+        let path = dir.path().join("b6").join("fc4c620b67d95f953a5c1c1230aaab5db5a1b0");
+        assert!(
+            path.is_file(),
+            "loose objects are placed into a directory named after the first byte of their id"
+        );
+
+        let mut buf = Vec::new();
+        assert_eq!(db.try_find(id, &mut buf)?.expect("present").data, b"hello");
+
+        std::fs::write(&path, b"marker")?;
+        assert_eq!(
+            db.write_buf(gix_object::Kind::Blob, b"hello")?,
+            id,
+            "writing an existing object is a no-op which still returns its id"
+        );
+        assert_eq!(std::fs::read(&path)?, b"marker", "the existing file wasn't touched");
+        Ok(())
+    }
 }
 
 mod contains {
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use gix_pack::cache::{lru, DecodeEntry};
+
+fn bundle() -> gix_pack::Bundle {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/objects/pack/pack-11fdfa9e156ab73caae3b6da867192221f2089c2.pack");
+    gix_pack::Bundle::at(path, gix_hash::Kind::Sha1).expect("fixture pack is valid")
+}
+
+/// Decode every object in the pack, in index order, resolving delta chains with the help of `cache`.
+fn decode_all(bundle: &gix_pack::Bundle, buf: &mut Vec<u8>, cache: &mut impl DecodeEntry) {
+    for entry in bundle.index.iter() {
+        bundle
+            .find(entry.oid, buf, cache)
+            .expect("decodable")
+            .expect("id present");
+    }
+}
+
+fn delta_base_caches(c: &mut Criterion) {
+    let bundle = bundle();
+    let mut buf = Vec::new();
+    let mut group = c.benchmark_group("decode all objects");
+    group.bench_function("no cache", |b| {
+        b.iter(|| decode_all(&bundle, &mut buf, &mut gix_pack::cache::Never))
+    });
+    group.bench_function("StaticLinkedList<64> (default)", |b| {
+        b.iter(|| decode_all(&bundle, &mut buf, &mut lru::StaticLinkedList::<64>::default()))
+    });
+    group.bench_function("MemoryCappedHashmap(96MB)", |b| {
+        b.iter(|| decode_all(&bundle, &mut buf, &mut lru::MemoryCappedHashmap::new(96 * 1024 * 1024)))
+    });
+    for max_entries in [8, 64] {
+        group.bench_function(format!("MemoryCappedHashmap(96MB, {max_entries} entries)"), |b| {
+            b.iter(|| {
+                decode_all(
+                    &bundle,
+                    &mut buf,
+                    &mut lru::MemoryCappedHashmap::with_max_entries(96 * 1024 * 1024, max_entries),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, delta_base_caches);
+criterion_main!(benches);
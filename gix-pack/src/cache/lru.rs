@@ -23,9 +23,13 @@ mod memory {
         }
     }
 
-    /// An LRU cache with hash map backing and an eviction rule based on the memory usage for object data in bytes.
+    /// An LRU cache with hash map backing and an eviction rule based on the memory usage for object data in bytes,
+    /// and optionally on the amount of objects it holds.
+    ///
+    /// The buffers of evicted objects are kept and reused for new objects to avoid allocations.
     pub struct MemoryCappedHashmap {
         inner: clru::CLruCache<Key, Entry, std::collections::hash_map::RandomState, CustomScale>,
+        max_entries: Option<usize>,
         free_list: Vec<Vec<u8>>,
         debug: gix_features::cache::Debug,
     }
@@ -39,15 +43,49 @@ mod memory {
                     clru::CLruCacheConfig::new(NonZeroUsize::new(memory_cap_in_bytes).expect("non zero"))
                         .with_scale(CustomScale),
                 ),
+                max_entries: None,
                 free_list: Vec::new(),
                 debug: gix_features::cache::Debug::new(format!("MemoryCappedHashmap({memory_cap_in_bytes}B)")),
             }
         }
+
+        /// Return a new instance which evicts least recently used items if it uses more than `memory_cap_in_bytes`
+        /// object data, or if it would hold more than `max_entries` objects.
+        ///
+        /// Limiting the amount of entries is useful to bound the cost of keeping many small delta bases around,
+        /// which a cap on memory alone can't do.
+        pub fn with_max_entries(memory_cap_in_bytes: usize, max_entries: usize) -> MemoryCappedHashmap {
+            let mut cache = Self::new(memory_cap_in_bytes);
+            cache.max_entries = Some(max_entries.max(1));
+            cache.debug = gix_features::cache::Debug::new(format!(
+                "MemoryCappedHashmap({memory_cap_in_bytes}B, {max_entries} entries)"
+            ));
+            cache
+        }
+
+        /// Return the amount of objects currently held in the cache.
+        pub fn len(&self) -> usize {
+            self.inner.len()
+        }
+
+        /// Return true if the cache holds no object.
+        pub fn is_empty(&self) -> bool {
+            self.inner.is_empty()
+        }
     }
 
     impl DecodeEntry for MemoryCappedHashmap {
         fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: gix_object::Kind, compressed_size: usize) {
             self.debug.put();
+            if let Some(max_entries) = self.max_entries {
+                let key = (pack_id, offset);
+                while self.inner.len() >= max_entries && !self.inner.contains(&key) {
+                    match self.inner.pop_back() {
+                        Some((_, evicted)) => self.free_list.push(evicted.data),
+                        None => break,
+                    }
+                }
+            }
             if let Ok(Some(previous_entry)) = self.inner.put_with_weight(
                 (pack_id, offset),
                 Entry {
@@ -45,6 +45,10 @@ pub trait Object {
     fn get(&mut self, id: &gix_hash::ObjectId, out: &mut Vec<u8>) -> Option<gix_object::Kind>;
 }
 
+///
+pub mod recorder;
+pub use recorder::Recorder;
+
 /// Various implementations of [`DecodeEntry`] using least-recently-used algorithms.
 #[cfg(any(feature = "pack-cache-lru-dynamic", feature = "pack-cache-lru-static"))]
 pub mod lru;
@@ -0,0 +1,74 @@
+use gix_object::Kind;
+
+use crate::cache::{DecodeEntry, Never};
+
+/// Statistics about the use of a [`DecodeEntry`] cache, as collected by a [`Recorder`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// The amount of times an object was retrieved from the cache.
+    pub hits: usize,
+    /// The amount of times an object was requested but wasn't present in the cache.
+    pub misses: usize,
+    /// The amount of times an object was offered to the cache for storage, whether it was stored or not.
+    pub inserts: usize,
+    /// The total amount of decoded bytes of all objects retrieved from the cache.
+    pub bytes_hit: u64,
+    /// The total amount of decoded bytes of all objects offered to the cache for storage.
+    pub bytes_inserted: u64,
+}
+
+/// A [`DecodeEntry`] implementation which records [statistics][Stats] about each use of the cache it wraps.
+///
+/// By default it wraps the [`Never`] cache, so it only records which objects would have been requested and stored.
+/// Wrap any other cache to measure its effectiveness.
+#[derive(Default)]
+pub struct Recorder<T = Never> {
+    inner: T,
+    stats: Stats,
+}
+
+impl<T> Recorder<T> {
+    /// Create a new instance which records how `inner` is used.
+    pub fn new(inner: T) -> Self {
+        Recorder {
+            inner,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Return the statistics collected so far.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Return the collected statistics, discarding the wrapped cache.
+    pub fn into_stats(self) -> Stats {
+        self.stats
+    }
+
+    /// Return the collected statistics along with the wrapped cache.
+    pub fn into_parts(self) -> (T, Stats) {
+        (self.inner, self.stats)
+    }
+}
+
+impl<T: DecodeEntry> DecodeEntry for Recorder<T> {
+    fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: Kind, compressed_size: usize) {
+        self.stats.inserts += 1;
+        self.stats.bytes_inserted += data.len() as u64;
+        self.inner.put(pack_id, offset, data, kind, compressed_size)
+    }
+
+    fn get(&mut self, pack_id: u32, offset: u64, out: &mut Vec<u8>) -> Option<(Kind, usize)> {
+        let res = self.inner.get(pack_id, offset, out);
+        match res {
+            Some(_) => {
+                self.stats.hits += 1;
+                self.stats.bytes_hit += out.len() as u64;
+            }
+            None => self.stats.misses += 1,
+        }
+        res
+    }
+}
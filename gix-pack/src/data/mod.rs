@@ -62,7 +62,11 @@ impl Default for Version {
     }
 }
 
-/// A pack data file
+/// A pack data file, which is memory-mapped in its entirety when opened.
+///
+/// The mapping is shared by all handles to the pack, so accessing its data never maps or unmaps parts of it.
+/// What's costly when resolving deltas is decompressing and applying them, which [caches][crate::cache::DecodeEntry]
+/// help with.
 pub struct File {
     data: Mmap,
     path: std::path::PathBuf,
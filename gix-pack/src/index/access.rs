@@ -154,6 +154,25 @@ impl index::File {
         }
     }
 
+    /// An iterator over all [`Entries`][Entry] of this index file in order of their offset into the pack data file,
+    /// i.e. in the order they are stored in the pack.
+    ///
+    /// Useful to decode all objects of a pack in a single sweep, which is the fastest way to do so as the pack data is read
+    /// sequentially and delta bases are typically seen before the objects that refer to them, making them easy to cache.
+    /// For bulk lookups of objects by id instead, `find_many()` on the handles of an object database does the same by
+    /// looking objects up in pack order.
+    ///
+    /// Note that this sorts the positions of all entries first, needing a `u32` for each object.
+    pub fn iter_by_offset(&self) -> impl Iterator<Item = Entry> + '_ {
+        let mut indices: Vec<EntryIndex> = (0..self.num_objects).collect();
+        indices.sort_by_key(|index| self.pack_offset_at_index(*index));
+        indices.into_iter().map(move |index| Entry {
+            oid: self.oid_at_index(index).to_owned(),
+            pack_offset: self.pack_offset_at_index(index),
+            crc32: self.crc32_at_index(index),
+        })
+    }
+
     /// Return a vector of ascending offsets into our respective pack data file.
     ///
     /// Useful to control an iteration over all pack entries in a cache-friendly way.
@@ -38,6 +38,88 @@ mod locate {
         }
     }
 
+    mod cache_recorder {
+        use std::collections::HashMap;
+
+        use gix_object::Kind;
+        use gix_odb::pack;
+
+        use crate::{fixture_path, pack::SMALL_PACK};
+
+        #[derive(Default)]
+        struct Unbounded(HashMap<(u32, u64), (Vec<u8>, Kind, usize)>);
+
+        impl pack::cache::DecodeEntry for Unbounded {
+            fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: Kind, compressed_size: usize) {
+                self.0.insert((pack_id, offset), (data.to_vec(), kind, compressed_size));
+            }
+
+            fn get(&mut self, pack_id: u32, offset: u64, out: &mut Vec<u8>) -> Option<(Kind, usize)> {
+                self.0.get(&(pack_id, offset)).map(|(data, kind, compressed_size)| {
+                    out.clear();
+                    out.extend_from_slice(data);
+                    (*kind, *compressed_size)
+                })
+            }
+        }
+
+        fn decode_all_twice(cache: &mut impl pack::cache::DecodeEntry) -> crate::Result {
+            let bundle = pack::Bundle::at(fixture_path(SMALL_PACK), gix_hash::Kind::Sha1)?;
+            let mut buf = Vec::new();
+            for _round in 0..2 {
+                for entry in bundle.index.iter() {
+                    let (obj, _location) = bundle.find(entry.oid, &mut buf, cache)?.expect("id present");
+                    obj.verify_checksum(entry.oid)?;
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn records_uses_of_the_wrapped_cache() -> crate::Result {
+            let mut recorder = pack::cache::Recorder::<pack::cache::Never>::default();
+            decode_all_twice(&mut recorder)?;
+            let never = recorder.into_stats();
+            assert_eq!(never.hits, 0, "nothing is ever cached");
+            assert_eq!(never.bytes_hit, 0);
+            assert_ne!(never.misses, 0, "delta bases are requested");
+            assert_ne!(never.inserts, 0, "decoded objects are offered");
+
+            let mut recorder = pack::cache::Recorder::new(Unbounded::default());
+            decode_all_twice(&mut recorder)?;
+            let (cache, stats) = recorder.into_parts();
+            assert!(!cache.0.is_empty());
+            assert_ne!(stats.hits, 0, "the second round can use cached objects");
+            assert_ne!(stats.bytes_hit, 0);
+            assert!(stats.misses < never.misses, "the cache avoids misses");
+            assert!(stats.bytes_inserted <= never.bytes_inserted);
+            Ok(())
+        }
+
+        #[test]
+        #[cfg(feature = "pack-cache-lru-dynamic")]
+        fn memory_capped_hashmap_with_max_entries_holds_no_more_than_allowed() -> crate::Result {
+            let mut recorder = pack::cache::Recorder::new(pack::cache::lru::MemoryCappedHashmap::new(usize::MAX));
+            decode_all_twice(&mut recorder)?;
+            let (unbounded, unbounded_stats) = recorder.into_parts();
+            assert!(unbounded.len() > 1, "there are multiple delta bases to cache");
+
+            let max_entries = 1;
+            let mut recorder = pack::cache::Recorder::new(pack::cache::lru::MemoryCappedHashmap::with_max_entries(
+                usize::MAX,
+                max_entries,
+            ));
+            decode_all_twice(&mut recorder)?;
+            let (capped, capped_stats) = recorder.into_parts();
+            assert_eq!(capped.len(), max_entries, "older entries are evicted");
+            assert!(
+                capped_stats.hits < unbounded_stats.hits,
+                "fewer entries means fewer hits"
+            );
+            Ok(())
+        }
+    }
+
     #[test]
     fn blob() -> Result<(), Box<dyn std::error::Error>> {
         let mut out = Vec::new();
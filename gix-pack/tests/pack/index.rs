@@ -504,3 +504,34 @@ fn iter() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+#[test]
+fn iter_by_offset() -> Result<(), Box<dyn std::error::Error>> {
+    for path in [INDEX_V1, INDEX_V2, SMALL_PACK_INDEX] {
+        let idx = index::File::at(&fixture_path(path), gix_hash::Kind::Sha1)?;
+        let entries: Vec<_> = idx.iter_by_offset().collect();
+        assert_eq!(
+            entries.iter().map(|e| e.pack_offset).collect::<Vec<_>>(),
+            idx.sorted_offsets(),
+            "entries are returned in ascending pack offset order"
+        );
+
+        let mut entries = entries;
+        entries.sort();
+        let mut expected: Vec<_> = idx.iter().collect();
+        expected.sort();
+        assert_eq!(entries, expected, "all entries are returned with the same information");
+    }
+
+    let idx = index::File::at(&fixture_path(SMALL_PACK_INDEX), gix_hash::Kind::Sha1)?;
+    let pack = pack::data::File::at(&fixture_path(SMALL_PACK), gix_hash::Kind::Sha1)?;
+    for (index_entry, pack_entry) in idx.iter_by_offset().zip(pack.streaming_iter()?) {
+        let pack_entry = pack_entry?;
+        assert_eq!(index_entry.pack_offset, pack_entry.pack_offset);
+        assert_eq!(
+            index_entry.crc32, pack_entry.crc32,
+            "the pack can be decoded in a single sweep"
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((&flags, input)) = data.split_first() {
+        let options = gix_pathspec::parse::Options {
+            force_literal: flags & 1 != 0,
+            force_icase: flags & 2 != 0,
+            no_magic: flags & 4 != 0,
+        };
+        drop(gix_pathspec::Pattern::from_bytes_with_options(input, options));
+    }
+});
@@ -4,11 +4,17 @@
 #![forbid(unsafe_code)]
 
 use bitflags::bitflags;
-use bstr::BString;
+use bstr::{BStr, BString};
+
+///
+pub mod normalize;
 
 ///
 pub mod parse;
 
+///
+pub mod search;
+
 /// The output of a pathspec [parsing][parse()] operation. It can be used to match against a one or more paths.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 pub struct Pattern {
@@ -35,6 +41,10 @@ bitflags! {
         const ICASE = 1 << 1;
         /// Excludes the matching patterns from the previous results
         const EXCLUDE = 1 << 2;
+        /// The path ends with a slash, so it only matches directories or paths within them.
+        ///
+        /// Unlike the other signatures it can't be spelled out, but is derived from the path itself.
+        const MUST_BE_DIR = 1 << 3;
     }
 }
 
@@ -58,7 +68,58 @@ impl Default for MatchMode {
     }
 }
 
+/// A list of [patterns][Pattern] which are matched against paths as a whole, using the same precedence rules as git.
+///
+/// Exclude patterns always win over positive patterns, and the last matching positive pattern is the one
+/// that is reported. If no or only exclude patterns are given, all other paths are considered included, just like in git.
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// The patterns in the order they were provided, possibly with an implicit match-all pattern at the end.
+    pub(crate) patterns: Vec<Pattern>,
+    /// The longest directory prefix, including the trailing slash, that all positive patterns have in common.
+    pub(crate) common_prefix: BString,
+    /// If `true`, the last pattern wasn't provided by the user but added as only exclude patterns were given.
+    pub(crate) implicit_match_all: bool,
+    /// If `true`, all patterns match case-insensitively as if they had the [`ICASE`][MagicSignature::ICASE] signature.
+    pub(crate) ignore_case: bool,
+}
+
+/// Lifecycle
+impl Pattern {
+    /// Return a pattern which matches every path, as [`Search`] uses it if no pattern or only exclude patterns were given.
+    ///
+    /// It can't be obtained by [parsing][parse()] as an empty string isn't a valid pathspec.
+    pub fn matches_all() -> Self {
+        Pattern {
+            path: BString::default(),
+            signature: MagicSignature::TOP,
+            search_mode: MatchMode::Literal,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Return `true` if this pattern matches every path as it has neither path nor attributes and is not an exclude
+    /// pattern, like the one returned by [`matches_all()`][Pattern::matches_all()].
+    pub fn is_match_all(&self) -> bool {
+        self.path.is_empty() && self.attributes.is_empty() && !self.signature.contains(MagicSignature::EXCLUDE)
+    }
+}
+
 /// Parse a git-style pathspec into a [`Pattern`][Pattern].
 pub fn parse(input: &[u8]) -> Result<Pattern, parse::Error> {
     Pattern::from_bytes(input)
 }
+
+/// Return `true` if `path` matches `pattern` according to `mode`, the way git matches a single pathspec item.
+///
+/// * [`ShellGlob`][MatchMode::ShellGlob] matches like `fnmatch(3)` without special treatment of slashes,
+///   so `*` and `?` match `/` as well.
+/// * [`PathAwareGlob`][MatchMode::PathAwareGlob] matches like git's `wildmatch()` with `WM_PATHNAME`, so `*`, `?` and
+///   character classes never match `/`, while `**/`, `/**/` and a trailing `/**` match across directories.
+/// * [`Literal`][MatchMode::Literal] compares `pattern` and `path` byte by byte.
+///
+/// Both globbing modes support `?`, `*`, `**`, character classes like `[a-z]`, `[!a-z]` and `[[:alpha:]]`,
+/// as well as escaping with backslashes.
+pub fn wildmatch(pattern: &BStr, path: &BStr, mode: MatchMode) -> bool {
+    search::wildmatch(pattern, path, &mode, false)
+}
@@ -0,0 +1,74 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::{MagicSignature, Pattern};
+
+/// The error returned by [`Pattern::normalize()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The path of pathspec {path:?} is absolute, but only paths relative to the repository are supported")]
+    AbsolutePath { path: BString },
+    #[error("The pathspec {path:?} leads outside of the repository when prefixed with {prefix:?}")]
+    OutsideOfRepository { path: BString, prefix: BString },
+}
+
+impl Pattern {
+    /// Return a copy of this pattern whose path is relative to the root of the repository, by prepending `prefix`,
+    /// the path of the current working directory relative to the root of the worktree, just like git does when
+    /// invoked from a subdirectory.
+    ///
+    /// `prefix` may be empty, and may or may not end with a slash. Patterns with the [`TOP`][MagicSignature::TOP]
+    /// signature are already relative to the root and only have their path normalized. `.` and `..` path components
+    /// are resolved, which fails if the path would leave the repository, as does an absolute path.
+    /// A trailing slash is kept, and `.` or `..` as last component turn into a trailing slash to only match
+    /// beneath the respective directory. The [`MUST_BE_DIR`][MagicSignature::MUST_BE_DIR] signature is adjusted
+    /// to match the resulting path.
+    ///
+    /// The returned pattern has the [`TOP`][MagicSignature::TOP] signature so normalizing it again won't change it.
+    pub fn normalize(&self, prefix: &BStr) -> Result<Pattern, Error> {
+        if self.path.starts_with(b"/") {
+            return Err(Error::AbsolutePath {
+                path: self.path.clone(),
+            });
+        }
+        let prefix: &[u8] = if self.signature.contains(MagicSignature::TOP) {
+            b""
+        } else {
+            prefix
+        };
+
+        let mut components = Vec::<&[u8]>::new();
+        let mut is_dir = false;
+        for component in prefix.split_str(b"/").chain(self.path.split_str(b"/")) {
+            is_dir = true;
+            match component {
+                b"" | b"." => {}
+                b".." => {
+                    if components.pop().is_none() {
+                        return Err(Error::OutsideOfRepository {
+                            path: self.path.clone(),
+                            prefix: prefix.into(),
+                        });
+                    }
+                }
+                _ => {
+                    components.push(component);
+                    is_dir = false;
+                }
+            }
+        }
+
+        let mut path = BString::from(components.join(&b'/'));
+        let mut signature = self.signature | MagicSignature::TOP;
+        signature.set(MagicSignature::MUST_BE_DIR, is_dir && !path.is_empty());
+        if signature.contains(MagicSignature::MUST_BE_DIR) {
+            path.push_byte(b'/');
+        }
+        Ok(Pattern {
+            path,
+            signature,
+            search_mode: self.search_mode.clone(),
+            attributes: self.attributes.clone(),
+        })
+    }
+}
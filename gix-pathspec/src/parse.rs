@@ -16,8 +16,14 @@ pub enum Error {
     Unimplemented { short_keyword: char },
     #[error("Missing ')' at the end of pathspec signature")]
     MissingClosingParenthesis,
-    #[error("Attribute has non-ascii characters or starts with '-': {attribute:?}")]
+    #[error("Attribute has characters that are not allowed in attribute names: {attribute:?}")]
     InvalidAttribute { attribute: BString },
+    #[error("Attribute has non-ascii characters: {attribute:?}")]
+    NonAsciiAttributeName { attribute: BString },
+    #[error("Attribute names must not start with '-': {attribute:?}")]
+    AttributeNameStartsWithDash { attribute: BString },
+    #[error("Attribute name is missing after its '-' or '!' prefix, or before its '=' value")]
+    EmptyAttributeName,
     #[error("Invalid character in attribute value: {character:?}")]
     InvalidAttributeValue { character: char },
     #[error("Escape character '\\' is not allowed as the last character in an attribute value")]
@@ -30,9 +36,30 @@ pub enum Error {
     IncompatibleSearchModes,
 }
 
+/// Options to affect how all pathspecs are parsed, similar to the `GIT_*_PATHSPECS` environment variables.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// If `true`, the input is taken as literal path without interpreting any magic signature or glob,
+    /// just like `GIT_LITERAL_PATHSPECS=1` does.
+    pub force_literal: bool,
+    /// If `true`, all pathspecs match case-insensitively as if the `icase` magic was specified,
+    /// just like `GIT_ICASE_PATHSPECS=1` does.
+    pub force_icase: bool,
+    /// If `true`, magic signatures aren't interpreted and are considered part of the path, which may still contain globs.
+    pub no_magic: bool,
+}
+
 impl Pattern {
     /// Try to parse a path-spec pattern from the given `input` bytes.
+    ///
+    /// Just like in git, long keywords in parentheses are only recognized right after the leading `:`, so short magic
+    /// can't be combined with them and `:!(icase)path` excludes the path `(icase)path`.
     pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_options(input, Options::default())
+    }
+
+    /// Try to parse a path-spec pattern from the given `input` bytes, with `options` affecting how it's interpreted.
+    pub fn from_bytes_with_options(input: &[u8], options: Options) -> Result<Self, Error> {
         if input.is_empty() {
             return Err(Error::EmptyString);
         }
@@ -40,21 +67,32 @@ impl Pattern {
         let mut p = Pattern {
             path: BString::default(),
             signature: MagicSignature::empty(),
-            search_mode: MatchMode::ShellGlob,
+            search_mode: if options.force_literal {
+                MatchMode::Literal
+            } else {
+                MatchMode::ShellGlob
+            },
             attributes: Vec::new(),
         };
+        if options.force_icase {
+            p.signature |= MagicSignature::ICASE;
+        }
 
         let mut cursor = 0;
-        if input.first() == Some(&b':') {
+        if input.first() == Some(&b':') && !(options.force_literal || options.no_magic) {
             cursor += 1;
-            p.signature |= parse_short_keywords(input, &mut cursor)?;
             if let Some(b'(') = input.get(cursor) {
                 cursor += 1;
                 parse_long_keywords(input, &mut p, &mut cursor)?;
+            } else {
+                p.signature |= parse_short_keywords(input, &mut cursor)?;
             }
         }
 
         p.path = BString::from(&input[cursor..]);
+        if p.path.ends_with(b"/") {
+            p.signature |= MagicSignature::MUST_BE_DIR;
+        }
         Ok(p)
     }
 }
@@ -85,7 +123,10 @@ fn parse_short_keywords(input: &[u8], cursor: &mut usize) -> Result<MagicSignatu
 }
 
 fn parse_long_keywords(input: &[u8], p: &mut Pattern, cursor: &mut usize) -> Result<(), Error> {
-    let end = input.find(")").ok_or(Error::MissingClosingParenthesis)?;
+    let end = input[*cursor..]
+        .find(")")
+        .map(|pos| *cursor + pos)
+        .ok_or(Error::MissingClosingParenthesis)?;
 
     let input = &input[*cursor..end];
     *cursor = end + 1;
@@ -155,9 +196,22 @@ fn parse_attributes(input: &[u8]) -> Result<Vec<gix_attributes::Assignment>, Err
     let unescaped = unescape_attribute_values(input.into())?;
 
     gix_attributes::parse::Iter::new(unescaped.as_bstr())
-        .map(|res| res.map(|v| v.to_owned()))
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| Error::InvalidAttribute { attribute: e.attribute })
+        .map(|res| match res {
+            Ok(assignment) if assignment.name.as_str().is_empty() => Err(Error::EmptyAttributeName),
+            Ok(assignment) => Ok(assignment.to_owned()),
+            Err(err) => Err(invalid_attribute_name(err.attribute)),
+        })
+        .collect()
+}
+
+fn invalid_attribute_name(attribute: BString) -> Error {
+    if !attribute.is_ascii() {
+        Error::NonAsciiAttributeName { attribute }
+    } else if attribute.first() == Some(&b'-') {
+        Error::AttributeNameStartsWithDash { attribute }
+    } else {
+        Error::InvalidAttribute { attribute }
+    }
 }
 
 fn unescape_attribute_values(input: &BStr) -> Result<Cow<'_, BStr>, Error> {
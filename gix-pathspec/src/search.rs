@@ -0,0 +1,238 @@
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{MagicSignature, MatchMode, Pattern, Search};
+
+/// Describes a matching pattern within a [`Search`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct Match<'a> {
+    /// The pattern that matched.
+    pub pattern: &'a Pattern,
+    /// The index at which the pattern was provided when creating the search, which may be one past the last
+    /// provided pattern if only excludes were given and everything else matched implicitly.
+    pub sequence_number: usize,
+    /// How the pattern matched the path.
+    pub kind: MatchKind,
+}
+
+/// Describes how a [`Pattern`] matched a path, from the most to the least specific kind of match.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum MatchKind {
+    /// The pattern is literal and equal to the path.
+    Exact,
+    /// The pattern contains wildcards which matched the path.
+    Glob,
+    /// The pattern is literal and names a directory containing the path, or it is empty and thus matches everything.
+    Prefix,
+}
+
+/// Lifecycle
+impl Search {
+    /// Create a search from the given `patterns`, which are typically obtained by [parsing][crate::parse()]
+    /// each pathspec provided on the command-line.
+    ///
+    /// Just like in git, an empty list of `patterns` matches every path, which is different from an empty pathspec
+    /// string which [fails to parse][crate::parse::Error::EmptyString].
+    pub fn from_specs(patterns: impl IntoIterator<Item = Pattern>) -> Self {
+        let mut patterns: Vec<_> = patterns.into_iter().collect();
+        let implicit_match_all = patterns.iter().all(is_exclude);
+        if implicit_match_all {
+            patterns.push(Pattern::matches_all());
+        }
+        let common_prefix = common_prefix(&patterns, false);
+        Search {
+            patterns,
+            common_prefix,
+            implicit_match_all,
+            ignore_case: false,
+        }
+    }
+
+    /// Combine this search with `other` into a new search which contains the patterns of both, with the ones of `other`
+    /// coming last.
+    ///
+    /// The result uses the union of all positive patterns and the union of all exclude patterns, so a path is excluded
+    /// if it is excluded by either input. Positive patterns of `other` take precedence when reporting the match.
+    ///
+    /// Note that the implicit match-all pattern of a search made only of excludes is dropped if the other search has
+    /// positive patterns, so these excludes limit what the positive patterns match. This makes it possible to scope
+    /// a user's exclude-only pathspecs to the directories a tool is interested in.
+    ///
+    /// The result matches case-insensitively if either input [does][Search::with_ignore_case()].
+    pub fn merge(self, other: Search) -> Search {
+        let ignore_case = self.ignore_case || other.ignore_case;
+        Search::from_specs(self.into_provided_patterns().chain(other.into_provided_patterns()))
+            .with_ignore_case(ignore_case)
+    }
+
+    fn into_provided_patterns(mut self) -> impl Iterator<Item = Pattern> {
+        if self.implicit_match_all {
+            self.patterns.pop();
+        }
+        self.patterns.into_iter()
+    }
+}
+
+/// Builder
+impl Search {
+    /// If `toggle` is `true`, match all patterns case-insensitively as if they had the [`ICASE`][MagicSignature::ICASE]
+    /// signature, which is what git does if `core.ignoreCase` is `true`.
+    pub fn with_ignore_case(mut self, toggle: bool) -> Self {
+        self.ignore_case = toggle;
+        self.common_prefix = common_prefix(&self.patterns, toggle);
+        self
+    }
+}
+
+/// Access
+impl Search {
+    /// Return all patterns in the order in which they are considered.
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Return the longest directory prefix shared by all positive patterns, with a trailing slash, or an empty string.
+    ///
+    /// Paths outside of this directory can never match, which allows to prune directory traversal early.
+    /// It's always empty if matching is case-insensitive.
+    pub fn common_prefix(&self) -> &BStr {
+        self.common_prefix.as_bstr()
+    }
+}
+
+impl Search {
+    /// Match `relative_path`, a path relative to the repository containing all patterns, and return the matching
+    /// pattern or `None` if there was no match or if the path was excluded.
+    ///
+    /// `is_dir` is `true` if `relative_path` is a directory, or `None` if this isn't known.
+    pub fn matches<'a>(&self, relative_path: impl Into<&'a BStr>, is_dir: Option<bool>) -> Option<Match<'_>> {
+        let relative_path = relative_path.into();
+        if relative_path.len() >= self.common_prefix.len() && !relative_path.starts_with(&self.common_prefix) {
+            return None;
+        }
+        if self.patterns.iter().any(|pattern| {
+            is_exclude(pattern) && pattern_matches(pattern, relative_path, is_dir, self.ignore_case).is_some()
+        }) {
+            return None;
+        }
+        self.patterns
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(sequence_number, pattern)| {
+                if is_exclude(pattern) {
+                    return None;
+                }
+                pattern_matches(pattern, relative_path, is_dir, self.ignore_case).map(|kind| Match {
+                    pattern,
+                    sequence_number,
+                    kind,
+                })
+            })
+    }
+}
+
+fn is_exclude(pattern: &Pattern) -> bool {
+    pattern.signature.contains(MagicSignature::EXCLUDE)
+}
+
+/// Return the amount of bytes at the beginning of `pattern` which can be compared literally.
+fn literal_prefix_len(pattern: &Pattern) -> usize {
+    match pattern.search_mode {
+        MatchMode::Literal => pattern.path.len(),
+        MatchMode::ShellGlob | MatchMode::PathAwareGlob => {
+            pattern.path.find_byteset(b"*?[\\").unwrap_or(pattern.path.len())
+        }
+    }
+}
+
+fn common_prefix(patterns: &[Pattern], ignore_case: bool) -> BString {
+    if ignore_case {
+        return BString::default();
+    }
+    let mut positive = patterns.iter().filter(|p| !is_exclude(p));
+    let first = match positive.next() {
+        Some(first) => first,
+        None => return BString::default(),
+    };
+    let mut prefix = prefix_of(first);
+    for pattern in positive {
+        let other = prefix_of(pattern);
+        let common_len = prefix.iter().zip(other).take_while(|(a, b)| a == b).count();
+        prefix = &prefix[..common_len];
+    }
+    let dir_len = prefix.rfind_byte(b'/').map_or(0, |pos| pos + 1);
+    prefix[..dir_len].into()
+}
+
+fn prefix_of(pattern: &Pattern) -> &[u8] {
+    if pattern.signature.contains(MagicSignature::ICASE) {
+        &[]
+    } else {
+        &pattern.path[..literal_prefix_len(pattern)]
+    }
+}
+
+fn pattern_matches(pattern: &Pattern, path: &BStr, is_dir: Option<bool>, ignore_case: bool) -> Option<MatchKind> {
+    let spec = pattern.path.as_bstr();
+    if spec.is_empty() {
+        return Some(MatchKind::Prefix);
+    }
+    let ignore_case = ignore_case || pattern.signature.contains(MagicSignature::ICASE);
+    let eq = |a: &[u8], b: &[u8]| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    };
+
+    let prefix_len = literal_prefix_len(pattern);
+    let is_literal = prefix_len == spec.len();
+    let matches_dir_itself = pattern.signature.contains(MagicSignature::MUST_BE_DIR) && is_dir == Some(true);
+    match path.get(..prefix_len) {
+        Some(path_prefix) if eq(&spec[..prefix_len], path_prefix) => {}
+        Some(_) => return None,
+        None => {
+            // `dir/` also matches the directory `dir` itself.
+            return (is_literal && matches_dir_itself && spec.ends_with(b"/") && eq(&spec[..prefix_len - 1], path))
+                .then_some(MatchKind::Exact);
+        }
+    }
+    if is_literal {
+        return if path.len() == prefix_len {
+            Some(MatchKind::Exact)
+        } else if spec.ends_with(b"/") || path[prefix_len] == b'/' {
+            Some(MatchKind::Prefix)
+        } else {
+            None
+        };
+    }
+
+    let matches = wildmatch(spec, path, &pattern.search_mode, ignore_case)
+        || (matches_dir_itself
+            && spec.ends_with(b"/")
+            && wildmatch(
+                spec[..spec.len() - 1].as_bstr(),
+                path,
+                &pattern.search_mode,
+                ignore_case,
+            ));
+    matches.then_some(MatchKind::Glob)
+}
+
+pub(crate) fn wildmatch(pattern: &BStr, path: &BStr, mode: &MatchMode, ignore_case: bool) -> bool {
+    let mut glob_mode = gix_glob::wildmatch::Mode::empty();
+    if ignore_case {
+        glob_mode |= gix_glob::wildmatch::Mode::IGNORE_CASE;
+    }
+    match mode {
+        MatchMode::Literal if ignore_case => pattern.eq_ignore_ascii_case(path),
+        MatchMode::Literal => pattern == path,
+        MatchMode::ShellGlob => gix_glob::wildmatch(pattern, path, glob_mode),
+        MatchMode::PathAwareGlob => gix_glob::wildmatch(
+            pattern,
+            path,
+            glob_mode | gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL,
+        ),
+    }
+}
@@ -45,6 +45,12 @@ baseline ': :some/path'
 baseline ': ()some/path'
 baseline ':! some/path'
 
+# trailing_slash_means_directory
+baseline 'some/dir/'
+baseline ':(literal)some/dir/'
+baseline ':(exclude,glob)some/*/'
+baseline 'some/dir//'
+
 # short_signatures
 baseline ':/some/path'
 baseline ':^some/path'
@@ -65,6 +71,15 @@ baseline ':!(literal)some/*path'
 baseline ':(top,literal,icase,attr,exclude)some/path'
 baseline ':(top,glob,icase,attr,exclude)some/path'
 
+# parentheses_after_short_signatures_are_part_of_the_path
+baseline ':!(icase)some/path'
+baseline ':^(glob)some/**/path'
+baseline ':/!(icase,literal)some/*path'
+baseline ':!/(top,icase)some/path'
+baseline ':/(exclude)some/path'
+baseline ':!(attr:someAttr)some/path'
+baseline ':/!()some/path'
+
 # attributes_in_signature
 baseline ':(attr:someAttr)'
 baseline ':(attr:!someAttr)'
@@ -117,6 +132,15 @@ baseline ':(attr:validAttr +invalidAttr)some/path'
 baseline ':(attr:+invalidAttr,attr:valid)some/path'
 baseline ':(attr:inva\lid)some/path'
 
+# invalid_attribute_names
+baseline ':(attr:-)some/path'
+baseline ':(attr:!)some/path'
+baseline ':(attr:=value)some/path'
+baseline ':(attr:--someAttr)some/path'
+baseline ':(attr:!-someAttr)some/path'
+baseline ':(attr:sömeAttr)some/path'
+baseline ':(attr:-attrö)some/path'
+
 # invalid_attribute_values
 baseline ':(attr:v=inva#lid)some/path'
 baseline ':(attr:v=inva\\lid)some/path'
@@ -143,3 +167,33 @@ baseline ':(top'
 
 # glob_and_literal_keywords_present
 baseline ':(glob,literal)some/path'
+
+# parentheses_after_short_signatures_are_part_of_the_path, recording the files each pathspec matches
+# as git parses parentheses after short magic as part of the path.
+git init -q matches
+(cd matches
+  for path in 'some/path' '(icase)some/path' '(glob)some/x/path' '(icase,literal)some/xpath' \
+              '(top,icase)some/path' '(exclude)some/path' '(attr:someAttr)some/path' '()some/path'; do
+    mkdir -p "$(dirname "$path")"
+    touch "$path"
+  done
+  git add .
+
+  function baseline_matches() {
+    local pathspec=$1 # first argument is the pathspec whose matches to record
+
+    {
+      echo "$pathspec"
+      git ls-files -- "$pathspec"
+      echo
+    } >> ../baseline-matches.git
+  }
+
+  baseline_matches ':!(icase)some/path'
+  baseline_matches ':^(glob)some/**/path'
+  baseline_matches ':/!(icase,literal)some/*path'
+  baseline_matches ':!/(top,icase)some/path'
+  baseline_matches ':/(exclude)some/path'
+  baseline_matches ':!(attr:someAttr)some/path'
+  baseline_matches ':/!()some/path'
+)
@@ -47,6 +47,47 @@ mod parse {
         .unwrap()
     });
 
+    /// The files in a repository matched by each pathspec according to `git ls-files`.
+    static BASELINE_MATCHES: Lazy<HashMap<BString, Vec<BString>>> = Lazy::new(|| {
+        let base = gix_testtools::scripted_fixture_read_only("generate_pathspec_baseline.sh").unwrap();
+
+        (|| -> crate::Result<_> {
+            let mut map = HashMap::new();
+            let baseline = std::fs::read(base.join("baseline-matches.git"))?;
+            let mut lines = baseline.lines();
+            while let Some(spec) = lines.next() {
+                let files = lines
+                    .by_ref()
+                    .take_while(|line| !line.is_empty())
+                    .map(Into::into)
+                    .collect();
+                map.insert(spec.into(), files);
+            }
+            Ok(map)
+        })()
+        .unwrap()
+    });
+
+    /// Return the files of the repository used for [`BASELINE_MATCHES`] that `pathspec` matches.
+    fn matching_files(pathspec: &str) -> Vec<BString> {
+        let files = [
+            "()some/path",
+            "(attr:someAttr)some/path",
+            "(exclude)some/path",
+            "(glob)some/x/path",
+            "(icase)some/path",
+            "(icase,literal)some/xpath",
+            "(top,icase)some/path",
+            "some/path",
+        ];
+        let search = gix_pathspec::Search::from_specs(Some(gix_pathspec::parse(pathspec.as_bytes()).expect("valid")));
+        files
+            .into_iter()
+            .filter(|file| search.matches(*file, Some(false)).is_some())
+            .map(Into::into)
+            .collect()
+    }
+
     #[test]
     fn baseline() {
         for (pattern, exit_code) in BASELINE.iter() {
@@ -63,9 +104,11 @@ mod parse {
         use gix_attributes::State;
         use gix_pathspec::{MagicSignature, MatchMode};
 
+        use bstr::BString;
+
         use crate::parse::{
-            check_valid_inputs, pat, pat_with_attrs, pat_with_path, pat_with_path_and_sig, pat_with_search_mode,
-            pat_with_sig,
+            check_valid_inputs, matching_files, pat, pat_with_attrs, pat_with_path, pat_with_path_and_sig,
+            pat_with_search_mode, pat_with_sig, BASELINE_MATCHES,
         };
 
         #[test]
@@ -81,7 +124,10 @@ mod parse {
                 (":(top,top)", pat_with_sig(MagicSignature::TOP)),
                 (":(icase,icase)", pat_with_sig(MagicSignature::ICASE)),
                 (":(attr,attr)", pat_with_attrs(vec![])),
-                (":!^(exclude,exclude)", pat_with_sig(MagicSignature::EXCLUDE)),
+                (
+                    ":!^(exclude,exclude)",
+                    pat_with_path_and_sig("(exclude,exclude)", MagicSignature::EXCLUDE),
+                ),
             ];
 
             check_valid_inputs(input);
@@ -121,6 +167,35 @@ mod parse {
             check_valid_inputs(inputs)
         }
 
+        #[test]
+        fn trailing_slash_means_directory() {
+            let inputs = vec![
+                (
+                    "some/dir/",
+                    pat_with_path_and_sig("some/dir/", MagicSignature::MUST_BE_DIR),
+                ),
+                (
+                    ":(literal)some/dir/",
+                    pat("some/dir/", MagicSignature::MUST_BE_DIR, MatchMode::Literal, vec![]),
+                ),
+                (
+                    ":(exclude,glob)some/*/",
+                    pat(
+                        "some/*/",
+                        MagicSignature::EXCLUDE | MagicSignature::MUST_BE_DIR,
+                        MatchMode::PathAwareGlob,
+                        vec![],
+                    ),
+                ),
+                (
+                    "some/dir//",
+                    pat_with_path_and_sig("some/dir//", MagicSignature::MUST_BE_DIR),
+                ),
+            ];
+
+            check_valid_inputs(inputs)
+        }
+
         #[test]
         fn short_signatures() {
             let inputs = vec![
@@ -165,21 +240,77 @@ mod parse {
                 ),
                 (
                     ":!(literal)some/*path",
-                    pat("some/*path", MagicSignature::EXCLUDE, MatchMode::Literal, vec![]),
+                    pat_with_path_and_sig("(literal)some/*path", MagicSignature::EXCLUDE),
                 ),
                 (
                     ":(top,literal,icase,attr,exclude)some/path",
-                    pat("some/path", MagicSignature::all(), MatchMode::Literal, vec![]),
+                    pat(
+                        "some/path",
+                        MagicSignature::TOP | MagicSignature::ICASE | MagicSignature::EXCLUDE,
+                        MatchMode::Literal,
+                        vec![],
+                    ),
                 ),
                 (
                     ":(top,glob,icase,attr,exclude)some/path",
-                    pat("some/path", MagicSignature::all(), MatchMode::PathAwareGlob, vec![]),
+                    pat(
+                        "some/path",
+                        MagicSignature::TOP | MagicSignature::ICASE | MagicSignature::EXCLUDE,
+                        MatchMode::PathAwareGlob,
+                        vec![],
+                    ),
                 ),
             ];
 
             check_valid_inputs(inputs);
         }
 
+        #[test]
+        fn parentheses_after_short_signatures_are_part_of_the_path() {
+            let inputs = vec![
+                (
+                    ":!(icase)some/path",
+                    pat_with_path_and_sig("(icase)some/path", MagicSignature::EXCLUDE),
+                ),
+                (
+                    ":^(glob)some/**/path",
+                    pat_with_path_and_sig("(glob)some/**/path", MagicSignature::EXCLUDE),
+                ),
+                (
+                    ":/!(icase,literal)some/*path",
+                    pat_with_path_and_sig(
+                        "(icase,literal)some/*path",
+                        MagicSignature::TOP | MagicSignature::EXCLUDE,
+                    ),
+                ),
+                (
+                    ":!/(top,icase)some/path",
+                    pat_with_path_and_sig("(top,icase)some/path", MagicSignature::TOP | MagicSignature::EXCLUDE),
+                ),
+                (
+                    ":/(exclude)some/path",
+                    pat_with_path_and_sig("(exclude)some/path", MagicSignature::TOP),
+                ),
+                (
+                    ":!(attr:someAttr)some/path",
+                    pat_with_path_and_sig("(attr:someAttr)some/path", MagicSignature::EXCLUDE),
+                ),
+                (
+                    ":/!()some/path",
+                    pat_with_path_and_sig("()some/path", MagicSignature::TOP | MagicSignature::EXCLUDE),
+                ),
+            ];
+            for (input, _) in &inputs {
+                assert_eq!(
+                    matching_files(input),
+                    BASELINE_MATCHES[&BString::from(*input)],
+                    "{input:?} matches the same files as in git"
+                );
+            }
+
+            check_valid_inputs(inputs)
+        }
+
         #[test]
         fn attributes_in_signature() {
             let inputs = vec![
@@ -325,6 +456,36 @@ mod parse {
             }
         }
 
+        #[test]
+        fn invalid_attribute_names() {
+            let inputs = vec![
+                (":(attr:-)some/path", "empty"),
+                (":(attr:!)some/path", "empty"),
+                (":(attr:=value)some/path", "empty"),
+                (":(attr:--someAttr)some/path", "dash"),
+                (":(attr:!-someAttr)some/path", "dash"),
+                (":(attr:sömeAttr)some/path", "non-ascii"),
+                (":(attr:-attrö)some/path", "non-ascii"),
+            ];
+
+            for (input, kind) in inputs {
+                assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+
+                let output = gix_pathspec::parse(input.as_bytes());
+                assert!(output.is_err(), "This pathspec did not produce an error {input}");
+                let err = output.unwrap_err();
+                match kind {
+                    "empty" => assert!(matches!(err, Error::EmptyAttributeName), "{input}: {err:?}"),
+                    "dash" => assert!(
+                        matches!(err, Error::AttributeNameStartsWithDash { .. }),
+                        "{input}: {err:?}"
+                    ),
+                    "non-ascii" => assert!(matches!(err, Error::NonAsciiAttributeName { .. }), "{input}: {err:?}"),
+                    _ => unreachable!("unknown kind"),
+                }
+            }
+        }
+
         #[test]
         fn invalid_attribute_values() {
             let inputs = vec![
@@ -412,6 +573,146 @@ mod parse {
         }
     }
 
+    mod robustness {
+        use gix_pathspec::{parse::Error, MagicSignature, Pattern};
+
+        #[test]
+        fn closing_parenthesis_is_only_searched_after_the_opening_one() {
+            for (input, expected_path) in [(":)(", ")("), (":)", ")"), (":(top)(", "("), ("::(", "(")] {
+                assert_eq!(
+                    Pattern::from_bytes(input.as_bytes()).expect("valid").path,
+                    expected_path
+                );
+            }
+            let p = Pattern::from_bytes(b":/)(").expect("valid");
+            assert_eq!(p.path, ")(");
+            assert_eq!(p.signature, MagicSignature::TOP);
+
+            let p = Pattern::from_bytes(b":/!(top").expect("parentheses after short magic are part of the path");
+            assert_eq!(p.path, "(top");
+            assert_eq!(p.signature, MagicSignature::TOP | MagicSignature::EXCLUDE);
+
+            for input in [":(", ":(attr:a=\\", ":(attr:a=b\\"] {
+                assert!(
+                    matches!(
+                        Pattern::from_bytes(input.as_bytes()),
+                        Err(Error::MissingClosingParenthesis)
+                    ),
+                    "{input:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn keywords_and_attributes_at_the_edges() {
+            for (input, expected) in [
+                (":(,)", "InvalidKeyword"),
+                (":(\\)", "InvalidKeyword"),
+                (":(attr:=)", "EmptyAttributeName"),
+                (":(attr:a=\\)", "TrailingEscapeCharacter"),
+            ] {
+                let err = Pattern::from_bytes(input.as_bytes()).expect_err("invalid");
+                assert!(format!("{err:?}").starts_with(expected), "{input:?}: {err:?}");
+            }
+        }
+
+        #[test]
+        fn short_inputs_never_panic() {
+            let alphabet = b":()!^/\\,=- a";
+            let mut input = Vec::new();
+            let mut indices = Vec::<usize>::new();
+            loop {
+                input.clear();
+                input.push(b':');
+                input.extend(indices.iter().map(|&idx| alphabet[idx]));
+                Pattern::from_bytes(&input).ok();
+                Pattern::from_bytes(&input[1..]).ok();
+
+                match indices.iter().rposition(|&idx| idx + 1 < alphabet.len()) {
+                    Some(pos) => {
+                        indices[pos] += 1;
+                        indices[pos + 1..].iter_mut().for_each(|idx| *idx = 0);
+                    }
+                    None if indices.len() < 5 => {
+                        indices = vec![0; indices.len() + 1];
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+
+    mod options {
+        use gix_pathspec::{parse::Options, MagicSignature, MatchMode, Pattern};
+
+        use crate::parse::{pat, PatternForTesting};
+
+        fn parse(input: &str, options: Options) -> PatternForTesting {
+            Pattern::from_bytes_with_options(input.as_bytes(), options)
+                .expect("valid")
+                .into()
+        }
+
+        #[test]
+        fn force_literal_ignores_all_magic() {
+            let options = Options {
+                force_literal: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                parse(":(glob)foo", options),
+                pat(":(glob)foo", MagicSignature::empty(), MatchMode::Literal, vec![])
+            );
+            assert_eq!(
+                parse("*.rs", options),
+                pat("*.rs", MagicSignature::empty(), MatchMode::Literal, vec![])
+            );
+        }
+
+        #[test]
+        fn force_icase_adds_the_icase_signature() {
+            let options = Options {
+                force_icase: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                parse(":(glob)foo", options),
+                pat("foo", MagicSignature::ICASE, MatchMode::PathAwareGlob, vec![])
+            );
+            assert_eq!(
+                parse(":!foo", options),
+                pat(
+                    "foo",
+                    MagicSignature::ICASE | MagicSignature::EXCLUDE,
+                    MatchMode::ShellGlob,
+                    vec![]
+                )
+            );
+        }
+
+        #[test]
+        fn no_magic_keeps_globs() {
+            let options = Options {
+                no_magic: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                parse(":(exclude)*.rs", options),
+                pat(":(exclude)*.rs", MagicSignature::empty(), MatchMode::ShellGlob, vec![])
+            );
+        }
+
+        #[test]
+        fn defaults_are_the_same_as_from_bytes() {
+            for input in [":(glob)foo", ":!^/bar", "*.rs"] {
+                assert_eq!(
+                    parse(input, Options::default()),
+                    gix_pathspec::parse(input.as_bytes()).expect("valid").into()
+                );
+            }
+        }
+    }
+
     fn check_valid_inputs<'a>(inputs: impl IntoIterator<Item = (&'a str, PatternForTesting)>) {
         for (input, expected) in inputs.into_iter() {
             assert!(
@@ -471,3 +772,425 @@ mod parse {
         *base == 0
     }
 }
+
+mod search {
+    use gix_pathspec::{Pattern, Search};
+
+    fn search(specs: &[&str]) -> Search {
+        Search::from_specs(
+            specs
+                .iter()
+                .map(|spec| gix_pathspec::parse(spec.as_bytes()).expect("valid")),
+        )
+    }
+
+    fn matching_index(search: &Search, path: &str) -> Option<usize> {
+        search.matches(path, Some(false)).map(|m| m.sequence_number)
+    }
+
+    #[test]
+    fn literal_and_directory_prefix() {
+        let search = search(&["dir", "file.txt"]);
+        assert_eq!(matching_index(&search, "dir"), Some(0));
+        assert_eq!(matching_index(&search, "dir/sub/file"), Some(0));
+        assert_eq!(
+            matching_index(&search, "dirty"),
+            None,
+            "prefixes match at directory boundaries"
+        );
+        assert_eq!(matching_index(&search, "file.txt"), Some(1));
+        assert_eq!(matching_index(&search, "other"), None);
+    }
+
+    #[test]
+    fn trailing_slash_matches_the_directory_itself() {
+        let search = search(&["dir/"]);
+        assert_eq!(search.matches("dir", Some(true)).map(|m| m.sequence_number), Some(0));
+        assert_eq!(matching_index(&search, "dir"), None);
+        assert_eq!(matching_index(&search, "dir/file"), Some(0));
+    }
+
+    #[test]
+    fn trailing_slash_only_matches_directories() {
+        let search = search(&["dir/", ":(glob)src/*/"]);
+        assert_eq!(search.matches("dir", Some(true)).map(|m| m.sequence_number), Some(0));
+        assert_eq!(
+            search.matches("dir", Some(false)),
+            None,
+            "a file of the same name doesn't match"
+        );
+        assert_eq!(
+            search.matches("dir", None),
+            None,
+            "if it's unknown whether it's a directory, it doesn't match either"
+        );
+        assert_eq!(matching_index(&search, "dir/file"), Some(0));
+
+        assert_eq!(
+            search
+                .matches("src/sub", Some(true))
+                .map(|m| (m.sequence_number, m.kind)),
+            Some((1, gix_pathspec::search::MatchKind::Glob)),
+            "globs match directories as well"
+        );
+        assert_eq!(search.matches("src/sub", Some(false)), None);
+        assert_eq!(search.matches("src/sub/deeper", Some(true)), None);
+    }
+
+    #[test]
+    fn globs() {
+        let search = search(&["*.rs", ":(glob)src/*.md", ":(literal)*.txt"]);
+        assert_eq!(
+            matching_index(&search, "a/b/lib.rs"),
+            Some(0),
+            "shell globs match slashes"
+        );
+        assert_eq!(matching_index(&search, "src/README.md"), Some(1));
+        assert_eq!(
+            matching_index(&search, "src/sub/README.md"),
+            None,
+            "path-aware globs don't match slashes with a single star"
+        );
+        assert_eq!(matching_index(&search, "file.txt"), None);
+        assert_eq!(matching_index(&search, "*.txt"), Some(2));
+    }
+
+    #[test]
+    fn match_kind() {
+        use gix_pathspec::search::MatchKind;
+
+        let files_search = search(&["dir", "file.txt", "*.rs"]);
+        let kind = |path: &str| files_search.matches(path, Some(false)).map(|m| m.kind);
+        assert_eq!(kind("file.txt"), Some(MatchKind::Exact));
+        assert_eq!(kind("dir"), Some(MatchKind::Exact));
+        assert_eq!(kind("src/lib.rs"), Some(MatchKind::Glob));
+        assert_eq!(kind("dir/sub/file"), Some(MatchKind::Prefix));
+        assert_eq!(kind("other"), None);
+
+        let dir_search = search(&["dir/"]);
+        assert_eq!(
+            dir_search.matches("dir", Some(true)).map(|m| m.kind),
+            Some(MatchKind::Exact),
+            "the directory itself matches exactly"
+        );
+    }
+
+    #[test]
+    fn icase() {
+        let search = search(&[":(icase)DIR/*.RS"]);
+        assert_eq!(matching_index(&search, "dir/lib.rs"), Some(0));
+        assert_eq!(
+            search.common_prefix(),
+            "",
+            "case-insensitive patterns can't be used for pruning"
+        );
+    }
+
+    #[test]
+    fn global_ignore_case() {
+        let case_sensitive = search(&["Dir/*.RS", ":(exclude)Dir/Skip.rs"]);
+        assert_eq!(matching_index(&case_sensitive, "dir/lib.rs"), None);
+        assert_eq!(case_sensitive.common_prefix(), "Dir/");
+
+        let search = case_sensitive.with_ignore_case(true);
+        assert_eq!(
+            matching_index(&search, "dir/lib.rs"),
+            Some(0),
+            "patterns without the icase signature now match case-insensitively"
+        );
+        assert_eq!(matching_index(&search, "DIR/LIB.RS"), Some(0));
+        assert_eq!(
+            matching_index(&search, "dir/skip.rs"),
+            None,
+            "excludes are case-insensitive as well"
+        );
+        assert_eq!(
+            search.common_prefix(),
+            "",
+            "the prefix can't be used for pruning anymore"
+        );
+
+        let merged = search.merge(Search::from_specs(Some(gix_pathspec::parse(b"file").unwrap())));
+        assert_eq!(matching_index(&merged, "FILE"), Some(2), "merging keeps ignoring case");
+    }
+
+    #[test]
+    fn later_positive_patterns_win() {
+        let search = search(&["dir", "dir/*.rs"]);
+        assert_eq!(matching_index(&search, "dir/lib.rs"), Some(1));
+        assert_eq!(matching_index(&search, "dir/lib.c"), Some(0));
+    }
+
+    #[test]
+    fn excludes_override_includes_regardless_of_order() {
+        let search = search(&[":!*.lock", "dir"]);
+        assert_eq!(matching_index(&search, "dir/Cargo.lock"), None);
+        assert_eq!(matching_index(&search, "dir/Cargo.toml"), Some(1));
+        assert_eq!(matching_index(&search, "Cargo.toml"), None);
+    }
+
+    #[test]
+    fn only_excludes_include_everything_else() {
+        let search = search(&[":!*.lock", ":^dir"]);
+        assert_eq!(matching_index(&search, "Cargo.lock"), None);
+        assert_eq!(matching_index(&search, "dir/file"), None);
+        assert_eq!(
+            matching_index(&search, "Cargo.toml"),
+            Some(2),
+            "the implicit match-all pattern is placed past the last provided one"
+        );
+        assert_eq!(search.common_prefix(), "");
+    }
+
+    #[test]
+    fn merge_combines_positives_and_excludes_of_both() {
+        let merged = search(&["src", ":!src/generated"]).merge(search(&["tests", ":!*.snap"]));
+        assert_eq!(merged.patterns().len(), 4);
+        assert_eq!(matching_index(&merged, "src/lib.rs"), Some(0));
+        assert_eq!(matching_index(&merged, "tests/it.rs"), Some(2));
+        assert_eq!(
+            matching_index(&merged, "src/generated/code.rs"),
+            None,
+            "excludes of the first search apply"
+        );
+        assert_eq!(
+            matching_index(&merged, "src/ui.snap"),
+            None,
+            "excludes of the second search apply to positives of the first one"
+        );
+        assert_eq!(matching_index(&merged, "README.md"), None);
+        assert_eq!(merged.common_prefix(), "");
+    }
+
+    #[test]
+    fn merge_with_only_excludes_scopes_them() {
+        let user = search(&[":!*.lock"]);
+        assert_eq!(matching_index(&user, "Cargo.toml"), Some(1));
+
+        let merged = user.clone().merge(search(&["crate/"]));
+        assert_eq!(
+            merged.patterns().len(),
+            2,
+            "the implicit match-all pattern is dropped as there are positive patterns now"
+        );
+        assert_eq!(matching_index(&merged, "crate/Cargo.toml"), Some(1));
+        assert_eq!(matching_index(&merged, "crate/Cargo.lock"), None);
+        assert_eq!(matching_index(&merged, "Cargo.toml"), None);
+        assert_eq!(merged.common_prefix(), "crate/");
+
+        let merged = user.merge(search(&[":!target"]));
+        assert_eq!(matching_index(&merged, "Cargo.lock"), None);
+        assert_eq!(matching_index(&merged, "target/debug"), None);
+        assert_eq!(
+            matching_index(&merged, "Cargo.toml"),
+            Some(2),
+            "with only excludes on both sides, everything else still matches"
+        );
+    }
+
+    #[test]
+    fn empty_list_matches_everything() {
+        let search = search(&[]);
+        assert_eq!(search.patterns(), &[Pattern::matches_all()]);
+        assert_eq!(matching_index(&search, "file"), Some(0));
+        assert_eq!(matching_index(&search, "dir/sub/file"), Some(0));
+        assert_eq!(search.common_prefix(), "");
+
+        assert!(
+            matches!(gix_pathspec::parse(b""), Err(gix_pathspec::parse::Error::EmptyString)),
+            "an empty string is an error, not a match-all pattern"
+        );
+    }
+
+    #[test]
+    fn match_all_pattern() {
+        assert!(Pattern::matches_all().is_match_all());
+        assert!(gix_pathspec::parse(b":/").unwrap().is_match_all());
+        assert!(
+            !gix_pathspec::parse(b":!").unwrap().is_match_all(),
+            "excludes never match all"
+        );
+        assert!(!gix_pathspec::parse(b"dir").unwrap().is_match_all());
+
+        let merged = search(&[]).merge(search(&["dir"]));
+        assert_eq!(
+            matching_index(&merged, "file"),
+            None,
+            "the implicit match-all pattern doesn't survive a merge with positive patterns"
+        );
+    }
+
+    #[test]
+    fn common_prefix() {
+        assert_eq!(search(&["a/b/c", "a/b/d/*.rs", ":!a/x"]).common_prefix(), "a/b/");
+        assert_eq!(search(&["a/b/c/"]).common_prefix(), "a/b/c/");
+        assert_eq!(search(&["a/b*/c"]).common_prefix(), "a/");
+        assert_eq!(search(&["a/b", "c"]).common_prefix(), "");
+
+        let search = search(&["a/b/c", "a/b/d"]);
+        assert_eq!(matching_index(&search, "x/y/z/w"), None);
+        assert_eq!(matching_index(&search, "a/b/d/e"), Some(1));
+    }
+}
+
+mod wildmatch {
+    use gix_pathspec::MatchMode;
+
+    #[test]
+    fn corpus() {
+        // A subset of git/t/t3070-wildmatch.sh, with the results of `wildmatch` (with WM_PATHNAME) and `pathmatch`.
+        #[rustfmt::skip]
+        let tests = [
+            (true, true, "foo", "foo"),
+            (false, false, "foo", "bar"),
+            (true, true, "foo", "???"),
+            (false, false, "foo", "??"),
+            (true, true, "foo", "*"),
+            (true, true, "foo", "f*"),
+            (false, false, "foo", "*f"),
+            (true, true, "foobar", "*ob*a*r*"),
+            (true, true, "foo*", r"foo\*"),
+            (false, false, "foobar", r"foo\*bar"),
+            (true, true, "ball", "*[al]?"),
+            (false, false, "ten", "[ten]"),
+            (true, true, "ten", "**[!te]"),
+            (false, false, "ten", "**[!ten]"),
+            (true, true, "ten", "t[a-g]n"),
+            (false, false, "ten", "t[!a-g]n"),
+            (true, true, "ton", "t[^a-g]n"),
+            (true, true, "a]b", "a[]]b"),
+            (true, true, "a-b", "a[]-]b"),
+            (false, false, "aab", "a[]-]b"),
+            (false, true, "foo/baz/bar", "foo*bar"),
+            (false, true, "foo/baz/bar", "foo**bar"),
+            (true, true, "foobazbar", "foo**bar"),
+            (true, true, "foo/baz/bar", "foo/**/bar"),
+            (true, false, "foo/baz/bar", "foo/**/**/bar"),
+            (true, true, "foo/b/a/z/bar", "foo/**/bar"),
+            (true, false, "foo/bar", "foo/**/bar"),
+            (false, true, "foo/bar", "foo?bar"),
+            (false, true, "foo/bar", "foo[/]bar"),
+            (false, true, "foo/bar", "foo[^a-z]bar"),
+            (true, false, "foo", "**/foo"),
+            (true, true, "bar/baz/foo", "**/foo"),
+            (false, true, "bar/baz/foo", "*/foo"),
+            (false, true, "foo/bar/baz", "**/bar*"),
+            (true, true, "deep/foo/bar/baz", "**/bar/*"),
+            (false, true, "deep/foo/bar/baz/", "**/bar/*"),
+            (true, true, "deep/foo/bar/baz/", "**/bar/**"),
+            (false, false, "deep/foo/bar", "**/bar/*"),
+            (true, true, "foo/bar/baz/x", "*/bar/**"),
+            (false, true, "deep/foo/bar/baz/x", "*/bar/**"),
+            (true, true, "deep/foo/bar/baz/x", "**/bar/*/*"),
+            (true, true, "a1B", "[[:alpha:]][[:digit:]][[:upper:]]"),
+            (false, false, "a", "[[:digit:][:upper:][:space:]]"),
+            (true, true, "A", "[[:digit:][:upper:][:space:]]"),
+            (false, false, "1", "[[:digit:][:upper:][:spaci:]]"),
+            (true, true, "5", "[a-c[:digit:]x-z]"),
+            (false, false, "q", "[a-c[:digit:]x-z]"),
+            (true, true, "-", "[--A]"),
+            (false, false, "j", "[a-e-n]"),
+            (true, true, "-", "[a-e-n]"),
+        ];
+
+        for (path_aware, shell, path, pattern) in tests {
+            assert_eq!(
+                gix_pathspec::wildmatch(pattern.into(), path.into(), MatchMode::PathAwareGlob),
+                path_aware,
+                "path-aware: '{pattern}' against '{path}'"
+            );
+            assert_eq!(
+                gix_pathspec::wildmatch(pattern.into(), path.into(), MatchMode::ShellGlob),
+                shell,
+                "shell: '{pattern}' against '{path}'"
+            );
+        }
+    }
+
+    #[test]
+    fn literal() {
+        assert!(gix_pathspec::wildmatch("f*".into(), "f*".into(), MatchMode::Literal));
+        assert!(!gix_pathspec::wildmatch("f*".into(), "foo".into(), MatchMode::Literal));
+    }
+}
+
+mod normalize {
+    use gix_pathspec::{normalize::Error, MagicSignature, MatchMode, Pattern};
+
+    fn normalize(spec: &str, prefix: &str) -> Result<Pattern, Error> {
+        gix_pathspec::parse(spec.as_bytes())
+            .expect("valid")
+            .normalize(prefix.into())
+    }
+
+    fn path_of(spec: &str, prefix: &str) -> String {
+        normalize(spec, prefix).expect("valid").path.to_string()
+    }
+
+    #[test]
+    fn glob_is_prefixed() {
+        let pattern = normalize(":(glob)*.rs", "sub/dir").unwrap();
+        assert_eq!(pattern.path, "sub/dir/*.rs");
+        assert_eq!(pattern.search_mode, MatchMode::PathAwareGlob);
+        assert_eq!(
+            pattern.signature,
+            MagicSignature::TOP,
+            "the result is relative to the root"
+        );
+        assert_eq!(pattern.normalize("sub/dir".into()).unwrap(), pattern, "it's idempotent");
+    }
+
+    #[test]
+    fn literal_is_prefixed() {
+        let pattern = normalize(":(literal)a*", "sub/").unwrap();
+        assert_eq!(pattern.path, "sub/a*");
+        assert_eq!(pattern.search_mode, MatchMode::Literal);
+    }
+
+    #[test]
+    fn top_is_not_prefixed() {
+        assert_eq!(path_of(":/file", "sub/dir"), "file");
+        assert_eq!(path_of(":(top)dir/", "sub"), "dir/");
+    }
+
+    #[test]
+    fn must_be_dir_follows_the_trailing_slash() {
+        let must_be_dir = |spec: &str, prefix: &str| {
+            normalize(spec, prefix)
+                .expect("valid")
+                .signature
+                .contains(MagicSignature::MUST_BE_DIR)
+        };
+        assert!(must_be_dir("dir/", "sub"));
+        assert!(must_be_dir(".", "sub/dir"), "the prefix directory itself");
+        assert!(must_be_dir("file/..", "sub"));
+        assert!(!must_be_dir("file", "sub"));
+        assert!(!must_be_dir("../..", "sub/dir"), "the root matches everything");
+    }
+
+    #[test]
+    fn dot_components_are_resolved() {
+        assert_eq!(path_of("../file", "sub/dir"), "sub/file");
+        assert_eq!(path_of("./a/./b", "sub"), "sub/a/b");
+        assert_eq!(path_of(".", "sub/dir"), "sub/dir/", "the prefix directory itself");
+        assert_eq!(path_of("..", "sub/dir"), "sub/");
+        assert_eq!(path_of("../..", "sub/dir"), "", "the root matches everything");
+        assert_eq!(path_of("file", ""), "file");
+    }
+
+    #[test]
+    fn leaving_the_repository_or_absolute_paths_fail() {
+        assert!(matches!(
+            normalize("../../file", "sub"),
+            Err(Error::OutsideOfRepository { .. })
+        ));
+        assert!(matches!(
+            normalize(":/../file", "sub"),
+            Err(Error::OutsideOfRepository { .. })
+        ));
+        assert!(matches!(
+            normalize("/etc/passwd", "sub"),
+            Err(Error::AbsolutePath { .. })
+        ));
+    }
+}
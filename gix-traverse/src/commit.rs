@@ -1,18 +1,30 @@
 /// An iterator over the ancestors one or more starting commits
 pub struct Ancestors<Find, Predicate, StateMut> {
     find: Find,
+    cache: Option<gix_commitgraph::Graph>,
     predicate: Predicate,
     state: StateMut,
     parents: Parents,
     sorting: Sorting,
+    /// If `true`, the queued commits are ordered by their generation number, which is only done with a commit-graph.
+    queue_is_ordered_by_generation: bool,
+}
+
+/// A commit obtained from a commit-graph, or by decoding its object if it isn't in the commit-graph.
+enum FoundCommit<'buf, 'cache> {
+    Decoded(gix_object::CommitRefIter<'buf>),
+    InGraph(gix_commitgraph::file::Commit<'cache>),
 }
 
 /// Specify how to handle commit parents during traversal.
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum Parents {
     /// Traverse all parents, useful for traversing the entire ancestry.
     All,
     /// Only traverse along the first parent, which commonly ignores all branches.
+    ///
+    /// This is like `git log --first-parent` and yields the mainline as a sequence in which each commit is the
+    /// first parent of the commit before it, so consecutive commits can be diffed pairwise.
     First,
 }
 
@@ -23,9 +35,14 @@ impl Default for Parents {
 }
 
 /// Specify how to sort commits during traversal.
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum Sorting {
     /// Commits are sorted as they are mentioned in the commit graph.
+    ///
+    /// With a [commit-graph][Ancestors::commit_graph()], commits are ordered by their generation number instead, so that
+    /// no commit in the commit-graph is returned before all of its descendants that are returned, like `git log --topo-order`
+    /// guarantees. Commits with the same generation number, and commits that aren't in the commit-graph, are still returned
+    /// in the order they are mentioned.
     Topological,
     /// Commits are sorted by their commit time in descending order, that is newest first.
     ///
@@ -52,19 +69,41 @@ impl Default for Sorting {
     }
 }
 
+/// Count all commits reachable from `tips`, including the tips themselves, while reporting each counted commit to `progress`.
+///
+/// `find` is used to lookup commits as described in [`Ancestors::new()`], and all parents are traversed in topological order.
+/// Use [`Discard`][gix_features::progress::Discard] for `progress` if no progress reporting is required.
+pub fn count_ancestors<Find, E>(
+    tips: impl IntoIterator<Item = impl Into<gix_hash::ObjectId>>,
+    find: Find,
+    mut progress: impl gix_features::progress::Progress,
+) -> Result<usize, ancestors::Error>
+where
+    Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Result<gix_object::CommitRefIter<'a>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    progress.init(None, gix_features::progress::count("commits"));
+    let start = std::time::Instant::now();
+    let mut count = 0;
+    for commit in Ancestors::new(tips, ancestors::State::default(), find) {
+        commit?;
+        count += 1;
+        progress.inc();
+    }
+    progress.show_throughput(start);
+    Ok(count)
+}
+
 ///
 pub mod ancestors {
-    use std::{
-        borrow::{Borrow, BorrowMut},
-        collections::VecDeque,
-        iter::FromIterator,
-    };
+    use std::{borrow::BorrowMut, collections::VecDeque, iter::FromIterator};
 
+    use gix_commitgraph::{GENERATION_NUMBER_INFINITY, GENERATION_NUMBER_MAX};
     use gix_hash::{oid, ObjectId};
     use gix_hashtable::HashSet;
     use gix_object::CommitRefIter;
 
-    use crate::commit::{Ancestors, Parents, Sorting};
+    use crate::commit::{Ancestors, FoundCommit, Parents, Sorting};
 
     /// The error is part of the item returned by the [Ancestors] iterator.
     #[derive(Debug, thiserror::Error)]
@@ -77,24 +116,213 @@ pub mod ancestors {
         },
         #[error(transparent)]
         ObjectDecode(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        CommitGraph(#[from] gix_commitgraph::file::commit::Error),
     }
 
     type TimeInSeconds = u32;
+    type Generation = u32;
 
     /// The state used and potentially shared by multiple graph traversals.
+    ///
+    /// With the `serde1` feature, it can be serialized to pause a traversal and [resume][Ancestors::resume()] it later,
+    /// possibly in another process, without walking the commits that were already returned again.
     #[derive(Default, Clone)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
     pub struct State {
+        /// The commits to return next, along with their commit time if sorted by time, or their generation number
+        /// if sorted topologically with a commit-graph.
         next: VecDeque<(ObjectId, TimeInSeconds)>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
         buf: Vec<u8>,
+        /// The commit that was returned last if it was obtained from the commit-graph, and thus isn't in `buf`.
+        #[cfg_attr(feature = "serde1", serde(skip))]
+        buf_lacks: Option<ObjectId>,
+        #[cfg_attr(feature = "serde1", serde(with = "id_set"))]
         seen: HashSet<ObjectId>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
         parents_buf: Vec<u8>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
+        parent_ids: Vec<ObjectId>,
+        boundary: Boundary,
+    }
+
+    /// The commits reachable from the boundary of a traversal, as far as they are known.
+    #[derive(Default, Clone)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    struct Boundary {
+        /// The commits whose parents weren't seen yet, ordered by generation number and then by commit time, youngest first.
+        next: VecDeque<(ObjectId, (Generation, TimeInSeconds))>,
+        /// All commits known to be reachable from the boundary.
+        #[cfg_attr(feature = "serde1", serde(with = "id_set"))]
+        seen: HashSet<ObjectId>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
+        buf: Vec<u8>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
+        parents_buf: Vec<u8>,
+        #[cfg_attr(feature = "serde1", serde(skip))]
+        parent_ids: Vec<ObjectId>,
+    }
+
+    /// (De)serialize sets of ids as sequences, as the hashtable they are kept in doesn't support `serde`.
+    #[cfg(feature = "serde1")]
+    mod id_set {
+        use gix_hash::ObjectId;
+        use gix_hashtable::HashSet;
+
+        pub fn serialize<S: serde::Serializer>(set: &HashSet<ObjectId>, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(set)
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<HashSet<ObjectId>, D::Error> {
+            let ids: Vec<ObjectId> = serde::Deserialize::deserialize(deserializer)?;
+            Ok(ids.into_iter().collect())
+        }
     }
 
     impl State {
         fn clear(&mut self) {
             self.next.clear();
             self.buf.clear();
+            self.buf_lacks = None;
             self.seen.clear();
+            self.boundary.next.clear();
+            self.boundary.seen.clear();
+        }
+    }
+
+    impl Boundary {
+        /// Return `true` if `commit` with `id` is reachable from a boundary commit.
+        ///
+        /// The boundary is traversed lazily, only until all of its commits with a higher generation number than `commit`
+        /// are known, as only these can have `commit` as ancestor. If `commit` has no generation number, i.e. if it isn't in the
+        /// commit-graph, boundary commits without generation number are traversed until all that are at least as young
+        /// as `commit` are known instead. This assumes that commits are never younger than their parents, as `git` does as well.
+        fn contains<Find, E>(
+            &mut self,
+            id: &oid,
+            commit: &FoundCommit<'_, '_>,
+            cache: Option<&gix_commitgraph::Graph>,
+            find: &mut Find,
+        ) -> Result<bool, Error>
+        where
+            Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+            E: std::error::Error + Send + Sync + 'static,
+        {
+            if self.seen.is_empty() {
+                return Ok(false);
+            }
+            let generation = commit.generation();
+            let commit_time = commit.commit_time()?;
+            while let Some((boundary_id, _)) = self
+                .next
+                .front()
+                .filter(|(_, (boundary_generation, boundary_time))| {
+                    *boundary_generation > generation
+                        || (generation == GENERATION_NUMBER_INFINITY
+                            && *boundary_generation == GENERATION_NUMBER_INFINITY
+                            && *boundary_time >= commit_time)
+                })
+                .copied()
+            {
+                self.next.pop_front();
+                find_commit(cache, find, &boundary_id, &mut self.buf)?.parents(cache, &mut self.parent_ids)?;
+                for id in self.parent_ids.drain(..) {
+                    if !self.seen.insert(id) {
+                        continue;
+                    }
+                    let parent = find_commit(cache, find, &id, &mut self.parents_buf)?;
+                    let key = (parent.generation(), parent.commit_time()?);
+                    let pos = self.next.partition_point(|(_, other)| *other >= key);
+                    self.next.insert(pos, (id, key));
+                }
+            }
+            Ok(self.seen.contains(id))
+        }
+    }
+
+    /// Lookup the commit with `id` in `cache`, or use `find` to decode it into `buf` if it isn't in the commit-graph.
+    fn find_commit<'cache, 'buf, Find, E>(
+        cache: Option<&'cache gix_commitgraph::Graph>,
+        find: &mut Find,
+        id: &oid,
+        buf: &'buf mut Vec<u8>,
+    ) -> Result<FoundCommit<'buf, 'cache>, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match cache.and_then(|cache| cache.commit_by_id(id)) {
+            Some(commit) => Ok(FoundCommit::InGraph(commit)),
+            None => decode_commit(find, id, buf).map(FoundCommit::Decoded),
+        }
+    }
+
+    /// Use `find` to decode the commit with `id` into `buf`.
+    fn decode_commit<'buf, Find, E>(
+        find: &mut Find,
+        id: &oid,
+        buf: &'buf mut Vec<u8>,
+    ) -> Result<CommitRefIter<'buf>, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        find(id, buf).map_err(|err| Error::FindExisting {
+            oid: id.to_owned(),
+            source: err.into(),
+        })
+    }
+
+    /// Return the generation number of the commit with `id` in `cache`, or [`GENERATION_NUMBER_INFINITY`] if it isn't known.
+    fn generation_of(cache: &gix_commitgraph::Graph, id: &oid) -> Generation {
+        cache.commit_by_id(id).map_or(GENERATION_NUMBER_INFINITY, |commit| {
+            FoundCommit::InGraph(commit).generation()
+        })
+    }
+
+    impl FoundCommit<'_, '_> {
+        /// Return the generation number of this commit, or [`GENERATION_NUMBER_INFINITY`] if it isn't known.
+        ///
+        /// Generation numbers that were capped or never computed can't be used to tell ancestors apart, and are unknown as well.
+        fn generation(&self) -> Generation {
+            match self {
+                FoundCommit::Decoded(_) => GENERATION_NUMBER_INFINITY,
+                FoundCommit::InGraph(commit) => match commit.generation() {
+                    0 | GENERATION_NUMBER_MAX.. => GENERATION_NUMBER_INFINITY,
+                    generation => generation,
+                },
+            }
+        }
+
+        fn commit_time(&self) -> Result<TimeInSeconds, Error> {
+            Ok(match self {
+                FoundCommit::Decoded(commit) => commit.committer()?.time.seconds_since_unix_epoch,
+                FoundCommit::InGraph(commit) => commit.committer_timestamp() as TimeInSeconds,
+            })
+        }
+
+        /// Replace the contents of `out` with the ids of all parents of this commit.
+        fn parents(&self, cache: Option<&gix_commitgraph::Graph>, out: &mut Vec<ObjectId>) -> Result<(), Error> {
+            out.clear();
+            match self {
+                FoundCommit::Decoded(commit) => {
+                    for token in *commit {
+                        match token? {
+                            gix_object::commit::ref_iter::Token::Tree { .. } => continue,
+                            gix_object::commit::ref_iter::Token::Parent { id } => out.push(id),
+                            _a_token_past_the_parents => break,
+                        }
+                    }
+                }
+                FoundCommit::InGraph(commit) => {
+                    let cache = cache.expect("cached commits are only obtained from a commit-graph");
+                    for pos in commit.iter_parents() {
+                        out.push(cache.id_at(pos?).to_owned());
+                    }
+                }
+            }
+            Ok(())
         }
     }
 
@@ -105,6 +333,18 @@ pub mod ancestors {
             self.parents = mode;
             self
         }
+
+        /// Set the commit-graph to use for looking up the parents and commit times of commits in `cache`, which avoids
+        /// having to find and decode them. Commits that aren't in the commit-graph are still looked up with `find`.
+        ///
+        /// With [topological sorting][Sorting::Topological], commits are also ordered by their generation number.
+        ///
+        /// Note that this should be set before [`sorting()`][Self::sorting()] to also speed up sorting the tips.
+        pub fn commit_graph(mut self, cache: Option<gix_commitgraph::Graph>) -> Self {
+            self.cache = cache;
+            self.queue_is_ordered_by_generation = false;
+            self
+        }
     }
 
     /// Builder
@@ -121,11 +361,8 @@ pub mod ancestors {
                 let mut cutoff_time_storage = self.sorting.cutoff_time().map(|cot| (cot, Vec::new()));
                 let state = self.state.borrow_mut();
                 for (commit_id, commit_time) in state.next.iter_mut() {
-                    let commit_iter = (self.find)(commit_id, &mut state.buf).map_err(|err| Error::FindExisting {
-                        oid: *commit_id,
-                        source: err.into(),
-                    })?;
-                    let time = commit_iter.committer()?.time.seconds_since_unix_epoch;
+                    let time =
+                        find_commit(self.cache.as_ref(), &mut self.find, commit_id, &mut state.buf)?.commit_time()?;
                     match &mut cutoff_time_storage {
                         Some((cutoff_time, storage)) if time >= *cutoff_time => {
                             storage.push((*commit_id, time));
@@ -161,10 +398,55 @@ pub mod ancestors {
         ///   this state.
         /// * `tips`
         ///   * the starting points of the iteration, usually commits
-        ///   * each commit they lead to will only be returned once, including the tip that started it
+        ///   * each commit they lead to will only be returned once, including the tip that started it, which guarantees
+        ///     termination even if the commit graph contains cycles as it may if it is corrupted or crafted maliciously.
         pub fn new(tips: impl IntoIterator<Item = impl Into<ObjectId>>, state: StateMut, find: Find) -> Self {
             Self::filtered(tips, state, find, |_| true)
         }
+
+        /// Create a new instance like [`new()`][Self::new()], which doesn't return commits reachable from any of the
+        /// `boundary` commits, like `git log tips ^boundary` would.
+        ///
+        /// The traversal stops at these commits instead of descending into their parents. To know them, the boundary
+        /// is traversed by commit time as well, but only until all of its commits are known that are at least as young
+        /// as the commit that is about to be returned. This way the history reachable from both the `tips` and the
+        /// `boundary` is typically traversed only once, instead of having to traverse the entire history of the
+        /// `boundary` upfront.
+        /// With a [commit-graph][Self::commit_graph()], the boundary is traversed by generation number instead and
+        /// never below the generation number of the commit that is about to be returned, which is exact even if clocks
+        /// are skewed.
+        /// Note that without generation numbers, this assumes that commits are never younger than their parents, so
+        /// commits with a committer time that is skewed into the past may be returned even though they are reachable
+        /// from the `boundary`.
+        pub fn new_with_boundary(
+            tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+            boundary: impl IntoIterator<Item = impl Into<ObjectId>>,
+            state: StateMut,
+            find: Find,
+        ) -> Self {
+            let mut ancestors = Self::new(tips, state, find);
+            let boundary_state = &mut ancestors.state.borrow_mut().boundary;
+            for id in boundary.into_iter().map(Into::into) {
+                if boundary_state.seen.insert(id) {
+                    // Boundary tips are traversed first as their generation and time aren't known yet.
+                    boundary_state
+                        .next
+                        .push_back((id, (GENERATION_NUMBER_INFINITY, TimeInSeconds::MAX)));
+                }
+            }
+            ancestors
+        }
+
+        /// Resume a traversal whose `state` was kept after it was paused, for instance by serializing and deserializing it,
+        /// to return exactly the commits the paused traversal didn't return yet.
+        ///
+        /// `sorting` must be the [sorting][Self::sorting()] of the paused traversal, which is applied without sorting the
+        /// queued commits again as they are already in order. The same is true for the [parents mode][Self::parents()]
+        /// and the [commit-graph][Self::commit_graph()], which have to be set again as well.
+        /// See [`resume_filtered()`][Ancestors::resume_filtered()] for resuming traversals that used a predicate.
+        pub fn resume(state: StateMut, sorting: Sorting, find: Find) -> Self {
+            Self::resume_filtered(state, sorting, find, |_| true)
+        }
     }
 
     /// Initialization
@@ -184,7 +466,8 @@ pub mod ancestors {
         ///   this state.
         /// * `tips`
         ///   * the starting points of the iteration, usually commits
-        ///   * each commit they lead to will only be returned once, including the tip that started it
+        ///   * each commit they lead to will only be returned once, including the tip that started it, which guarantees
+        ///     termination even if the commit graph contains cycles as it may if it is corrupted or crafted maliciously.
         /// * `predicate` - indicate whether a given commit should be included in the result as well
         ///   as whether its parent commits should be traversed.
         pub fn filtered(
@@ -207,21 +490,46 @@ pub mod ancestors {
             }
             Self {
                 find,
+                cache: None,
                 predicate,
                 state,
                 parents: Default::default(),
                 sorting: Default::default(),
+                queue_is_ordered_by_generation: false,
+            }
+        }
+
+        /// Like [`resume()`][Ancestors::resume()], but for traversals that were created with [`filtered()`][Self::filtered()],
+        /// with `predicate` being the same as the one of the paused traversal.
+        pub fn resume_filtered(state: StateMut, sorting: Sorting, find: Find, predicate: Predicate) -> Self {
+            Self {
+                find,
+                cache: None,
+                predicate,
+                state,
+                parents: Default::default(),
+                sorting,
+                queue_is_ordered_by_generation: false,
             }
         }
     }
+
     /// Access
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, E> Ancestors<Find, Predicate, StateMut>
     where
-        StateMut: Borrow<State>,
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        StateMut: BorrowMut<State>,
+        E: std::error::Error + Send + Sync + 'static,
     {
-        /// Return an iterator for accessing more of the current commits data.
-        pub fn commit_iter(&self) -> CommitRefIter<'_> {
-            CommitRefIter::from_bytes(&self.state.borrow().buf)
+        /// Return an iterator for accessing more of the data of the commit that was returned last.
+        ///
+        /// If it was obtained from a [commit-graph][Self::commit_graph()], it's looked up with `find` first.
+        pub fn commit_iter(&mut self) -> Result<CommitRefIter<'_>, Error> {
+            let state = self.state.borrow_mut();
+            if let Some(id) = state.buf_lacks.take() {
+                decode_commit(&mut self.find, &id, &mut state.buf)?;
+            }
+            Ok(CommitRefIter::from_bytes(&state.buf))
         }
     }
 
@@ -271,63 +579,49 @@ pub mod ancestors {
     {
         fn next_by_commit_date(&mut self, cutoff_older_than: Option<TimeInSeconds>) -> Option<Result<ObjectId, Error>> {
             let state = self.state.borrow_mut();
+            let cache = self.cache.as_ref();
 
-            let (oid, _commit_time) = state.next.pop_front()?;
-            match (self.find)(&oid, &mut state.buf) {
-                Ok(commit_iter) => {
-                    let mut count = 0;
-                    for token in commit_iter {
-                        count += 1;
-                        let is_first = count == 1;
-                        match token {
-                            Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
-                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
-                                let was_inserted = state.seen.insert(id);
-                                if !(was_inserted && (self.predicate)(&id)) {
-                                    if is_first && matches!(self.parents, Parents::First) {
-                                        break;
-                                    } else {
-                                        continue;
-                                    }
-                                }
-
-                                let parent = (self.find)(id.as_ref(), &mut state.parents_buf).ok();
-                                let parent_commit_time = parent
-                                    .and_then(|parent| {
-                                        parent
-                                            .committer()
-                                            .ok()
-                                            .map(|committer| committer.time.seconds_since_unix_epoch)
-                                    })
-                                    .unwrap_or_default();
-
-                                let pos = match state.next.binary_search_by(|c| c.1.cmp(&parent_commit_time).reverse())
-                                {
-                                    Ok(_) => None,
-                                    Err(pos) => Some(pos),
-                                };
-                                match cutoff_older_than {
-                                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                                    Some(_) | None => match pos {
-                                        Some(pos) => state.next.insert(pos, (id, parent_commit_time)),
-                                        None => state.next.push_back((id, parent_commit_time)),
-                                    },
-                                }
-
-                                if is_first && matches!(self.parents, Parents::First) {
-                                    break;
-                                }
-                            }
-                            Ok(_unused_token) => break,
-                            Err(err) => return Some(Err(err.into())),
-                        }
-                    }
+            let oid = loop {
+                let (oid, _key) = state.next.pop_front()?;
+                let commit = match find_commit(cache, &mut self.find, &oid, &mut state.buf) {
+                    Ok(commit) => commit,
+                    Err(err) => return Some(Err(err)),
+                };
+                match state.boundary.contains(&oid, &commit, cache, &mut self.find) {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(err) => return Some(Err(err)),
                 }
-                Err(err) => {
-                    return Some(Err(Error::FindExisting {
-                        oid,
-                        source: err.into(),
-                    }))
+                if let Err(err) = commit.parents(cache, &mut state.parent_ids) {
+                    return Some(Err(err));
+                }
+                state.buf_lacks = matches!(commit, FoundCommit::InGraph(_)).then_some(oid);
+                break oid;
+            };
+
+            for (idx, id) in state.parent_ids.iter().enumerate() {
+                if idx > 0 && matches!(self.parents, Parents::First) {
+                    break;
+                }
+                let was_inserted = state.seen.insert(*id);
+                if !(was_inserted && (self.predicate)(id)) {
+                    continue;
+                }
+
+                let parent_commit_time = find_commit(cache, &mut self.find, id, &mut state.parents_buf)
+                    .and_then(|parent| parent.commit_time())
+                    .unwrap_or_default();
+
+                let pos = match state.next.binary_search_by(|c| c.1.cmp(&parent_commit_time).reverse()) {
+                    Ok(_) => None,
+                    Err(pos) => Some(pos),
+                };
+                match cutoff_older_than {
+                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
+                    Some(_) | None => match pos {
+                        Some(pos) => state.next.insert(pos, (*id, parent_commit_time)),
+                        None => state.next.push_back((*id, parent_commit_time)),
+                    },
                 }
             }
             Some(Ok(oid))
@@ -344,31 +638,51 @@ pub mod ancestors {
     {
         fn next_by_topology(&mut self) -> Option<Result<ObjectId, Error>> {
             let state = self.state.borrow_mut();
-            let (oid, _commit_time) = state.next.pop_front()?;
-            match (self.find)(&oid, &mut state.buf) {
-                Ok(commit_iter) => {
-                    for token in commit_iter {
-                        match token {
-                            Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
-                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
-                                let was_inserted = state.seen.insert(id);
-                                if was_inserted && (self.predicate)(&id) {
-                                    state.next.push_back((id, 0));
-                                }
-                                if matches!(self.parents, Parents::First) {
-                                    break;
-                                }
-                            }
-                            Ok(_a_token_past_the_parents) => break,
-                            Err(err) => return Some(Err(err.into())),
+            let cache = self.cache.as_ref();
+            if let Some(cache) = cache.filter(|_| !self.queue_is_ordered_by_generation) {
+                // The tips, or the commits queued by a paused traversal, are ordered only once the commit-graph is known.
+                for (id, generation) in state.next.iter_mut() {
+                    *generation = generation_of(cache, id);
+                }
+                state
+                    .next
+                    .make_contiguous()
+                    .sort_by(|(_, a), (_, b)| a.cmp(b).reverse());
+                self.queue_is_ordered_by_generation = true;
+            }
+
+            let oid = loop {
+                let (oid, _key) = state.next.pop_front()?;
+                let commit = match find_commit(cache, &mut self.find, &oid, &mut state.buf) {
+                    Ok(commit) => commit,
+                    Err(err) => return Some(Err(err)),
+                };
+                match state.boundary.contains(&oid, &commit, cache, &mut self.find) {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(err) => return Some(Err(err)),
+                }
+                if let Err(err) = commit.parents(cache, &mut state.parent_ids) {
+                    return Some(Err(err));
+                }
+                state.buf_lacks = matches!(commit, FoundCommit::InGraph(_)).then_some(oid);
+                break oid;
+            };
+
+            for id in &state.parent_ids {
+                let was_inserted = state.seen.insert(*id);
+                if was_inserted && (self.predicate)(id) {
+                    match cache {
+                        Some(cache) => {
+                            let generation = generation_of(cache, id);
+                            let pos = state.next.partition_point(|(_, other)| *other >= generation);
+                            state.next.insert(pos, (*id, generation));
                         }
+                        None => state.next.push_back((*id, 0)),
                     }
                 }
-                Err(err) => {
-                    return Some(Err(Error::FindExisting {
-                        oid,
-                        source: err.into(),
-                    }))
+                if matches!(self.parents, Parents::First) {
+                    break;
                 }
             }
             Some(Ok(oid))
@@ -12,13 +12,38 @@ pub enum Error {
     Cancelled,
     #[error(transparent)]
     ObjectDecode(#[from] gix_object::decode::Error),
+    #[error("Trees are nested more than {max_depth} levels deep")]
+    TooDeep { max_depth: usize },
+}
+
+/// What to do if a tree is nested more deeply than configured with [`State::with_max_depth()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepthExceeded {
+    /// Abort the traversal with [`Error::TooDeep`].
+    Fail,
+    /// Visit the tree but don't descend into it, as if the delegate returned [`Skip`][crate::tree::visit::Action::Skip].
+    Skip,
 }
 
 /// The state used and potentially shared by multiple tree traversals.
 #[derive(Default, Clone)]
 pub struct State {
-    next: VecDeque<ObjectId>,
+    /// The trees to traverse next along with their depth.
+    next: VecDeque<(ObjectId, usize)>,
     buf: Vec<u8>,
+    max_depth: Option<(usize, DepthExceeded)>,
+}
+
+/// Builder
+impl State {
+    /// Do not descend into trees that are nested more than `max_depth` levels below the root tree, and act according to
+    /// `on_exceeded` if such a tree is encountered. With a `max_depth` of 0, only the entries of the root tree are seen.
+    ///
+    /// This bounds the traversal of deeply nested trees, which may be crafted maliciously. By default, the depth is unbounded.
+    pub fn with_max_depth(mut self, max_depth: usize, on_exceeded: DepthExceeded) -> Self {
+        self.max_depth = Some((max_depth, on_exceeded));
+        self
+    }
 }
 
 impl State {
@@ -34,7 +59,7 @@ pub(crate) mod impl_ {
     use gix_hash::oid;
     use gix_object::{tree::EntryMode, TreeRefIter};
 
-    use super::{Error, State};
+    use super::{DepthExceeded, Error, State};
     use crate::tree::Visit;
 
     /// Start a breadth-first iteration over the `root` trees entries.
@@ -42,7 +67,7 @@ pub(crate) mod impl_ {
     /// * `root`
     ///   * the tree to iterate in a nested fashion.
     /// * `state` - all state used for the iteration. If multiple iterations are performed, allocations can be minimized by reusing
-    ///   this state.
+    ///   this state. It also configures the [maximum depth][State::with_max_depth()] of the traversal.
     /// * `find` - a way to lookup new object data during traversal by their ObjectId, writing their data into buffer and returning
     ///    an iterator over entries if the object is present and is a tree. Caching should be implemented within this function
     ///    as needed. The return value is `Option<TreeIter>` which degenerates all error information. Not finding a commit should also
@@ -63,6 +88,7 @@ pub(crate) mod impl_ {
         let state = state.borrow_mut();
         state.clear();
         let mut tree = root;
+        let mut depth = 0;
         loop {
             for entry in tree {
                 let entry = entry?;
@@ -73,11 +99,17 @@ pub(crate) mod impl_ {
                         let action = delegate.visit_tree(&entry);
                         match action {
                             Skip => {}
-                            Continue => {
-                                delegate.pop_path_component();
-                                delegate.push_back_tracked_path_component(entry.filename);
-                                state.next.push_back(entry.oid.to_owned())
-                            }
+                            Continue => match state.max_depth {
+                                Some((max_depth, DepthExceeded::Fail)) if depth == max_depth => {
+                                    return Err(Error::TooDeep { max_depth });
+                                }
+                                Some((max_depth, DepthExceeded::Skip)) if depth == max_depth => {}
+                                _ => {
+                                    delegate.pop_path_component();
+                                    delegate.push_back_tracked_path_component(entry.filename);
+                                    state.next.push_back((entry.oid.to_owned(), depth + 1))
+                                }
+                            },
                             Cancel => {
                                 return Err(Error::Cancelled);
                             }
@@ -93,7 +125,8 @@ pub(crate) mod impl_ {
                 delegate.pop_path_component();
             }
             match state.next.pop_front() {
-                Some(oid) => {
+                Some((oid, tree_depth)) => {
+                    depth = tree_depth;
                     delegate.pop_front_tracked_path_and_set_current();
                     match find(&oid, &mut state.buf) {
                         Some(tree_iter) => tree = tree_iter,
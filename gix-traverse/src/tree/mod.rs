@@ -28,10 +28,13 @@ pub trait Visit {
 }
 
 /// A [Visit][Visit] implementation to record every observed change and keep track of the changed paths.
+///
+/// By default, it descends into all trees, see [`recorder::Recurse`] for how to stay at the top level.
 #[derive(Clone, Debug, Default)]
 pub struct Recorder {
     path_deque: VecDeque<BString>,
     path: BString,
+    recurse: recorder::Recurse,
     /// The observed entries.
     pub records: Vec<recorder::Entry>,
 }
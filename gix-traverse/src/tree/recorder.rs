@@ -20,6 +20,16 @@ pub struct Entry {
     pub oid: ObjectId,
 }
 
+/// Whether a [`Recorder`] descends into the trees it observes.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Recurse {
+    /// Record the entries of all trees, recursively.
+    #[default]
+    Yes,
+    /// Only record the entries of the root tree, without descending into the trees it contains.
+    No,
+}
+
 impl Entry {
     fn new(entry: &tree::EntryRef<'_>, filepath: BString) -> Self {
         Entry {
@@ -30,6 +40,15 @@ impl Entry {
     }
 }
 
+/// Builder
+impl Recorder {
+    /// Set whether or not to descend into trees, see [`Recurse`].
+    pub fn recurse(mut self, recurse: Recurse) -> Self {
+        self.recurse = recurse;
+        self
+    }
+}
+
 impl Recorder {
     fn pop_element(&mut self) {
         if let Some(pos) = self.path.rfind_byte(b'/') {
@@ -74,7 +93,10 @@ impl Visit for Recorder {
 
     fn visit_tree(&mut self, entry: &tree::EntryRef<'_>) -> Action {
         self.records.push(Entry::new(entry, self.path_clone()));
-        Action::Continue
+        match self.recurse {
+            Recurse::Yes => Action::Continue,
+            Recurse::No => Action::Skip,
+        }
     }
 
     fn visit_nontree(&mut self, entry: &tree::EntryRef<'_>) -> Action {
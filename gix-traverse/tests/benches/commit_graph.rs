@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use gix_odb::pack::FindExt;
+use gix_traverse::commit;
+
+fn ancestors(c: &mut Criterion) {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repo_for_commit_graph_benchmark.sh")
+        .expect("fixture is available");
+    let git_dir = dir.join(".git");
+    let store = gix_odb::at(git_dir.join("objects")).expect("valid object database");
+    let info_dir = git_dir.join("objects").join("info");
+    let tip = gix_hash::ObjectId::from_hex(
+        std::fs::read_to_string(git_dir.join("refs").join("heads").join("main"))
+            .expect("main branch")
+            .trim()
+            .as_bytes(),
+    )
+    .expect("valid id");
+
+    let mut group = c.benchmark_group("ancestors");
+    for (sorting, name) in [
+        (commit::Sorting::Topological, "topological"),
+        (commit::Sorting::ByCommitTimeNewestFirst, "by commit time"),
+    ] {
+        for (use_graph, graph_name) in [(false, "objects"), (true, "commit-graph")] {
+            group.bench_function(format!("{name} from {graph_name}"), |b| {
+                b.iter(|| {
+                    let count =
+                        commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), |oid, buf| {
+                            store.find_commit_iter(oid, buf).map(|t| t.0)
+                        })
+                        .commit_graph(use_graph.then(|| {
+                            gix_commitgraph::Graph::from_info_dir(&info_dir).expect("commit-graph is present")
+                        }))
+                        .sorting(sorting)
+                        .expect("tip can be found")
+                        .count();
+                    assert_eq!(count, 11000, "all commits are returned");
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, ancestors);
+criterion_main!(benches);
@@ -1,9 +1,53 @@
+/// Return a writable copy of the repository created by `script` with a commit-graph of all of its commits,
+/// along with the commit-graph.
+fn fixture_with_commit_graph(
+    script: &str,
+) -> crate::Result<(gix_testtools::tempfile::TempDir, gix_commitgraph::Graph)> {
+    let dir = gix_testtools::scripted_fixture_writable_standalone(script)?;
+    let status = std::process::Command::new("git")
+        .args(["commit-graph", "write", "--no-progress", "--reachable"])
+        .current_dir(dir.path())
+        .status()?;
+    assert!(status.success(), "git commit-graph write failed");
+    let graph = gix_commitgraph::Graph::from_info_dir(dir.path().join(".git/objects/info"))?;
+    Ok((dir, graph))
+}
+
+/// Assert that `actual` contains the same commits as `expected`, with each commit being returned before all of its
+/// parents that are returned as well, which is the order of topological sorting with a commit-graph.
+fn assert_topological_order(
+    store: &gix_odb::Handle,
+    actual: &[gix_hash::ObjectId],
+    expected: &[gix_hash::ObjectId],
+) -> crate::Result {
+    use gix_odb::pack::FindExt;
+
+    let (mut sorted_actual, mut sorted_expected) = (actual.to_vec(), expected.to_vec());
+    sorted_actual.sort();
+    sorted_expected.sort();
+    assert_eq!(sorted_actual, sorted_expected, "the same commits are returned");
+
+    let mut buf = Vec::new();
+    for (pos, id) in actual.iter().enumerate() {
+        for parent in store.find_commit(id, &mut buf)?.0.parents() {
+            assert!(
+                !actual[..pos].contains(&parent),
+                "the parent {parent} of {id} must not be returned before it"
+            );
+        }
+    }
+    Ok(())
+}
+
 mod ancestor {
     use gix_hash::{oid, ObjectId};
     use gix_odb::pack::FindExt;
     use gix_traverse::commit;
 
-    use crate::hex_to_id;
+    use crate::{
+        commit::{assert_topological_order, fixture_with_commit_graph},
+        hex_to_id,
+    };
 
     struct TraversalAssertion<'a> {
         init_script: &'a str,
@@ -47,35 +91,63 @@ mod ancestor {
                 .collect();
             Ok((store, tips, expected))
         }
-        fn check_with_predicate(&mut self, predicate: impl FnMut(&oid) -> bool) -> crate::Result<()> {
+
+        fn check_with_predicate(&mut self, predicate: impl FnMut(&oid) -> bool + Clone) -> crate::Result<()> {
             let (store, tips, expected) = self.setup()?;
+            let (_dir, graph) = fixture_with_commit_graph(self.init_script)?;
 
-            let oids: Result<Vec<_>, _> = commit::Ancestors::filtered(
-                tips,
-                commit::ancestors::State::default(),
-                move |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
-                predicate,
-            )
-            .sorting(self.sorting)?
-            .parents(self.mode)
-            .collect();
+            for cache in [None, Some(graph)] {
+                let with_graph = cache.is_some();
+                let oids: Result<Vec<_>, _> = commit::Ancestors::filtered(
+                    tips.clone(),
+                    commit::ancestors::State::default(),
+                    |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+                    predicate.clone(),
+                )
+                .commit_graph(cache)
+                .sorting(self.sorting)?
+                .parents(self.mode)
+                .collect();
 
-            assert_eq!(oids?, expected);
+                self.assert_order(&store, &oids?, &expected, with_graph)?;
+            }
             Ok(())
         }
 
         fn check(&self) -> crate::Result {
             let (store, tips, expected) = self.setup()?;
-            let oids: Result<Vec<_>, _> =
-                commit::Ancestors::new(tips, commit::ancestors::State::default(), move |oid, buf| {
-                    store.find_commit_iter(oid, buf).map(|t| t.0)
-                })
-                .sorting(self.sorting)?
-                .parents(self.mode)
-                .collect();
-            assert_eq!(oids?, expected);
+            let (_dir, graph) = fixture_with_commit_graph(self.init_script)?;
+
+            for cache in [None, Some(graph)] {
+                let with_graph = cache.is_some();
+                let oids: Result<Vec<_>, _> =
+                    commit::Ancestors::new(tips.clone(), commit::ancestors::State::default(), |oid, buf| {
+                        store.find_commit_iter(oid, buf).map(|t| t.0)
+                    })
+                    .commit_graph(cache)
+                    .sorting(self.sorting)?
+                    .parents(self.mode)
+                    .collect();
+                self.assert_order(&store, &oids?, &expected, with_graph)?;
+            }
             Ok(())
         }
+
+        /// Assert that `actual` is `expected`, or only in topological order if it was sorted topologically with a commit-graph.
+        fn assert_order(
+            &self,
+            store: &gix_odb::Handle,
+            actual: &[ObjectId],
+            expected: &[ObjectId],
+            with_graph: bool,
+        ) -> crate::Result {
+            if with_graph && matches!(self.sorting, commit::Sorting::Topological) {
+                assert_topological_order(store, actual, expected)
+            } else {
+                assert_eq!(actual, expected, "with commit-graph: {with_graph}");
+                Ok(())
+            }
+        }
     }
 
     #[test]
@@ -127,6 +199,37 @@ mod ancestor {
         .check()
     }
 
+    #[test]
+    fn first_parent_only_yields_each_commit_followed_by_its_first_parent() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_traversal_repo_for_commits.sh")?;
+        let store = gix_odb::at(dir.join(".git").join("objects"))?;
+        let mainline = commit::Ancestors::new(
+            Some(hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b")),
+            commit::ancestors::State::default(),
+            |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+        )
+        .parents(commit::Parents::First)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let mut buf = Vec::new();
+        let mut num_merges = 0;
+        for pair in mainline.windows(2) {
+            let parents: Vec<_> = store.find_commit_iter(pair[0], &mut buf)?.0.parent_ids().collect();
+            assert_eq!(parents.first(), Some(&pair[1]), "merged branches are never traversed");
+            if parents.len() > 1 {
+                num_merges += 1;
+                assert!(
+                    parents[1..].iter().all(|parent| !mainline.contains(parent)),
+                    "the merged parents are not part of the mainline"
+                );
+            }
+        }
+        assert!(num_merges > 0, "the mainline passes through a merge commit");
+        let (root, _) = store.find_commit_iter(mainline.last().expect("non-empty"), &mut buf)?;
+        assert_eq!(root.parent_ids().count(), 0, "the mainline ends at the root commit");
+        Ok(())
+    }
+
     #[test]
     fn multiple_tips() -> crate::Result {
         TraversalAssertion::new(
@@ -270,4 +373,473 @@ mod ancestor {
         .with_parents(commit::Parents::First)
         .check()
     }
+
+    #[test]
+    fn count_ancestors_is_the_same_as_collecting_them() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_traversal_repo_for_commits.sh")?;
+        let store = gix_odb::at(dir.join(".git").join("objects"))?;
+        let tips = [
+            hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"),
+            hex_to_id("9556057aee5abb06912922e9f26c46386a816822"),
+        ];
+        let expected = commit::Ancestors::new(tips, commit::ancestors::State::default(), |oid, buf| {
+            store.find_commit_iter(oid, buf).map(|t| t.0)
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .len();
+        let count = commit::count_ancestors(
+            tips,
+            |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+            gix_features::progress::Discard,
+        )?;
+        assert_eq!(count, expected);
+        assert_eq!(count, 8, "the tips are counted as well");
+        Ok(())
+    }
+
+    #[test]
+    fn paused_traversals_can_be_resumed_from_serialized_state() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_traversal_repo_for_commits.sh")?;
+        let store = gix_odb::at(dir.join(".git").join("objects"))?;
+        let tips = [
+            hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"),
+            hex_to_id("9556057aee5abb06912922e9f26c46386a816822"),
+        ];
+        let boundary = [hex_to_id("ce2e8ffaa9608a26f7b21afc1db89cadb54fd353")];
+        for sorting in [
+            commit::Sorting::Topological,
+            commit::Sorting::ByCommitTimeNewestFirst,
+            commit::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+                time_in_seconds_since_epoch: 978393600, // =2001-01-02 00:00:00 +0000
+            },
+        ] {
+            for parents in [commit::Parents::All, commit::Parents::First] {
+                for boundary in [&[][..], &boundary] {
+                    let expected = commit::Ancestors::new_with_boundary(
+                        tips,
+                        boundary.iter().copied(),
+                        commit::ancestors::State::default(),
+                        |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+                    )
+                    .parents(parents)
+                    .sorting(sorting)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                    let mut state = commit::ancestors::State::default();
+                    let mut actual =
+                        commit::Ancestors::new_with_boundary(tips, boundary.iter().copied(), &mut state, |oid, buf| {
+                            store.find_commit_iter(oid, buf).map(|t| t.0)
+                        })
+                        .parents(parents)
+                        .sorting(sorting)?
+                        .take(expected.len() / 2)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let serialized = serde_json::to_string(&state)?;
+                    let state: commit::ancestors::State = serde_json::from_str(&serialized)?;
+                    for id in commit::Ancestors::resume(state, sorting, |oid, buf| {
+                        store.find_commit_iter(oid, buf).map(|t| t.0)
+                    })
+                    .parents(parents)
+                    {
+                        actual.push(id?);
+                    }
+                    assert_eq!(
+                        actual, expected,
+                        "the resumed traversal yields exactly the remaining commits with {sorting:?}, {parents:?} and boundary {boundary:?}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    mod commit_graph {
+        use std::cell::{Cell, RefCell};
+
+        use gix_odb::pack::FindExt;
+        use gix_traverse::commit;
+
+        use crate::{commit::fixture_with_commit_graph, hex_to_id};
+
+        #[test]
+        fn commits_in_the_graph_are_not_looked_up_in_the_object_database() -> crate::Result {
+            let (dir, _graph) = fixture_with_commit_graph("make_traversal_repo_for_commits.sh")?;
+            let info_dir = dir.path().join(".git").join("objects").join("info");
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let lookups = Cell::new(0);
+            for sorting in [commit::Sorting::Topological, commit::Sorting::ByCommitTimeNewestFirst] {
+                let commits = commit::Ancestors::new(
+                    Some(hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b")),
+                    commit::ancestors::State::default(),
+                    |oid, buf| {
+                        lookups.set(lookups.get() + 1);
+                        store.find_commit_iter(oid, buf).map(|t| t.0)
+                    },
+                )
+                .commit_graph(Some(gix_commitgraph::Graph::from_info_dir(&info_dir)?))
+                .sorting(sorting)?
+                .collect::<Result<Vec<_>, _>>()?;
+                assert_eq!(commits.len(), 8, "all commits are still returned");
+            }
+            assert_eq!(
+                lookups.get(),
+                0,
+                "parents and commit times are read from the commit-graph"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn commits_not_in_the_graph_are_looked_up_in_the_object_database() -> crate::Result {
+            let (dir, graph) = fixture_with_commit_graph("make_traversal_repo_for_commits.sh")?;
+            let status = std::process::Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=author",
+                    "-c",
+                    "user.email=author@example.com",
+                    "commit",
+                    "-q",
+                    "--allow-empty",
+                    "-m",
+                    "not in the commit-graph",
+                ])
+                .current_dir(dir.path())
+                .status()?;
+            assert!(status.success(), "git commit failed");
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .current_dir(dir.path())
+                .output()?;
+            let tip = gix_hash::ObjectId::from_hex(std::str::from_utf8(&output.stdout)?.trim().as_bytes())?;
+            assert!(
+                graph.commit_by_id(tip).is_none(),
+                "the new commit isn't part of the graph"
+            );
+
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let looked_up = RefCell::new(Vec::new());
+            let mut results = Vec::new();
+            for cache in [None, Some(graph)] {
+                looked_up.borrow_mut().clear();
+                results.push(
+                    commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), |oid, buf| {
+                        looked_up.borrow_mut().push(oid.to_owned());
+                        store.find_commit_iter(oid, buf).map(|t| t.0)
+                    })
+                    .commit_graph(cache)
+                    .sorting(commit::Sorting::ByCommitTimeNewestFirst)?
+                    .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            assert!(
+                looked_up.borrow().iter().all(|id| *id == tip),
+                "only the commit that is missing in the commit-graph is read from the object database"
+            );
+            assert_eq!(results[0], results[1], "the commit-graph doesn't affect the result");
+            assert_eq!(results[0].len(), 8 + 1);
+            Ok(())
+        }
+
+        #[test]
+        fn topological_sorting_returns_commits_before_their_parents() -> crate::Result {
+            let (dir, graph) = fixture_with_commit_graph("make_traversal_repo_for_commits.sh")?;
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let tips = [
+                "01ec18a3ebf2855708ad3c9d244306bc1fae3e9b",
+                "9556057aee5abb06912922e9f26c46386a816822",
+            ];
+            let traverse = |cache| {
+                commit::Ancestors::new(
+                    tips.iter().copied().map(hex_to_id),
+                    commit::ancestors::State::default(),
+                    |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+                )
+                .commit_graph(cache)
+                .collect::<Result<Vec<_>, _>>()
+            };
+
+            let without_graph = traverse(None)?;
+            let efd9 = hex_to_id("efd9a841189668f1bab5b8ebade9cd0a1b139a37");
+            let pos = |id| without_graph.iter().position(|other| *other == id).expect("returned");
+            assert!(
+                pos(hex_to_id(tips[1])) < pos(efd9),
+                "without generation numbers, the tip 9556 is returned before its child efd9"
+            );
+
+            assert_eq!(
+                traverse(Some(graph))?,
+                [
+                    "01ec18a3ebf2855708ad3c9d244306bc1fae3e9b",
+                    "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                    "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                    "9152eeee2328073cf23dcf8e90c949170b711659",
+                    "9556057aee5abb06912922e9f26c46386a816822",
+                    "17d78c64cef6c33a10a604573fd2c429e477fd63",
+                    "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                    "134385f6d781b7e97062102c6a483440bfda2a03",
+                ]
+                .iter()
+                .copied()
+                .map(hex_to_id)
+                .collect::<Vec<_>>(),
+                "with the commit-graph, commits are ordered by generation number"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn commit_iter_provides_the_data_of_commits_in_the_graph() -> crate::Result {
+            let (dir, graph) = fixture_with_commit_graph("make_traversal_repo_for_commits.sh")?;
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let lookups = Cell::new(0);
+            let mut commits = commit::Ancestors::new(
+                Some(hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b")),
+                commit::ancestors::State::default(),
+                |oid, buf| {
+                    lookups.set(lookups.get() + 1);
+                    store.find_commit_iter(oid, buf).map(|t| t.0)
+                },
+            )
+            .commit_graph(Some(graph));
+            let mut buf = Vec::new();
+            let mut count = 0;
+            while let Some(id) = commits.next() {
+                let id = id?;
+                count += 1;
+                assert_eq!(
+                    commits.commit_iter()?.tree_id()?,
+                    store.find_commit(id, &mut buf)?.0.tree(),
+                    "the data of the commit that was returned last is looked up on demand"
+                );
+                assert_eq!(
+                    commits.commit_iter()?.tree_id()?,
+                    store.find_commit(id, &mut buf)?.0.tree()
+                );
+            }
+            assert_eq!(
+                lookups.get(),
+                count,
+                "each commit is looked up once, and only if its data is needed"
+            );
+            Ok(())
+        }
+    }
+
+    mod boundary {
+        use gix_hash::ObjectId;
+        use gix_odb::pack::FindExt;
+        use gix_traverse::commit;
+
+        use crate::{
+            commit::{assert_topological_order, fixture_with_commit_graph},
+            hex_to_id,
+        };
+
+        /// Traverse with and without a commit-graph, and assert both yield the same commits, which are only in the
+        /// same order if they aren't sorted topologically.
+        fn traverse(tips: &[&str], boundary: &[&str], sorting: commit::Sorting) -> crate::Result<Vec<ObjectId>> {
+            let (dir, graph) = fixture_with_commit_graph("make_traversal_repo_for_commits.sh")?;
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let mut results = Vec::new();
+            for cache in [None, Some(graph)] {
+                results.push(
+                    commit::Ancestors::new_with_boundary(
+                        tips.iter().copied().map(hex_to_id),
+                        boundary.iter().copied().map(hex_to_id),
+                        commit::ancestors::State::default(),
+                        |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+                    )
+                    .commit_graph(cache)
+                    .sorting(sorting)?
+                    .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            let with_graph = results.pop().expect("two results");
+            let without_graph = results.pop().expect("two results");
+            if matches!(sorting, commit::Sorting::Topological) {
+                assert_topological_order(&store, &with_graph, &without_graph)?;
+            } else {
+                assert_eq!(with_graph, without_graph, "the commit-graph doesn't affect the result");
+            }
+            Ok(without_graph)
+        }
+
+        fn ids(hex: &[&str]) -> Vec<ObjectId> {
+            hex.iter().copied().map(hex_to_id).collect()
+        }
+
+        #[test]
+        fn tip_excluding_an_ancestor() -> crate::Result {
+            // A ^B
+            let expected = ids(&[
+                "01ec18a3ebf2855708ad3c9d244306bc1fae3e9b",
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+            ]);
+            let tips = &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"];
+            let boundary = &["9556057aee5abb06912922e9f26c46386a816822"];
+            assert_eq!(
+                traverse(tips, boundary, commit::Sorting::Topological)?,
+                expected,
+                "the boundary and all of its ancestors are excluded"
+            );
+
+            let mut actual = traverse(tips, boundary, commit::Sorting::ByCommitTimeNewestFirst)?;
+            actual.sort();
+            let mut expected = expected;
+            expected.sort();
+            assert_eq!(actual, expected, "the same commits are returned when sorting by date");
+            Ok(())
+        }
+
+        #[test]
+        fn multiple_tips_excluding_a_shared_ancestor() -> crate::Result {
+            // A B ^C
+            let actual = traverse(
+                &[
+                    "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                    "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                ],
+                &["17d78c64cef6c33a10a604573fd2c429e477fd63"],
+                commit::Sorting::Topological,
+            )?;
+            assert_eq!(
+                actual,
+                ids(&[
+                    "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                    "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                    "9556057aee5abb06912922e9f26c46386a816822",
+                    "9152eeee2328073cf23dcf8e90c949170b711659",
+                ])
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn boundary_reachable_from_tip_excludes_everything() -> crate::Result {
+            let actual = traverse(
+                &["9556057aee5abb06912922e9f26c46386a816822"],
+                &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+                commit::Sorting::Topological,
+            )?;
+            assert!(actual.is_empty(), "tips reachable from the boundary aren't returned");
+            Ok(())
+        }
+
+        #[test]
+        fn generation_numbers_are_not_fooled_by_clock_skew() -> crate::Result {
+            // c3 ^b1c2, with c2 being reachable from b1c2 only through b1c1, which is older than c2.
+            let (dir, graph) = fixture_with_commit_graph("make_traversal_repo_for_commits_with_clock_skew.sh")?;
+            let store = gix_odb::at(dir.path().join(".git").join("objects"))?;
+            let c3 = "0e6cea6b0199a5d16cc12878cd421d473a097248";
+            let c2 = "0e175eb32cfa247f259af134ffb250cb6177087f";
+            let b1c2 = "afccf4ad14c47b6fe99a136f4208a5130df1466a";
+            let mut results = Vec::new();
+            for cache in [None, Some(graph)] {
+                results.push(
+                    commit::Ancestors::new_with_boundary(
+                        Some(hex_to_id(c3)),
+                        Some(hex_to_id(b1c2)),
+                        commit::ancestors::State::default(),
+                        |oid, buf| store.find_commit_iter(oid, buf).map(|t| t.0),
+                    )
+                    .commit_graph(cache)
+                    .sorting(commit::Sorting::ByCommitTimeNewestFirst)?
+                    .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            assert_eq!(
+                results[0],
+                ids(&[c3, c2]),
+                "without generation numbers, the boundary isn't traversed past b1c1 as it's older than c2"
+            );
+            assert_eq!(
+                results[1],
+                ids(&[c3]),
+                "generation numbers tell that b1c1 may still lead to c2, which is excluded like `git log` would"
+            );
+            Ok(())
+        }
+    }
+
+    mod cycles {
+        use std::collections::HashMap;
+
+        use gix_hash::ObjectId;
+        use gix_object::CommitRefIter;
+        use gix_traverse::commit;
+
+        use crate::hex_to_id;
+
+        /// Return commits which have the given parents, keyed by the given ids, whose hashes don't match their content.
+        /// This allows to create cycles which can otherwise only be created with a broken hash function.
+        fn commits(graph: &[(&str, &[&str], u32)]) -> HashMap<ObjectId, Vec<u8>> {
+            graph
+                .iter()
+                .map(|(id, parents, time)| {
+                    let mut data = String::from("tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n");
+                    for parent in parents.iter() {
+                        data.push_str(&format!("parent {parent}\n"));
+                    }
+                    data.push_str(&format!(
+                        "author a <a@example.com> {time} +0000\ncommitter a <a@example.com> {time} +0000\n\nmessage\n"
+                    ));
+                    (hex_to_id(id), data.into_bytes())
+                })
+                .collect()
+        }
+
+        fn traverse(
+            commits: &HashMap<ObjectId, Vec<u8>>,
+            tip: &str,
+            parents: commit::Parents,
+            sorting: commit::Sorting,
+        ) -> crate::Result<Vec<ObjectId>> {
+            Ok(commit::Ancestors::new(
+                Some(hex_to_id(tip)),
+                commit::ancestors::State::default(),
+                |oid, buf| -> Result<_, std::convert::Infallible> {
+                    buf.clear();
+                    buf.extend_from_slice(&commits[&oid.to_owned()]);
+                    Ok(CommitRefIter::from_bytes(buf))
+                },
+            )
+            .sorting(sorting)?
+            .parents(parents)
+            .collect::<Result<Vec<_>, _>>()?)
+        }
+
+        const A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        const B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+        const C: &str = "cccccccccccccccccccccccccccccccccccccccc";
+
+        #[test]
+        fn traversal_terminates_in_all_modes() -> crate::Result {
+            let self_referencing = commits(&[(A, &[A], 1)]);
+            let ring = commits(&[(A, &[B], 3), (B, &[C], 2), (C, &[A], 1)]);
+            for parents in [commit::Parents::All, commit::Parents::First] {
+                for sorting in [
+                    commit::Sorting::Topological,
+                    commit::Sorting::ByCommitTimeNewestFirst,
+                    commit::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+                        time_in_seconds_since_epoch: 0,
+                    },
+                ] {
+                    assert_eq!(
+                        traverse(&self_referencing, A, parents, sorting)?,
+                        vec![hex_to_id(A)],
+                        "a commit that is its own parent is returned only once"
+                    );
+                    assert_eq!(
+                        traverse(&ring, A, parents, sorting)?,
+                        [A, B, C].iter().copied().map(hex_to_id).collect::<Vec<_>>(),
+                        "each commit in a cycle is returned once"
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
 }
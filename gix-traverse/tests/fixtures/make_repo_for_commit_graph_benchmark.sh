@@ -0,0 +1,31 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+# A mainline of 10000 commits, with a commit on a side branch being merged into every tenth one.
+{
+  for i in $(seq 1 10000); do
+    if (( i % 10 == 0 )); then
+      echo "commit refs/heads/side"
+      echo "mark :$((i + 100000))"
+      echo "committer author <author@example.com> $((1000000000 + i * 60)) +0000"
+      echo "data 5"
+      echo "side"
+      echo "from :$((i - 5))"
+    fi
+    echo "commit refs/heads/main"
+    echo "mark :$i"
+    echo "committer author <author@example.com> $((1000000000 + i * 60 + 30)) +0000"
+    echo "data 5"
+    echo "main"
+    if (( i > 1 )); then
+      echo "from :$((i - 1))"
+    fi
+    if (( i % 10 == 0 )); then
+      echo "merge :$((i + 100000))"
+    fi
+  done
+} | git fast-import --quiet
+
+git commit-graph write --no-progress --reachable
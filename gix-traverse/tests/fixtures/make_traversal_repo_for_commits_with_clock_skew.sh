@@ -0,0 +1,16 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+git checkout -q -b main
+GIT_COMMITTER_DATE="2000-01-01 00:00:00 +0000" git commit -q --allow-empty -m c1
+GIT_COMMITTER_DATE="2000-01-03 00:00:00 +0000" git commit -q --allow-empty -m c2
+
+# The first commit on the branch has a clock that is skewed into the past, it is older than its parent.
+git checkout -q -b branch1
+GIT_COMMITTER_DATE="2000-01-02 00:00:00 +0000" git commit -q --allow-empty -m b1c1
+GIT_COMMITTER_DATE="2000-01-04 00:00:00 +0000" git commit -q --allow-empty -m b1c2
+
+git checkout -q main
+GIT_COMMITTER_DATE="2000-01-05 00:00:00 +0000" git commit -q --allow-empty -m c3
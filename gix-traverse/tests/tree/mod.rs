@@ -95,3 +95,142 @@ fn basic_nesting() -> crate::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn no_recursion() -> crate::Result<()> {
+    let db = db()?;
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut commit = db
+        .find_commit_iter(hex_to_id("85df34aa34848b8138b2b3dcff5fb5c2b734e0ce"), &mut buf)?
+        .0;
+    let mut recorder = tree::Recorder::default().recurse(tree::recorder::Recurse::No);
+    gix_traverse::tree::breadthfirst(
+        db.find_tree_iter(commit.tree_id().expect("a tree is available in a commit"), &mut buf2)?
+            .0,
+        tree::breadthfirst::State::default(),
+        |_oid, _buf| unreachable!("no tree is looked up without recursion"),
+        &mut recorder,
+    )?;
+
+    use gix_object::tree::EntryMode::*;
+    assert_eq!(
+        recorder
+            .records
+            .iter()
+            .map(|entry| (entry.filepath.to_string(), entry.mode))
+            .collect::<Vec<_>>(),
+        vec![
+            ("a".into(), Blob),
+            ("b".into(), Blob),
+            ("c".into(), Blob),
+            ("d".into(), Tree),
+            ("e".into(), Tree),
+            ("f".into(), Tree),
+        ],
+        "only the entries of the root tree are recorded"
+    );
+    Ok(())
+}
+
+mod max_depth {
+    use std::collections::HashMap;
+
+    use gix_hash::ObjectId;
+    use gix_object::{tree, TreeRefIter, WriteTo};
+    use gix_traverse::tree::{
+        breadthfirst::{DepthExceeded, Error, State},
+        Recorder,
+    };
+
+    const LEVELS: u32 = 10_000;
+
+    /// A chain of `LEVELS` trees, each containing only the next one, with the last one containing a blob.
+    fn pathologically_deep_tree() -> (ObjectId, HashMap<ObjectId, Vec<u8>>) {
+        let id = |level: u32| {
+            let mut id = [0xff; 20];
+            id[..4].copy_from_slice(&level.to_be_bytes());
+            ObjectId::from(id)
+        };
+        let trees = (0..LEVELS)
+            .map(|level| {
+                let entry = if level + 1 == LEVELS {
+                    tree::Entry {
+                        mode: tree::EntryMode::Blob,
+                        filename: "file".into(),
+                        oid: gix_hash::Kind::Sha1.null(),
+                    }
+                } else {
+                    tree::Entry {
+                        mode: tree::EntryMode::Tree,
+                        filename: "d".into(),
+                        oid: id(level + 1),
+                    }
+                };
+                let mut data = Vec::new();
+                gix_object::Tree { entries: vec![entry] }
+                    .write_to(&mut data)
+                    .expect("in-memory writes don't fail");
+                (id(level), data)
+            })
+            .collect();
+        (id(0), trees)
+    }
+
+    fn traverse(state: State) -> (Result<(), Error>, Recorder) {
+        let (root, trees) = pathologically_deep_tree();
+        let mut recorder = Recorder::default();
+        let res = gix_traverse::tree::breadthfirst(
+            TreeRefIter::from_bytes(&trees[&root]),
+            state,
+            |oid, buf| {
+                buf.clear();
+                buf.extend_from_slice(trees.get(&oid.to_owned())?);
+                Some(TreeRefIter::from_bytes(buf))
+            },
+            &mut recorder,
+        );
+        (res, recorder)
+    }
+
+    #[test]
+    fn is_unbounded_by_default() {
+        let (res, recorder) = traverse(State::default());
+        res.expect("deep trees don't exhaust the stack");
+        assert_eq!(recorder.records.len(), LEVELS as usize);
+        assert_eq!(
+            recorder.records.last().expect("present").filepath.len(),
+            "d/".len() * (LEVELS as usize - 1) + "file".len()
+        );
+    }
+
+    #[test]
+    fn fails_if_exceeded() {
+        let (res, recorder) = traverse(State::default().with_max_depth(2, DepthExceeded::Fail));
+        assert!(matches!(res, Err(Error::TooDeep { max_depth: 2 })));
+        assert_eq!(
+            recorder
+                .records
+                .iter()
+                .map(|entry| entry.filepath.to_string())
+                .collect::<Vec<_>>(),
+            vec!["d", "d/d", "d/d/d"],
+            "the tree that is too deep is still observed"
+        );
+    }
+
+    #[test]
+    fn skips_deeper_trees_if_exceeded() {
+        let (res, recorder) = traverse(State::default().with_max_depth(0, DepthExceeded::Skip));
+        res.expect("skipping isn't an error");
+        assert_eq!(
+            recorder
+                .records
+                .iter()
+                .map(|entry| entry.filepath.to_string())
+                .collect::<Vec<_>>(),
+            vec!["d"],
+            "only the entries of the root tree are observed"
+        );
+    }
+}
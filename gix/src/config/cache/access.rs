@@ -7,7 +7,7 @@ use crate::{
     bstr::BStr,
     config,
     config::{
-        cache::util::{ApplyLeniency, ApplyLeniencyDefault},
+        cache::util::ApplyLeniency,
         checkout_options,
         tree::{Checkout, Core, Key},
         Cache,
@@ -29,10 +29,9 @@ impl Cache {
                 config::tree::Diff::ALGORITHM
                     .try_into_algorithm(name)
                     .or_else(|err| match err {
-                        Error::Unimplemented { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Histogram),
+                        Error::Unknown { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Myers),
                         err => Err(err),
                     })
-                    .with_lenient_default(self.lenient_config)
             })
             .copied()
     }
@@ -69,6 +68,7 @@ impl Cache {
             .get_or_try_init(|| remote::url::SchemePermission::from_config(&self.resolved, self.filter_config_section))
     }
 
+    /// Returns `None` if rename tracking is disabled, or the default like git does if it's not configured at all.
     pub(crate) fn diff_renames(
         &self,
     ) -> Result<Option<crate::object::tree::diff::Renames>, crate::object::tree::diff::renames::Error> {
@@ -79,6 +79,15 @@ impl Cache {
             .copied()
     }
 
+    /// Returns `true` if `core.autocrlf` is `true` or `input`, which is `false` if it's unset.
+    pub(crate) fn autocrlf(&self) -> Result<bool, config::key::GenericErrorWithValue> {
+        let key = "core.autocrlf";
+        Ok(Core::AUTO_CRLF
+            .try_into_autocrlf(self.resolved.boolean_by_key(key), || self.resolved.string_by_key(key))
+            .with_leniency(self.lenient_config)?
+            .unwrap_or_default())
+    }
+
     /// Returns (file-timeout, pack-refs timeout)
     pub(crate) fn lock_timeout(
         &self,
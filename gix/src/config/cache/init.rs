@@ -160,7 +160,7 @@ impl Cache {
             lenient_config,
         )?;
         let object_kind_hint = util::disambiguate_hint(&config, lenient_config)?;
-        let (pack_cache_bytes, object_cache_bytes) =
+        let (pack_cache_bytes, pack_cache_entries, object_cache_bytes) =
             util::parse_object_caches(&config, lenient_config, filter_config_section)?;
         // NOTE: When adding a new initial cache, consider adjusting `reread_values_and_clear_caches()` as well.
         Ok(Cache {
@@ -169,6 +169,7 @@ impl Cache {
             object_hash,
             object_kind_hint,
             pack_cache_bytes,
+            pack_cache_entries,
             object_cache_bytes,
             reflog,
             is_bare,
@@ -231,7 +232,7 @@ impl Cache {
         self.url_rewrite = Default::default();
         self.diff_renames = Default::default();
         self.diff_algorithm = Default::default();
-        (self.pack_cache_bytes, self.object_cache_bytes) =
+        (self.pack_cache_bytes, self.pack_cache_entries, self.object_cache_bytes) =
             util::parse_object_caches(config, self.lenient_config, self.filter_config_section)?;
         #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
         {
@@ -409,6 +410,10 @@ fn apply_environment_overrides(
                     let key = &gitoxide::Objects::CACHE_LIMIT;
                     (env(key), key.name)
                 },
+                {
+                    let key = &gitoxide::Objects::DELTA_BASE_CACHE_ENTRIES;
+                    (env(key), key.name)
+                },
             ],
         ),
         (
@@ -67,24 +67,29 @@ pub(crate) fn reflog_or_default(
     })
 }
 
-/// Return `(pack_cache_bytes, object_cache_bytes)` as parsed from gix-config
+/// Return `(pack_cache_bytes, pack_cache_entries, object_cache_bytes)` as parsed from gix-config
 pub(crate) fn parse_object_caches(
     config: &gix_config::File<'static>,
     lenient: bool,
     mut filter_config_section: fn(&gix_config::file::Metadata) -> bool,
-) -> Result<(Option<usize>, usize), Error> {
+) -> Result<(Option<usize>, Option<usize>, usize), Error> {
     let pack_cache_bytes = config
         .integer_filter_by_key("core.deltaBaseCacheLimit", &mut filter_config_section)
         .map(|res| Core::DELTA_BASE_CACHE_LIMIT.try_into_usize(res))
         .transpose()
         .with_leniency(lenient)?;
+    let pack_cache_entries = config
+        .integer_filter_by_key("gitoxide.objects.deltaBaseCacheEntries", &mut filter_config_section)
+        .map(|res| gitoxide::Objects::DELTA_BASE_CACHE_ENTRIES.try_into_usize(res))
+        .transpose()
+        .with_leniency(lenient)?;
     let object_cache_bytes = config
         .integer_filter_by_key("gitoxide.objects.cacheLimit", &mut filter_config_section)
         .map(|res| gitoxide::Objects::CACHE_LIMIT.try_into_usize(res))
         .transpose()
         .with_leniency(lenient)?
         .unwrap_or_default();
-    Ok((pack_cache_bytes, object_cache_bytes))
+    Ok((pack_cache_bytes, pack_cache_entries, object_cache_bytes))
 }
 
 pub(crate) fn parse_core_abbrev(
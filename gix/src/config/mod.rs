@@ -90,10 +90,8 @@ pub mod diff {
         #[derive(Debug, thiserror::Error)]
         #[allow(missing_docs)]
         pub enum Error {
-            #[error("Unknown diff algorithm named '{name}'")]
+            #[error("Unknown diff algorithm named '{name}', expected one of 'myers', 'default', 'minimal', 'patience' or 'histogram'")]
             Unknown { name: BString },
-            #[error("The '{name}' algorithm is not yet implemented")]
-            Unimplemented { name: BString },
         }
     }
 }
@@ -435,6 +433,9 @@ pub(crate) struct Cache {
     /// The amount of bytes to use for a memory backed delta pack cache. If `Some(0)`, no cache is used, if `None`
     /// a standard cache is used which costs near to nothing and always pays for itself.
     pub(crate) pack_cache_bytes: Option<usize>,
+    /// The maximum amount of delta bases to keep in the pack cache, in addition to its memory limit. If `Some(0)`,
+    /// no cache is used.
+    pub(crate) pack_cache_entries: Option<usize>,
     /// The amount of bytes to use for caching whole objects, or 0 to turn it off entirely.
     pub(crate) object_cache_bytes: usize,
     /// The config section filter from the options used to initialize this instance. Keep these in sync!
@@ -6,8 +6,17 @@ use crate::{
 impl Core {
     /// The `core.abbrev` key.
     pub const ABBREV: Abbrev = Abbrev::new_with_validate("abbrev", &config::Tree::CORE, validate::Abbrev);
+    /// The `core.autocrlf` key.
+    pub const AUTO_CRLF: AutoCrlf = AutoCrlf::new_with_validate("autocrlf", &config::Tree::CORE, validate::AutoCrlf)
+        .with_note(
+            "only used to normalize line endings when diffing blobs against data that wasn't added to the index yet",
+        );
     /// The `core.bare` key.
     pub const BARE: keys::Boolean = keys::Boolean::new_boolean("bare", &config::Tree::CORE);
+    /// The `core.bigFileThreshold` key.
+    pub const BIG_FILE_THRESHOLD: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("bigFileThreshold", &config::Tree::CORE)
+            .with_note("only used to skip similarity checks of large blobs during rename tracking as large packed objects can't be streamed yet");
     /// The `core.checkStat` key.
     pub const CHECK_STAT: CheckStat =
         CheckStat::new_with_validate("checkStat", &config::Tree::CORE, validate::CheckStat);
@@ -73,7 +82,9 @@ impl Section for Core {
     fn keys(&self) -> &[&dyn Key] {
         &[
             &Self::ABBREV,
+            &Self::AUTO_CRLF,
             &Self::BARE,
+            &Self::BIG_FILE_THRESHOLD,
             &Self::CHECK_STAT,
             &Self::DELTA_BASE_CACHE_LIMIT,
             &Self::DISAMBIGUATE,
@@ -105,6 +116,9 @@ pub type Abbrev = keys::Any<validate::Abbrev>;
 /// The `core.logAllRefUpdates` key.
 pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 
+/// The `core.autocrlf` key.
+pub type AutoCrlf = keys::Any<validate::AutoCrlf>;
+
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
@@ -163,6 +177,32 @@ mod log_all_ref_updates {
     }
 }
 
+mod auto_crlf {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::core::AutoCrlf};
+
+    impl AutoCrlf {
+        /// Returns `true` if line endings are converted when adding files to the index as parsed from `value`, which is
+        /// the case if it's `true` or `input`. If `value` is not a boolean, `string_on_failure` will be called to obtain
+        /// the key `core.autocrlf` as string instead, just like for [`core.logAllRefUpdates`][super::Core::LOG_ALL_REF_UPDATES].
+        pub fn try_into_autocrlf<'a>(
+            &'static self,
+            value: Option<Result<bool, gix_config::value::Error>>,
+            string_on_failure: impl FnOnce() -> Option<Cow<'a, BStr>>,
+        ) -> Result<Option<bool>, config::key::GenericErrorWithValue> {
+            match value.transpose().ok().flatten() {
+                Some(bool) => Ok(Some(bool)),
+                None => match string_on_failure() {
+                    Some(val) if val.eq_ignore_ascii_case(b"input") => Ok(Some(true)),
+                    Some(val) => Err(config::key::GenericErrorWithValue::from_value(self, val.into_owned())),
+                    None => Ok(None),
+                },
+            }
+        }
+    }
+}
+
 mod check_stat {
     use std::borrow::Cow;
 
@@ -274,6 +314,17 @@ mod validate {
         }
     }
 
+    pub struct AutoCrlf;
+    impl keys::Validate for AutoCrlf {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::AUTO_CRLF
+                .try_into_autocrlf(Some(gix_config::Boolean::try_from(value).map(|b| b.0)), || {
+                    Some(value.into())
+                })?;
+            Ok(())
+        }
+    }
+
     pub struct CheckStat;
     impl keys::Validate for CheckStat {
         fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -265,6 +265,16 @@ mod subsections {
             keys::UnsignedInteger::new_unsigned_integer("cacheLimit", &Gitoxide::OBJECTS)
                 .with_note("If unset or 0, there is no object cache")
                 .with_environment_override("GITOXIDE_OBJECT_CACHE_MEMORY");
+        /// The `gitoxide.objects.deltaBaseCacheEntries` key.
+        ///
+        /// Each thread keeps its own cache of delta bases, each of which may use up to `core.deltaBaseCacheLimit`
+        /// (`GITOXIDE_PACK_CACHE_MEMORY`) bytes. Limiting the amount of entries bounds the bookkeeping of many small
+        /// bases, which a memory limit can't do, and applies on top of the memory limit, which is unbounded if unset.
+        /// The object cache (`GITOXIDE_OBJECT_CACHE_MEMORY`) is queried first and not affected.
+        pub const DELTA_BASE_CACHE_ENTRIES: keys::UnsignedInteger =
+            keys::UnsignedInteger::new_unsigned_integer("deltaBaseCacheEntries", &Gitoxide::OBJECTS)
+                .with_note("The maximum amount of delta bases to keep per thread, in addition to the memory limit set by `core.deltaBaseCacheLimit`. Set to 0 to deactivate the cache entirely")
+                .with_environment_override("GITOXIDE_PACK_CACHE_ENTRIES");
         /// The `gitoxide.objects.noReplace` key.
         pub const NO_REPLACE: keys::Boolean = keys::Boolean::new_boolean("noReplace", &Gitoxide::OBJECTS)
             .with_environment_override("GIT_NO_REPLACE_OBJECTS");
@@ -279,7 +289,12 @@ mod subsections {
         }
 
         fn keys(&self) -> &[&dyn Key] {
-            &[&Self::CACHE_LIMIT, &Self::NO_REPLACE, &Self::REPLACE_REF_BASE]
+            &[
+                &Self::CACHE_LIMIT,
+                &Self::DELTA_BASE_CACHE_ENTRIES,
+                &Self::NO_REPLACE,
+                &Self::REPLACE_REF_BASE,
+            ]
         }
 
         fn parent(&self) -> Option<&dyn Section> {
@@ -68,6 +68,7 @@
 // APIs/instances anyway.
 pub use gix_actor as actor;
 pub use gix_attributes as attrs;
+pub use gix_commitgraph as commitgraph;
 pub use gix_credentials as credentials;
 pub use gix_date as date;
 pub use gix_features as features;
@@ -81,6 +82,7 @@ pub use gix_lock as lock;
 pub use gix_object as objs;
 pub use gix_object::bstr;
 pub use gix_odb as odb;
+pub use gix_pathspec as pathspec;
 pub use gix_prompt as prompt;
 #[cfg(all(feature = "gix-protocol"))]
 pub use gix_protocol as protocol;
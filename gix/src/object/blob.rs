@@ -1,34 +1,401 @@
 ///
 pub mod diff {
-    use std::ops::Range;
+    use std::{borrow::Cow, ops::Range};
+
+    use gix_diff::blob::{intern::InternedInput, Sink};
 
     use crate::{bstr::ByteSlice, object::blob::diff::line::Change};
 
+    /// The maximum combined size in bytes of the old and the new blob for [byte][Platform::byte_tokens()] or
+    /// [character][Platform::char_tokens()] tokenization, as each byte may become its own token.
+    pub const MAX_FINE_GRAINED_DIFF_INPUT_SIZE: usize = 1024 * 1024;
+
+    /// The amount of bytes at the beginning of a blob which are searched for a NUL byte to determine if it is
+    /// [binary][is_binary()], like git does.
+    pub const BINARY_DETECTION_SIZE: usize = 8000;
+
+    /// Return `true` if `data` is considered binary as it contains a NUL byte within its first [`BINARY_DETECTION_SIZE`]
+    /// bytes.
+    ///
+    /// Line-based diffs and similarities of binary data aren't meaningful.
+    pub fn is_binary(data: &[u8]) -> bool {
+        data[..data.len().min(BINARY_DETECTION_SIZE)].contains(&0)
+    }
+
+    /// The way whitespace is treated when comparing lines, similar to the whitespace options of `git diff`.
+    #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    pub enum WhitespaceMode {
+        /// Lines have to match exactly, including their whitespace. This is the default.
+        #[default]
+        Exact,
+        /// Ignore all whitespace when comparing lines, even if one line has whitespace where the other has none,
+        /// like `git diff --ignore-all-space`.
+        IgnoreAll,
+        /// Ignore changes in the amount of whitespace, so that sequences of whitespace compare equal to each other
+        /// and whitespace at the end of lines is ignored, like `git diff --ignore-space-change`.
+        IgnoreChange,
+        /// Ignore changes whose lines are all blank, like `git diff --ignore-blank-lines`.
+        IgnoreBlankLines,
+    }
+
+    impl WhitespaceMode {
+        /// Return `line` in the form it's compared in, with whitespace removed or collapsed into a single space as needed.
+        ///
+        /// Note that line terminators are whitespace too, so lines which only differ by their terminator, like a final
+        /// line without newline, are equal unless whitespace has to match [exactly][WhitespaceMode::Exact].
+        /// [Blank lines][WhitespaceMode::IgnoreBlankLines] are ignored as a whole and thus aren't altered.
+        pub fn normalize<'a>(&self, line: &'a [u8]) -> Cow<'a, [u8]> {
+            match self {
+                WhitespaceMode::Exact | WhitespaceMode::IgnoreBlankLines => line.into(),
+                WhitespaceMode::IgnoreAll => {
+                    if line.iter().any(|b| is_space(*b)) {
+                        line.iter()
+                            .copied()
+                            .filter(|b| !is_space(*b))
+                            .collect::<Vec<_>>()
+                            .into()
+                    } else {
+                        line.into()
+                    }
+                }
+                WhitespaceMode::IgnoreChange => {
+                    let line = &line[..line.len() - line.iter().rev().take_while(|b| is_space(**b)).count()];
+                    let mut out = Vec::with_capacity(line.len());
+                    let mut in_space = false;
+                    for b in line.iter().copied() {
+                        if is_space(b) {
+                            if !in_space {
+                                out.push(b' ');
+                            }
+                            in_space = true;
+                        } else {
+                            out.push(b);
+                            in_space = false;
+                        }
+                    }
+                    out.into()
+                }
+            }
+        }
+
+        /// Return the lines of `data` in the form they are compared in to determine the similarity of blobs, omitting
+        /// blank lines if these are ignored.
+        pub(crate) fn similarity_lines<'a>(&self, data: &'a [u8]) -> impl Iterator<Item = Cow<'a, [u8]>> + 'a {
+            let mode = *self;
+            gix_diff::blob::sources::byte_lines_with_terminator(data)
+                .filter(move |line| mode != WhitespaceMode::IgnoreBlankLines || !is_blank(line))
+                .map(move |line| mode.normalize(line))
+        }
+    }
+
+    /// Like `isspace()`, which is what git uses to determine whitespace when comparing lines.
+    fn is_space(b: u8) -> bool {
+        matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'\x0b' | b'\x0c')
+    }
+
+    fn is_blank(line: &[u8]) -> bool {
+        line.iter().all(|b| is_space(*b))
+    }
+
+    /// The way line endings of blobs are converted before they are diffed, similar to the conversion git performs
+    /// when adding a file to the index.
+    ///
+    /// It's useful to avoid spurious changes when diffing data which wasn't normalized yet, like a file in the worktree.
+    #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    pub enum Conversion {
+        /// Data is diffed as it is. This is the default.
+        #[default]
+        None,
+        /// The data is text, so `CRLF` line endings are converted to `LF`.
+        Text,
+        /// `CRLF` line endings are converted to `LF` unless the data [is binary][is_binary()].
+        TextAuto,
+    }
+
+    impl Conversion {
+        /// Determine the conversion for a path with the given `attributes`, where only `text`, `eol` and `binary` are
+        /// relevant, falling back to `autocrlf` if the `text` attribute isn't specified.
+        ///
+        /// `autocrlf` is `true` if `core.autocrlf` is `true` or `input`. Just like in git, `text` converts unconditionally,
+        /// `text=auto` only converts data that isn't binary, and `-text` or `binary` turn off the conversion.
+        /// An `eol` attribute implies `text` if it isn't specified.
+        pub fn from_attributes<'a>(
+            attributes: impl IntoIterator<Item = gix_attributes::AssignmentRef<'a>>,
+            autocrlf: bool,
+        ) -> Self {
+            use gix_attributes::StateRef;
+            let mut text = StateRef::Unspecified;
+            let mut has_eol = false;
+            for attr in attributes {
+                match attr.name.as_str() {
+                    "text" => text = attr.state,
+                    "eol" => has_eol = matches!(attr.state, StateRef::Value(_)),
+                    "binary" if attr.state == StateRef::Set => text = StateRef::Unset,
+                    _ => {}
+                }
+            }
+            match text {
+                StateRef::Set => Conversion::Text,
+                StateRef::Unset => Conversion::None,
+                StateRef::Value(value) if value == "auto" => Conversion::TextAuto,
+                StateRef::Value(_) | StateRef::Unspecified if has_eol => Conversion::Text,
+                StateRef::Value(_) | StateRef::Unspecified if autocrlf => Conversion::TextAuto,
+                StateRef::Value(_) | StateRef::Unspecified => Conversion::None,
+            }
+        }
+
+        /// Like [`from_attributes()`][Self::from_attributes()], but obtains the fallback for paths without `text`
+        /// attribute from `core.autocrlf` as configured in `repo`.
+        ///
+        /// Note that rename tracking never converts blobs, just like git, as blobs in the object database are already
+        /// stored in their normalized form.
+        pub fn from_attributes_and_config<'a>(
+            attributes: impl IntoIterator<Item = gix_attributes::AssignmentRef<'a>>,
+            repo: &crate::Repository,
+        ) -> Result<Self, crate::config::key::GenericErrorWithValue> {
+            Ok(Self::from_attributes(attributes, repo.config.autocrlf()?))
+        }
+
+        /// Return `data` with its line endings converted, or unchanged if there is nothing to convert.
+        pub fn convert<'a>(&self, data: &'a [u8]) -> Cow<'a, [u8]> {
+            let needs_conversion = match self {
+                Conversion::None => false,
+                Conversion::Text => true,
+                Conversion::TextAuto => !is_binary(data),
+            };
+            if !needs_conversion || data.find(b"\r\n").is_none() {
+                return data.into();
+            }
+            let mut out = Vec::with_capacity(data.len());
+            for line in data.split_inclusive(|b| *b == b'\n') {
+                match line.strip_suffix(b"\r\n") {
+                    Some(line) => {
+                        out.extend_from_slice(line);
+                        out.push(b'\n');
+                    }
+                    None => out.extend_from_slice(line),
+                }
+            }
+            out.into()
+        }
+    }
+
     /// A platform to keep temporary information to perform line diffs on modified blobs.
     ///
     pub struct Platform<'old, 'new> {
         /// The previous version of the blob.
-        pub old: crate::Object<'old>,
+        pub old: Data<'old>,
         /// The new version of the blob.
-        pub new: crate::Object<'new>,
-        /// The algorithm to use when calling [imara_diff::diff()][gix_diff::blob::diff()].
+        pub new: Data<'new>,
+        /// The algorithm to use when calling [`gix_diff::blob::diff()`].
         /// This value is determined by the `diff.algorithm` configuration.
         pub algo: gix_diff::blob::Algorithm,
+        /// The way whitespace is treated when comparing lines, which is [exact][WhitespaceMode::Exact] by default.
+        ///
+        /// It affects all line-based diffs, but never the lines themselves, which are always passed as they are.
+        pub whitespace: WhitespaceMode,
+    }
+
+    /// The data of the old or the new version of a blob in a [`Platform`], which is either a blob loaded from the
+    /// object database or data that was provided directly.
+    ///
+    /// It dereferences to the data itself.
+    pub enum Data<'a> {
+        /// A blob as loaded from the object database, whose buffer is returned to the repository for reuse once dropped.
+        Object(crate::Object<'a>),
+        /// Data that doesn't have to exist in the object database, like a file in the worktree or the converted data of a blob.
+        Bytes(Cow<'a, [u8]>),
+    }
+
+    impl<'a> Data<'a> {
+        /// Return the id of the blob if the data was loaded from the object database and wasn't changed since.
+        pub fn id(&self) -> Option<crate::Id<'a>> {
+            self.as_object().map(crate::Object::id)
+        }
+
+        /// Return the blob if the data was loaded from the object database and wasn't changed since.
+        pub fn as_object(&self) -> Option<&crate::Object<'a>> {
+            match self {
+                Data::Object(object) => Some(object),
+                Data::Bytes(_) => None,
+            }
+        }
+
+        /// Return the data itself.
+        pub fn as_bytes(&self) -> &[u8] {
+            match self {
+                Data::Object(object) => &object.data,
+                Data::Bytes(data) => data,
+            }
+        }
+    }
+
+    impl std::ops::Deref for Data<'_> {
+        type Target = [u8];
+
+        fn deref(&self) -> &Self::Target {
+            self.as_bytes()
+        }
+    }
+
+    impl<'a> From<crate::Object<'a>> for Data<'a> {
+        fn from(object: crate::Object<'a>) -> Self {
+            Data::Object(object)
+        }
+    }
+
+    impl<'a> From<&'a [u8]> for Data<'a> {
+        fn from(data: &'a [u8]) -> Self {
+            Data::Bytes(data.into())
+        }
+    }
+
+    impl From<Vec<u8>> for Data<'_> {
+        fn from(data: Vec<u8>) -> Self {
+            Data::Bytes(data.into())
+        }
     }
 
     ///
     pub mod init {
-        /// The error returned by [`Platform::from_ids()`][super::Platform::from_ids()].
+        /// The error returned by [`Platform::from_ids()`][super::Platform::from_ids()] and
+        /// [`Repository::diff_blobs()`][crate::Repository::diff_blobs()].
         #[derive(Debug, thiserror::Error)]
         #[allow(missing_docs)]
         pub enum Error {
             #[error("Could not find the previous blob or the new blob to diff against")]
             FindExisting(#[from] crate::object::find::existing::Error),
+            #[error(transparent)]
+            ObjectKind(#[from] crate::object::try_into::Error),
             #[error("Could not obtain diff algorithm from configuration")]
             DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
         }
     }
 
+    ///
+    pub mod worktree {
+        use std::path::{Path, PathBuf};
+
+        use crate::bstr::{BStr, BString, ByteSlice};
+
+        /// The maximum amount of symbolic links to follow before assuming a loop, which matches the limit of Linux.
+        pub const MAX_SYMLINK_HOPS: usize = 40;
+
+        /// Options for [reading files from the worktree][read()] to diff them against blobs.
+        #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        pub struct Options {
+            /// If `true`, symbolic links are followed to diff the content of the file they point to, instead of
+            /// the path they point to, which is what git does and what's done by default.
+            ///
+            /// Links may only point to files within the worktree, and loops of links are detected.
+            pub follow_symlinks: bool,
+        }
+
+        /// The error returned by [`read()`] and [`Platform::from_id_and_worktree_file()`][super::Platform::from_id_and_worktree_file()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("Could not read '{}' from the worktree", path.display())]
+            Io { source: std::io::Error, path: PathBuf },
+            #[error(
+                "The path '{rela_path}' must be relative to the worktree and must not contain '.' or '..' components"
+            )]
+            InvalidPath { rela_path: BString },
+            #[error("The directory at '{}' leading to the file to read is a symbolic link", path.display())]
+            SymlinkInLeadingPath { path: PathBuf },
+            #[error("The symbolic link at '{}' is part of a loop, or a chain of more than {MAX_SYMLINK_HOPS} links", path.display())]
+            SymlinkLoop { path: PathBuf },
+            #[error("The symbolic link at '{}' points to '{}' which doesn't exist", path.display(), target.display())]
+            DanglingSymlink { path: PathBuf, target: PathBuf },
+            #[error("The symbolic link at '{}' points to '{}' which is outside of the worktree", path.display(), target.display())]
+            OutsideWorktree { path: PathBuf, target: PathBuf },
+            #[error(transparent)]
+            FindExisting(#[from] crate::object::find::existing::Error),
+            #[error("Could not obtain diff algorithm from configuration")]
+            DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+        }
+
+        /// Read the file at `rela_path` within the worktree at `root` the way it should be compared to the blob it
+        /// was checked out from, according to `options`.
+        ///
+        /// Symbolic links are read as the path they point to, just like git stores them, unless they should be
+        /// [followed][Options::follow_symlinks], in which case the file they eventually point to is read instead.
+        ///
+        /// `rela_path` must be a relative path without `.` or `..` components, and just like git, we refuse to read
+        /// files whose leading directories are symbolic links as they aren't part of the worktree.
+        pub fn read(root: &Path, rela_path: &BStr, options: Options) -> Result<Vec<u8>, Error> {
+            let io_err = |path: &Path| {
+                let path = path.to_owned();
+                move |source| Error::Io { source, path }
+            };
+            let invalid_path = || Error::InvalidPath {
+                rela_path: rela_path.to_owned(),
+            };
+            let mut path = root.to_owned();
+            let mut components = rela_path.split_str("/").peekable();
+            while let Some(component) = components.next() {
+                if component.is_empty() || component == b"." || component == b".." {
+                    return Err(invalid_path());
+                }
+                let component = gix_path::try_from_byte_slice(component).map_err(|_| invalid_path())?;
+                if component.components().count() != 1
+                    || !matches!(component.components().next(), Some(std::path::Component::Normal(_)))
+                {
+                    return Err(invalid_path());
+                }
+                path.push(component);
+                if components.peek().is_some()
+                    && std::fs::symlink_metadata(&path)
+                        .map_err(io_err(&path))?
+                        .file_type()
+                        .is_symlink()
+                {
+                    return Err(Error::SymlinkInLeadingPath { path });
+                }
+            }
+            if !options.follow_symlinks {
+                let meta = std::fs::symlink_metadata(&path).map_err(io_err(&path))?;
+                return if meta.file_type().is_symlink() {
+                    let target = std::fs::read_link(&path).map_err(io_err(&path))?;
+                    Ok(gix_path::into_bstr(target).into_owned().into())
+                } else {
+                    std::fs::read(&path).map_err(io_err(&path))
+                };
+            }
+
+            let mut target = path.clone();
+            let mut hops = 0;
+            loop {
+                let meta = match std::fs::symlink_metadata(&target) {
+                    Ok(meta) => meta,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound && hops > 0 => {
+                        return Err(Error::DanglingSymlink { path, target })
+                    }
+                    Err(err) => return Err(io_err(&target)(err)),
+                };
+                if !meta.file_type().is_symlink() {
+                    break;
+                }
+                if hops == MAX_SYMLINK_HOPS {
+                    return Err(Error::SymlinkLoop { path });
+                }
+                hops += 1;
+                let link = std::fs::read_link(&target).map_err(io_err(&target))?;
+                target = match target.parent() {
+                    Some(dir) => dir.join(link),
+                    None => link,
+                };
+            }
+
+            let root = std::fs::canonicalize(root).map_err(io_err(root))?;
+            let resolved = std::fs::canonicalize(&target).map_err(io_err(&target))?;
+            if !resolved.starts_with(&root) {
+                return Err(Error::OutsideWorktree { path, target });
+            }
+            std::fs::read(&resolved).map_err(io_err(&resolved))
+        }
+    }
+
     impl<'old, 'new> Platform<'old, 'new> {
         /// Produce a platform for performing various diffs after obtaining the object data of `previous_id` and `new_id`.
         ///
@@ -46,11 +413,67 @@ pub mod diff {
                         Ok(algo) => algo,
                         Err(err) => return Err(err.into()),
                     };
-                    Ok(Platform { old, new, algo })
+                    Ok(Platform {
+                        old: old.into(),
+                        new: new.into(),
+                        algo,
+                        whitespace: WhitespaceMode::default(),
+                    })
                 }
                 Err(err) => Err(err.into()),
             }
         }
+
+        /// Produce a platform to diff the blob with `previous_id` against the file at `rela_path` in the worktree at `root`,
+        /// which is [read][worktree::read()] according to `options`, using the diff algorithm as configured.
+        ///
+        /// Note that the file is diffed as it is, without converting it like git would when adding it to the index.
+        /// Use [`with_conversion()`][Self::with_conversion()] to normalize line endings.
+        pub fn from_id_and_worktree_file(
+            previous_id: &crate::Id<'old>,
+            root: &std::path::Path,
+            rela_path: &crate::bstr::BStr,
+            options: worktree::Options,
+        ) -> Result<Platform<'old, 'static>, worktree::Error> {
+            let old = previous_id.object()?;
+            let new = worktree::read(root, rela_path, options)?;
+            Ok(Platform {
+                old: old.into(),
+                new: new.into(),
+                algo: previous_id.repo.config.diff_algorithm()?,
+                whitespace: WhitespaceMode::default(),
+            })
+        }
+
+        /// Produce a platform for performing various diffs of the `old` and `new` data with `algo`, without having
+        /// to load any object.
+        ///
+        /// This allows to diff a blob against data that isn't stored in the object database, like a file in the worktree.
+        pub fn from_bytes(old: &'old [u8], new: &'new [u8], algo: gix_diff::blob::Algorithm) -> Platform<'old, 'new> {
+            Platform {
+                old: old.into(),
+                new: new.into(),
+                algo,
+                whitespace: WhitespaceMode::default(),
+            }
+        }
+
+        /// Convert the line endings of the old and the new blob according to `conversion` before diffing them, which
+        /// is off by default.
+        ///
+        /// Use [`Conversion::from_attributes_and_config()`] to obtain the conversion git would use for a path, so that blobs which
+        /// only differ in their line endings compare equal if they are text.
+        ///
+        /// Note that converted data [has no id][Data::id()] anymore.
+        pub fn with_conversion(mut self, conversion: Conversion) -> Self {
+            if let Cow::Owned(converted) = conversion.convert(&self.old) {
+                self.old = converted.into();
+            }
+            if let Cow::Owned(converted) = conversion.convert(&self.new) {
+                self.new = converted.into();
+            }
+            self
+        }
     }
 
     ///
@@ -93,7 +516,7 @@ pub mod diff {
             let input = self.line_tokens();
             let mut err = None;
             let mut lines = Vec::new();
-            gix_diff::blob::diff(self.algo, &input, |before: Range<u32>, after: Range<u32>| {
+            self.diff_lines(&input, 0, |before: Range<u32>, after: Range<u32>| {
                 if err.is_some() {
                     return;
                 }
@@ -101,13 +524,13 @@ pub mod diff {
                 lines.extend(
                     input.before[before.start as usize..before.end as usize]
                         .iter()
-                        .map(|&line| input.interner[line].as_bstr()),
+                        .map(|&line| without_terminator(input.interner[line])),
                 );
                 let end_of_before = lines.len();
                 lines.extend(
                     input.after[after.start as usize..after.end as usize]
                         .iter()
-                        .map(|&line| input.interner[line].as_bstr()),
+                        .map(|&line| without_terminator(input.interner[line])),
                 );
                 let hunk_before = &lines[..end_of_before];
                 let hunk_after = &lines[end_of_before..];
@@ -131,18 +554,518 @@ pub mod diff {
         }
 
         /// Count the amount of removed and inserted lines efficiently.
+        ///
+        /// The lines of hunks are never materialized, which makes this cheaper than counting the lines passed to
+        /// [`lines()`][Self::lines()], but changes are buffered when [blank lines are ignored][WhitespaceMode::IgnoreBlankLines].
+        /// Use [`line_counts_only()`][Self::line_counts_only()] to avoid that.
         pub fn line_counts(&self) -> gix_diff::blob::sink::Counter<()> {
             let tokens = self.line_tokens();
-            gix_diff::blob::diff(self.algo, &tokens, gix_diff::blob::sink::Counter::default())
+            self.diff_lines(&tokens, 0, gix_diff::blob::sink::Counter::default())
+        }
+
+        /// Count the amount of removed and inserted lines just like [`line_counts()`][Self::line_counts()], but only
+        /// accumulate the amount of lines per side without ever buffering changes or their lines.
+        ///
+        /// If both blobs are equal or one of them is empty, the lines are counted without tokenizing or diffing them at all.
+        pub fn line_counts_only(&self) -> gix_diff::blob::sink::Counter<()> {
+            let mut counts = gix_diff::blob::sink::Counter::default();
+            if *self.old == *self.new {
+                return counts;
+            }
+            if self.old.is_empty() || self.new.is_empty() {
+                let count_lines = |data: &[u8]| {
+                    let lines = gix_diff::blob::sources::byte_lines_with_terminator(data);
+                    let only_blank_lines = || lines.into_iter().all(is_blank);
+                    match self.whitespace {
+                        WhitespaceMode::IgnoreBlankLines if only_blank_lines() => 0,
+                        _ => lines.count() as u32,
+                    }
+                };
+                counts.removals = count_lines(&self.old);
+                counts.insertions = count_lines(&self.new);
+                return counts;
+            }
+            let tokens = self.line_tokens();
+            match self.whitespace {
+                WhitespaceMode::IgnoreBlankLines => gix_diff::blob::diff(
+                    self.algo,
+                    &tokens,
+                    NonBlankLineCounter {
+                        input: &tokens,
+                        last_before_end: None,
+                        cluster: counts,
+                        cluster_is_blank: true,
+                        counts: gix_diff::blob::sink::Counter::default(),
+                    },
+                ),
+                _ => self.diff_lines(&tokens, 0, counts),
+            }
+        }
+
+        /// Perform a diff on lines between the old and the new version of a blob and return each hunk as byte ranges
+        /// into the old and the new data, which includes the line terminators.
+        ///
+        /// This allows to map hunks back to the data of both blobs, which isn't possible with line indices alone.
+        pub fn line_byte_ranges(&self) -> Vec<gix_diff::blob::byte_ranges::Hunk> {
+            let tokens = self.line_tokens();
+            self.diff_lines(
+                &tokens,
+                0,
+                gix_diff::blob::byte_ranges::ByteRanges::for_lines(&self.old, &self.new),
+            )
+        }
+
+        /// Render the diff of lines between the old and the new version of a blob as patch in the unified format, exactly like
+        /// `git diff` would show it with `context_lines` unchanged lines around each change, using
+        /// [`DEFAULT_CONTEXT_LEN`][gix_diff::blob::unified::DEFAULT_CONTEXT_LEN] by default.
+        ///
+        /// `old_path` and `new_path` are the paths of the old and the new version of the blob, with `None` indicating that
+        /// the blob was added or deleted respectively, which is shown as `/dev/null`. If both paths are set but differ,
+        /// the patch starts with `rename from` and `rename to` lines. Binary blobs are only reported to differ.
+        /// Paths with special or non-ASCII bytes are quoted like git does it by default.
+        ///
+        /// The result is empty if there is nothing to show, which includes changes to whitespace that is
+        /// [ignored][Platform::whitespace], and note that the `diff --git` and `index` lines are never
+        /// produced as they need information which isn't known here, like object ids, modes and similarity.
+        pub fn to_unified_patch(
+            &self,
+            old_path: Option<&crate::bstr::BStr>,
+            new_path: Option<&crate::bstr::BStr>,
+            context_lines: u32,
+        ) -> crate::bstr::BString {
+            let mut out = crate::bstr::BString::default();
+            if let (Some(old_path), Some(new_path)) = (old_path, new_path) {
+                if old_path != new_path {
+                    out.extend_from_slice(b"rename from ");
+                    push_quoted_path(&mut out, b"", old_path);
+                    out.extend_from_slice(b"\nrename to ");
+                    push_quoted_path(&mut out, b"", new_path);
+                    out.push(b'\n');
+                }
+            }
+            if *self.old == *self.new {
+                return out;
+            }
+            let label = |prefix: &[u8], path: Option<&crate::bstr::BStr>| {
+                let mut label = crate::bstr::BString::default();
+                match path {
+                    Some(path) => push_quoted_path(&mut label, prefix, path),
+                    None => label.extend_from_slice(b"/dev/null"),
+                }
+                label
+            };
+            let (old_label, new_label) = (label(b"a/", old_path), label(b"b/", new_path));
+            if self.is_binary() {
+                out.extend_from_slice(b"Binary files ");
+                out.extend_from_slice(&old_label);
+                out.extend_from_slice(b" and ");
+                out.extend_from_slice(&new_label);
+                out.extend_from_slice(b" differ\n");
+                return out;
+            }
+            let tokens = self.line_tokens();
+            let hunks = self.diff_lines(
+                &tokens,
+                context_lines,
+                gix_diff::blob::unified::UnifiedDiff::new(&tokens, context_lines),
+            );
+            if !hunks.is_empty() {
+                for (marker, label) in [(b"--- ", &old_label), (b"+++ ", &new_label)] {
+                    out.extend_from_slice(marker);
+                    out.extend_from_slice(label);
+                    // Like git, make the end of labels with spaces unambiguous.
+                    if label.contains(&b' ') {
+                        out.push(b'\t');
+                    }
+                    out.push(b'\n');
+                }
+                out.extend_from_slice(&hunks);
+            }
+            out
+        }
+
+        /// Return `true` if the old or the new version of the blob [is binary][is_binary()], which makes line-based diffs
+        /// meaningless and typically means they shouldn't be displayed.
+        pub fn is_binary(&self) -> bool {
+            is_binary(&self.old) || is_binary(&self.new)
         }
 
         /// Return a tokenizer which treats lines as smallest unit for use in a [diff operation][gix_diff::blob::diff()].
         ///
         /// The line separator is determined according to normal git rules and filters.
+        /// Each line includes its terminator, so a final line without newline differs from the same line with newline,
+        /// just like in `git diff`. Note that the [whitespace mode][Platform::whitespace] isn't applied to these tokens.
         pub fn line_tokens(&self) -> gix_diff::blob::intern::InternedInput<&[u8]> {
             // TODO: make use of `core.eol` and/or filters to do line-counting correctly. It's probably
             //       OK to just know how these objects are saved to know what constitutes a line.
-            gix_diff::blob::intern::InternedInput::new(self.old.data.as_bytes(), self.new.data.as_bytes())
+            gix_diff::blob::intern::InternedInput::new(
+                gix_diff::blob::sources::byte_lines_with_terminator(&self.old),
+                gix_diff::blob::sources::byte_lines_with_terminator(&self.new),
+            )
+        }
+
+        /// Return `true` if the old version of the blob doesn't end with a newline, which `git diff` indicates
+        /// with `\ No newline at end of file`.
+        ///
+        /// Empty blobs have no lines and thus are never missing a final newline.
+        pub fn old_missing_final_newline(&self) -> bool {
+            missing_final_newline(&self.old)
+        }
+
+        /// Return `true` if the new version of the blob doesn't end with a newline, which `git diff` indicates
+        /// with `\ No newline at end of file`.
+        ///
+        /// Empty blobs have no lines and thus are never missing a final newline.
+        pub fn new_missing_final_newline(&self) -> bool {
+            missing_final_newline(&self.new)
+        }
+
+        /// Return a tokenizer which treats each byte as smallest unit for use in a [diff operation][gix_diff::blob::diff()],
+        /// or `None` if the combined size of both blobs exceeds [`MAX_FINE_GRAINED_DIFF_INPUT_SIZE`].
+        ///
+        /// This is useful for character-level diffs of small inputs, but very costly for large ones.
+        pub fn byte_tokens(&self) -> Option<gix_diff::blob::intern::InternedInput<u8>> {
+            self.fine_grained_diff_is_affordable().then(|| {
+                gix_diff::blob::intern::InternedInput::new(
+                    gix_diff::blob::tokens::bytes(&self.old),
+                    gix_diff::blob::tokens::bytes(&self.new),
+                )
+            })
+        }
+
+        /// Return a tokenizer which treats each UTF-8 character as smallest unit for use in a [diff operation][gix_diff::blob::diff()],
+        /// or `None` if the combined size of both blobs exceeds [`MAX_FINE_GRAINED_DIFF_INPUT_SIZE`].
+        ///
+        /// Bytes that aren't valid UTF-8 are treated as individual characters.
+        pub fn char_tokens(&self) -> Option<gix_diff::blob::intern::InternedInput<&[u8]>> {
+            self.fine_grained_diff_is_affordable().then(|| {
+                gix_diff::blob::intern::InternedInput::new(
+                    gix_diff::blob::tokens::chars(&self.old),
+                    gix_diff::blob::tokens::chars(&self.new),
+                )
+            })
+        }
+
+        fn fine_grained_diff_is_affordable(&self) -> bool {
+            self.old.len() + self.new.len() <= MAX_FINE_GRAINED_DIFF_INPUT_SIZE
+        }
+
+        /// Diff `input` as obtained by [`line_tokens()`][Self::line_tokens()] with the configured algorithm and
+        /// whitespace mode, for `sink` to show changes with `context_len` lines around them.
+        ///
+        /// The ranges passed to `sink` always refer to the lines of `input`, even if lines are compared in their
+        /// normalized form.
+        fn diff_lines<S: Sink>(&self, input: &InternedInput<&[u8]>, context_len: u32, sink: S) -> S::Out {
+            match self.whitespace {
+                WhitespaceMode::Exact => gix_diff::blob::diff(self.algo, input, sink),
+                WhitespaceMode::IgnoreBlankLines => gix_diff::blob::diff(
+                    self.algo,
+                    input,
+                    WithoutBlankChanges {
+                        input,
+                        context_len,
+                        cluster: Vec::new(),
+                        cluster_is_blank: true,
+                        inner: sink,
+                    },
+                ),
+                WhitespaceMode::IgnoreAll | WhitespaceMode::IgnoreChange => {
+                    let num_tokens = input.interner.num_tokens();
+                    let mut interner = gix_diff::blob::intern::Interner::new(num_tokens as usize);
+                    let normalized: Vec<_> = (0..num_tokens)
+                        .map(|token| interner.intern(self.whitespace.normalize(input.interner[token.into()])))
+                        .collect();
+                    let normalize = |tokens: &[gix_diff::blob::intern::Token]| -> Vec<_> {
+                        tokens.iter().map(|token| normalized[token.0 as usize]).collect()
+                    };
+                    gix_diff::blob::diff_with_tokens(
+                        self.algo,
+                        &normalize(&input.before),
+                        &normalize(&input.after),
+                        interner.num_tokens(),
+                        sink,
+                    )
+                }
+            }
+        }
+    }
+
+    /// A sink which passes changes on to `inner` unless they only consist of blank lines, in which case they are dropped
+    /// along with all other changes which would be shown in the same hunk with `context_len` lines of context.
+    ///
+    /// This is similar to how git handles `--ignore-blank-lines`, and keeps lines between the hunks that are passed on the
+    /// same on both sides, which is what hunks need to be rendered correctly.
+    struct WithoutBlankChanges<'a, S> {
+        input: &'a InternedInput<&'a [u8]>,
+        context_len: u32,
+        /// Changes that would end up in the same hunk.
+        cluster: Vec<(Range<u32>, Range<u32>)>,
+        /// `true` if all changes in `cluster` only consist of blank lines.
+        cluster_is_blank: bool,
+        inner: S,
+    }
+
+    impl<S> WithoutBlankChanges<'_, S>
+    where
+        S: Sink,
+    {
+        fn flush(&mut self) {
+            if !self.cluster_is_blank {
+                for (before, after) in self.cluster.drain(..) {
+                    self.inner.process_change(before, after);
+                }
+            }
+            self.cluster.clear();
+            self.cluster_is_blank = true;
+        }
+    }
+
+    impl<S> Sink for WithoutBlankChanges<'_, S>
+    where
+        S: Sink,
+    {
+        type Out = S::Out;
+
+        fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+            if let Some((last_before, _)) = self.cluster.last() {
+                if before.start - last_before.end > 2 * self.context_len {
+                    self.flush();
+                }
+            }
+            let input = self.input;
+            let is_blank_line = |token: &gix_diff::blob::intern::Token| is_blank(input.interner[*token]);
+            self.cluster_is_blank &= input.before[before.start as usize..before.end as usize]
+                .iter()
+                .chain(&input.after[after.start as usize..after.end as usize])
+                .all(is_blank_line);
+            self.cluster.push((before, after));
+        }
+
+        fn finish(mut self) -> Self::Out {
+            self.flush();
+            self.inner.finish()
+        }
+    }
+
+    /// A sink which counts the lines of changes like [`WithoutBlankChanges`] without context would pass them on, but only
+    /// remembers the end of the last change and the amount of lines in its cluster instead of buffering changes.
+    struct NonBlankLineCounter<'a> {
+        input: &'a InternedInput<&'a [u8]>,
+        /// The end of the `before` range of the last change.
+        last_before_end: Option<u32>,
+        /// The amount of lines of adjacent changes which only count if not all of them are blank.
+        cluster: gix_diff::blob::sink::Counter<()>,
+        /// `true` if all changes in `cluster` only consist of blank lines.
+        cluster_is_blank: bool,
+        counts: gix_diff::blob::sink::Counter<()>,
+    }
+
+    impl NonBlankLineCounter<'_> {
+        fn flush(&mut self) {
+            if !self.cluster_is_blank {
+                self.counts.removals += self.cluster.removals;
+                self.counts.insertions += self.cluster.insertions;
+            }
+            self.cluster = Default::default();
+            self.cluster_is_blank = true;
+        }
+    }
+
+    impl Sink for NonBlankLineCounter<'_> {
+        type Out = gix_diff::blob::sink::Counter<()>;
+
+        fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+            if self.last_before_end.map_or(false, |end| before.start > end) {
+                self.flush();
+            }
+            self.last_before_end = Some(before.end);
+            let input = self.input;
+            let is_blank_line = |token: &gix_diff::blob::intern::Token| is_blank(input.interner[*token]);
+            self.cluster_is_blank &= input.before[before.start as usize..before.end as usize]
+                .iter()
+                .chain(&input.after[after.start as usize..after.end as usize])
+                .all(is_blank_line);
+            self.cluster.process_change(before, after);
+        }
+
+        fn finish(mut self) -> Self::Out {
+            self.flush();
+            self.counts
+        }
+    }
+
+    /// Append `prefix` and `path` to `out`, enclosed in double quotes with special and non-ASCII bytes escaped
+    /// if `path` contains any of them, just like git does it with `core.quotePath` enabled.
+    fn push_quoted_path(out: &mut crate::bstr::BString, prefix: &[u8], path: &crate::bstr::BStr) {
+        let needs_quoting = |b: u8| b < 0x20 || b >= 0x7f || b == b'"' || b == b'\\';
+        if !path.iter().copied().any(needs_quoting) {
+            out.extend_from_slice(prefix);
+            out.extend_from_slice(path);
+            return;
+        }
+        out.push(b'"');
+        out.extend_from_slice(prefix);
+        for &b in path.iter() {
+            let escaped = match b {
+                0x07 => b'a',
+                0x08 => b'b',
+                b'\t' => b't',
+                b'\n' => b'n',
+                0x0b => b'v',
+                0x0c => b'f',
+                b'\r' => b'r',
+                b'"' | b'\\' => b,
+                b if needs_quoting(b) => {
+                    out.extend_from_slice(format!("\\{b:03o}").as_bytes());
+                    continue;
+                }
+                b => {
+                    out.push(b);
+                    continue;
+                }
+            };
+            out.push(b'\\');
+            out.push(escaped);
+        }
+        out.push(b'"');
+    }
+
+    fn missing_final_newline(data: &[u8]) -> bool {
+        !data.is_empty() && !data.ends_with(b"\n")
+    }
+
+    fn without_terminator(line: &[u8]) -> &crate::bstr::BStr {
+        match line.strip_suffix(b"\n") {
+            Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+            None => line,
+        }
+        .as_bstr()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{
+            alloc::{GlobalAlloc, Layout, System},
+            cell::Cell,
+            convert::Infallible,
+        };
+
+        use super::{line::Change, Platform, WhitespaceMode};
+
+        /// An allocator which counts the allocations made by each thread, to see what it takes to count lines.
+        struct CountingAllocator;
+
+        thread_local! {
+            static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[allow(unsafe_code)]
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        fn allocations_of<T>(f: impl FnOnce() -> T) -> (T, usize) {
+            let before = ALLOCATIONS.with(Cell::get);
+            let out = f();
+            (out, ALLOCATIONS.with(Cell::get) - before)
+        }
+
+        const WHITESPACE_MODES: [WhitespaceMode; 4] = [
+            WhitespaceMode::Exact,
+            WhitespaceMode::IgnoreAll,
+            WhitespaceMode::IgnoreChange,
+            WhitespaceMode::IgnoreBlankLines,
+        ];
+
+        fn platform<'a>(old: &'a str, new: &'a str, whitespace: WhitespaceMode) -> Platform<'a, 'a> {
+            let mut diff = Platform::from_bytes(old.as_bytes(), new.as_bytes(), gix_diff::blob::Algorithm::Myers);
+            diff.whitespace = whitespace;
+            diff
+        }
+
+        /// Count the lines of all hunks passed by `lines()`, which materializes them.
+        fn counts_of_hunk_lines(diff: &Platform<'_, '_>) -> (u32, u32) {
+            let (mut removals, mut insertions) = (0, 0);
+            diff.lines(|change| {
+                let (before, after) = match change {
+                    Change::Addition { lines } => (0, lines.len()),
+                    Change::Deletion { lines } => (lines.len(), 0),
+                    Change::Modification {
+                        lines_before,
+                        lines_after,
+                    } => (lines_before.len(), lines_after.len()),
+                };
+                removals += before as u32;
+                insertions += after as u32;
+                Ok::<_, Infallible>(())
+            })
+            .expect("infallible");
+            (removals, insertions)
+        }
+
+        #[test]
+        fn line_counts_only_match_the_lines_of_hunks_without_buffering_changes() {
+            let (old, new) = ("a\n\nb\n c\n\nd\n", "a\nb\n\nc\nd\n\n\ne\n");
+            for whitespace in WHITESPACE_MODES {
+                let diff = platform(old, new, whitespace);
+                let counts = diff.line_counts_only();
+                assert_eq!(
+                    (counts.removals, counts.insertions),
+                    counts_of_hunk_lines(&diff),
+                    "{whitespace:?}"
+                );
+                let counts = diff.line_counts();
+                assert_eq!(
+                    (counts.removals, counts.insertions),
+                    counts_of_hunk_lines(&diff),
+                    "{whitespace:?}"
+                );
+            }
+
+            let (_, exact) = allocations_of(|| platform(old, new, WhitespaceMode::Exact).line_counts_only());
+            let (_, blank_lines_ignored) =
+                allocations_of(|| platform(old, new, WhitespaceMode::IgnoreBlankLines).line_counts_only());
+            assert_eq!(
+                blank_lines_ignored, exact,
+                "ignoring blank lines doesn't need a single allocation on top of counting all lines"
+            );
+            let (_, buffered) = allocations_of(|| platform(old, new, WhitespaceMode::IgnoreBlankLines).line_counts());
+            assert!(
+                buffered > blank_lines_ignored,
+                "line_counts() buffers changes to ignore blank lines, which is what line_counts_only() avoids"
+            );
+        }
+
+        #[test]
+        fn line_counts_only_of_equal_or_empty_blobs_need_no_allocation() {
+            for (old, new, expected) in [
+                ("a\nb\n", "a\nb\n", (0, 0)),
+                ("", "a\nb", (0, 2)),
+                ("a\n\nb\n", "", (3, 0)),
+                ("", "\n \n", (0, 2)),
+            ] {
+                for whitespace in WHITESPACE_MODES {
+                    let diff = platform(old, new, whitespace);
+                    let (counts, allocations) = allocations_of(|| diff.line_counts_only());
+                    assert_eq!(allocations, 0, "{old:?} {new:?} {whitespace:?}");
+                    let expected = match whitespace {
+                        WhitespaceMode::IgnoreBlankLines if new == "\n \n" => (0, 0),
+                        _ => expected,
+                    };
+                    assert_eq!((counts.removals, counts.insertions), expected, "{whitespace:?}");
+                    assert_eq!(counts_of_hunk_lines(&diff), expected, "{old:?} {new:?} {whitespace:?}");
+                }
+            }
         }
     }
 }
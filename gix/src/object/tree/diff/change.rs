@@ -1,7 +1,11 @@
-use crate::bstr::BStr;
+use crate::bstr::{BStr, BString};
+use gix_hash::ObjectId;
 use gix_object::tree::EntryMode;
 
-use crate::Id;
+use crate::{
+    object::tree::diff::{Change, ChangeDetached, ChangesByKind},
+    Id,
+};
 
 /// An event emitted when finding differences between two trees.
 #[derive(Debug, Clone, Copy)]
@@ -83,6 +87,97 @@ pub enum Event<'a, 'old, 'new> {
         /// The object id after the copy, or the destination of it.
         id: Id<'new>,
     },
+    /// A directory is considered renamed to the directory at [`location`][Change::location] as enough of the files
+    /// removed from it were [renamed][Event::Rename] into it.
+    ///
+    /// This is only a hint passed after all other changes, and only if [directory renames][super::Renames::directory_renames]
+    /// are tracked. The files within it are still passed as renames, and the directories may also be passed as
+    /// [deletion][Event::Deletion] and [addition][Event::Addition] respectively.
+    DirectoryRename {
+        /// The location of the directory before the rename.
+        source_location: &'a BStr,
+    },
+}
+
+/// An [`Event`] which owns its data and thus doesn't borrow from the diff or the repository, as obtained with
+/// [`Event::detach()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventDetached {
+    /// See [`Event::Addition`].
+    Addition {
+        /// The mode of the added entry.
+        entry_mode: EntryMode,
+        /// The object id of the added entry.
+        id: ObjectId,
+    },
+    /// See [`Event::Deletion`].
+    Deletion {
+        /// The mode of the deleted entry.
+        entry_mode: EntryMode,
+        /// The object id of the deleted entry.
+        id: ObjectId,
+    },
+    /// See [`Event::Modification`].
+    Modification {
+        /// The mode of the entry before the modification.
+        previous_entry_mode: EntryMode,
+        /// The object id of the entry before the modification.
+        previous_id: ObjectId,
+        /// The mode of the entry after the modification.
+        entry_mode: EntryMode,
+        /// The object id after the modification.
+        id: ObjectId,
+    },
+    /// See [`Event::Rename`].
+    Rename {
+        /// The location of the source of the rename operation.
+        source_location: BString,
+        /// The mode of the entry before the rename.
+        source_entry_mode: EntryMode,
+        /// The object id of the entry before the rename.
+        source_id: ObjectId,
+        /// The mode of the entry after the rename.
+        entry_mode: EntryMode,
+        /// The object id after the rename.
+        id: ObjectId,
+    },
+    /// See [`Event::Copy`].
+    Copy {
+        /// The location of the source of the copy operation.
+        source_location: BString,
+        /// The mode of the entry that is considered the source.
+        source_entry_mode: EntryMode,
+        /// The object id of the source of the copy.
+        source_id: ObjectId,
+        /// The mode of the entry after the copy, or the destination of it.
+        entry_mode: EntryMode,
+        /// The object id after the copy, or the destination of it.
+        id: ObjectId,
+    },
+    /// See [`Event::DirectoryRename`].
+    DirectoryRename {
+        /// The location of the directory before the rename.
+        source_location: BString,
+    },
+}
+
+///
+pub mod blobs {
+    /// The error returned by [`Event::blobs()`][super::Event::blobs()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not find the previous version of the modified blob {id}")]
+        FindPrevious {
+            id: gix_hash::ObjectId,
+            source: crate::object::find::existing::Error,
+        },
+        #[error("Could not find the new version of the modified blob {id}")]
+        FindNew {
+            id: gix_hash::ObjectId,
+            source: crate::object::find::existing::Error,
+        },
+    }
 }
 
 impl<'a, 'old, 'new> Event<'a, 'old, 'new> {
@@ -102,6 +197,107 @@ impl<'a, 'old, 'new> Event<'a, 'old, 'new> {
         }
     }
 
+    /// Obtain the previous and the new version of the blob if this is a [`Modification`][Event::Modification] of a blob,
+    /// or `None` otherwise, similar to [`diff()`][Self::diff()].
+    ///
+    /// This is a shortcut for calling `object()` on both ids, with an error that tells which of them couldn't be found.
+    pub fn blobs(&self) -> Option<Result<(crate::Object<'old>, crate::Object<'new>), blobs::Error>> {
+        let (previous_id, id) = match self {
+            Event::Modification {
+                previous_entry_mode: EntryMode::BlobExecutable | EntryMode::Blob,
+                previous_id,
+                entry_mode: EntryMode::BlobExecutable | EntryMode::Blob,
+                id,
+            } => (previous_id, id),
+            _ => return None,
+        };
+        let previous = match previous_id.object() {
+            Ok(previous) => previous,
+            Err(source) => {
+                return Some(Err(blobs::Error::FindPrevious {
+                    id: previous_id.detach(),
+                    source,
+                }))
+            }
+        };
+        Some(
+            id.object()
+                .map(|new| (previous, new))
+                .map_err(|source| blobs::Error::FindNew {
+                    id: id.detach(),
+                    source,
+                }),
+        )
+    }
+
+    /// Return `true` if this is a [`Modification`][Event::Modification] which only changed the mode of the entry
+    /// while its content stayed the same, like `git diff` shows as `mode change 100644 => 100755`.
+    pub fn is_mode_change_only(&self) -> bool {
+        match self {
+            Event::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => previous_entry_mode != entry_mode && previous_id == id,
+            _ => false,
+        }
+    }
+
+    /// Return a copy of this event which owns all of its data, to keep it beyond the lifetime of the diff.
+    pub fn detach(&self) -> EventDetached {
+        match *self {
+            Event::Addition { entry_mode, id } => EventDetached::Addition {
+                entry_mode,
+                id: id.detach(),
+            },
+            Event::Deletion { entry_mode, id } => EventDetached::Deletion {
+                entry_mode,
+                id: id.detach(),
+            },
+            Event::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => EventDetached::Modification {
+                previous_entry_mode,
+                previous_id: previous_id.detach(),
+                entry_mode,
+                id: id.detach(),
+            },
+            Event::Rename {
+                source_location,
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+            } => EventDetached::Rename {
+                source_location: source_location.to_owned(),
+                source_entry_mode,
+                source_id: source_id.detach(),
+                entry_mode,
+                id: id.detach(),
+            },
+            Event::Copy {
+                source_location,
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+            } => EventDetached::Copy {
+                source_location: source_location.to_owned(),
+                source_entry_mode,
+                source_id: source_id.detach(),
+                entry_mode,
+                id: id.detach(),
+            },
+            Event::DirectoryRename { source_location } => EventDetached::DirectoryRename {
+                source_location: source_location.to_owned(),
+            },
+        }
+    }
+
     /// Return the current mode of this instance.
     pub fn entry_mode(&self) -> gix_object::tree::EntryMode {
         match self {
@@ -110,6 +306,134 @@ impl<'a, 'old, 'new> Event<'a, 'old, 'new> {
             | Event::Modification { entry_mode, .. }
             | Event::Rename { entry_mode, .. } => *entry_mode,
             Event::Copy { entry_mode, .. } => *entry_mode,
+            Event::DirectoryRename { .. } => EntryMode::Tree,
+        }
+    }
+}
+
+impl<'a, 'old, 'new> Change<'a, 'old, 'new> {
+    /// Return the location of this entry before the change, which is `None` for [additions][Event::Addition].
+    ///
+    /// For [renames][Event::Rename], [copies][Event::Copy] and [directory renames][Event::DirectoryRename] this is
+    /// the location of the source.
+    /// Note that locations are empty if neither [file names][super::Platform::track_filename()] nor
+    /// [file paths][super::Platform::track_path()] are tracked.
+    pub fn old_location(&self) -> Option<&'a BStr> {
+        match self.event {
+            Event::Addition { .. } => None,
+            Event::Deletion { .. } | Event::Modification { .. } => Some(self.location),
+            Event::Rename { source_location, .. }
+            | Event::Copy { source_location, .. }
+            | Event::DirectoryRename { source_location } => Some(source_location),
+        }
+    }
+
+    /// Return the location of this entry after the change, which is `None` for [deletions][Event::Deletion].
+    ///
+    /// Note that locations are empty if neither [file names][super::Platform::track_filename()] nor
+    /// [file paths][super::Platform::track_path()] are tracked.
+    pub fn new_location(&self) -> Option<&'a BStr> {
+        match self.event {
+            Event::Deletion { .. } => None,
+            Event::Addition { .. }
+            | Event::Modification { .. }
+            | Event::Rename { .. }
+            | Event::Copy { .. }
+            | Event::DirectoryRename { .. } => Some(self.location),
+        }
+    }
+
+    /// Return a copy of this change which owns all of its data, to keep it beyond the lifetime of the diff.
+    pub fn detach(&self) -> ChangeDetached {
+        ChangeDetached {
+            location: self.location.to_owned(),
+            event: self.event.detach(),
+        }
+    }
+
+    /// Compare this change with `other` by their [`location`][Change::location] in the order `git diff` lists changes,
+    /// which is the order of entries in trees, with the locations of trees being compared as if they had a trailing slash.
+    ///
+    /// Use it like `changes.sort_by(|a, b| a.cmp_by_path(b))` to sort changes collected during a diff. As sorting is
+    /// stable, changes at the same location keep their order.
+    /// This is only meaningful if [file paths][super::Platform::track_path()] are tracked.
+    pub fn cmp_by_path(&self, other: &Change<'_, '_, '_>) -> std::cmp::Ordering {
+        gix_object::tree::compare_paths(
+            self.location,
+            self.event.entry_mode(),
+            other.location,
+            other.event.entry_mode(),
+        )
+    }
+
+    /// Return `true` if this change is about a tree, as opposed to a blob, a symbolic link or a submodule.
+    ///
+    /// This is the case for [directory renames][Event::DirectoryRename] as well.
+    pub fn is_tree_change(&self) -> bool {
+        self.event.entry_mode().is_tree()
+    }
+
+    /// Return the amount of path components in [`location`][Change::location], which is `1` for entries at the root of the tree
+    /// and one more for each directory they are nested in.
+    ///
+    /// This is only meaningful if [file paths][super::Platform::track_path()] are tracked, and it's `0` if no location is tracked.
+    pub fn depth(&self) -> usize {
+        if self.location.is_empty() {
+            0
+        } else {
+            self.location.split(|b| *b == b'/').count()
+        }
+    }
+}
+
+impl EventDetached {
+    /// Return the current mode of this instance.
+    pub fn entry_mode(&self) -> EntryMode {
+        match self {
+            EventDetached::Addition { entry_mode, .. }
+            | EventDetached::Deletion { entry_mode, .. }
+            | EventDetached::Modification { entry_mode, .. }
+            | EventDetached::Rename { entry_mode, .. }
+            | EventDetached::Copy { entry_mode, .. } => *entry_mode,
+            EventDetached::DirectoryRename { .. } => EntryMode::Tree,
         }
     }
 }
+
+impl ChangeDetached {
+    /// Compare this change with `other` by their location like [`Change::cmp_by_path()`] does, to list them in the
+    /// order `git diff` does.
+    pub fn cmp_by_path(&self, other: &ChangeDetached) -> std::cmp::Ordering {
+        gix_object::tree::compare_paths(
+            &self.location,
+            self.event.entry_mode(),
+            &other.location,
+            other.event.entry_mode(),
+        )
+    }
+
+    /// Return `true` if this change is about a tree, like [`Change::is_tree_change()`] does.
+    pub fn is_tree_change(&self) -> bool {
+        self.event.entry_mode().is_tree()
+    }
+}
+
+impl Extend<ChangeDetached> for ChangesByKind {
+    fn extend<T: IntoIterator<Item = ChangeDetached>>(&mut self, iter: T) {
+        for change in iter {
+            if change.is_tree_change() {
+                self.tree_changes.push(change);
+            } else {
+                self.blob_changes.push(change);
+            }
+        }
+    }
+}
+
+impl FromIterator<ChangeDetached> for ChangesByKind {
+    fn from_iter<T: IntoIterator<Item = ChangeDetached>>(iter: T) -> Self {
+        let mut changes = ChangesByKind::default();
+        changes.extend(iter);
+        changes
+    }
+}
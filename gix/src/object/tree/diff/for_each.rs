@@ -1,10 +1,9 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, convert::Infallible};
 
 use gix_object::TreeRefIter;
-use gix_odb::FindExt;
+use gix_odb::{FindExt, HeaderExt};
 
-use super::{change, Action, Change, Platform, Tracking};
-use crate::object::tree::diff::Renames;
+use super::{change, renames, tracked, Action, Change, Platform, Tracking};
 use crate::{
     bstr::{BStr, BString, ByteSlice, ByteVec},
     ext::ObjectIdExt,
@@ -19,6 +18,16 @@ pub enum Error {
     Diff(#[from] gix_diff::tree::changes::Error),
     #[error("The user-provided callback failed")]
     ForEach(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Could not find blob for similarity checking during rename tracking")]
+    FindExistingBlob(#[from] gix_odb::find::existing_object::Error<gix_odb::store::find::Error>),
+    #[error("Could not obtain diff algorithm from configuration")]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error("Could not find a tree leading to the subtree to diff")]
+    FindExistingTree(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error("The trees differ outside of the subtree to diff, in the tree at '{location}'")]
+    SubtreeOutsideDiffers { location: BString },
 }
 
 /// Add the item to compare to.
@@ -35,27 +44,178 @@ impl<'a, 'old> Platform<'a, 'old> {
     where
         E: std::error::Error + Sync + Send + 'static,
     {
+        let lhs = self.lhs;
+        self.for_each_to_obtain_tree_data(&lhs.data, &other.data, other.repo, "".into(), for_each)
+    }
+
+    /// Like [`for_each_to_obtain_tree()`][Self::for_each_to_obtain_tree()], but only diff the trees at `subtree`, a path
+    /// like `a/b` relative to the root of both trees, which is useful if it's known that only this subtree changed.
+    ///
+    /// The locations of all changes are relative to the root of both trees if [paths are tracked][Platform::track_path()].
+    /// If `subtree` doesn't exist as tree on one side, it is treated like an empty tree.
+    ///
+    /// If `verify_others_are_equal` is `true`, [an error][Error::SubtreeOutsideDiffers] is returned if the trees differ
+    /// anywhere else but at `subtree`. Otherwise, changes outside of `subtree` go unnoticed.
+    pub fn for_each_to_obtain_subtree<'new, E>(
+        &mut self,
+        other: &Tree<'new>,
+        subtree: &BStr,
+        verify_others_are_equal: bool,
+        for_each: impl FnMut(Change<'_, 'old, 'new>) -> Result<Action, E>,
+    ) -> Result<(), Error>
+    where
+        E: std::error::Error + Sync + Send + 'static,
+    {
+        let mut lhs = self.lhs.data.clone();
+        let mut rhs = other.data.clone();
+        let mut location = BString::default();
+        for component in subtree.split_str("/").filter(|component| !component.is_empty()) {
+            let is_subtree_entry = |entry: &gix_object::tree::EntryRef<'_>| {
+                entry.filename == component && entry.mode == gix_object::tree::EntryMode::Tree
+            };
+            if verify_others_are_equal {
+                let is_other_entry =
+                    |entry: &Result<gix_object::tree::EntryRef<'_>, _>| !entry.as_ref().map_or(false, is_subtree_entry);
+                let lhs_others: Vec<_> = TreeRefIter::from_bytes(&lhs)
+                    .filter(is_other_entry)
+                    .collect::<Result<_, _>>()?;
+                let rhs_others: Vec<_> = TreeRefIter::from_bytes(&rhs)
+                    .filter(is_other_entry)
+                    .collect::<Result<_, _>>()?;
+                if lhs_others != rhs_others {
+                    return Err(Error::SubtreeOutsideDiffers { location });
+                }
+            }
+            let subtree_data = |data: &[u8], repo: &Repository| -> Result<Vec<u8>, Error> {
+                let id = TreeRefIter::from_bytes(data)
+                    .filter_map(Result::ok)
+                    .find(is_subtree_entry)
+                    .map(|entry| entry.oid.to_owned());
+                Ok(match id {
+                    Some(id) => repo.find_object(id)?.detach().data,
+                    None => Vec::new(),
+                })
+            };
+            lhs = subtree_data(&lhs, self.lhs.repo)?;
+            rhs = subtree_data(&rhs, other.repo)?;
+            if !location.is_empty() {
+                location.push(b'/');
+            }
+            location.push_str(component);
+        }
+        self.for_each_to_obtain_tree_data(&lhs, &rhs, other.repo, location.as_ref(), for_each)
+    }
+
+    /// Diff this tree with `other` like [`for_each_to_obtain_tree()`][Self::for_each_to_obtain_tree()] would, but only
+    /// to count the candidates for rename tracking instead of tracking renames, or return `None` if
+    /// [renames aren't tracked][Platform::track_renames()].
+    ///
+    /// This only loads trees, so it's cheap compared to the similarity checks of rename tracking, and can be used
+    /// to decide whether rename tracking should be performed at all.
+    pub fn analyze_renames(&mut self, other: &Tree<'_>) -> Result<Option<renames::Analysis>, Error> {
+        let renames = match self.renames {
+            Some(renames) => renames,
+            None => return Ok(None),
+        };
         let repo = self.lhs.repo;
         let mut delegate = Delegate {
-            repo: self.lhs.repo,
+            repo,
             other_repo: other.repo,
             tracking: self.tracking,
-            _renames: self.renames,
+            tracked: Some(tracked::State::new(renames, repo.config.diff_algorithm()?, 0)),
             location: BString::default(),
             path_deque: Default::default(),
-            visit: for_each,
+            pathspec: self.pathspec.as_ref(),
+            location_prefix: None,
+            visit: |_change: Change<'_, '_, '_>| -> Result<_, Infallible> { Ok(Action::Continue) },
             err: None,
         };
-        match gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.lhs.data)).needed_to_obtain(
-            TreeRefIter::from_bytes(&other.data),
+        diff_trees(
+            &self.lhs.data,
+            &other.data,
             &mut self.state,
-            |oid, buf| repo.objects.find_tree_iter(oid, buf),
+            repo,
+            self.pathspec.as_ref(),
             &mut delegate,
-        ) {
-            Ok(()) => match delegate.err {
-                Some(err) => Err(Error::ForEach(Box::new(err))),
-                None => Ok(()),
+        )?;
+        Ok(delegate.tracked.map(|tracked| tracked.analyze()))
+    }
+
+    fn for_each_to_obtain_tree_data<'new, E>(
+        &mut self,
+        lhs: &[u8],
+        rhs: &[u8],
+        other_repo: &'new Repository,
+        location: &BStr,
+        for_each: impl FnMut(Change<'_, 'old, 'new>) -> Result<Action, E>,
+    ) -> Result<(), Error>
+    where
+        E: std::error::Error + Sync + Send + 'static,
+    {
+        let repo = self.lhs.repo;
+        let mut delegate = Delegate {
+            repo: self.lhs.repo,
+            other_repo,
+            tracking: self.tracking,
+            tracked: match (self.renames, self.tracked.take()) {
+                (Some(_), Some(mut tracked)) => {
+                    tracked.clear();
+                    Some(tracked)
+                }
+                (Some(renames), None) => Some(tracked::State::new(
+                    renames,
+                    repo.config.diff_algorithm()?,
+                    self.rename_cache_size,
+                )),
+                (None, _) => None,
+            },
+            location: match (self.tracking, &self.pathspec) {
+                (Some(Tracking::Path), _) | (_, Some(_)) => location.to_owned(),
+                (Some(Tracking::FileName) | None, None) => BString::default(),
             },
+            path_deque: Default::default(),
+            pathspec: self.pathspec.as_ref(),
+            location_prefix: self.location_prefix.as_ref().map(|prefix| prefix.as_bstr()),
+            visit: for_each,
+            err: None,
+        };
+        match diff_trees(lhs, rhs, &mut self.state, repo, self.pathspec.as_ref(), &mut delegate) {
+            Ok(_) => {
+                if let Some(mut tracked) = delegate.tracked.take() {
+                    tracked.set_token_cache(self.rename_token_cache.take());
+                    tracked.collect_near_misses(self.rename_near_misses);
+                    let skip_missing = self.renames.map_or(false, |renames| {
+                        renames.missing_blob == renames::MissingBlob::SkipSimilarity
+                    });
+                    let action = tracked.emit(
+                        |dest, source| delegate.emit_change(dest.change, dest.location, source),
+                        |oid, buf| match repo.objects.find_blob(oid, buf) {
+                            Ok(blob) => Ok(Some(blob)),
+                            Err(gix_odb::find::existing_object::Error::NotFound { .. }) if skip_missing => Ok(None),
+                            Err(err) => Err(err),
+                        },
+                        |oid| match repo.objects.header(oid) {
+                            Ok(header) => Ok(Some(header.size())),
+                            Err(gix_odb::find::existing::Error::NotFound { .. }) if skip_missing => Ok(None),
+                            Err(err) => Err(err.into()),
+                        },
+                    );
+                    self.rename_token_cache = tracked.take_token_cache();
+                    let action = action?;
+                    if !action.cancelled() {
+                        for (source_location, location) in tracked.directory_renames() {
+                            if delegate.emit_directory_rename(source_location, location).cancelled() {
+                                break;
+                            }
+                        }
+                    }
+                    self.tracked = Some(tracked);
+                }
+                match delegate.err {
+                    Some(err) => Err(Error::ForEach(Box::new(err))),
+                    None => Ok(()),
+                }
+            }
             Err(gix_diff::tree::changes::Error::Cancelled) => delegate
                 .err
                 .map(|err| Err(Error::ForEach(Box::new(err))))
@@ -65,18 +225,172 @@ impl<'a, 'old> Platform<'a, 'old> {
     }
 }
 
-struct Delegate<'old, 'new, VisitFn, E> {
+/// Diff the trees `lhs` and `rhs` of `repo` for `delegate`, without descending into trees which can't contain paths
+/// matching `pathspec`.
+fn diff_trees(
+    lhs: &[u8],
+    rhs: &[u8],
+    state: &mut gix_diff::tree::State,
+    repo: &Repository,
+    pathspec: Option<&gix_pathspec::Search>,
+    delegate: &mut impl gix_diff::tree::Visit,
+) -> Result<gix_diff::tree::changes::Outcome, gix_diff::tree::changes::Error> {
+    let changes = gix_diff::tree::Changes::from(TreeRefIter::from_bytes(lhs));
+    let rhs = TreeRefIter::from_bytes(rhs);
+    match pathspec {
+        Some(search) => changes.needed_to_obtain(
+            rhs,
+            state,
+            |oid, buf| repo.objects.find_tree_iter(oid, buf),
+            // Only descend into trees which are on the way to or within the directory shared by all positive pathspecs.
+            &mut gix_diff::tree::Filter::new(delegate, |dir| {
+                let prefix = search.common_prefix();
+                if prefix.len() > dir.len() {
+                    prefix.starts_with(dir) && (dir.is_empty() || prefix[dir.len()] == b'/')
+                } else {
+                    dir.starts_with(prefix)
+                }
+            }),
+        ),
+        None => changes.needed_to_obtain(rhs, state, |oid, buf| repo.objects.find_tree_iter(oid, buf), delegate),
+    }
+}
+
+struct Delegate<'a, 'old, 'new, VisitFn, E> {
     repo: &'old Repository,
     other_repo: &'new Repository,
     tracking: Option<Tracking>,
-    _renames: Option<Renames>,
+    tracked: Option<tracked::State>,
+    /// The full path of the current entry if paths are tracked, or its filename if only filenames are tracked.
     location: BString,
     path_deque: VecDeque<BString>,
+    /// If set, only changes at matching paths are passed on, which requires the full path to be tracked.
+    pathspec: Option<&'a gix_pathspec::Search>,
+    /// If set, the directory to make locations relative to when passing them to the user.
+    location_prefix: Option<&'a BStr>,
     visit: VisitFn,
     err: Option<E>,
 }
 
-impl<A, B> Delegate<'_, '_, A, B> {
+impl<'old, 'new, VisitFn, E> Delegate<'_, 'old, 'new, VisitFn, E>
+where
+    VisitFn: for<'delegate> FnMut(Change<'delegate, 'old, 'new>) -> Result<Action, E>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    /// Call the user-provided callback with `change` at `location`, which is the destination of a rename or copy
+    /// if `source` is set.
+    fn emit_change(
+        &mut self,
+        change: gix_diff::tree::visit::Change,
+        location: &BStr,
+        source: Option<tracked::visit::Source<'_>>,
+    ) -> gix_diff::tree::visit::Action {
+        use gix_diff::tree::visit::Change::*;
+        let location = self.relative(location);
+        let event = match (change, source) {
+            (Addition { entry_mode, oid }, Some(source)) => {
+                let source_location = self.relative(source.location);
+                let source_entry_mode = source.entry_mode;
+                let source_id = source.id.to_owned().attach(self.repo);
+                let id = oid.attach(self.other_repo);
+                match source.kind {
+                    tracked::visit::SourceKind::Rename => change::Event::Rename {
+                        source_location,
+                        source_entry_mode,
+                        source_id,
+                        entry_mode,
+                        id,
+                    },
+                    tracked::visit::SourceKind::Copy => change::Event::Copy {
+                        source_location,
+                        source_entry_mode,
+                        source_id,
+                        entry_mode,
+                        id,
+                    },
+                }
+            }
+            (_, Some(_)) => unreachable!("BUG: only additions can be the destination of a rename or copy"),
+            (Addition { entry_mode, oid }, None) => change::Event::Addition {
+                entry_mode,
+                id: oid.attach(self.other_repo),
+            },
+            (Deletion { entry_mode, oid }, None) => change::Event::Deletion {
+                entry_mode,
+                id: oid.attach(self.repo),
+            },
+            (
+                Modification {
+                    previous_entry_mode,
+                    previous_oid,
+                    entry_mode,
+                    oid,
+                },
+                None,
+            ) => change::Event::Modification {
+                previous_entry_mode,
+                entry_mode,
+                previous_id: previous_oid.attach(self.repo),
+                id: oid.attach(self.other_repo),
+            },
+        };
+        self.call_visit(Change { event, location })
+    }
+
+    /// Call the user-provided callback with the directory at `source_location` having been renamed to `location`.
+    fn emit_directory_rename(&mut self, source_location: &BStr, location: &BStr) -> gix_diff::tree::visit::Action {
+        self.call_visit(Change {
+            event: change::Event::DirectoryRename {
+                source_location: self.relative(source_location),
+            },
+            location: self.relative(location),
+        })
+    }
+
+    fn call_visit(&mut self, change: Change<'_, 'old, 'new>) -> gix_diff::tree::visit::Action {
+        match (self.visit)(change) {
+            Ok(Action::Cancel) => gix_diff::tree::visit::Action::Cancel,
+            Ok(Action::Continue) => gix_diff::tree::visit::Action::Continue,
+            Err(err) => {
+                self.err = Some(err);
+                gix_diff::tree::visit::Action::Cancel
+            }
+        }
+    }
+}
+
+impl<A, B> Delegate<'_, '_, '_, A, B> {
+    /// Return `true` if the full path is tracked, either because the user wants it or because it's needed for pathspecs.
+    fn tracks_path(&self) -> bool {
+        matches!(self.tracking, Some(Tracking::Path)) || self.pathspec.is_some()
+    }
+
+    /// Return `true` if the change at the current location passes the pathspec, if there is one.
+    fn is_selected(&self, change: &gix_diff::tree::visit::Change) -> bool {
+        use gix_diff::tree::visit::Change::*;
+        let entry_mode = match change {
+            Addition { entry_mode, .. } | Deletion { entry_mode, .. } | Modification { entry_mode, .. } => entry_mode,
+        };
+        self.pathspec.map_or(true, |search| {
+            search
+                .matches(self.location.as_bstr(), Some(entry_mode.is_tree()))
+                .is_some()
+        })
+    }
+
+    /// Return `location` relative to the location prefix, or unchanged if there is none or `location` is outside of it.
+    fn relative<'b>(&self, location: &'b BStr) -> &'b BStr {
+        let prefix = match self.location_prefix {
+            Some(prefix) => prefix,
+            None => return location,
+        };
+        match location.strip_prefix(prefix.as_bytes()) {
+            Some(rest) if rest.is_empty() => rest.as_bstr(),
+            Some(rest) if rest[0] == b'/' => rest[1..].as_bstr(),
+            _ => location,
+        }
+    }
+
     fn pop_element(&mut self) {
         if let Some(pos) = self.location.rfind_byte(b'/') {
             self.location.resize(pos, 0);
@@ -93,13 +407,13 @@ impl<A, B> Delegate<'_, '_, A, B> {
     }
 }
 
-impl<'old, 'new, VisitFn, E> gix_diff::tree::Visit for Delegate<'old, 'new, VisitFn, E>
+impl<'old, 'new, VisitFn, E> gix_diff::tree::Visit for Delegate<'_, 'old, 'new, VisitFn, E>
 where
     VisitFn: for<'delegate> FnMut(Change<'delegate, 'old, 'new>) -> Result<Action, E>,
     E: std::error::Error + Sync + Send + 'static,
 {
     fn pop_front_tracked_path_and_set_current(&mut self) {
-        if let Some(Tracking::Path) = self.tracking {
+        if self.tracks_path() {
             self.location = self
                 .path_deque
                 .pop_front()
@@ -108,64 +422,48 @@ where
     }
 
     fn push_back_tracked_path_component(&mut self, component: &BStr) {
-        if let Some(Tracking::Path) = self.tracking {
+        if self.tracks_path() {
             self.push_element(component);
             self.path_deque.push_back(self.location.clone());
         }
     }
 
     fn push_path_component(&mut self, component: &BStr) {
-        match self.tracking {
-            Some(Tracking::FileName) => {
-                self.location.clear();
-                self.location.push_str(component);
-            }
-            Some(Tracking::Path) => {
-                self.push_element(component);
-            }
-            None => {}
+        if self.tracks_path() {
+            self.push_element(component);
+        } else if let Some(Tracking::FileName) = self.tracking {
+            self.location.clear();
+            self.location.push_str(component);
         }
     }
 
     fn pop_path_component(&mut self) {
-        if let Some(Tracking::Path) = self.tracking {
+        if self.tracks_path() {
             self.pop_element();
         }
     }
 
     fn visit(&mut self, change: gix_diff::tree::visit::Change) -> gix_diff::tree::visit::Action {
-        use gix_diff::tree::visit::Change::*;
-        let event = match change {
-            Addition { entry_mode, oid } => change::Event::Addition {
-                entry_mode,
-                id: oid.attach(self.other_repo),
-            },
-            Deletion { entry_mode, oid } => change::Event::Deletion {
-                entry_mode,
-                id: oid.attach(self.repo),
-            },
-            Modification {
-                previous_entry_mode,
-                previous_oid,
-                entry_mode,
-                oid,
-            } => change::Event::Modification {
-                previous_entry_mode,
-                entry_mode,
-                previous_id: previous_oid.attach(self.repo),
-                id: oid.attach(self.other_repo),
-            },
-        };
-        match (self.visit)(Change {
-            event,
-            location: self.location.as_ref(),
-        }) {
-            Ok(Action::Cancel) => gix_diff::tree::visit::Action::Cancel,
-            Ok(Action::Continue) => gix_diff::tree::visit::Action::Continue,
-            Err(err) => {
-                self.err = Some(err);
-                gix_diff::tree::visit::Action::Cancel
-            }
+        if !self.is_selected(&change) {
+            return gix_diff::tree::visit::Action::Continue;
         }
+        let location = std::mem::take(&mut self.location);
+        let user_location = match (self.tracking, self.pathspec) {
+            (Some(Tracking::Path), _) | (_, None) => location.as_bstr(),
+            (Some(Tracking::FileName), Some(_)) => location
+                .rfind_byte(b'/')
+                .map_or(location.as_bstr(), |pos| location[pos + 1..].as_bstr()),
+            (None, Some(_)) => "".into(),
+        };
+        let change = match self.tracked.as_mut() {
+            Some(tracked) => tracked.try_push_change(change, user_location),
+            None => Some(change),
+        };
+        let action = match change {
+            Some(change) => self.emit_change(change, user_location, None),
+            None => gix_diff::tree::visit::Action::Continue,
+        };
+        self.location = location;
+        action
     }
 }
@@ -1,4 +1,9 @@
-use crate::{bstr::BStr, Tree};
+use std::collections::HashMap;
+
+use crate::{
+    bstr::{BStr, BString},
+    Tree,
+};
 
 /// Returned by the `for_each` function to control flow.
 #[derive(Clone, Copy, PartialOrd, PartialEq, Ord, Eq, Hash)]
@@ -26,9 +31,54 @@ pub struct Change<'a, 'old, 'new> {
     pub event: change::Event<'a, 'old, 'new>,
 }
 
+/// A [`Change`] which owns its data, as obtained with [`Change::detach()`] or returned by
+/// [`Repository::diff_tree_to_tree()`][crate::Repository::diff_tree_to_tree()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeDetached {
+    /// The location of the file or directory described by `event`, if tracking was enabled.
+    ///
+    /// Otherwise this value is always an empty path.
+    pub location: BString,
+    /// The diff event itself to provide information about what would need to change.
+    pub event: change::EventDetached,
+}
+
+/// [Detached changes][ChangeDetached] in the order they were collected, but separated into changes of trees and all other changes.
+///
+/// Collect an iterator of changes into it, like the ones returned by
+/// [`Repository::diff_tree_to_tree()`][crate::Repository::diff_tree_to_tree()], to handle them without having to
+/// check the [kind of change][ChangeDetached::is_tree_change()] each time.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ChangesByKind {
+    /// Changes of trees, including [directory renames][change::EventDetached::DirectoryRename].
+    pub tree_changes: Vec<ChangeDetached>,
+    /// Changes of everything that isn't a tree, i.e. blobs, symbolic links and submodules.
+    pub blob_changes: Vec<ChangeDetached>,
+}
+
 ///
 pub mod change;
 
+///
+pub mod tree_to_tree {
+    /// The error returned by [`Repository::diff_tree_to_tree()`][crate::Repository::diff_tree_to_tree()] and
+    /// [`Repository::diff_tree_to_tree_by_id()`][crate::Repository::diff_tree_to_tree_by_id()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        RevParse(#[from] crate::revision::spec::parse::single::Error),
+        #[error(transparent)]
+        FindExisting(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        RenamesConfiguration(#[from] super::renames::Error),
+        #[error(transparent)]
+        Diff(#[from] super::for_each::Error),
+    }
+}
+
 /// Diffing
 impl<'repo> Tree<'repo> {
     /// Return a platform to see the changes needed to create other trees, for instance.
@@ -43,7 +93,13 @@ impl<'repo> Tree<'repo> {
             state: Default::default(),
             lhs: self,
             tracking: None,
-            renames: self.repo.config.diff_renames()?.unwrap_or_default().into(),
+            location_prefix: None,
+            renames: self.repo.config.diff_renames()?,
+            rename_cache_size: DEFAULT_RENAME_CACHE_SIZE,
+            rename_token_cache: None,
+            rename_near_misses: false,
+            tracked: None,
+            pathspec: None,
         })
     }
 }
@@ -54,9 +110,23 @@ pub struct Platform<'a, 'repo> {
     state: gix_diff::tree::State,
     lhs: &'a Tree<'repo>,
     tracking: Option<Tracking>,
+    /// If set, the directory to make the locations of changes relative to, without trailing slash.
+    location_prefix: Option<BString>,
     renames: Option<Renames>,
+    rename_cache_size: usize,
+    /// Tokenized blobs to keep across diffs, passed to `tracked` for each diff.
+    rename_token_cache: Option<renames::TokenCache>,
+    /// If `true`, collect the most similar sources of unmatched destinations during rename tracking.
+    rename_near_misses: bool,
+    /// The state for rename tracking, kept to reuse its allocations in subsequent diffs.
+    tracked: Option<tracked::State>,
+    /// If set, only changes at matching paths are emitted.
+    pathspec: Option<gix_pathspec::Search>,
 }
 
+/// The default amount of bytes used to cache blobs while checking for similarity during rename tracking.
+pub const DEFAULT_RENAME_CACHE_SIZE: usize = 64 * 1024 * 1024;
+
 #[derive(Clone, Copy)]
 enum Tracking {
     FileName,
@@ -64,31 +134,109 @@ enum Tracking {
 }
 
 /// A structure to capture how to perform rename tracking
+///
+/// # Choosing among equally good sources
+///
+/// If a destination has multiple sources with the same content, a source that was deleted is preferred over one that
+/// can only be copied, and among those the source with the lexicographically smallest location wins, comparing the
+/// bytes of both locations. If sources are equally similar instead, the one with the smallest object id wins, and then
+/// the one with the smallest location. Thus, the choice is deterministic and doesn't depend on the order in which
+/// changes are encountered, unless [locations aren't tracked][Platform::track_path()] and are all empty.
+///
+/// If [`prefer_similar_paths`][Renames::prefer_similar_paths] is set, sources with paths similar to the destination
+/// are preferred over sources which would win by their location or id otherwise.
+///
+/// As a deleted file can only be renamed once, destinations are matched in the order of their location, so if
+/// multiple destinations have the same best source, the one with the smallest location is its rename.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Renames {
     /// If `Some(…)`, do also find copies. `None` is the default which does not try to detect copies at all.
     ///
     /// Note that this is an even more expensive operation than detecting renames as files.
     pub copies: Option<renames::Copies>,
-    /// The percentage of similarity needed for files to be considered renamed or copied, defaulting to `Some(0.5)`.
-    /// This field is similar to `git diff -M50%`.
+    /// The percentage of similarity needed for files to be considered renamed, defaulting to `Some(0.5)`.
+    /// This field is similar to `git diff -M50%`, and copies may use it as well as configured in [`Copies`][renames::Copies].
     ///
-    /// If `None`, files are only considered equal if their content matches 100%.
+    /// If `None`, files are only considered equal if their content matches 100%, which is determined by their ids alone
+    /// so no blob is ever loaded. The same is true for `Some(1.0)` unless [whitespace][Self::whitespace] is ignored,
+    /// which makes this mode usable even if blobs aren't available, like in partial clones.
     /// Note that values greater than 1.0 have no different effect than 1.0.
     pub percentage: Option<f32>,
     /// The amount of files to consider for rename or copy tracking. Defaults to 1000.
-    /// If 0, there is no limit.
+    /// If 0, there is no limit, which is also what `diff.renameLimit = 0` means to git.
     pub limit: usize,
+    /// If `Some(fraction)`, also detect renamed directories, which is `None` by default.
+    ///
+    /// A directory is considered renamed if at least `fraction` of the files removed from it, in the range of 0.0 to 1.0,
+    /// were renamed into the same directory. This is reported as [`Event::DirectoryRename`][change::Event::DirectoryRename]
+    /// in addition to the renames of the files within it, and requires [paths to be tracked][Platform::track_path()].
+    pub directory_renames: Option<f32>,
+    /// If `Some(bytes)`, blobs larger than this are only matched by identity and never loaded to compare their content.
+    ///
+    /// This avoids loading huge files, which are typically binary anyway. It defaults to 512MiB or the value
+    /// of `core.bigFileThreshold`, like git does.
+    pub max_file_size: Option<u64>,
+    /// The way whitespace is treated when comparing the lines of blobs for similarity, which is
+    /// [exact][crate::object::blob::diff::WhitespaceMode::Exact] by default, just like in git.
+    ///
+    /// Ignoring whitespace lets files which were reindented while being renamed be found as if they were unchanged.
+    pub whitespace: crate::object::blob::diff::WhitespaceMode,
+    /// If `true`, among sources that are equally good, prefer the one whose path is most similar to the destination, i.e.
+    /// the one with the same file name, and then the one with the most leading directories in common.
+    ///
+    /// This is similar to how git chooses among identical sources, which prefers sources with the same file name.
+    /// It's `false` by default, so that sources are only chosen by their location as described in the
+    /// [section on choosing among sources][Renames#choosing-among-equally-good-sources].
+    pub prefer_similar_paths: bool,
+    /// Control what happens if a blob can't be found while checking for similarity, which is typical for partial clones.
+    ///
+    /// By default, this is an [error][renames::MissingBlob::Error], but it can be set to
+    /// [skip similarity checks][renames::MissingBlob::SkipSimilarity] for missing blobs instead, so that these can
+    /// still be matched by identity.
+    pub missing_blob: renames::MissingBlob,
+    /// If `true`, the default, empty blobs can be the source and destination of renames and copies like any other blob,
+    /// which is what git does unless `--no-rename-empty` is passed.
+    ///
+    /// As all empty blobs have the same id, unrelated empty files, like placeholders, are then matched with each other
+    /// by identity. Set it to `false` to report them as independent additions and deletions instead.
+    pub rename_empty: bool,
 }
 
 ///
 pub mod renames;
 
+/// Return the similarity of the `old` and `new` blob as a value from 0.0 to 1.0, computed with `algorithm` the same way
+/// as when [finding renames and copies][Renames::percentage] with whitespace compared exactly.
+///
+/// It's the fraction of lines of the larger blob that both have in common, which makes it symmetric so that lines added
+/// to `new` lower the similarity just like lines removed from `old` do. Two empty blobs are considered identical.
+pub fn blob_similarity(old: &[u8], new: &[u8], algorithm: gix_diff::blob::Algorithm) -> f32 {
+    tracked::similarity(algorithm, crate::object::blob::diff::WhitespaceMode::Exact, old, new)
+}
+
+/// Like [`blob_similarity()`], but reads the `old` and `new` blob line by line without ever holding them in memory,
+/// which makes it suitable for blobs too large to be loaded.
+///
+/// Only a hash of each line is kept, so lines are compared by their hashes which makes the result an estimate,
+/// even though it only differs from [`blob_similarity()`] if distinct lines happen to have the same hash.
+///
+/// Note that the object database can only provide fully decoded objects, which is why blobs larger than
+/// [`Renames::max_file_size`] are still only matched by identity when tracking renames. Use this function to compare
+/// such blobs from other sources, like files in the worktree.
+pub fn blob_similarity_streaming(
+    old: impl std::io::BufRead,
+    new: impl std::io::BufRead,
+    algorithm: gix_diff::blob::Algorithm,
+) -> std::io::Result<f32> {
+    tracked::similarity_of_readers(algorithm, old, new)
+}
+
 /// Configuration
 impl<'a, 'repo> Platform<'a, 'repo> {
     /// Keep track of file-names, which makes the [`location`][Change::location] field usable with the filename of the changed item.
     pub fn track_filename(&mut self) -> &mut Self {
         self.tracking = Some(Tracking::FileName);
+        self.location_prefix = None;
         self
     }
 
@@ -97,6 +245,24 @@ impl<'a, 'repo> Platform<'a, 'repo> {
     /// This makes the [`location`][Change::location] field usable.
     pub fn track_path(&mut self) -> &mut Self {
         self.tracking = Some(Tracking::Path);
+        self.location_prefix = None;
+        self
+    }
+
+    /// Keep track of the entire path of a change like [`track_path()`][Platform::track_path()], but make the
+    /// [`location`][Change::location] relative to the directory `prefix`, like `git diff --relative` does when run from
+    /// within that directory.
+    ///
+    /// Changes at `prefix` itself have an empty location, while the locations of changes outside of `prefix`
+    /// are passed through unchanged, i.e. relative to the repository. Use [pathspecs][Platform::with_pathspecs()]
+    /// to only see the changes within `prefix`.
+    pub fn track_path_relative_to(&mut self, prefix: impl Into<BString>) -> &mut Self {
+        let mut prefix = prefix.into();
+        while prefix.last() == Some(&b'/') {
+            prefix.pop();
+        }
+        self.tracking = Some(Tracking::Path);
+        self.location_prefix = (!prefix.is_empty()).then_some(prefix);
         self
     }
 
@@ -105,11 +271,181 @@ impl<'a, 'repo> Platform<'a, 'repo> {
     ///
     /// Note that by default, the configuration determines rename tracking and standard git defaults are used
     /// if nothing is configured, which turns on rename tracking with `-M50%`.
+    ///
+    /// Passing `None` disables rename and copy tracking for this platform regardless of `diff.renames`, similar to
+    /// `git diff --no-renames`. Renamed files are then passed as [deletion][change::Event::Deletion] and
+    /// [addition][change::Event::Addition], each as soon as it's encountered as no change is held back for matching.
     pub fn track_renames(&mut self, renames: Option<Renames>) -> &mut Self {
         self.renames = renames;
+        self.tracked = None;
+        self
+    }
+
+    /// Use at most `bytes` of memory to cache blobs while checking them for similarity during rename tracking,
+    /// evicting the least recently used ones first. Use `0` to disable the cache.
+    ///
+    /// The default is [`DEFAULT_RENAME_CACHE_SIZE`]. Note that this has no effect on the outcome, only on how often
+    /// blobs have to be loaded.
+    pub fn rename_cache_size(&mut self, bytes: usize) -> &mut Self {
+        self.rename_cache_size = bytes;
+        self.tracked = None;
+        self
+    }
+
+    /// Use `cache` to look up blobs which were already tokenized in previous diffs when checking for similarity during
+    /// rename tracking, and to keep the ones tokenized during this diff, instead of using the
+    /// [rename cache][Platform::rename_cache_size()].
+    ///
+    /// Use [`take_rename_token_cache()`][Platform::take_rename_token_cache()] to get it back once the diff is done,
+    /// to pass it to the platform of the next diff. This makes sense when diffing many trees with
+    /// blobs in common, like each commit and its parent when walking the history. Note that this
+    /// doesn't affect the outcome.
+    pub fn rename_token_cache(&mut self, cache: renames::TokenCache) -> &mut Self {
+        self.rename_token_cache = Some(cache);
         self
     }
+
+    /// Return the cache previously set with [`rename_token_cache()`][Platform::rename_token_cache()],
+    /// along with all blobs tokenized since then.
+    pub fn take_rename_token_cache(&mut self) -> Option<renames::TokenCache> {
+        self.rename_token_cache.take()
+    }
+
+    /// If `toggle` is `true`, collect the most similar source of each addition which couldn't be matched to a source
+    /// during rename tracking as its similarity was too low, to obtain them with
+    /// [`rename_near_misses()`][Platform::rename_near_misses()] after the diff.
+    ///
+    /// This is `false` by default to avoid the overhead if unused. Note that near misses are only found if
+    /// similarity is checked at all, so not if the [percentage][Renames::percentage] is `None` or if the
+    /// [limit][Renames::limit] is exceeded.
+    pub fn track_rename_near_misses(&mut self, toggle: bool) -> &mut Self {
+        self.rename_near_misses = toggle;
+        self
+    }
+
+    /// Only emit changes whose path matches `specs`, pathspecs like `dir/`, `*.rs` or `:(exclude)*.lock` which are
+    /// matched relative to the root of both trees, with the same precedence rules as [`Search`][gix_pathspec::Search].
+    ///
+    /// Trees which can't contain any matching path aren't descended into, and only the matching changes are considered
+    /// for rename tracking. Paths are tracked internally to match them, but the [`location`][Change::location] is
+    /// still only provided as configured with [`track_path()`][Platform::track_path()] or
+    /// [`track_filename()`][Platform::track_filename()].
+    ///
+    /// Paths are matched case-insensitively if `core.ignoreCase` is set. Note that attributes in pathspecs are ignored as
+    /// trees have no attributes to match against.
+    pub fn with_pathspecs(
+        &mut self,
+        specs: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> Result<&mut Self, gix_pathspec::parse::Error> {
+        let patterns = specs
+            .into_iter()
+            .map(|spec| gix_pathspec::parse(spec.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.pathspec =
+            Some(gix_pathspec::Search::from_specs(patterns).with_ignore_case(self.lhs.repo.config.ignore_case));
+        Ok(self)
+    }
+}
+
+/// Access
+impl<'a, 'repo> Platform<'a, 'repo> {
+    /// Return all additions which weren't matched to a source during the last diff along with their most similar
+    /// source, in the order the additions were encountered, if [near misses are tracked][Platform::track_rename_near_misses()].
+    pub fn rename_near_misses(&self) -> impl Iterator<Item = renames::NearMiss<'_>> {
+        self.tracked
+            .iter()
+            .flat_map(|tracked| tracked.near_misses())
+            .map(|(source_location, location, similarity)| renames::NearMiss {
+                source_location,
+                location,
+                similarity,
+            })
+    }
+}
+
+/// Identification
+impl<'a, 'repo> Platform<'a, 'repo> {
+    /// Return an id which identifies the changes needed to turn this tree into `other` with all options that affect them,
+    /// which is useful as key when caching the results of a diff.
+    ///
+    /// The id is derived from the ids of both trees, the diff algorithm, the tracking of locations, the pathspecs and the
+    /// normalized rename tracking configuration, so it's the same for the same inputs and differs if any of them changes.
+    /// Options that don't affect the outcome, like the [rename cache size][Platform::rename_cache_size()], are ignored.
+    pub fn diff_id(&self, other: &Tree<'_>) -> Result<gix_hash::ObjectId, crate::config::diff::algorithm::Error> {
+        let mut hasher = gix_features::hash::hasher(self.lhs.repo.object_hash());
+        hasher.update(&self.to_canonical_encoding(other)?);
+        Ok(gix_hash::ObjectId::from(hasher.digest()))
+    }
+
+    /// Return a canonical encoding of the ids of this tree and `other` along with all options that affect their diff,
+    /// with one `key=value` pair per line and byte strings prefixed with their length.
+    fn to_canonical_encoding(&self, other: &Tree<'_>) -> Result<Vec<u8>, crate::config::diff::algorithm::Error> {
+        fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+            out.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+            out.extend_from_slice(bytes);
+        }
+        let mut out = format!(
+            "tree-diff-v2\nlhs={lhs}\nrhs={rhs}\nalgorithm={algorithm}\ntracking={tracking}\n",
+            lhs = self.lhs.id,
+            rhs = other.id,
+            algorithm = match self.lhs.repo.config.diff_algorithm()? {
+                gix_diff::blob::Algorithm::Histogram => "histogram",
+                gix_diff::blob::Algorithm::Myers => "myers",
+                gix_diff::blob::Algorithm::MyersMinimal => "myers-minimal",
+                gix_diff::blob::Algorithm::Patience => "patience",
+            },
+            tracking = match self.tracking {
+                None => "none",
+                Some(Tracking::FileName) => "filename",
+                Some(Tracking::Path) => "path",
+            },
+        )
+        .into_bytes();
+        if let Some(prefix) = &self.location_prefix {
+            out.extend_from_slice(b"location-prefix=");
+            push_bytes(&mut out, prefix);
+            out.push(b'\n');
+        }
+        if let Some(search) = &self.pathspec {
+            out.extend_from_slice(format!("pathspec.ignore-case={}\n", self.lhs.repo.config.ignore_case).as_bytes());
+            for pattern in search.patterns() {
+                out.extend_from_slice(
+                    format!(
+                        "pathspec={signature}:{mode}:",
+                        signature = pattern.signature.bits(),
+                        mode = match pattern.search_mode {
+                            gix_pathspec::MatchMode::ShellGlob => "shell-glob",
+                            gix_pathspec::MatchMode::Literal => "literal",
+                            gix_pathspec::MatchMode::PathAwareGlob => "path-aware-glob",
+                        }
+                    )
+                    .as_bytes(),
+                );
+                push_bytes(&mut out, &pattern.path);
+                out.push(b'\n');
+            }
+        }
+        match self.renames {
+            None => out.extend_from_slice(b"renames=none\n"),
+            Some(renames) => out.extend_from_slice(renames.to_canonical_string().as_bytes()),
+        }
+        Ok(out)
+    }
 }
 
 ///
 pub mod for_each;
+
+/// A visitor to accumulate the amount of inserted and removed lines per file, similar to `git diff --stat`.
+///
+/// Pass each change to [`record()`][Stats::record()] while [diffing trees][Platform::for_each_to_obtain_tree()],
+/// with [paths being tracked][Platform::track_path()] as files are keyed by their [`location`][Change::location].
+#[derive(Default, Debug, Clone)]
+pub struct Stats {
+    files: HashMap<BString, stats::LineCounts>,
+}
+
+///
+pub mod stats;
+
+mod tracked;
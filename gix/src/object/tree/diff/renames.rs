@@ -1,15 +1,62 @@
+use std::collections::{HashMap, VecDeque};
+
+use gix_diff::blob::intern::Token;
+use gix_hash::ObjectId;
+
+use crate::bstr::BStr;
+
 use crate::config::cache::util::ApplyLeniency;
-use crate::config::tree::Diff;
+use crate::config::tree::{Core, Diff};
 use crate::diff::rename::Tracking;
+use crate::object::blob::diff::{is_binary, WhitespaceMode};
 use crate::object::tree::diff::Renames;
 
 /// The way copies are located.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Copies {
+    /// The set of files to find the sources of copies in.
+    pub source: CopySource,
+    /// The percentage of similarity needed for files to be considered copied, similar to `git diff -C50%`.
+    ///
+    /// It takes precedence over `use_rename_threshold`, and if `None`, which is the default, copies are only found if
+    /// their content matches 100%, i.e. by identity. This allows to combine similarity-based renames with copies which are
+    /// much cheaper to find.
+    pub percentage: Option<f32>,
+    /// If `true` and `percentage` is `None`, copies need the same similarity as renames, as configured by
+    /// [`Renames::percentage`]. This is `false` by default.
+    pub use_rename_threshold: bool,
+}
+
+impl Default for Copies {
+    fn default() -> Self {
+        Copies {
+            source: CopySource::FromSetOfChangedFiles,
+            percentage: None,
+            use_rename_threshold: false,
+        }
+    }
+}
+
+/// The set of files to find the sources of copies in.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Copies {
+pub enum CopySource {
     /// Find copies from the set of changed files only.
     FromSetOfChangedFiles,
 }
 
+/// What to do if a blob needed to compute the similarity of a possible source and destination isn't present in the
+/// object database, as is common in partial clones.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MissingBlob {
+    /// Fail the diff with an error, which is the default.
+    #[default]
+    Error,
+    /// Don't compute the similarity for pairs involving missing blobs, so they can only be matched by identity.
+    ///
+    /// Changes whose blobs are missing are still emitted, just like when no source could be found for them.
+    SkipSimilarity,
+}
+
 /// The error returned by [`Renames::try_from_config()].
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -20,21 +67,68 @@ pub enum Error {
     DiffRenameLimit(#[from] crate::config::unsigned_integer::Error),
 }
 
+/// A destination of a rename or copy which wasn't matched to any source, along with its most similar source which
+/// fell below the [required similarity][Renames::percentage], as collected if
+/// [near misses are tracked][super::Platform::track_rename_near_misses()].
+///
+/// This helps to find a good value for `diff.renameThreshold`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NearMiss<'a> {
+    /// The location of the most similar source, as tracked during the diff.
+    pub source_location: &'a BStr,
+    /// The location of the unmatched destination, as tracked during the diff.
+    pub location: &'a BStr,
+    /// The similarity of source and destination in the range of 0.0 to 1.0, which is always below the required one.
+    pub similarity: f32,
+}
+
+/// The candidates for rename tracking as collected during a diff, as returned by
+/// [`Platform::analyze_renames()`][super::Platform::analyze_renames()].
+///
+/// It helps to decide whether rename tracking is worth it before actually performing it, as it's obtained without
+/// loading any blob.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    /// The amount of added files, which are the possible destinations of renames and copies.
+    pub additions: usize,
+    /// The amount of deleted files, which are the possible sources of renames.
+    pub deletions: usize,
+    /// The amount of modified files, which are possible sources of copies and are only counted if copies are tracked.
+    pub modifications: usize,
+    /// If `true`, there are more pairs of additions and sources than the [limit][Renames::limit] allows, so similarity
+    /// checks would be skipped and only identical files could be matched.
+    ///
+    /// This assumes that no addition is matched by identity, which would reduce the amount of pairs to check,
+    /// so it may be `true` even though similarity checks end up being performed.
+    /// It's always `false` if no similarity checks are configured.
+    pub would_skip_similarity: bool,
+}
+
+/// The default for [`Renames::max_file_size`], which is the default of `core.bigFileThreshold` as well.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 512 * 1024 * 1024;
+
 impl Default for Renames {
     fn default() -> Self {
         Renames {
             copies: None,
             percentage: Some(0.5),
             limit: 1000,
+            directory_renames: None,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            whitespace: WhitespaceMode::Exact,
+            prefer_similar_paths: false,
+            missing_blob: MissingBlob::Error,
+            rename_empty: true,
         }
     }
 }
 
 impl Renames {
     /// Create an instance by reading all relevant information from the `config`uration, while being `lenient` or not.
-    /// Returns `Ok(None)` if nothing is configured.
+    /// Returns `Ok(None)` if rename tracking is disabled.
     ///
-    /// Note that missing values will be defaulted similar to what git does.
+    /// Note that missing values will be defaulted similar to what git does, which also tracks renames if `diff.renames`
+    /// isn't set.
     #[allow(clippy::result_large_err)]
     pub fn try_from_config(config: &gix_config::File<'static>, lenient: bool) -> Result<Option<Self>, Error> {
         let key = "diff.renames";
@@ -47,9 +141,13 @@ impl Renames {
             Some(renames) => match renames {
                 Tracking::Disabled => return Ok(None),
                 Tracking::Renames => None,
-                Tracking::RenamesAndCopies => Some(Copies::FromSetOfChangedFiles),
+                // Like git, which uses the same similarity for copies as for renames.
+                Tracking::RenamesAndCopies => Some(Copies {
+                    use_rename_threshold: true,
+                    ..Default::default()
+                }),
             },
-            None => return Ok(None),
+            None => Self::default().copies,
         };
 
         let default = Self::default();
@@ -61,8 +159,204 @@ impl Renames {
                 .transpose()
                 .with_leniency(lenient)?
                 .unwrap_or(default.limit),
+            max_file_size: config
+                .integer_by_key("core.bigFileThreshold")
+                .map(|value| Core::BIG_FILE_THRESHOLD.try_into_u64(value))
+                .transpose()
+                .with_leniency(lenient)?
+                .or(default.max_file_size),
             ..default
         }
         .into())
     }
+
+    /// Return a canonical encoding of all values that affect the outcome of rename tracking, which is the same for all
+    /// instances that behave the same.
+    ///
+    /// Floating point numbers are normalized so that `-0.0` and `0.0` encode the same, just like all NaNs and
+    /// all percentages greater than 1.0 do.
+    pub(crate) fn to_canonical_string(self) -> String {
+        fn float(value: Option<f32>) -> String {
+            match value {
+                None => "none".into(),
+                Some(value) if value.is_nan() => "nan".into(),
+                // `-0.0` is the same as `0.0`.
+                Some(value) => (if value == 0.0 { 0.0 } else { value }).to_string(),
+            }
+        }
+        fn percentage(value: Option<f32>) -> String {
+            float(value.map(|value| if value > 1.0 { 1.0 } else { value }))
+        }
+        fn option(value: Option<impl std::fmt::Display>) -> String {
+            value.map_or_else(|| "none".into(), |value| value.to_string())
+        }
+        format!(
+            "copies={copies}\n\
+             percentage={percentage}\n\
+             copy-percentage={copy_percentage}\n\
+             limit={limit}\n\
+             directory-renames={directory_renames}\n\
+             max-file-size={max_file_size}\n\
+             whitespace={whitespace}\n\
+             prefer-similar-paths={prefer_similar_paths}\n\
+             missing-blob={missing_blob}\n\
+             rename-empty={rename_empty}\n",
+            copies = match self.copies.map(|copies| copies.source) {
+                None => "none",
+                Some(CopySource::FromSetOfChangedFiles) => "changed-files",
+            },
+            percentage = percentage(self.percentage),
+            copy_percentage = percentage(self.copy_percentage()),
+            limit = self.limit,
+            directory_renames = float(self.directory_renames),
+            max_file_size = option(self.max_file_size),
+            whitespace = match self.whitespace {
+                WhitespaceMode::Exact => "exact",
+                WhitespaceMode::IgnoreAll => "ignore-all",
+                WhitespaceMode::IgnoreChange => "ignore-change",
+                WhitespaceMode::IgnoreBlankLines => "ignore-blank-lines",
+            },
+            prefer_similar_paths = self.prefer_similar_paths,
+            missing_blob = match self.missing_blob {
+                MissingBlob::Error => "error",
+                MissingBlob::SkipSimilarity => "skip-similarity",
+            },
+            rename_empty = self.rename_empty,
+        )
+    }
+
+    /// Return the percentage of similarity needed for files to be considered copied, or `None` if copies are only found
+    /// by identity or not at all.
+    ///
+    /// It's [`Copies::percentage`] if set, or the [rename percentage][Renames::percentage] if
+    /// [`Copies::use_rename_threshold`] is `true`.
+    pub fn copy_percentage(&self) -> Option<f32> {
+        let copies = self.copies?;
+        match copies.percentage {
+            Some(percentage) => Some(percentage),
+            None if copies.use_rename_threshold => self.percentage,
+            None => None,
+        }
+    }
+}
+
+/// Blobs split into lines which are kept across diffs, to avoid loading and tokenizing the same blobs again when
+/// checking for similarity during rename tracking.
+///
+/// This is useful when diffing many pairs of trees in a row, like when walking the history, as renamed files
+/// often are the same blobs as seen in the previous diff. Pass it to the next diff with
+/// [`Platform::rename_token_cache()`][super::Platform::rename_token_cache()] and take it back afterwards with
+/// [`Platform::take_rename_token_cache()`][super::Platform::take_rename_token_cache()].
+///
+/// # Memory usage
+///
+/// At most [`capacity`][TokenCache::new()] blobs are kept, evicting the ones added first. Lines are interned across all
+/// blobs so they can be compared quickly, and as the lines of evicted blobs can't be forgotten individually, the
+/// cache is cleared entirely before a diff once it knows more than twice as many distinct lines as the kept blobs
+/// contain. This trades memory proportional to the size of the kept blobs for not having to load and tokenize them
+/// again, which only pays off if the same blobs are compared in multiple diffs.
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    capacity: usize,
+    /// The token of each distinct line.
+    lines: HashMap<Vec<u8>, Token>,
+    /// The line tokens of each blob, or `None` if it is binary.
+    blobs: HashMap<ObjectId, Option<Vec<Token>>>,
+    /// The ids of all `blobs` in the order they were added.
+    order: VecDeque<ObjectId>,
+    /// The amount of tokens in all `blobs`.
+    num_kept_tokens: usize,
+    /// The way whitespace was treated when tokenizing lines.
+    whitespace: WhitespaceMode,
+}
+
+/// Lifecycle
+impl TokenCache {
+    /// Create a new instance to keep the blobs of the last `pairs` diffs, assuming that each diff compares at most
+    /// as many blobs as the rename `limit` allows for each side, like [`Renames::limit`].
+    /// A `limit` of 0 is taken as the default limit instead of being unlimited.
+    pub fn new(limit: usize, pairs: usize) -> Self {
+        let limit = if limit == 0 { Renames::default().limit } else { limit };
+        Self::with_capacity(limit.saturating_mul(2).saturating_mul(pairs))
+    }
+
+    /// Create a new instance which keeps at most `capacity` blobs, but always the last one that was added.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        TokenCache {
+            capacity,
+            lines: HashMap::new(),
+            blobs: HashMap::new(),
+            order: VecDeque::new(),
+            num_kept_tokens: 0,
+            whitespace: WhitespaceMode::default(),
+        }
+    }
+}
+
+impl TokenCache {
+    /// Return `None` if the blob with `id` isn't kept, or `Some(None)` if it is binary.
+    pub(crate) fn tokens(&self, id: &gix_hash::oid) -> Option<Option<&[Token]>> {
+        self.blobs.get(id).map(|tokens| tokens.as_deref())
+    }
+
+    /// Keep the lines of `data`, the blob with `id`, evicting the oldest blobs if there are too many.
+    /// The blob is kept even if this cache has no capacity, to make it available until the next blob is added.
+    pub(crate) fn insert(&mut self, id: &gix_hash::oid, data: &[u8]) {
+        if self.blobs.contains_key(id) {
+            return;
+        }
+        let tokens = (!is_binary(data)).then(|| {
+            self.whitespace
+                .similarity_lines(data)
+                .map(|line| match self.lines.get(line.as_ref()) {
+                    Some(token) => *token,
+                    None => {
+                        let token = Token(self.lines.len() as u32);
+                        self.lines.insert(line.into_owned(), token);
+                        token
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+        self.num_kept_tokens += tokens.as_ref().map_or(0, Vec::len);
+        self.blobs.insert(id.to_owned(), tokens);
+        self.order.push_back(id.to_owned());
+        while self.order.len() > self.capacity.max(1) {
+            let oldest = self.order.pop_front().expect("not empty");
+            if let Some(Some(tokens)) = self.blobs.remove(&oldest) {
+                self.num_kept_tokens -= tokens.len();
+            }
+        }
+    }
+
+    /// Return the amount of distinct lines in all blobs seen so far.
+    pub(crate) fn num_tokens(&self) -> u32 {
+        self.lines.len() as u32
+    }
+
+    /// Tokenize lines as determined by `whitespace` from now on, and clear everything if they were tokenized differently
+    /// so far.
+    pub(crate) fn set_whitespace_mode(&mut self, whitespace: WhitespaceMode) {
+        if self.whitespace != whitespace {
+            self.whitespace = whitespace;
+            self.clear();
+        }
+    }
+
+    /// Clear everything if there are more than twice as many distinct lines as there are lines in all kept blobs.
+    ///
+    /// This must not be called while tokens obtained from this instance are still in use.
+    pub(crate) fn trim(&mut self) {
+        if self.lines.len() > 2 * self.num_kept_tokens {
+            self.clear();
+        }
+    }
+
+    /// Forget all blobs and their lines.
+    pub(crate) fn clear(&mut self) {
+        self.lines.clear();
+        self.blobs.clear();
+        self.order.clear();
+        self.num_kept_tokens = 0;
+    }
 }
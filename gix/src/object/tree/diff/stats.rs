@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use gix_object::tree::EntryMode;
+
+use super::{change::Event, Action, Change, Stats};
+use crate::{
+    bstr::{BString, ByteSlice},
+    object::blob::diff::{is_binary, Platform},
+};
+
+/// The error returned by [`Stats::record()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Init(#[from] crate::object::blob::diff::init::Error),
+    #[error("Could not find the added or deleted blob")]
+    FindExisting(#[from] crate::object::find::existing::Error),
+}
+
+/// The amount of lines inserted and removed in a single file.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineCounts {
+    /// The amount of lines that were added.
+    pub insertions: u32,
+    /// The amount of lines that were removed.
+    pub removals: u32,
+}
+
+/// Lifecycle
+impl Stats {
+    /// Record `change`, loading and diffing the blobs it involves, and return [`Action::Continue`] to keep going.
+    ///
+    /// This is meant to be called with each change passed to [`for_each_to_obtain_tree()`][super::Platform::for_each_to_obtain_tree()].
+    ///
+    /// * Additions and deletions count all lines of the added or deleted blob.
+    /// * [Renames][Event::Rename] and [copies][Event::Copy] are recorded only once, under their destination, and count the
+    ///   lines changed between source and destination, which is nothing if they have the same id.
+    /// * Trees, submodules and [directory renames][Event::DirectoryRename] are ignored.
+    /// * Binary files are recorded without any inserted or removed lines.
+    pub fn record(&mut self, change: Change<'_, '_, '_>) -> Result<Action, Error> {
+        let counts = match change.event {
+            Event::Addition { entry_mode, id } if is_blob(entry_mode) => LineCounts {
+                insertions: count_lines(&id.object()?.data),
+                removals: 0,
+            },
+            Event::Deletion { entry_mode, id } if is_blob(entry_mode) => LineCounts {
+                insertions: 0,
+                removals: count_lines(&id.object()?.data),
+            },
+            Event::Modification {
+                previous_entry_mode,
+                previous_id: source_id,
+                entry_mode,
+                id,
+            }
+            | Event::Rename {
+                source_entry_mode: previous_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                ..
+            }
+            | Event::Copy {
+                source_entry_mode: previous_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                ..
+            } if is_blob(previous_entry_mode) && is_blob(entry_mode) => {
+                if source_id == id {
+                    LineCounts::default()
+                } else {
+                    let diff = Platform::from_ids(&source_id, &id)?;
+                    if diff.is_binary() {
+                        LineCounts::default()
+                    } else {
+                        let counter = diff.line_counts();
+                        LineCounts {
+                            insertions: counter.insertions,
+                            removals: counter.removals,
+                        }
+                    }
+                }
+            }
+            _ => return Ok(Action::Continue),
+        };
+        let file = self.files.entry(change.location.to_owned()).or_default();
+        file.insertions += counts.insertions;
+        file.removals += counts.removals;
+        Ok(Action::Continue)
+    }
+}
+
+/// Access
+impl Stats {
+    /// Return the amount of inserted and removed lines for each recorded file, keyed by its location.
+    pub fn files(&self) -> &HashMap<BString, LineCounts> {
+        &self.files
+    }
+
+    /// Return the amount of lines inserted into all files.
+    pub fn total_insertions(&self) -> u64 {
+        self.files.values().map(|counts| u64::from(counts.insertions)).sum()
+    }
+
+    /// Return the amount of lines removed from all files.
+    pub fn total_removals(&self) -> u64 {
+        self.files.values().map(|counts| u64::from(counts.removals)).sum()
+    }
+}
+
+fn is_blob(mode: EntryMode) -> bool {
+    matches!(mode, EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link)
+}
+
+fn count_lines(data: &[u8]) -> u32 {
+    if is_binary(data) {
+        0
+    } else {
+        data.lines().count() as u32
+    }
+}
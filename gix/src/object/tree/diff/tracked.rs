@@ -0,0 +1,2213 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use gix_diff::tree::visit::Change;
+use gix_object::tree::EntryMode;
+use gix_pack::cache::Object;
+
+use crate::{
+    bstr::{BStr, ByteSlice},
+    object::{
+        blob::diff::WhitespaceMode,
+        tree::diff::{
+            renames::{Analysis, TokenCache},
+            Renames,
+        },
+    },
+};
+
+/// A set of tracked items which are collected during traversal to be processed once all changes are known.
+///
+/// Only additions and deletions of non-tree entries are held back for rename tracking, along with modifications
+/// if copies should be found as well. All other changes pass right through.
+///
+/// Changes are [pushed][State::try_push_change()] during a diff and [emitted][State::emit()] once it's done.
+/// To reuse the same instance and its allocations for another diff, call [`clear()`][State::clear()] afterwards.
+#[derive(Clone)]
+pub(crate) struct State {
+    items: Vec<Item>,
+    path_backing: Vec<u8>,
+    renames: Renames,
+    algorithm: gix_diff::blob::Algorithm,
+    /// The amount of bytes to use for caching blobs during similarity checks, or `0` to not cache them.
+    cache_size: usize,
+    /// Pairs of ranges into `path_backing` for the source and destination directories of renamed directories.
+    directory_renames: Vec<(Range<usize>, Range<usize>)>,
+    /// Tokenized blobs kept across diffs, used instead of loading blobs for similarity checks if set.
+    token_cache: Option<TokenCache>,
+    /// If `Some`, the indices of the most similar source and of each unmatched destination along with their similarity,
+    /// for all destinations whose most similar source fell below the required similarity.
+    near_misses: Option<Vec<(usize, usize, f32)>>,
+}
+
+#[derive(Clone)]
+struct Item {
+    /// The range into `path_backing` which contains the location of this change.
+    location: Range<usize>,
+    /// The change itself.
+    change: Change,
+    /// If `true`, this change was already passed on and may only serve as source for copies.
+    emitted: bool,
+}
+
+impl Item {
+    fn location<'a>(&self, backing: &'a [u8]) -> &'a BStr {
+        backing[self.location.clone()].as_ref()
+    }
+
+    /// Return the mode and id of this item as it can be seen as source of a rename or copy, or `None` if it can't be one.
+    fn as_source(&self) -> Option<(EntryMode, &gix_hash::oid)> {
+        match &self.change {
+            Change::Addition { .. } => None,
+            Change::Deletion { entry_mode, oid } => Some((*entry_mode, oid)),
+            Change::Modification {
+                previous_entry_mode,
+                previous_oid,
+                ..
+            } => Some((*previous_entry_mode, previous_oid)),
+        }
+    }
+
+    fn is_destination(&self) -> bool {
+        matches!(self.change, Change::Addition { .. })
+    }
+}
+
+pub(crate) mod visit {
+    use gix_object::tree::EntryMode;
+
+    use crate::bstr::BStr;
+
+    /// The kind of relation between a source and its destination.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum SourceKind {
+        /// The source was removed and the destination is where it moved to.
+        Rename,
+        /// The source still exists and the destination is a copy of it.
+        Copy,
+    }
+
+    impl SourceKind {
+        /// Return `true` if `change` can be the source of this kind of relation.
+        ///
+        /// Only deleted files can be renamed, while copies can be made of files that still exist, which are the
+        /// modified ones, as well as of deleted files as they existed before the change.
+        pub fn can_use_change(&self, change: &gix_diff::tree::visit::Change) -> bool {
+            use gix_diff::tree::visit::Change::*;
+            match self {
+                SourceKind::Rename => matches!(change, Deletion { .. }),
+                SourceKind::Copy => matches!(change, Deletion { .. } | Modification { .. }),
+            }
+        }
+
+        /// Return `true` if a source can't be used for other renames once it was used for this kind of relation,
+        /// which is the case for renames as a file can only move to one location, but not for copies.
+        pub fn consumes_source(&self) -> bool {
+            matches!(self, SourceKind::Rename)
+        }
+    }
+
+    /// The source of a rename or copy.
+    pub struct Source<'a> {
+        /// The mode of the source.
+        pub entry_mode: EntryMode,
+        /// The object id of the source.
+        pub id: &'a gix_hash::oid,
+        /// How the source relates to the destination.
+        pub kind: SourceKind,
+        /// The location of the source, which is empty if no location is tracked.
+        pub location: &'a BStr,
+    }
+
+    /// A change along with its location, which is the destination of a rename or copy if a [`Source`] is present.
+    pub struct Destination<'a> {
+        /// The change at the destination.
+        pub change: gix_diff::tree::visit::Change,
+        /// The location of the destination, which is empty if no location is tracked.
+        pub location: &'a BStr,
+    }
+}
+
+/// Lifecycle
+impl State {
+    pub(crate) fn new(renames: Renames, algorithm: gix_diff::blob::Algorithm, cache_size: usize) -> Self {
+        State {
+            items: Vec::new(),
+            path_backing: Vec::new(),
+            renames,
+            algorithm,
+            cache_size,
+            directory_renames: Vec::new(),
+            token_cache: None,
+            near_misses: None,
+        }
+    }
+
+    /// If `toggle` is `true`, collect the most similar source of destinations which couldn't be matched
+    /// to make them available via [`near_misses()`][Self::near_misses()].
+    pub(crate) fn collect_near_misses(&mut self, toggle: bool) {
+        match (toggle, &mut self.near_misses) {
+            (true, None) => self.near_misses = Some(Vec::new()),
+            (true, Some(_)) => {}
+            (false, _) => self.near_misses = None,
+        }
+    }
+
+    /// Use `cache` to keep tokenized blobs across diffs, or don't keep them if `None`.
+    pub(crate) fn set_token_cache(&mut self, cache: Option<TokenCache>) {
+        self.token_cache = cache;
+    }
+
+    /// Take the token cache previously set with [`set_token_cache()`][Self::set_token_cache()], if any.
+    pub(crate) fn take_token_cache(&mut self) -> Option<TokenCache> {
+        self.token_cache.take()
+    }
+
+    /// Remove all tracked items to prepare for another diff, while keeping allocated memory.
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+        self.path_backing.clear();
+        self.directory_renames.clear();
+        if let Some(near_misses) = self.near_misses.as_mut() {
+            near_misses.clear();
+        }
+    }
+}
+
+/// Preparation
+impl State {
+    /// Keep `change` at `location` for later rename tracking, or return it if it can't participate and should
+    /// be passed on right away.
+    ///
+    /// Modifications are always returned, but may still be kept as possible source for copies.
+    pub(crate) fn try_push_change(&mut self, change: Change, location: &BStr) -> Option<Change> {
+        if !is_trackable(&change, &self.renames) {
+            return Some(change);
+        }
+        let location = self.push_location(location);
+        if let Change::Modification { .. } = change {
+            self.items.push(Item {
+                location,
+                change: change.clone(),
+                emitted: true,
+            });
+            return Some(change);
+        }
+        self.items.push(Item {
+            location,
+            change,
+            emitted: false,
+        });
+        None
+    }
+
+    fn push_location(&mut self, location: &BStr) -> Range<usize> {
+        let start = self.path_backing.len();
+        self.path_backing.extend_from_slice(location);
+        start..self.path_backing.len()
+    }
+}
+
+/// Return `true` if `change` can be the source or destination of a rename or copy as configured by `renames`.
+///
+/// Changes to trees never are, as only their entries are tracked, and modifications can only be sources of copies.
+/// Empty blobs are only tracked if [`Renames::rename_empty`] is set.
+fn is_trackable(change: &Change, renames: &Renames) -> bool {
+    let is_trackable_blob =
+        |mode: &EntryMode, oid: &gix_hash::ObjectId| !mode.is_tree() && (renames.rename_empty || !oid.is_empty_blob());
+    match change {
+        Change::Addition { entry_mode, oid } | Change::Deletion { entry_mode, oid } => {
+            is_trackable_blob(entry_mode, oid)
+        }
+        Change::Modification {
+            previous_entry_mode,
+            previous_oid,
+            ..
+        } => renames.copies.is_some() && is_trackable_blob(previous_entry_mode, previous_oid),
+    }
+}
+
+/// Processing
+impl State {
+    /// Find renames and copies among all tracked items and call `cb` for each of them in the order they were
+    /// pushed, along with their source if one was found.
+    /// Deletions which turned out to be the source of a rename are not passed to `cb`.
+    ///
+    /// `find` is used to obtain blob data for similarity checks, which only happens if no source with an identical
+    /// id could be found. Sources are kept in a cache which never uses more than the configured amount of bytes,
+    /// evicting the least recently used blobs first.
+    /// Note that identical ids are matched without loading any object.
+    /// `size_of` is used to obtain the size of blobs before loading them, to match blobs larger than the configured
+    /// [maximum file size][Renames::max_file_size] only by identity.
+    /// If `find` or `size_of` return `None`, the blob isn't available and can only be matched by identity, which allows
+    /// callers to decide whether missing blobs are an error or not.
+    /// If a [token cache][Self::set_token_cache()] is set, blobs it already knows aren't loaded at all.
+    ///
+    /// If directory renames should be tracked, renames are clustered by their source and destination directories
+    /// afterwards to make renamed directories available via [`directory_renames()`][Self::directory_renames()].
+    /// Returns [`Cancel`][gix_diff::tree::visit::Action::Cancel] if `cb` cancelled the operation.
+    pub(crate) fn emit<FindFn, E>(
+        &mut self,
+        mut cb: impl FnMut(visit::Destination<'_>, Option<visit::Source<'_>>) -> gix_diff::tree::visit::Action,
+        mut find: FindFn,
+        mut size_of: impl FnMut(&gix_hash::oid) -> Result<Option<u64>, E>,
+    ) -> Result<gix_diff::tree::visit::Action, E>
+    where
+        FindFn: for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Result<Option<gix_object::BlobRef<'b>>, E>,
+    {
+        let mut token_cache = self.token_cache.take();
+        if let Some(token_cache) = token_cache.as_mut() {
+            token_cache.set_whitespace_mode(self.renames.whitespace);
+            token_cache.trim();
+        }
+        let mut near_misses = self.near_misses.take();
+        let matches = self.find_matches(&mut find, &mut size_of, &mut token_cache, &mut near_misses);
+        self.token_cache = token_cache;
+        self.near_misses = near_misses;
+        let (matches, used_for_rename) = matches?;
+
+        for (idx, item) in self.items.iter().enumerate() {
+            if item.emitted || used_for_rename[idx] {
+                continue;
+            }
+            let source = matches[idx].map(|(src_idx, kind)| {
+                let src = &self.items[src_idx];
+                let (entry_mode, id) = src.as_source().expect("matches are always sources");
+                visit::Source {
+                    entry_mode,
+                    id,
+                    kind,
+                    location: src.location(&self.path_backing),
+                }
+            });
+            let destination = visit::Destination {
+                change: item.change.clone(),
+                location: item.location(&self.path_backing),
+            };
+            if cb(destination, source).cancelled() {
+                return Ok(gix_diff::tree::visit::Action::Cancel);
+            }
+        }
+
+        if let Some(fraction) = self.renames.directory_renames {
+            self.directory_renames = self.cluster_directory_renames(&matches, fraction);
+        }
+        Ok(gix_diff::tree::visit::Action::Continue)
+    }
+
+    /// Find the source of each destination, first by identity and then by similarity if configured, and return
+    /// the source of each item along with whether it was used as source of a rename.
+    ///
+    /// If `near_misses` is set, destinations without source are added to it along with their most similar source.
+    #[allow(clippy::type_complexity)]
+    fn find_matches<FindFn, E>(
+        &self,
+        find: &mut FindFn,
+        size_of: &mut impl FnMut(&gix_hash::oid) -> Result<Option<u64>, E>,
+        token_cache: &mut Option<TokenCache>,
+        near_misses: &mut Option<Vec<(usize, usize, f32)>>,
+    ) -> Result<(Vec<Option<(usize, visit::SourceKind)>>, Vec<bool>), E>
+    where
+        FindFn: for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Result<Option<gix_object::BlobRef<'b>>, E>,
+    {
+        let mut sources: Vec<usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| item.as_source().map(|_| idx))
+            .collect();
+        // Ordering by id allows to find identical sources quickly, and the location makes the choice among equally
+        // good sources deterministic, as the first one is chosen.
+        sources.sort_by(|a, b| {
+            let (a, b) = (&self.items[*a], &self.items[*b]);
+            let a_id = a.as_source().expect("only sources").1;
+            let b_id = b.as_source().expect("only sources").1;
+            a_id.cmp(b_id)
+                .then_with(|| a.location(&self.path_backing).cmp(b.location(&self.path_backing)))
+        });
+
+        let mut matches: Vec<Option<(usize, visit::SourceKind)>> = vec![None; self.items.len()];
+        let mut used_for_rename = vec![false; self.items.len()];
+        let mut destinations: Vec<usize> = (0..self.items.len())
+            .filter(|idx| self.items[*idx].is_destination())
+            .collect();
+        // Sources used for a rename can't be used again, so destinations are matched in the order of their location
+        // to make the outcome independent of the order in which changes were pushed.
+        destinations.sort_by(|a, b| {
+            self.items[*a]
+                .location(&self.path_backing)
+                .cmp(self.items[*b].location(&self.path_backing))
+        });
+        let mut scratch_tokens;
+        let (tokens, tokens_are_kept) = match token_cache.as_mut() {
+            Some(token_cache) => (token_cache, true),
+            None => {
+                scratch_tokens = TokenCache::with_capacity(0);
+                scratch_tokens.set_whitespace_mode(self.renames.whitespace);
+                (&mut scratch_tokens, false)
+            }
+        };
+        let mut lines = Lines {
+            find,
+            size_of,
+            sizes: Default::default(),
+            max_file_size: self.renames.max_file_size,
+            blob_cache: None,
+            tokens,
+            tokens_are_kept,
+            buf: Vec::new(),
+        };
+        for dest_idx in destinations.iter().copied() {
+            matches[dest_idx] = self.find_match(dest_idx, &sources, &used_for_rename, None, &mut lines, &mut None)?;
+            if let Some((src_idx, kind)) = matches[dest_idx] {
+                used_for_rename[src_idx] |= kind.consumes_source();
+            }
+        }
+
+        let percentages = self.percentages();
+        if percentages.needs_similarity() {
+            let unmatched_destinations = destinations.iter().filter(|idx| matches[**idx].is_none()).count();
+            if !self.exceeds_limit(unmatched_destinations, sources.len()) {
+                lines.blob_cache = (self.cache_size != 0 && !lines.tokens_are_kept)
+                    .then(|| gix_pack::cache::object::MemoryCappedHashmap::new(self.cache_size));
+                for dest_idx in destinations.iter().copied() {
+                    if matches[dest_idx].is_some() {
+                        continue;
+                    }
+                    let mut near_miss = None;
+                    matches[dest_idx] = self.find_match(
+                        dest_idx,
+                        &sources,
+                        &used_for_rename,
+                        Some(percentages),
+                        &mut lines,
+                        &mut near_miss,
+                    )?;
+                    match matches[dest_idx] {
+                        Some((src_idx, kind)) => used_for_rename[src_idx] |= kind.consumes_source(),
+                        None => {
+                            if let (Some(near_misses), Some((src_idx, similarity))) = (near_misses.as_mut(), near_miss)
+                            {
+                                near_misses.push((src_idx, dest_idx, similarity));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(near_misses) = near_misses.as_mut() {
+            near_misses.sort_by_key(|(_, dest_idx, _)| *dest_idx);
+        }
+        Ok((matches, used_for_rename))
+    }
+
+    /// Return the amount of tracked items by kind and whether similarity checks would be skipped because of the
+    /// [limit][Renames::limit], without loading any object.
+    pub(crate) fn analyze(&self) -> Analysis {
+        let mut analysis = Analysis::default();
+        for item in &self.items {
+            match item.change {
+                Change::Addition { .. } => analysis.additions += 1,
+                Change::Deletion { .. } => analysis.deletions += 1,
+                Change::Modification { .. } => analysis.modifications += 1,
+            }
+        }
+        analysis.would_skip_similarity = self.percentages().needs_similarity()
+            && self.exceeds_limit(analysis.additions, analysis.deletions + analysis.modifications);
+        analysis
+    }
+
+    /// Return the similarity needed for sources to be renamed or copied, without the ones that only identity can satisfy.
+    fn percentages(&self) -> Percentages {
+        // Blobs which are 100% similar are identical, unless they only differ in ignored whitespace.
+        let needs_similarity = |percentage: &f32| *percentage < 1.0 || self.renames.whitespace != WhitespaceMode::Exact;
+        Percentages {
+            rename: self.renames.percentage.filter(needs_similarity),
+            copy: self.renames.copy_percentage().filter(needs_similarity),
+        }
+    }
+
+    /// Return the locations of the most similar source and of each destination which couldn't be matched along with their
+    /// similarity, in the order the destinations were pushed, once [`emit()`][Self::emit()] was called.
+    ///
+    /// It's always empty unless [near misses are collected][Self::collect_near_misses()].
+    pub(crate) fn near_misses(&self) -> impl Iterator<Item = (&BStr, &BStr, f32)> {
+        self.near_misses
+            .iter()
+            .flatten()
+            .map(|(src_idx, dest_idx, similarity)| {
+                (
+                    self.items[*src_idx].location(&self.path_backing),
+                    self.items[*dest_idx].location(&self.path_backing),
+                    *similarity,
+                )
+            })
+    }
+
+    /// Return the locations of the source and destination directories of all renamed directories, ordered by
+    /// their source, once [`emit()`][Self::emit()] was called.
+    pub(crate) fn directory_renames(&self) -> impl Iterator<Item = (&BStr, &BStr)> {
+        self.directory_renames.iter().map(|(source, destination)| {
+            (
+                self.path_backing[source.clone()].as_ref(),
+                self.path_backing[destination.clone()].as_ref(),
+            )
+        })
+    }
+
+    /// Group all renames in `matches` by their source and destination directory, and return the directories
+    /// into which at least `fraction` of the files removed from their source directory were renamed.
+    fn cluster_directory_renames(
+        &self,
+        matches: &[Option<(usize, visit::SourceKind)>],
+        fraction: f32,
+    ) -> Vec<(Range<usize>, Range<usize>)> {
+        let parent = |idx: usize| {
+            let location = &self.items[idx].location;
+            let len = self.path_backing[location.clone()].rfind_byte(b'/').unwrap_or(0);
+            location.start..location.start + len
+        };
+        let dir_of = |range: &Range<usize>| &self.path_backing[range.clone()];
+
+        let mut removed_per_directory = BTreeMap::<&[u8], usize>::new();
+        for (idx, item) in self.items.iter().enumerate() {
+            if let Change::Deletion { .. } = item.change {
+                *removed_per_directory.entry(dir_of(&parent(idx))).or_default() += 1;
+            }
+        }
+
+        let mut renames_per_directory_pair = BTreeMap::<(&[u8], &[u8]), (Range<usize>, Range<usize>, usize)>::new();
+        for (dest_idx, source) in matches.iter().enumerate() {
+            let src_idx = match source {
+                Some((src_idx, visit::SourceKind::Rename)) => *src_idx,
+                _ => continue,
+            };
+            let (source_dir, dest_dir) = (parent(src_idx), parent(dest_idx));
+            if source_dir.is_empty() || dir_of(&source_dir) == dir_of(&dest_dir) {
+                continue;
+            }
+            renames_per_directory_pair
+                .entry((dir_of(&source_dir), dir_of(&dest_dir)))
+                .or_insert_with(|| (source_dir.clone(), dest_dir.clone(), 0))
+                .2 += 1;
+        }
+
+        renames_per_directory_pair
+            .into_iter()
+            .filter(|((source_dir, _), (_, _, renamed))| {
+                *renamed as f32 / removed_per_directory[source_dir] as f32 >= fraction
+            })
+            .map(|(_, (source_dir, dest_dir, _))| (source_dir, dest_dir))
+            .collect()
+    }
+
+    /// Return `true` if there are too many pairs of `num_destinations` and `num_sources` to compare their content.
+    fn exceeds_limit(&self, num_destinations: usize, num_sources: usize) -> bool {
+        self.renames.limit != 0
+            && num_destinations.saturating_mul(num_sources) > self.renames.limit.saturating_mul(self.renames.limit)
+    }
+
+    /// Find the best source among `sources` for the destination at `dest_idx`, without using sources that
+    /// are already `used_for_rename` as rename source.
+    ///
+    /// If `percentages` is `None`, only sources with the same id are considered which never loads any object.
+    /// Otherwise, the source with the highest similarity of at least the percentage for its kind is chosen, with the
+    /// lines of blobs obtained from `lines`. Sources of a kind without percentage aren't considered then.
+    /// No object is loaded if there is no viable source, for instance because all deletions were already used by
+    /// identity renames.
+    /// Binary blobs and submodules are never similar to anything, so only sources with the same id can match them.
+    /// The same is true for blobs that `lines` [can't provide][Lines::load()].
+    /// Symbolic links and submodules are only matched with entries of the same kind.
+    ///
+    /// `near_miss` is set to the most similar source which didn't reach its percentage, if it has any similarity at all.
+    fn find_match<FindFn, SizeFn, E>(
+        &self,
+        dest_idx: usize,
+        sources: &[usize],
+        used_for_rename: &[bool],
+        percentages: Option<Percentages>,
+        lines: &mut Lines<'_, FindFn, SizeFn>,
+        near_miss: &mut Option<(usize, f32)>,
+    ) -> Result<Option<(usize, visit::SourceKind)>, E>
+    where
+        FindFn: for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Result<Option<gix_object::BlobRef<'b>>, E>,
+        SizeFn: FnMut(&gix_hash::oid) -> Result<Option<u64>, E>,
+    {
+        let (dest_mode, dest_id) = match &self.items[dest_idx].change {
+            Change::Addition { entry_mode, oid } => (*entry_mode, oid.as_ref()),
+            _ => unreachable!("BUG: only additions are destinations"),
+        };
+        let kind_of = |src_idx: usize| -> Option<visit::SourceKind> {
+            let src = &self.items[src_idx];
+            let (src_mode, _) = src.as_source()?;
+            if src_mode.is_symlink() != dest_mode.is_symlink() || src_mode.is_commit() != dest_mode.is_commit() {
+                return None;
+            }
+            if visit::SourceKind::Rename.can_use_change(&src.change) && !used_for_rename[src_idx] {
+                Some(visit::SourceKind::Rename)
+            } else if self.renames.copies.is_some() && visit::SourceKind::Copy.can_use_change(&src.change) {
+                Some(visit::SourceKind::Copy)
+            } else {
+                None
+            }
+        };
+
+        // Submodule commits usually aren't available to compare their content.
+        let percentages = match percentages.filter(|_| !dest_mode.is_commit()) {
+            None => {
+                let id_of = |src_idx: &usize| self.items[*src_idx].as_source().expect("only sources").1;
+                let first = sources.partition_point(|src_idx| id_of(src_idx) < dest_id);
+                let candidates = sources[first..]
+                    .iter()
+                    .take_while(|src_idx| id_of(src_idx) == dest_id)
+                    .filter_map(|src_idx| kind_of(*src_idx).map(|kind| (*src_idx, kind)));
+                return Ok(self.best_of(dest_idx, candidates));
+            }
+            Some(percentages) => percentages,
+        };
+        let kind_of = |src_idx: usize| kind_of(src_idx).filter(|kind| percentages.of(*kind).is_some());
+
+        if !sources.iter().any(|src_idx| kind_of(*src_idx).is_some()) {
+            return Ok(None);
+        }
+        let mut best: Option<(usize, visit::SourceKind, f32)> = None;
+        let mut consider = |src_idx: usize, kind: visit::SourceKind, similarity: f32| {
+            if similarity < percentages.of(kind).expect("only kinds with percentage are considered") {
+                if similarity > 0.0 && near_miss.map_or(true, |(_, best_similarity)| similarity > best_similarity) {
+                    *near_miss = Some((src_idx, similarity));
+                }
+            } else if best.map_or(true, |(best_idx, best_kind, best_similarity)| {
+                similarity > best_similarity
+                    || (similarity == best_similarity
+                        && self.is_preferred(dest_idx, (src_idx, kind), (best_idx, best_kind)))
+            }) {
+                best = Some((src_idx, kind, similarity));
+            }
+        };
+        lines.forget_unkept_tokens();
+        if !lines.load(dest_id)? {
+            return Ok(None);
+        }
+        let dest_tokens = match lines.tokens.tokens(dest_id).expect("just loaded") {
+            Some(tokens) => tokens.to_owned(),
+            None => return Ok(None),
+        };
+        for src_idx in sources.iter().copied() {
+            let kind = match kind_of(src_idx) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let src_id = self.items[src_idx].as_source().expect("only sources").1;
+            if !lines.load(src_id)? {
+                continue;
+            }
+            if let Some(src_tokens) = lines.tokens.tokens(src_id).expect("just loaded") {
+                consider(
+                    src_idx,
+                    kind,
+                    similarity_of_tokens(self.algorithm, src_tokens, &dest_tokens, lines.tokens.num_tokens()),
+                );
+            }
+        }
+        Ok(best.map(|(src_idx, kind, _)| (src_idx, kind)))
+    }
+}
+
+/// Provides the lines of blobs as tokens for similarity checks, and loads blobs only if their tokens aren't known yet.
+struct Lines<'a, FindFn, SizeFn> {
+    /// Obtains blob data, or `None` if the blob is missing.
+    find: &'a mut FindFn,
+    /// Obtains the size of blobs before loading them, or `None` if the blob is missing.
+    size_of: &'a mut SizeFn,
+    /// The sizes obtained by `size_of` so far, as the same blob is typically checked for many pairs.
+    sizes: gix_hashtable::HashMap<gix_hash::ObjectId, Option<u64>>,
+    /// Blobs larger than this are never loaded.
+    max_file_size: Option<u64>,
+    /// The data of loaded blobs, to avoid loading them again if `tokens` doesn't keep them.
+    blob_cache: Option<gix_pack::cache::object::MemoryCappedHashmap>,
+    /// The tokens of the blobs loaded so far.
+    tokens: &'a mut TokenCache,
+    /// If `true`, `tokens` are kept across destinations and diffs, otherwise they are only kept for one destination.
+    tokens_are_kept: bool,
+    buf: Vec<u8>,
+}
+
+impl<'a, FindFn, SizeFn, E> Lines<'a, FindFn, SizeFn>
+where
+    FindFn: for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Result<Option<gix_object::BlobRef<'b>>, E>,
+    SizeFn: FnMut(&gix_hash::oid) -> Result<Option<u64>, E>,
+{
+    /// Make the tokens of the blob with `id` available in `tokens`, or return `false` if it can't be loaded
+    /// as it's missing or larger than the maximum file size.
+    fn load(&mut self, id: &gix_hash::oid) -> Result<bool, E> {
+        if self.tokens.tokens(id).is_some() {
+            return Ok(true);
+        }
+        if let Some(cache) = self.blob_cache.as_mut() {
+            if cache.get(&id.to_owned(), &mut self.buf).is_some() {
+                self.tokens.insert(id, &self.buf);
+                return Ok(true);
+            }
+        }
+        if let Some(max_file_size) = self.max_file_size {
+            let size = match self.sizes.get(id) {
+                Some(size) => *size,
+                None => {
+                    let size = (self.size_of)(id)?;
+                    self.sizes.insert(id.to_owned(), size);
+                    size
+                }
+            };
+            if size.map_or(true, |size| size > max_file_size) {
+                return Ok(false);
+            }
+        }
+        let data = match (self.find)(id, &mut self.buf)? {
+            Some(blob) => blob.data,
+            None => return Ok(false),
+        };
+        if let Some(cache) = self.blob_cache.as_mut() {
+            cache.put(id.to_owned(), gix_object::Kind::Blob, data);
+        }
+        self.tokens.insert(id, data);
+        Ok(true)
+    }
+
+    /// Forget all tokens unless they are kept across destinations, as the lines of all blobs would accumulate otherwise.
+    fn forget_unkept_tokens(&mut self) {
+        if !self.tokens_are_kept {
+            self.tokens.clear();
+        }
+    }
+}
+
+/// The similarity needed for sources to be considered renamed or copied, with `None` meaning only identity can match them.
+#[derive(Clone, Copy)]
+struct Percentages {
+    rename: Option<f32>,
+    copy: Option<f32>,
+}
+
+impl Percentages {
+    fn of(&self, kind: visit::SourceKind) -> Option<f32> {
+        match kind {
+            visit::SourceKind::Rename => self.rename,
+            visit::SourceKind::Copy => self.copy,
+        }
+    }
+
+    fn needs_similarity(&self) -> bool {
+        self.rename.is_some() || self.copy.is_some()
+    }
+}
+
+/// Tie-breaking
+impl State {
+    /// Among equally good `candidates` for the destination at `dest_idx`, use the [preferred][Self::is_preferred()] one,
+    /// or the first one if none is preferred.
+    fn best_of(
+        &self,
+        dest_idx: usize,
+        candidates: impl Iterator<Item = (usize, visit::SourceKind)>,
+    ) -> Option<(usize, visit::SourceKind)> {
+        let mut best = None;
+        for candidate in candidates {
+            match best {
+                Some(best) if !self.is_preferred(dest_idx, candidate, best) => {}
+                _ => best = Some(candidate),
+            }
+        }
+        best
+    }
+
+    /// Return `true` if the source `candidate` is preferable to `other` for the destination at `dest_idx`, as both
+    /// are equally similar to it.
+    ///
+    /// Renames are preferred over copies, and if [enabled][Renames::prefer_similar_paths], sources with a more
+    /// [similar path][path_similarity()] are preferred after that.
+    fn is_preferred(
+        &self,
+        dest_idx: usize,
+        (src_idx, kind): (usize, visit::SourceKind),
+        (other_idx, other_kind): (usize, visit::SourceKind),
+    ) -> bool {
+        if kind != other_kind {
+            return kind == visit::SourceKind::Rename;
+        }
+        if !self.renames.prefer_similar_paths {
+            return false;
+        }
+        let location = |idx: usize| self.items[idx].location(&self.path_backing);
+        path_similarity(location(src_idx), location(dest_idx))
+            > path_similarity(location(other_idx), location(dest_idx))
+    }
+}
+
+/// Return how similar the paths `a` and `b` are, as whether their file names are the same and the amount of leading
+/// directories they have in common, with greater values meaning more similar.
+fn path_similarity(a: &BStr, b: &BStr) -> (bool, usize) {
+    let (a_dir, a_name) = split_file_name(a);
+    let (b_dir, b_name) = split_file_name(b);
+    let common_dirs = match (a_dir, b_dir) {
+        (Some(a_dir), Some(b_dir)) => a_dir
+            .split(|b| *b == b'/')
+            .zip(b_dir.split(|b| *b == b'/'))
+            .take_while(|(a, b)| a == b)
+            .count(),
+        _ => 0,
+    };
+    (a_name == b_name, common_dirs)
+}
+
+/// Split `path` into its directory, if it has one, and its file name.
+fn split_file_name(path: &BStr) -> (Option<&[u8]>, &[u8]) {
+    match path.rfind_byte(b'/') {
+        Some(pos) => (Some(&path[..pos]), &path[pos + 1..]),
+        None => (None, path),
+    }
+}
+
+/// Return the similarity of `old` and `new` in the range of 0.0 to 1.0 as the fraction of lines of the larger
+/// blob that both have in common.
+///
+/// This is symmetric, so lines added to `new` lower the similarity just like lines removed from `old` do.
+/// Lines are compared as determined by `whitespace`, so blobs which only differ in ignored whitespace are identical.
+pub(super) fn similarity(
+    algorithm: gix_diff::blob::Algorithm,
+    whitespace: WhitespaceMode,
+    old: &[u8],
+    new: &[u8],
+) -> f32 {
+    let mut interner = gix_diff::blob::intern::Interner::new(0);
+    let mut tokenize = |data| -> Vec<_> {
+        whitespace
+            .similarity_lines(data)
+            .map(|line| interner.intern(line))
+            .collect()
+    };
+    let (old, new) = (tokenize(old), tokenize(new));
+    similarity_of_tokens(algorithm, &old, &new, interner.num_tokens())
+}
+
+/// Like [`similarity()`], but for blobs read line by line from `old` and `new`, with lines compared by their hash
+/// to avoid keeping their content.
+pub(super) fn similarity_of_readers(
+    algorithm: gix_diff::blob::Algorithm,
+    old: impl std::io::BufRead,
+    new: impl std::io::BufRead,
+) -> std::io::Result<f32> {
+    let mut interner = gix_diff::blob::intern::Interner::new(0);
+    let old = line_hash_tokens(old, &mut interner)?;
+    let new = line_hash_tokens(new, &mut interner)?;
+    Ok(similarity_of_tokens(algorithm, &old, &new, interner.num_tokens()))
+}
+
+/// Intern the hash of each line in `read`, including its terminator.
+///
+/// Lines are hashed piece by piece as they are read, so even a blob without any line terminator is never held
+/// in memory.
+fn line_hash_tokens(
+    mut read: impl std::io::BufRead,
+    interner: &mut gix_diff::blob::intern::Interner<u64>,
+) -> std::io::Result<Vec<gix_diff::blob::intern::Token>> {
+    use std::hash::Hasher;
+    let mut tokens = Vec::new();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut line_is_empty = true;
+    loop {
+        let buf = read.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for piece in buf.split_inclusive(|b| *b == b'\n') {
+            hasher.write(piece);
+            line_is_empty = false;
+            if piece.ends_with(b"\n") {
+                tokens.push(interner.intern(std::mem::take(&mut hasher).finish()));
+                line_is_empty = true;
+            }
+        }
+        let len = buf.len();
+        read.consume(len);
+    }
+    if !line_is_empty {
+        tokens.push(interner.intern(hasher.finish()));
+    }
+    Ok(tokens)
+}
+
+/// Like [`similarity()`], but for lines that were already interned into `old` and `new`, with `num_tokens` being
+/// the amount of distinct lines known to the interner.
+fn similarity_of_tokens(
+    algorithm: gix_diff::blob::Algorithm,
+    old: &[gix_diff::blob::intern::Token],
+    new: &[gix_diff::blob::intern::Token],
+    num_tokens: u32,
+) -> f32 {
+    let counts =
+        gix_diff::blob::diff_with_tokens(algorithm, old, new, num_tokens, gix_diff::blob::sink::Counter::new(()));
+    let common = old.len() - counts.removals as usize;
+    debug_assert_eq!(
+        common,
+        new.len() - counts.insertions as usize,
+        "lines that weren't removed from the old blob must remain in the new one"
+    );
+    let max_lines = old.len().max(new.len());
+    if max_lines == 0 {
+        return 1.0;
+    }
+    common as f32 / max_lines as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use gix_diff::tree::visit::{Action, Change};
+    use gix_object::tree::EntryMode;
+
+    use super::{is_trackable, similarity, visit::SourceKind, State};
+    use crate::object::blob::diff::WhitespaceMode;
+    use crate::object::tree::diff::{
+        renames::{Copies, TokenCache},
+        Renames,
+    };
+
+    fn id(byte: u8) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from([byte; 20])
+    }
+
+    fn deletion(oid: gix_hash::ObjectId) -> Change {
+        Change::Deletion {
+            entry_mode: EntryMode::Blob,
+            oid,
+        }
+    }
+
+    fn addition(oid: gix_hash::ObjectId) -> Change {
+        Change::Addition {
+            entry_mode: EntryMode::Blob,
+            oid,
+        }
+    }
+
+    /// Return a new state for `renames` without blob cache, with all `changes` pushed in order.
+    fn state_with<'a>(renames: Renames, changes: impl IntoIterator<Item = (Change, &'a str)>) -> State {
+        let mut state = State::new(renames, gix_diff::blob::Algorithm::Histogram, 0);
+        push_all(&mut state, changes);
+        state
+    }
+
+    fn push_all<'a>(state: &mut State, changes: impl IntoIterator<Item = (Change, &'a str)>) {
+        for (change, location) in changes {
+            let is_modification = matches!(change, Change::Modification { .. });
+            assert_eq!(
+                state.try_push_change(change, location.into()).is_some(),
+                is_modification,
+                "additions and deletions are kept for matching, modifications are passed on right away: {location}"
+            );
+        }
+    }
+
+    /// Return a function to pass as `find` to [`State::emit()`] which obtains all blobs from `blobs`.
+    fn find_in<D: AsRef<[u8]>>(
+        blobs: &HashMap<gix_hash::ObjectId, D>,
+    ) -> impl for<'b> FnMut(&gix_hash::oid, &'b mut Vec<u8>) -> Result<Option<gix_object::BlobRef<'b>>, Infallible> + '_
+    {
+        move |oid, buf| {
+            buf.clear();
+            buf.extend_from_slice(blobs[&oid.to_owned()].as_ref());
+            Ok(Some(gix_object::BlobRef { data: buf }))
+        }
+    }
+
+    #[test]
+    fn identity_renames_never_load_objects() {
+        let large_blob = gix_hash::ObjectId::from_hex(b"bcfe6d1f9cb33f9d24d4e0c5ba1dfe0e0bc5b3b5").unwrap();
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        assert_eq!(
+            state.try_push_change(
+                Change::Deletion {
+                    entry_mode: EntryMode::Blob,
+                    oid: large_blob,
+                },
+                "assets/video.bin".into(),
+            ),
+            None,
+            "deletions are kept as possible sources"
+        );
+        assert_eq!(
+            state.try_push_change(
+                Change::Addition {
+                    entry_mode: EntryMode::BlobExecutable,
+                    oid: large_blob,
+                },
+                "moved/video.bin".into(),
+            ),
+            None,
+            "additions are kept as possible destinations"
+        );
+
+        let mut loads = 0;
+        let mut actual = Vec::new();
+        state
+            .emit(
+                |dest, source| {
+                    let source = source.expect("a rename was found");
+                    actual.push((
+                        source.location.to_owned(),
+                        source.kind,
+                        source.entry_mode,
+                        dest.location.to_owned(),
+                    ));
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                    loads += 1;
+                    Ok(Some(gix_object::BlobRef { data: &[] }))
+                },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!(
+            actual,
+            vec![(
+                "assets/video.bin".into(),
+                SourceKind::Rename,
+                EntryMode::Blob,
+                "moved/video.bin".into()
+            )]
+        );
+        assert_eq!(loads, 0, "no blob content was loaded");
+    }
+
+    #[test]
+    fn identity_only_mode_never_accesses_objects() {
+        for renames in [
+            Renames {
+                percentage: None,
+                ..Default::default()
+            },
+            Renames {
+                percentage: Some(1.0),
+                copies: Some(Copies {
+                    percentage: Some(1.0),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            Renames {
+                percentage: None,
+                copies: Some(Copies {
+                    use_rename_threshold: true,
+                    ..Default::default()
+                }),
+                max_file_size: Some(1),
+                directory_renames: Some(0.5),
+                prefer_similar_paths: true,
+                ..Default::default()
+            },
+        ] {
+            let copies = renames.copies.is_some();
+            for token_cache in [None, Some(TokenCache::new(1000, 1))] {
+                let mut state = State::new(renames, gix_diff::blob::Algorithm::Histogram, 1000);
+                state.set_token_cache(token_cache);
+                push_all(
+                    &mut state,
+                    [
+                        (deletion(id(1)), "old/renamed"),
+                        (deletion(id(2)), "old/deleted"),
+                        (
+                            Change::Modification {
+                                previous_entry_mode: EntryMode::Blob,
+                                previous_oid: id(3),
+                                entry_mode: EntryMode::Blob,
+                                oid: id(4),
+                            },
+                            "modified",
+                        ),
+                        (addition(id(1)), "new/renamed"),
+                        (addition(id(3)), "new/copied"),
+                        (addition(id(5)), "new/added"),
+                    ],
+                );
+
+                let mut actual = Vec::new();
+                state
+                    .emit(
+                        |dest, source| {
+                            actual.push((
+                                dest.location.to_string(),
+                                source.map(|source| (source.location.to_string(), source.kind)),
+                            ));
+                            Action::Continue
+                        },
+                        |oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                            panic!("{oid} must not be loaded in identity-only mode")
+                        },
+                        |oid| panic!("the size of {oid} must not be queried in identity-only mode"),
+                    )
+                    .unwrap();
+                let expected = vec![
+                    ("old/deleted".to_string(), None),
+                    (
+                        "new/renamed".to_string(),
+                        Some(("old/renamed".to_string(), SourceKind::Rename)),
+                    ),
+                    (
+                        "new/copied".to_string(),
+                        copies.then(|| ("modified".to_string(), SourceKind::Copy)),
+                    ),
+                    ("new/added".to_string(), None),
+                ];
+                assert_eq!(actual, expected, "{renames:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_smallest_location_wins_among_identical_sources() {
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        for location in ["c", "a", "b"] {
+            assert_eq!(state.try_push_change(deletion(id(1)), location.into()), None);
+        }
+        for location in ["z", "x", "y"] {
+            assert_eq!(state.try_push_change(addition(id(1)), location.into()), None);
+        }
+
+        let mut actual = Vec::new();
+        state
+            .emit(
+                |dest, source| {
+                    actual.push((
+                        source.expect("all destinations have a source").location.to_string(),
+                        dest.location.to_string(),
+                    ));
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<_, Infallible> { unreachable!("identical sources aren't loaded") },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!(
+            actual,
+            [("c", "z"), ("a", "x"), ("b", "y")]
+                .iter()
+                .map(|(source, dest)| (source.to_string(), dest.to_string()))
+                .collect::<Vec<_>>(),
+            "destinations are matched in the order of their location, each with the smallest unused source location, \
+            but passed in the order they were pushed"
+        );
+    }
+
+    #[test]
+    fn matches_do_not_depend_on_the_order_of_changes() {
+        let text = |n: usize, suffix: &str| -> Vec<u8> {
+            (0..10)
+                .flat_map(|line| format!("{n} line {line}\n").into_bytes())
+                .chain(suffix.bytes())
+                .collect()
+        };
+        let blobs: HashMap<_, _> = [
+            (id(1), text(1, "")),
+            (id(2), text(1, "edited\n")),
+            (id(3), text(1, "edited differently\n")),
+            (id(4), text(2, "")),
+            (id(5), text(2, "edited\n")),
+            (id(6), text(3, "")),
+        ]
+        .into_iter()
+        .collect();
+        let mut changes = vec![
+            (deletion(id(1)), "a/one"),
+            (deletion(id(1)), "b/one"),
+            (deletion(id(4)), "two"),
+            (
+                Change::Modification {
+                    previous_entry_mode: EntryMode::Blob,
+                    previous_oid: id(6),
+                    entry_mode: EntryMode::Blob,
+                    oid: id(7),
+                },
+                "three",
+            ),
+            (addition(id(1)), "c/one"),
+            (addition(id(1)), "d/one"),
+            (addition(id(1)), "e/one"),
+            (addition(id(2)), "one-edited"),
+            (addition(id(3)), "one-edited-differently"),
+            (addition(id(5)), "two-edited"),
+            (addition(id(6)), "three-copied"),
+        ];
+
+        let mut expected = None;
+        // A fixed xorshift generator makes the shuffles reproducible.
+        let mut seed = 0x2545_f491_u32;
+        for round in 0..50 {
+            for idx in (1..changes.len()).rev() {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                changes.swap(idx, seed as usize % (idx + 1));
+            }
+            let mut state = state_with(
+                Renames {
+                    copies: Some(Copies {
+                        use_rename_threshold: true,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                changes.iter().cloned(),
+            );
+            let mut actual = Vec::new();
+            state
+                .emit(
+                    |dest, source| {
+                        actual.push((
+                            dest.location.to_string(),
+                            source.map(|source| (source.location.to_string(), source.kind)),
+                        ));
+                        Action::Continue
+                    },
+                    find_in(&blobs),
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            actual.sort_by(|a, b| a.0.cmp(&b.0));
+            match &expected {
+                None => expected = Some(actual),
+                Some(expected) => assert_eq!(&actual, expected, "round {round} with changes in order {changes:?}"),
+            }
+        }
+
+        let rename = |source: &str| Some((source.to_string(), SourceKind::Rename));
+        let copy = |source: &str| Some((source.to_string(), SourceKind::Copy));
+        assert_eq!(
+            expected.expect("set"),
+            vec![
+                ("c/one".to_string(), rename("a/one")),
+                ("d/one".into(), rename("b/one")),
+                ("e/one".into(), copy("a/one")),
+                ("one-edited".into(), copy("a/one")),
+                ("one-edited-differently".into(), copy("a/one")),
+                ("three-copied".into(), copy("three")),
+                ("two-edited".into(), rename("two")),
+            ],
+            "the outcome is the same as if changes were pushed in the order of their location"
+        );
+    }
+
+    #[test]
+    fn sources_with_similar_paths_are_preferred_if_enabled() {
+        for (sources, destination, expected, expected_with_similar_paths) in [
+            (["a/file", "b/name"], "c/name", "a/file", "b/name"),
+            (["a/f1", "x/y/f2"], "x/y/g", "a/f1", "x/y/f2"),
+            (["x/name", "x/y/name"], "x/y/z/name", "x/name", "x/y/name"),
+        ] {
+            for prefer_similar_paths in [false, true] {
+                let mut state = state_with(
+                    Renames {
+                        prefer_similar_paths,
+                        ..Default::default()
+                    },
+                    sources
+                        .into_iter()
+                        .map(|location| (deletion(id(1)), location))
+                        .chain(Some((addition(id(1)), destination))),
+                );
+
+                let mut actual = None;
+                state
+                    .emit(
+                        |_dest, source| {
+                            actual = source.map(|source| source.location.to_string());
+                            Action::Continue
+                        },
+                        |_oid, _buf| -> Result<_, Infallible> { unreachable!("identical sources aren't loaded") },
+                        |_oid| Ok(Some(0)),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    actual.as_deref(),
+                    Some(if prefer_similar_paths {
+                        expected_with_similar_paths
+                    } else {
+                        expected
+                    }),
+                    "like git, the same file name is preferred, then the most directories in common: {sources:?} -> {destination}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn similarity_checks_without_viable_sources_never_load_objects() {
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        let num_renames = 1000u16;
+        for idx in 0..num_renames {
+            let mut id = [0u8; 20];
+            id[..2].copy_from_slice(&idx.to_be_bytes());
+            let id = gix_hash::ObjectId::from(id);
+            for (change, location) in [
+                (deletion(id), format!("old/{idx}")),
+                (addition(id), format!("new/{idx}")),
+            ] {
+                assert_eq!(state.try_push_change(change, location.as_str().into()), None);
+            }
+        }
+        assert_eq!(state.try_push_change(addition(id(0xff)), "unrelated".into()), None);
+
+        let mut loads = 0;
+        let (mut renames, mut additions) = (0, 0);
+        state
+            .emit(
+                |_dest, source| {
+                    match source {
+                        Some(_) => renames += 1,
+                        None => additions += 1,
+                    }
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                    loads += 1;
+                    Ok(Some(gix_object::BlobRef { data: &[] }))
+                },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!((renames, additions), (num_renames, 1));
+        assert_eq!(
+            loads, 0,
+            "all deletions are used by identity renames, so there is nothing to compare the unmatched addition with"
+        );
+    }
+
+    #[test]
+    fn cleared_state_can_be_reused() {
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        let mut renames_of_diff = |from: &str, to: &str, byte: u8| {
+            state.clear();
+            for (change, location) in [(deletion(id(byte)), from), (addition(id(byte)), to)] {
+                assert_eq!(state.try_push_change(change, location.into()), None);
+            }
+            let mut actual = Vec::new();
+            state
+                .emit(
+                    |dest, source| {
+                        actual.push((
+                            source.map(|source| source.location.to_owned()),
+                            dest.location.to_owned(),
+                        ));
+                        Action::Continue
+                    },
+                    |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                        unreachable!("identity renames don't need objects")
+                    },
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            actual
+        };
+
+        assert_eq!(renames_of_diff("a", "b", 1), vec![(Some("a".into()), "b".into())]);
+        assert_eq!(
+            renames_of_diff("c", "d", 2),
+            vec![(Some("c".into()), "d".into())],
+            "nothing of the previous diff remains"
+        );
+    }
+
+    #[test]
+    fn similarity_results_do_not_depend_on_the_cache_size() {
+        let lines = |prefix: &str, extra: &str| -> Vec<u8> {
+            (0..20)
+                .map(|n| format!("{prefix} line {n}\n"))
+                .chain(Some(format!("{extra}\n")))
+                .collect::<String>()
+                .into_bytes()
+        };
+        let blobs: HashMap<_, _> = [
+            (id(1), lines("one", "a")),
+            (id(2), lines("two", "a")),
+            (id(3), lines("three", "a")),
+            (id(11), lines("one", "b")),
+            (id(12), lines("two", "b")),
+            (id(13), lines("three", "b")),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut outcomes = Vec::new();
+        for cache_size in [64 * 1024, 1] {
+            let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, cache_size);
+            push_all(
+                &mut state,
+                [
+                    (deletion(id(1)), "one"),
+                    (deletion(id(2)), "two"),
+                    (deletion(id(3)), "three"),
+                    (addition(id(13)), "three-moved"),
+                    (addition(id(12)), "two-moved"),
+                    (addition(id(11)), "one-moved"),
+                ],
+            );
+
+            let mut loads = 0;
+            let mut find = find_in(&blobs);
+            let mut actual = Vec::new();
+            state
+                .emit(
+                    |dest, source| {
+                        let source = source.expect("all destinations are similar to a source");
+                        actual.push((source.location.to_owned(), dest.location.to_owned()));
+                        Action::Continue
+                    },
+                    |oid, buf| {
+                        loads += 1;
+                        find(oid, buf)
+                    },
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            outcomes.push((actual, loads));
+        }
+
+        let expected = vec![
+            ("three".into(), "three-moved".into()),
+            ("two".into(), "two-moved".into()),
+            ("one".into(), "one-moved".into()),
+        ];
+        assert_eq!(outcomes[0].0, expected);
+        assert_eq!(outcomes[1].0, expected, "a tiny cache doesn't affect the outcome");
+        assert_eq!(
+            outcomes[0].1, 6,
+            "each destination and each source is loaded once with a sufficiently large cache"
+        );
+        assert!(
+            outcomes[1].1 > outcomes[0].1,
+            "a cache too small to hold any blob needs more loads"
+        );
+    }
+
+    #[test]
+    fn token_cache_avoids_loading_blobs_again_in_subsequent_diffs() {
+        let lines = |prefix: &str, extra: &str| -> Vec<u8> {
+            (0..20)
+                .map(|n| format!("{prefix} line {n}\n"))
+                .chain(Some(format!("{extra}\n")))
+                .collect::<String>()
+                .into_bytes()
+        };
+        let blobs: HashMap<_, _> = [
+            (id(1), lines("one", "a")),
+            (id(2), lines("two", "a")),
+            (id(11), lines("one", "b")),
+            (id(12), lines("two", "b")),
+        ]
+        .into_iter()
+        .collect();
+
+        for (token_cache, expected_loads) in [(TokenCache::new(1000, 1), [4, 0]), (TokenCache::new(1, 1), [5, 4])] {
+            let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+            state.set_token_cache(Some(token_cache));
+            for expected_loads in expected_loads {
+                state.clear();
+                push_all(
+                    &mut state,
+                    [
+                        (deletion(id(1)), "one"),
+                        (deletion(id(2)), "two"),
+                        (addition(id(12)), "moved/two"),
+                        (addition(id(11)), "other/one"),
+                    ],
+                );
+
+                let mut loads = 0;
+                let mut find = find_in(&blobs);
+                let mut actual = Vec::new();
+                state
+                    .emit(
+                        |dest, source| {
+                            let source = source.expect("all destinations are similar to a source");
+                            actual.push((source.location.to_owned(), dest.location.to_owned()));
+                            Action::Continue
+                        },
+                        |oid, buf| {
+                            loads += 1;
+                            find(oid, buf)
+                        },
+                        |_oid| Ok(Some(0)),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    actual,
+                    vec![("two".into(), "moved/two".into()), ("one".into(), "other/one".into())],
+                    "the token cache doesn't affect the outcome"
+                );
+                assert_eq!(
+                    loads, expected_loads,
+                    "blobs kept in the cache aren't loaded again, but a cache too small for all blobs of a diff needs more loads"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trackable_changes_for_all_kinds_and_modes() {
+        let id = gix_hash::ObjectId::null(gix_hash::Kind::Sha1);
+        let non_trees = [
+            EntryMode::Blob,
+            EntryMode::BlobExecutable,
+            EntryMode::Link,
+            EntryMode::Commit,
+        ];
+        let all_modes = non_trees.iter().copied().chain(Some(EntryMode::Tree));
+        for copies in [None, Some(Copies::default())] {
+            let renames = Renames {
+                copies,
+                ..Default::default()
+            };
+            for entry_mode in all_modes.clone() {
+                let expected = entry_mode != EntryMode::Tree;
+                for change in [
+                    Change::Addition { entry_mode, oid: id },
+                    Change::Deletion { entry_mode, oid: id },
+                ] {
+                    assert_eq!(
+                        is_trackable(&change, &renames),
+                        expected,
+                        "additions and deletions are trackable unless they are trees: {change:?}, copies = {copies:?}"
+                    );
+                }
+                for previous_entry_mode in all_modes.clone() {
+                    let change = Change::Modification {
+                        previous_entry_mode,
+                        previous_oid: id,
+                        entry_mode,
+                        oid: id,
+                    };
+                    assert_eq!(
+                        is_trackable(&change, &renames),
+                        copies.is_some() && previous_entry_mode != EntryMode::Tree,
+                        "modifications can only be copy sources, which trees never are: {change:?}, copies = {copies:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn untrackable_changes_are_passed_through_without_being_kept() {
+        let id = gix_hash::ObjectId::null(gix_hash::Kind::Sha1);
+        let modification = Change::Modification {
+            previous_entry_mode: EntryMode::Blob,
+            previous_oid: id,
+            entry_mode: EntryMode::Blob,
+            oid: id,
+        };
+        let tree_addition = Change::Addition {
+            entry_mode: EntryMode::Tree,
+            oid: id,
+        };
+
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        for change in [modification.clone(), tree_addition.clone()] {
+            assert_eq!(state.try_push_change(change.clone(), "path".into()), Some(change));
+        }
+        assert!(state.items.is_empty(), "nothing is kept if copies aren't tracked");
+
+        let mut state = State::new(
+            Renames {
+                copies: Some(Copies::default()),
+                ..Default::default()
+            },
+            gix_diff::blob::Algorithm::Histogram,
+            0,
+        );
+        for change in [modification.clone(), tree_addition] {
+            assert_eq!(state.try_push_change(change.clone(), "path".into()), Some(change));
+        }
+        assert_eq!(state.items.len(), 1, "only the modification is kept as copy source");
+        assert!(state.items[0].emitted, "it was passed on already");
+    }
+
+    #[test]
+    fn empty_blobs_are_matched_by_identity_by_default() {
+        let empty = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1);
+        let mut state = state_with(
+            Renames::default(),
+            [
+                (
+                    Change::Deletion {
+                        entry_mode: EntryMode::Blob,
+                        oid: empty,
+                    },
+                    "a/.gitkeep",
+                ),
+                (
+                    Change::Addition {
+                        entry_mode: EntryMode::Blob,
+                        oid: empty,
+                    },
+                    "b/.gitkeep",
+                ),
+            ],
+        );
+        let mut actual = Vec::new();
+        state
+            .emit(
+                |dest, source| {
+                    actual.push((
+                        source.map(|source| source.location.to_owned()),
+                        dest.location.to_owned(),
+                    ));
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                    unreachable!("identical blobs are never loaded")
+                },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!(
+            actual,
+            vec![(Some("a/.gitkeep".into()), "b/.gitkeep".into())],
+            "like git, unrelated empty files are renamed"
+        );
+    }
+
+    #[test]
+    fn empty_blobs_are_never_matched_unless_configured() {
+        let empty = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1);
+        for copies in [None, Some(Copies::default())] {
+            let mut state = State::new(
+                Renames {
+                    copies,
+                    rename_empty: false,
+                    ..Default::default()
+                },
+                gix_diff::blob::Algorithm::Histogram,
+                0,
+            );
+            let modification = Change::Modification {
+                previous_entry_mode: EntryMode::Blob,
+                previous_oid: empty,
+                entry_mode: EntryMode::Blob,
+                oid: gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+            };
+            assert_eq!(
+                state.try_push_change(modification.clone(), "modified".into()),
+                Some(modification)
+            );
+            for (change, location) in [
+                (
+                    Change::Deletion {
+                        entry_mode: EntryMode::Blob,
+                        oid: empty,
+                    },
+                    "deleted",
+                ),
+                (
+                    Change::Addition {
+                        entry_mode: EntryMode::Blob,
+                        oid: empty,
+                    },
+                    "added",
+                ),
+            ] {
+                assert_eq!(
+                    state.try_push_change(change.clone(), location.into()),
+                    Some(change),
+                    "empty blobs are passed on right away"
+                );
+            }
+            assert!(
+                state.items.is_empty(),
+                "nothing is kept as unrelated empty files would all match each other"
+            );
+        }
+    }
+
+    #[test]
+    fn binary_blobs_are_only_matched_by_identity() {
+        let text: Vec<u8> = (0..20).flat_map(|n| format!("line {n}\n").into_bytes()).collect();
+        let binary = |last: u8| -> Vec<u8> { text.iter().copied().chain([0, last]).collect() };
+        let blobs: HashMap<_, _> = [(id(1), binary(1)), (id(2), binary(2)), (id(3), text.clone())]
+            .into_iter()
+            .collect();
+
+        for (source, destination, expected_loads) in [(1, 2, 1), (1, 3, 2)] {
+            let mut state = state_with(
+                Renames::default(),
+                [
+                    (deletion(id(source)), "source"),
+                    (addition(id(destination)), "destination"),
+                ],
+            );
+
+            let mut loads = 0;
+            let mut find = find_in(&blobs);
+            let mut matches = 0;
+            state
+                .emit(
+                    |_dest, source| {
+                        matches += usize::from(source.is_some());
+                        Action::Continue
+                    },
+                    |oid, buf| {
+                        loads += 1;
+                        find(oid, buf)
+                    },
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            assert_eq!(
+                matches, 0,
+                "binary blobs have no similarity, no matter how similar they are"
+            );
+            assert_eq!(
+                loads, expected_loads,
+                "binary destinations don't even load sources, binary sources are skipped"
+            );
+        }
+    }
+
+    #[test]
+    fn renames_consume_their_source_while_copies_retain_it() {
+        let modification = Change::Modification {
+            previous_entry_mode: EntryMode::Blob,
+            previous_oid: id(2),
+            entry_mode: EntryMode::Blob,
+            oid: id(3),
+        };
+        assert!(SourceKind::Rename.can_use_change(&deletion(id(1))));
+        assert!(!SourceKind::Rename.can_use_change(&modification));
+        assert!(SourceKind::Copy.can_use_change(&deletion(id(1))));
+        assert!(SourceKind::Copy.can_use_change(&modification));
+        assert!(!SourceKind::Rename.can_use_change(&addition(id(1))));
+        assert!(!SourceKind::Copy.can_use_change(&addition(id(1))));
+        assert!(SourceKind::Rename.consumes_source());
+        assert!(!SourceKind::Copy.consumes_source());
+
+        let mut state = state_with(
+            Renames {
+                copies: Some(Copies::default()),
+                ..Default::default()
+            },
+            [
+                (deletion(id(1)), "deleted"),
+                (modification, "modified"),
+                (addition(id(1)), "a-renamed"),
+                (addition(id(1)), "b-copy-of-deleted"),
+                (addition(id(2)), "c-copy-of-modified"),
+            ],
+        );
+
+        let mut actual = Vec::new();
+        state
+            .emit(
+                |dest, source| {
+                    actual.push((
+                        dest.location.to_string(),
+                        source.map(|source| (source.location.to_string(), source.kind)),
+                    ));
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<_, Infallible> { unreachable!("identity only") },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    "a-renamed".to_string(),
+                    Some(("deleted".to_string(), SourceKind::Rename))
+                ),
+                ("b-copy-of-deleted".into(), Some(("deleted".into(), SourceKind::Copy))),
+                ("c-copy-of-modified".into(), Some(("modified".into(), SourceKind::Copy))),
+            ],
+            "the deleted file is consumed by its rename and isn't passed on, but can still be copied, \
+            while the modified file remains a source for copies"
+        );
+    }
+
+    #[test]
+    fn copies_are_found_by_identity_unless_a_percentage_is_set() {
+        let text: Vec<u8> = (0..20).flat_map(|n| format!("line {n}\n").into_bytes()).collect();
+        let edited = |suffix: &str| -> Vec<u8> { text.iter().copied().chain(suffix.bytes()).collect() };
+        let blobs: HashMap<_, _> = [
+            (id(1), text.clone()),
+            (id(2), edited("modified\n")),
+            (id(3), edited("copied\n")),
+        ]
+        .into_iter()
+        .collect();
+
+        for (copies, expected_copies) in [
+            (Copies::default(), vec!["identical"]),
+            (
+                Copies {
+                    use_rename_threshold: true,
+                    ..Default::default()
+                },
+                vec!["identical", "similar"],
+            ),
+            (
+                Copies {
+                    percentage: Some(0.99),
+                    use_rename_threshold: true,
+                    ..Default::default()
+                },
+                vec!["identical"],
+            ),
+        ] {
+            let modification = Change::Modification {
+                previous_entry_mode: EntryMode::Blob,
+                previous_oid: id(1),
+                entry_mode: EntryMode::Blob,
+                oid: id(2),
+            };
+            let mut state = state_with(
+                Renames {
+                    copies: Some(copies),
+                    ..Default::default()
+                },
+                [
+                    (modification, "source"),
+                    (addition(id(1)), "identical"),
+                    (addition(id(3)), "similar"),
+                ],
+            );
+
+            let mut actual = Vec::new();
+            state
+                .emit(
+                    |dest, source| {
+                        if let Some(source) = source {
+                            assert_eq!(source.kind, SourceKind::Copy);
+                            actual.push(dest.location.to_string());
+                        }
+                        Action::Continue
+                    },
+                    find_in(&blobs),
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            assert_eq!(actual, expected_copies, "{copies:?}");
+        }
+    }
+
+    #[test]
+    fn copy_percentage_precedence() {
+        let renames = |copies: Option<Copies>| Renames {
+            copies,
+            percentage: Some(0.7),
+            ..Default::default()
+        };
+        assert_eq!(renames(None).copy_percentage(), None, "no copies, no percentage");
+        assert_eq!(
+            renames(Some(Copies::default())).copy_percentage(),
+            None,
+            "copies are found by identity by default"
+        );
+        let use_rename_threshold = Copies {
+            use_rename_threshold: true,
+            ..Default::default()
+        };
+        assert_eq!(renames(Some(use_rename_threshold)).copy_percentage(), Some(0.7));
+        assert_eq!(
+            renames(Some(Copies {
+                percentage: Some(0.9),
+                ..use_rename_threshold
+            }))
+            .copy_percentage(),
+            Some(0.9),
+            "an explicit percentage takes precedence"
+        );
+    }
+
+    #[test]
+    fn near_misses_are_collected_only_if_enabled() {
+        let blobs: HashMap<_, &[u8]> = [
+            (id(1), &b"a\nb\nc\nd\n"[..]),
+            (id(2), b"unrelated\n"),
+            (id(3), b"a\nx\ny\nz\n"),
+            (id(4), b"a\nb\nc\nd\ne\n"),
+        ]
+        .into_iter()
+        .collect();
+
+        for collect in [false, true] {
+            let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+            state.collect_near_misses(collect);
+            push_all(
+                &mut state,
+                [
+                    (deletion(id(1)), "old"),
+                    (deletion(id(2)), "unrelated"),
+                    (addition(id(3)), "new"),
+                    (addition(id(4)), "renamed"),
+                ],
+            );
+
+            let mut matches = 0;
+            state
+                .emit(
+                    |_dest, source| {
+                        matches += usize::from(source.is_some());
+                        Action::Continue
+                    },
+                    find_in(&blobs),
+                    |_oid| Ok(Some(0)),
+                )
+                .unwrap();
+            assert_eq!(matches, 1, "only 'renamed' is similar enough to 'old'");
+
+            let near_misses: Vec<_> = state
+                .near_misses()
+                .map(|(source, destination, similarity)| (source.to_owned(), destination.to_owned(), similarity))
+                .collect();
+            if collect {
+                assert_eq!(
+                    near_misses,
+                    vec![("old".into(), "new".into(), 0.25)],
+                    "the most similar source is reported even though it was used for a rename"
+                );
+            } else {
+                assert_eq!(near_misses, vec![], "nothing is collected by default");
+            }
+        }
+    }
+
+    #[test]
+    fn similarity_is_symmetric_for_asymmetric_additions() {
+        let algorithm = gix_diff::blob::Algorithm::Histogram;
+        let small = b"a\n";
+        let large = b"a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        assert_eq!(
+            similarity(algorithm, WhitespaceMode::Exact, small, large),
+            0.1,
+            "only one of the ten lines of the grown destination is shared with the source"
+        );
+        assert_eq!(
+            similarity(algorithm, WhitespaceMode::Exact, large, small),
+            similarity(algorithm, WhitespaceMode::Exact, small, large),
+            "removing lines is scored like adding them"
+        );
+
+        let grown = b"a\nb\nc\nd\ne\n";
+        assert_eq!(similarity(algorithm, WhitespaceMode::Exact, &large[..6], grown), 0.6);
+        assert_eq!(similarity(algorithm, WhitespaceMode::Exact, grown, &large[..6]), 0.6);
+    }
+
+    #[test]
+    fn similarity_depends_on_the_algorithm() {
+        let old = b"unique\n}\n}\n}\nother\n";
+        let new = b"other\n}\n}\n}\nunique\n";
+        assert_eq!(
+            similarity(gix_diff::blob::Algorithm::Myers, WhitespaceMode::Exact, old, new),
+            0.6,
+            "the repeated lines are kept"
+        );
+        assert_eq!(
+            similarity(gix_diff::blob::Algorithm::Patience, WhitespaceMode::Exact, old, new),
+            0.2,
+            "patience anchors at one of the unique lines, so all repeated lines before it are changed"
+        );
+    }
+
+    #[test]
+    fn similarity_of_empty_blobs() {
+        let algorithm = gix_diff::blob::Algorithm::Histogram;
+        assert_eq!(
+            similarity(algorithm, WhitespaceMode::Exact, b"", b""),
+            1.0,
+            "empty blobs are equal"
+        );
+        assert_eq!(similarity(algorithm, WhitespaceMode::Exact, b"", b"a\n"), 0.0);
+        assert_eq!(similarity(algorithm, WhitespaceMode::Exact, b"a\n", b""), 0.0);
+    }
+
+    #[test]
+    fn similarity_of_reindented_blobs_depends_on_the_whitespace_mode() {
+        let algorithm = gix_diff::blob::Algorithm::Histogram;
+        let old = b"fn f() {\n  a;\n  b  c;\n}\n";
+        let reindented = b"fn f() {\n    a;\n\n    b c;\n}\n";
+        for (whitespace, expected) in [
+            (WhitespaceMode::Exact, 0.4),
+            (WhitespaceMode::IgnoreAll, 0.8),
+            (WhitespaceMode::IgnoreChange, 0.8),
+            (WhitespaceMode::IgnoreBlankLines, 0.5),
+        ] {
+            assert_eq!(
+                similarity(algorithm, whitespace, old, reindented),
+                expected,
+                "{whitespace:?}"
+            );
+            assert_eq!(
+                similarity(algorithm, whitespace, reindented, old),
+                expected,
+                "{whitespace:?}"
+            );
+        }
+        let reindented_without_blank_line = b"fn f() {\n\ta;\n\tb c;\n}\n";
+        assert_eq!(
+            similarity(algorithm, WhitespaceMode::IgnoreAll, old, reindented_without_blank_line),
+            1.0,
+            "blobs which only differ in whitespace are identical"
+        );
+    }
+
+    #[test]
+    fn reindented_blobs_are_renamed_if_whitespace_is_ignored() {
+        let blobs: HashMap<_, _> = [(id(1), &b"fn f() {\n  a;\n}\n"[..]), (id(2), b"fn f() {\n\ta;\n}\n")]
+            .into_iter()
+            .collect();
+        for use_token_cache in [false, true] {
+            for (whitespace, expected_matches) in [(WhitespaceMode::Exact, 0), (WhitespaceMode::IgnoreAll, 1)] {
+                let mut state = State::new(
+                    Renames {
+                        percentage: Some(1.0),
+                        whitespace,
+                        ..Default::default()
+                    },
+                    gix_diff::blob::Algorithm::Histogram,
+                    0,
+                );
+                state.set_token_cache(use_token_cache.then(|| TokenCache::new(10, 1)));
+                push_all(
+                    &mut state,
+                    [(deletion(id(1)), "source"), (addition(id(2)), "destination")],
+                );
+                let mut matches = 0;
+                state
+                    .emit(
+                        |_dest, source| {
+                            matches += usize::from(source.is_some());
+                            Action::Continue
+                        },
+                        find_in(&blobs),
+                        |_oid| Ok(Some(0)),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    matches, expected_matches,
+                    "{whitespace:?}, token cache = {use_token_cache}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn submodules_are_only_matched_by_identity_with_other_submodules() {
+        let (submodule, other_submodule) = (id(1), id(2));
+        let mut state = State::new(Renames::default(), gix_diff::blob::Algorithm::Histogram, 0);
+        for (change, location) in [
+            (
+                Change::Deletion {
+                    entry_mode: EntryMode::Commit,
+                    oid: submodule,
+                },
+                "old/sub",
+            ),
+            (
+                Change::Deletion {
+                    entry_mode: EntryMode::Commit,
+                    oid: other_submodule,
+                },
+                "old/other-sub",
+            ),
+            (addition(other_submodule), "file-with-submodule-id"),
+            (
+                Change::Addition {
+                    entry_mode: EntryMode::Commit,
+                    oid: id(3),
+                },
+                "new/updated-sub",
+            ),
+            (
+                Change::Addition {
+                    entry_mode: EntryMode::Commit,
+                    oid: submodule,
+                },
+                "new/sub",
+            ),
+        ] {
+            assert_eq!(state.try_push_change(change, location.into()), None);
+        }
+
+        let mut actual = Vec::new();
+        state
+            .emit(
+                |dest, source| {
+                    actual.push((
+                        dest.location.to_owned(),
+                        source.map(|source| source.location.to_owned()),
+                    ));
+                    Action::Continue
+                },
+                |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                    unreachable!("submodule commits are never loaded for similarity checks")
+                },
+                |_oid| Ok(Some(0)),
+            )
+            .unwrap();
+        assert_eq!(
+            actual,
+            vec![
+                ("old/other-sub".into(), None),
+                ("file-with-submodule-id".into(), None),
+                ("new/updated-sub".into(), None),
+                ("new/sub".into(), Some("old/sub".into())),
+            ],
+            "only the submodule with the same id is a rename, blobs are never matched with submodules"
+        );
+    }
+
+    #[test]
+    fn blobs_larger_than_max_file_size_are_only_matched_by_identity() {
+        let (source, destination) = (id(1), id(2));
+        let renames_with_sizes = |source_size: u64, destination_size: u64| {
+            let mut state = state_with(
+                Renames {
+                    max_file_size: Some(10),
+                    ..Default::default()
+                },
+                [(deletion(source), "old"), (addition(destination), "new")],
+            );
+            let mut loads = 0;
+            let mut renames = 0;
+            state
+                .emit(
+                    |_dest, source| {
+                        renames += usize::from(source.is_some());
+                        Action::Continue
+                    },
+                    |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                        loads += 1;
+                        Ok(Some(gix_object::BlobRef { data: b"a\nb\n" }))
+                    },
+                    |oid| Ok(Some(if oid == source { source_size } else { destination_size })),
+                )
+                .unwrap();
+            (renames, loads)
+        };
+
+        assert_eq!(renames_with_sizes(4, 4), (1, 2), "small blobs are compared by content");
+        assert_eq!(
+            renames_with_sizes(4, 11),
+            (0, 0),
+            "a large destination isn't loaded and can't be similar to anything"
+        );
+        assert_eq!(
+            renames_with_sizes(11, 4),
+            (0, 1),
+            "a large source isn't loaded either, but the destination was"
+        );
+    }
+
+    #[test]
+    fn sizes_are_obtained_once_per_blob() {
+        let large_source = id(1);
+        let mut state = state_with(
+            Renames {
+                max_file_size: Some(10),
+                ..Default::default()
+            },
+            [
+                (deletion(large_source), "old"),
+                (addition(id(2)), "new-a"),
+                (addition(id(3)), "new-b"),
+            ],
+        );
+        let mut size_lookups = HashMap::<_, usize>::new();
+        state
+            .emit(
+                |_dest, _source| Action::Continue,
+                |_oid, _buf| -> Result<Option<gix_object::BlobRef<'_>>, Infallible> {
+                    Ok(Some(gix_object::BlobRef { data: b"a\nb\n" }))
+                },
+                |oid| {
+                    *size_lookups.entry(oid.to_owned()).or_default() += 1;
+                    Ok(Some(if oid == large_source { 11 } else { 4 }))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            size_lookups,
+            [(id(1), 1), (id(2), 1), (id(3), 1)].into_iter().collect(),
+            "the large source is checked for each destination, but its size is only looked up once"
+        );
+    }
+
+    #[test]
+    fn missing_blobs_can_only_be_matched_by_identity() {
+        let blobs: HashMap<_, &[u8]> = [
+            (id(1), &b"a\nb\nc\nd\n"[..]),
+            (id(2), b"e\nf\ng\nh\n"),
+            (id(3), b"a\nb\nc\nd\nx\n"),
+            (id(4), b"e\nf\ng\nh\nx\n"),
+            (id(5), b"i\nj\nk\nl\n"),
+            (id(6), b"i\nj\nk\nl\nx\n"),
+        ]
+        .into_iter()
+        .collect();
+        let missing = [id(3), id(2)];
+        for max_file_size in [None, Some(100)] {
+            let mut state = state_with(
+                Renames {
+                    max_file_size,
+                    ..Default::default()
+                },
+                [
+                    (deletion(id(1)), "a"),
+                    (deletion(id(2)), "b"),
+                    (deletion(id(5)), "c"),
+                    (deletion(id(7)), "same"),
+                    (addition(id(3)), "a-edited"),
+                    (addition(id(4)), "b-edited"),
+                    (addition(id(6)), "c-edited"),
+                    (addition(id(7)), "same-renamed"),
+                ],
+            );
+            let mut find = find_in(&blobs);
+            let mut actual = Vec::new();
+            state
+                .emit(
+                    |dest, source| {
+                        actual.push((
+                            dest.location.to_string(),
+                            source.map(|source| source.location.to_string()),
+                        ));
+                        Action::Continue
+                    },
+                    |oid, buf| {
+                        assert_ne!(oid, id(7), "identical blobs are never loaded");
+                        if missing.iter().any(|missing| missing.as_ref() == oid) {
+                            return Ok(None);
+                        }
+                        find(oid, buf)
+                    },
+                    |oid| {
+                        Ok((!missing.iter().any(|missing| missing.as_ref() == oid))
+                            .then(|| blobs[&oid.to_owned()].len() as u64))
+                    },
+                )
+                .unwrap();
+            actual.sort();
+            assert_eq!(
+                actual,
+                vec![
+                    ("a".to_string(), None),
+                    ("a-edited".into(), None),
+                    ("b".into(), None),
+                    ("b-edited".into(), None),
+                    ("c-edited".into(), Some("c".into())),
+                    ("same-renamed".into(), Some("same".into())),
+                ],
+                "missing sources and destinations are skipped without error, with max_file_size = {max_file_size:?}"
+            );
+        }
+    }
+}
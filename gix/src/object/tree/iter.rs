@@ -35,8 +35,8 @@ impl<'repo, 'a> std::fmt::Display for EntryRef<'repo, 'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{:06o} {:>6} {}\t{}",
-            self.mode() as u32,
+            "{} {:>6} {}\t{}",
+            self.mode().as_octal_str(),
             self.mode().as_str(),
             self.id().shorten_or_id(),
             self.filename()
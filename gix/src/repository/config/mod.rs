@@ -68,6 +68,13 @@ impl crate::Repository {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.config.object_hash
     }
+
+    /// The algorithm to use for diffing blobs, as configured by `diff.algorithm`, or Myers if it isn't set, like git does.
+    ///
+    /// Unknown algorithms fall back to Myers if the configuration is lenient, and are an error otherwise.
+    pub fn diff_algorithm(&self) -> Result<gix_diff::blob::Algorithm, config::diff::algorithm::Error> {
+        self.config.diff_algorithm()
+    }
 }
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
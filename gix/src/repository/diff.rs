@@ -0,0 +1,93 @@
+use std::convert::Infallible;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::BStr,
+    ext::ObjectIdExt,
+    object::{
+        blob,
+        tree::diff::{tree_to_tree, Action, ChangeDetached},
+    },
+};
+
+/// Diffing
+impl crate::Repository {
+    /// Return all changes needed to turn the tree of `old` into the tree of `new`, both of which are revision specifications
+    /// like `HEAD~1` or `main:dir` that are [parsed][Self::rev_parse_single()] and peeled to a tree, so commits
+    /// can be passed as well.
+    ///
+    /// Changes are collected in the order they are encountered with their [`location`][ChangeDetached::location] being
+    /// the path relative to the root of the trees. Renames are tracked like [`Tree::changes()`][crate::Tree::changes()] does
+    /// by default, as [configured][crate::config::tree::Diff::RENAMES] or just like `git diff` does if nothing is configured,
+    /// and directories are passed along with the files within them.
+    /// Collect them into [`ChangesByKind`][crate::object::tree::diff::ChangesByKind] to handle changes of directories
+    /// separately from changes of files.
+    ///
+    /// For more control, like to enable rename tracking regardless of the configuration, use the platform returned
+    /// by [`Tree::changes()`][crate::Tree::changes()] and configure it with
+    /// [`track_renames()`][crate::object::tree::diff::Platform::track_renames()] before diffing the trees with
+    /// [`for_each_to_obtain_tree()`][crate::object::tree::diff::Platform::for_each_to_obtain_tree()].
+    #[allow(clippy::result_large_err)]
+    pub fn diff_tree_to_tree<'a>(
+        &self,
+        old: impl Into<&'a BStr>,
+        new: impl Into<&'a BStr>,
+    ) -> Result<Vec<ChangeDetached>, tree_to_tree::Error> {
+        let old = self.rev_parse_single(old)?.detach();
+        let new = self.rev_parse_single(new)?.detach();
+        self.diff_tree_to_tree_by_id(old, new)
+    }
+
+    /// Like [`diff_tree_to_tree()`][Self::diff_tree_to_tree()], but with the ids of the objects to peel to trees
+    /// instead of revision specifications.
+    #[allow(clippy::result_large_err)]
+    pub fn diff_tree_to_tree_by_id(
+        &self,
+        old: impl Into<ObjectId>,
+        new: impl Into<ObjectId>,
+    ) -> Result<Vec<ChangeDetached>, tree_to_tree::Error> {
+        let old = self.find_object(old)?.peel_to_tree()?;
+        let new = self.find_object(new)?.peel_to_tree()?;
+        let mut changes = Vec::new();
+        old.changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&new, |change| -> Result<_, Infallible> {
+                changes.push(change.detach());
+                Ok(Action::Continue)
+            })?;
+        Ok(changes)
+    }
+
+    /// Load the blobs with the ids `old` and `new` and return a platform to diff their lines, using the diff algorithm
+    /// as [configured][crate::config::tree::Diff::ALGORITHM].
+    ///
+    /// This is useful if the ids of the blobs are already known, like the ones of a
+    /// [modification][crate::object::tree::diff::change::Event::Modification], which would otherwise be diffed with
+    /// [`Event::diff()`][crate::object::tree::diff::change::Event::diff()].
+    /// It's an error if one of the objects isn't a blob.
+    pub fn diff_blobs(
+        &self,
+        old: impl Into<ObjectId>,
+        new: impl Into<ObjectId>,
+    ) -> Result<blob::diff::Platform<'_, '_>, blob::diff::init::Error> {
+        let blob = |id: ObjectId| -> Result<_, blob::diff::init::Error> {
+            let object = id.attach(self).object()?;
+            if object.kind != gix_object::Kind::Blob {
+                return Err(crate::object::try_into::Error {
+                    id,
+                    actual: object.kind,
+                    expected: gix_object::Kind::Blob,
+                }
+                .into());
+            }
+            Ok(object)
+        };
+        Ok(blob::diff::Platform {
+            old: blob(old.into())?.into(),
+            new: blob(new.into())?.into(),
+            algo: self.config.diff_algorithm()?,
+            whitespace: Default::default(),
+        })
+    }
+}
@@ -33,12 +33,18 @@ impl crate::Repository {
 fn setup_objects(mut objects: crate::OdbHandle, config: &crate::config::Cache) -> crate::OdbHandle {
     #[cfg(feature = "max-performance-safe")]
     {
-        match config.pack_cache_bytes {
-            None => objects.set_pack_cache(|| Box::<gix_pack::cache::lru::StaticLinkedList<64>>::default()),
-            Some(0) => objects.unset_pack_cache(),
-            Some(bytes) => objects.set_pack_cache(move || -> Box<gix_odb::cache::PackCache> {
+        match (config.pack_cache_bytes, config.pack_cache_entries) {
+            (None, None) => objects.set_pack_cache(|| Box::<gix_pack::cache::lru::StaticLinkedList<64>>::default()),
+            (Some(0), _) | (_, Some(0)) => objects.unset_pack_cache(),
+            (Some(bytes), None) => objects.set_pack_cache(move || -> Box<gix_odb::cache::PackCache> {
                 Box::new(gix_pack::cache::lru::MemoryCappedHashmap::new(bytes))
             }),
+            (bytes, Some(entries)) => objects.set_pack_cache(move || -> Box<gix_odb::cache::PackCache> {
+                Box::new(gix_pack::cache::lru::MemoryCappedHashmap::with_max_entries(
+                    bytes.unwrap_or(usize::MAX),
+                    entries,
+                ))
+            }),
         };
         if config.object_cache_bytes == 0 {
             objects.unset_object_cache();
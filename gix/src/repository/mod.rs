@@ -21,6 +21,7 @@ impl crate::Repository {
 
 mod cache;
 mod config;
+mod diff;
 pub(crate) mod identity;
 mod impls;
 mod init;
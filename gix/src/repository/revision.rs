@@ -1,4 +1,8 @@
-use crate::{bstr::BStr, revision, Id};
+use crate::{
+    bstr::{BStr, BString},
+    object::Kind,
+    revision, Commit, Id, Object, Tree,
+};
 
 /// Methods for resolving revisions by spec or working with the commit graph.
 impl crate::Repository {
@@ -30,6 +34,47 @@ impl crate::Repository {
             .ok_or(revision::spec::parse::single::Error::RangedRev { spec: spec.into() })
     }
 
+    /// Parse a revision specification like [`rev_parse_single()`][Self::rev_parse_single()] and peel the object it
+    /// resolves to into a tree, following tags to their target and commits to their tree.
+    #[allow(clippy::result_large_err)]
+    pub fn rev_parse_tree<'repo, 'a>(
+        &'repo self,
+        spec: impl Into<&'a BStr>,
+    ) -> Result<Tree<'repo>, revision::spec::parse::peel::Error> {
+        Ok(self.rev_parse_peeled(spec, Kind::Tree)?.into_tree())
+    }
+
+    /// Parse a revision specification like [`rev_parse_single()`][Self::rev_parse_single()] and peel the object it
+    /// resolves to into a commit, following tags to their target.
+    #[allow(clippy::result_large_err)]
+    pub fn rev_parse_commit<'repo, 'a>(
+        &'repo self,
+        spec: impl Into<&'a BStr>,
+    ) -> Result<Commit<'repo>, revision::spec::parse::peel::Error> {
+        Ok(self.rev_parse_peeled(spec, Kind::Commit)?.into_commit())
+    }
+
+    /// Parse a revision specification like [`rev_parse_single()`][Self::rev_parse_single()] and peel the object it
+    /// resolves to into a blob, following tags to their target.
+    ///
+    /// Use a specification like `HEAD:path/to/file` to obtain the blob of a file in a commit.
+    #[allow(clippy::result_large_err)]
+    pub fn rev_parse_blob<'repo, 'a>(
+        &'repo self,
+        spec: impl Into<&'a BStr>,
+    ) -> Result<Object<'repo>, revision::spec::parse::peel::Error> {
+        self.rev_parse_peeled(spec, Kind::Blob)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn rev_parse_peeled<'repo, 'a>(
+        &'repo self,
+        spec: impl Into<&'a BStr>,
+        kind: Kind,
+    ) -> Result<Object<'repo>, revision::spec::parse::peel::Error> {
+        Ok(self.rev_parse_single(spec)?.object()?.peel_to_kind(kind)?)
+    }
+
     /// Create the baseline for a revision walk by initializing it with the `tips` to start iterating on.
     ///
     /// It can be configured further before starting the actual walk.
@@ -39,4 +84,17 @@ impl crate::Repository {
     ) -> revision::walk::Platform<'_> {
         revision::walk::Platform::new(tips, self)
     }
+
+    /// Return an iterator over all commits reachable from `HEAD` which changed the file at `path`, youngest first,
+    /// following it through renames like `git log --follow <path>` would.
+    ///
+    /// `path` is relative to the root of the repository, like `dir/file`. Whenever the file was renamed, the path of the source
+    /// is followed instead. Renames are detected as [configured][crate::config::tree::Diff::RENAMES], so the file isn't
+    /// followed through renames if rename tracking is disabled, and copies are followed as well if they are detected.
+    pub fn path_history(
+        &self,
+        path: impl Into<BString>,
+    ) -> Result<revision::path_history::Iter<'_>, crate::reference::head_id::Error> {
+        Ok(revision::path_history::Iter::new(self, self.head_id()?, path.into()))
+    }
 }
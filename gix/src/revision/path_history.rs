@@ -0,0 +1,214 @@
+use std::{collections::VecDeque, convert::Infallible};
+
+use gix_hash::ObjectId;
+use gix_hashtable::HashSet;
+use gix_object::TreeRefIter;
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    ext::ObjectIdExt,
+    object::tree::diff::{change::Event, Action},
+    Id, Repository, Tree,
+};
+
+/// The error returned by the [`Iter`] returned by [`Repository::path_history()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    RenamesConfiguration(#[from] crate::object::tree::diff::renames::Error),
+    #[error(transparent)]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+}
+
+/// A commit that changed the file whose history is followed, as returned by [`Iter`].
+#[derive(Debug, Clone)]
+pub struct Item<'repo> {
+    /// The id of the commit which changed the file.
+    pub id: Id<'repo>,
+    /// The path of the file in this commit.
+    pub location: BString,
+    /// The path of the file in the parent commit if it was renamed or copied from there, or `None` if it's at the same
+    /// `location` in the parent or was added by this commit.
+    pub source_location: Option<BString>,
+    /// `true` if the file was added by this commit as none of its parents has it at `location` or any source location,
+    /// which is always the case for root commits as they have no parent. The history of the file ends here.
+    pub added: bool,
+}
+
+/// An iterator over all commits that changed a file, following it through renames like `git log --follow` does,
+/// as created by [`Repository::path_history()`].
+///
+/// Commits are returned by commit time, youngest first. If a commit didn't change the file compared to one of its parents,
+/// only this parent is followed, which is what `git log` does to simplify history as well.
+pub struct Iter<'repo> {
+    repo: &'repo Repository,
+    /// The commits to look at along with their commit time and the location of the file in them, youngest first.
+    next: VecDeque<(ObjectId, u32, BString)>,
+    seen: HashSet<ObjectId>,
+    buf: Vec<u8>,
+}
+
+impl<'repo> Iter<'repo> {
+    pub(crate) fn new(repo: &'repo Repository, tip: Id<'repo>, location: BString) -> Self {
+        let mut seen = HashSet::default();
+        seen.insert(tip.detach());
+        Iter {
+            repo,
+            next: VecDeque::from(vec![(tip.detach(), 0, location)]),
+            seen,
+            buf: Vec::new(),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn next_inner(&mut self) -> Result<Option<Item<'repo>>, Error> {
+        while let Some((id, _commit_time, location)) = self.next.pop_front() {
+            let commit = self.repo.find_object(id)?.try_into_commit()?;
+            let tree = commit.tree()?;
+            let entry = match lookup(self.repo, &tree, location.as_ref(), &mut self.buf)? {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let mut parents = Vec::new();
+            let mut unchanged = false;
+            for parent_id in commit.parent_ids() {
+                let parent_tree = parent_id.object()?.try_into_commit()?.tree()?;
+                let parent_entry = lookup(self.repo, &parent_tree, location.as_ref(), &mut self.buf)?;
+                if parent_entry == Some(entry) {
+                    // The file didn't change compared to this parent, so it's the only one to follow.
+                    self.push(parent_id.detach(), location.clone())?;
+                    unchanged = true;
+                    break;
+                }
+                parents.push((parent_id.detach(), parent_tree, parent_entry));
+            }
+            if unchanged {
+                continue;
+            }
+
+            let mut source_location = None;
+            let mut added = true;
+            for (parent_id, parent_tree, parent_entry) in parents {
+                if parent_entry.is_some() {
+                    self.push(parent_id, location.clone())?;
+                    added = false;
+                    continue;
+                }
+                if let Some(source) = self.find_source(&parent_tree, &tree, &location)? {
+                    self.push(parent_id, source.clone())?;
+                    source_location.get_or_insert(source);
+                    added = false;
+                }
+            }
+            return Ok(Some(Item {
+                id: id.attach(self.repo),
+                location,
+                source_location,
+                added,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Return the location of the file that `location` in `tree` was renamed or copied from, as found in `parent_tree`.
+    ///
+    /// Nothing is found if rename tracking is disabled by configuration.
+    #[allow(clippy::result_large_err)]
+    fn find_source(
+        &self,
+        parent_tree: &Tree<'_>,
+        tree: &Tree<'_>,
+        location: &BString,
+    ) -> Result<Option<BString>, Error> {
+        let renames = match self.repo.config.diff_renames()? {
+            Some(renames) => renames,
+            None => return Ok(None),
+        };
+        let mut source = None;
+        parent_tree
+            .changes()?
+            .track_path()
+            .track_renames(Some(renames))
+            .for_each_to_obtain_tree(tree, |change| -> Result<_, Infallible> {
+                Ok(match change.event {
+                    Event::Rename { source_location, .. } | Event::Copy { source_location, .. }
+                        if change.location == location =>
+                    {
+                        source = Some(source_location.to_owned());
+                        Action::Cancel
+                    }
+                    _ => Action::Continue,
+                })
+            })?;
+        Ok(source)
+    }
+
+    /// Queue the commit with `id` to look at the file at `location` in it, unless it was seen already.
+    #[allow(clippy::result_large_err)]
+    fn push(&mut self, id: ObjectId, location: BString) -> Result<(), Error> {
+        if !self.seen.insert(id) {
+            return Ok(());
+        }
+        let commit_time = self
+            .repo
+            .find_object(id)?
+            .try_into_commit()?
+            .time()?
+            .seconds_since_unix_epoch;
+        let pos = self.next.partition_point(|(_, time, _)| *time >= commit_time);
+        self.next.insert(pos, (id, commit_time, location));
+        Ok(())
+    }
+}
+
+/// Return the id and mode of the entry at `location` in `tree`, if there is one, using `buf` to read the trees
+/// leading to it.
+#[allow(clippy::result_large_err)]
+fn lookup(
+    repo: &Repository,
+    tree: &Tree<'_>,
+    location: &BStr,
+    buf: &mut Vec<u8>,
+) -> Result<Option<(ObjectId, gix_object::tree::EntryMode)>, Error> {
+    let mut entry: Option<(ObjectId, gix_object::tree::EntryMode)> = None;
+    for component in location.split_str("/") {
+        let mut entries = match entry {
+            None => TreeRefIter::from_bytes(&tree.data),
+            Some((id, mode)) if mode.is_tree() => TreeRefIter::from_bytes(repo.objects.find(id, buf)?.data),
+            Some(_) => return Ok(None),
+        };
+        entry = entries
+            .find(|entry| entry.as_ref().map_or(true, |entry| entry.filename == component))
+            .transpose()?
+            .map(|entry| (entry.oid.to_owned(), entry.mode));
+        if entry.is_none() {
+            break;
+        }
+    }
+    Ok(entry)
+}
+
+impl<'repo> Iterator for Iter<'repo> {
+    type Item = Result<Item<'repo>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_inner() {
+            Ok(item) => item.map(Ok),
+            Err(err) => {
+                self.next.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
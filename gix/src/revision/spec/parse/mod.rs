@@ -23,6 +23,22 @@ pub mod single {
     }
 }
 
+///
+pub mod peel {
+    /// The error returned by [`crate::Repository::rev_parse_tree()`], [`crate::Repository::rev_parse_commit()`] and
+    /// [`crate::Repository::rev_parse_blob()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Single(#[from] super::single::Error),
+        #[error(transparent)]
+        FindExisting(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToKind(#[from] crate::object::peel::to_kind::Error),
+    }
+}
+
 ///
 pub mod error;
 
@@ -10,6 +10,7 @@ pub struct Platform<'repo> {
     pub(crate) tips: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
     pub(crate) parents: gix_traverse::commit::Parents,
+    pub(crate) use_commit_graph: bool,
 }
 
 impl<'repo> Platform<'repo> {
@@ -19,6 +20,7 @@ impl<'repo> Platform<'repo> {
             tips: tips.into_iter().map(Into::into).collect(),
             sorting: Default::default(),
             parents: Default::default(),
+            use_commit_graph: true,
         }
     }
 }
@@ -36,6 +38,14 @@ impl<'repo> Platform<'repo> {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
+
+    /// If `toggle` is `true`, the default, use the commit-graph of the repository if there is one, which avoids
+    /// looking up most commits and orders [topologically sorted][gix_traverse::commit::Sorting::Topological]
+    /// commits by their generation number.
+    pub fn use_commit_graph(mut self, toggle: bool) -> Self {
+        self.use_commit_graph = toggle;
+        self
+    }
 }
 
 /// Produce the iterator
@@ -46,13 +56,19 @@ impl<'repo> Platform<'repo> {
     ///
     /// It's highly recommended to set an [`object cache`][Repository::object_cache_size()] on the parent repo
     /// to greatly speed up performance if the returned id is supposed to be looked up right after.
+    ///
+    /// Note that a commit-graph that can't be read is ignored, just like a missing one.
     pub fn all(self) -> Result<revision::Walk<'repo>, gix_traverse::commit::ancestors::Error> {
         let Platform {
             repo,
             tips,
             sorting,
             parents,
+            use_commit_graph,
         } = self;
+        let commit_graph = use_commit_graph
+            .then(|| gix_commitgraph::Graph::from_info_dir(repo.objects.store_ref().path().join("info")).ok())
+            .flatten();
         Ok(revision::Walk {
             repo,
             inner: Box::new(
@@ -61,6 +77,7 @@ impl<'repo> Platform<'repo> {
                     gix_traverse::commit::ancestors::State::default(),
                     move |oid, buf| repo.objects.find_commit_iter(oid, buf),
                 )
+                .commit_graph(commit_graph)
                 .sorting(sorting)?
                 .parents(parents),
             ),
@@ -169,20 +169,15 @@ mod diff {
             ("Default", Algorithm::Myers),
             ("minimal", Algorithm::MyersMinimal),
             ("histogram", Algorithm::Histogram),
+            ("patience", Algorithm::Patience),
+            ("Patience", Algorithm::Patience),
         ] {
             assert_eq!(Diff::ALGORITHM.try_into_algorithm(bcow(actual))?, expected);
             assert!(Diff::ALGORITHM.validate(actual.into()).is_ok());
         }
-        assert_eq!(
-            Diff::ALGORITHM
-                .try_into_algorithm(bcow("patience"))
-                .unwrap_err()
-                .to_string(),
-            "The 'patience' algorithm is not yet implemented"
-        );
         assert_eq!(
             Diff::ALGORITHM.try_into_algorithm(bcow("foo")).unwrap_err().to_string(),
-            "Unknown diff algorithm named 'foo'"
+            "Unknown diff algorithm named 'foo', expected one of 'myers', 'default', 'minimal', 'patience' or 'histogram'"
         );
         Ok(())
     }
@@ -285,6 +280,33 @@ mod core {
         Ok(())
     }
 
+    #[test]
+    fn autocrlf() -> crate::Result {
+        assert_eq!(Core::AUTO_CRLF.try_into_autocrlf(Some(Ok(true)), || None)?, Some(true));
+        assert!(Core::AUTO_CRLF.validate("true".into()).is_ok());
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(Some(Ok(false)), || None)?,
+            Some(false)
+        );
+        assert!(Core::AUTO_CRLF.validate("0".into()).is_ok());
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(None, || Some(bcow("input")))?,
+            Some(true),
+            "input converts when adding files just like true"
+        );
+        assert!(Core::AUTO_CRLF.validate("input".into()).is_ok());
+        assert_eq!(Core::AUTO_CRLF.try_into_autocrlf(None, || None)?, None);
+        assert_eq!(
+            Core::AUTO_CRLF
+                .try_into_autocrlf(None, || Some(bcow("invalid")))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.autocrlf=invalid\" was invalid"
+        );
+        assert!(Core::AUTO_CRLF.validate("invalid".into()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn abbrev() -> crate::Result {
         let object_hash = gix_hash::Kind::Sha1;
@@ -550,6 +572,23 @@ mod gitoxide {
             );
         }
     }
+    mod objects {
+        use gix::config::tree::{gitoxide, Key};
+
+        #[test]
+        fn delta_base_cache_entries() -> crate::Result {
+            let key = &gitoxide::Objects::DELTA_BASE_CACHE_ENTRIES;
+            assert_eq!(key.try_into_usize(Ok(64))?, 64);
+            assert_eq!(key.try_into_usize(Ok(0))?, 0);
+            assert!(key.validate("32".into()).is_ok());
+            assert_eq!(
+                key.try_into_usize(Ok(-1)).unwrap_err().to_string(),
+                "The value of key \"gitoxide.objects.deltaBaseCacheEntries\" (possibly from GITOXIDE_PACK_CACHE_ENTRIES) could not be parsed as unsigned integer"
+            );
+            assert!(key.validate("-1".into()).is_err());
+            Ok(())
+        }
+    }
 }
 
 #[cfg(any(
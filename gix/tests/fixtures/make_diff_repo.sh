@@ -20,3 +20,30 @@ git commit -q -am c3
 
 git mv a dir/a-moved
 git commit -q -am r1-identity
+
+mkdir src
+echo one > src/one
+echo two > src/two
+echo three > src/three
+git add src
+git commit -q -m r2-prep
+
+mkdir lib
+git mv src/one src/two lib/
+git rm -q src/three
+git commit -q -am r2-directory
+
+echo dir/c2 >> dir/c
+git commit -q -am r3-subtree
+
+mkdir similar
+seq 1 20 > similar/text
+{ seq 1 20; printf '\0\n'; } > similar/binary
+git add similar
+git commit -q -m r4-prep
+
+git mv similar/text similar/text-moved
+echo 21 >> similar/text-moved
+git mv similar/binary similar/binary-moved
+echo 21 >> similar/binary-moved
+git commit -q -am r4-similarity
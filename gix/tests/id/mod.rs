@@ -68,6 +68,7 @@ fn display_and_debug() -> crate::Result {
 }
 
 mod ancestors {
+    use gix::prelude::ObjectIdExt;
     use gix_traverse::commit;
 
     #[test]
@@ -99,4 +100,40 @@ mod ancestors {
         );
         Ok(())
     }
+
+    #[test]
+    fn commit_graph_is_used_if_present() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_repo_with_fork_and_dates.sh")?;
+        let mut expected = repo
+            .head_id()?
+            .ancestors()
+            .all()?
+            .map(|id| id.map(gix::Id::detach))
+            .collect::<Result<Vec<_>, _>>()?;
+        let status = std::process::Command::new("git")
+            .args(["commit-graph", "write", "--no-progress", "--reachable"])
+            .current_dir(repo.git_dir())
+            .status()?;
+        assert!(status.success(), "git commit-graph write failed");
+        for id in &expected {
+            let hex = id.to_hex().to_string();
+            std::fs::remove_file(repo.objects.store_ref().path().join(&hex[..2]).join(&hex[2..]))?;
+        }
+
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        let head = expected[0].attach(&repo);
+        let mut actual = head
+            .ancestors()
+            .all()?
+            .map(|id| id.map(gix::Id::detach))
+            .collect::<Result<Vec<_>, _>>()?;
+        actual.sort();
+        expected.sort();
+        assert_eq!(actual, expected, "all commits are obtained from the commit-graph");
+
+        let mut walk = head.ancestors().use_commit_graph(false).all()?;
+        assert!(walk.next().is_none(), "without it, the commits can't be found");
+        assert_eq!(walk.is_shallow, Some(true));
+        Ok(())
+    }
 }
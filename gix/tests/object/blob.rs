@@ -1,2 +1,441 @@
 // TODO: needs repos with specific known objects for proper testing
-mod diff {}
+mod diff {
+    use gix::{
+        bstr::{BStr, ByteSlice},
+        object::blob::diff::Platform,
+    };
+    use gix_diff::blob::{unified::DEFAULT_CONTEXT_LEN, Algorithm};
+
+    fn patch(old: &str, new: &str, old_path: Option<&str>, new_path: Option<&str>) -> gix::bstr::BString {
+        Platform::from_bytes(old.as_bytes(), new.as_bytes(), Algorithm::Myers).to_unified_patch(
+            old_path.map(Into::into),
+            new_path.map(Into::into),
+            DEFAULT_CONTEXT_LEN,
+        )
+    }
+
+    #[test]
+    fn unified_patch_of_modification() {
+        assert_eq!(
+            patch("a\nb\nc", "a\nB\nc", Some("f"), Some("f")),
+            "--- a/f\n+++ b/f\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn unified_patch_of_additions_and_deletions_uses_dev_null() {
+        assert_eq!(
+            patch("", "new\n", None, Some("f")),
+            "--- /dev/null\n+++ b/f\n@@ -0,0 +1 @@\n+new\n"
+        );
+        assert_eq!(
+            patch("old\n", "", Some("f"), None),
+            "--- a/f\n+++ /dev/null\n@@ -1 +0,0 @@\n-old\n"
+        );
+    }
+
+    #[test]
+    fn unified_patch_of_renames() {
+        assert_eq!(
+            patch("a\n", "a\n", Some("old"), Some("new")),
+            "rename from old\nrename to new\n",
+            "pure renames only have rename headers"
+        );
+        assert_eq!(
+            patch("a\nb\n", "a\nc\n", Some("old"), Some("dir/new")),
+            "rename from old\nrename to dir/new\n--- a/old\n+++ b/dir/new\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+    }
+
+    #[test]
+    fn unified_patch_quotes_paths_like_git() {
+        assert_eq!(
+            patch("a\n", "b\n", Some("caf\u{e9}\t\"x"), Some("caf\u{e9}\t\"x")),
+            "--- \"a/caf\\303\\251\\t\\\"x\"\n+++ \"b/caf\\303\\251\\t\\\"x\"\n@@ -1 +1 @@\n-a\n+b\n"
+        );
+        assert_eq!(
+            patch("a\n", "b\n", None, Some("sp ace")),
+            "--- /dev/null\n+++ b/sp ace\t\n@@ -1 +1 @@\n-a\n+b\n",
+            "labels with spaces are terminated with a tab"
+        );
+        assert_eq!(
+            patch("a\n", "a\n", Some("back\\slash"), Some("n w")),
+            "rename from \"back\\\\slash\"\nrename to n w\n"
+        );
+        assert_eq!(
+            Platform::from_bytes(b"a\n", b"a\n", Algorithm::Myers).to_unified_patch(
+                Some(b"invalid-\xff".as_bstr()),
+                Some("f".into()),
+                DEFAULT_CONTEXT_LEN
+            ),
+            "rename from \"invalid-\\377\"\nrename to f\n",
+            "paths which aren't valid UTF-8 are retained"
+        );
+        assert_eq!(
+            patch("a\0", "b\0", Some("b in"), Some("b in")),
+            "Binary files a/b in and b/b in differ\n"
+        );
+    }
+
+    #[test]
+    fn unified_patch_of_binary_blobs() {
+        assert_eq!(
+            patch("a\0", "b\0", Some("f"), Some("f")),
+            "Binary files a/f and b/f differ\n"
+        );
+        assert_eq!(patch("a\0", "a\0", Some("f"), Some("f")), BStr::new(""));
+    }
+
+    mod whitespace {
+        use gix::object::blob::diff::{Platform, WhitespaceMode};
+        use gix_diff::blob::{unified::DEFAULT_CONTEXT_LEN, Algorithm};
+
+        const OLD: &str = "fn f() {\n  a;\n  b  c;\n}\ne;\n";
+        const REINDENTED: &str = "\nfn f() {\n    a;\n    b c;\n}\n  e;\n";
+
+        fn platform<'a>(old: &'a str, new: &'a str, whitespace: WhitespaceMode) -> Platform<'a, 'a> {
+            let mut platform = Platform::from_bytes(old.as_bytes(), new.as_bytes(), Algorithm::Myers);
+            platform.whitespace = whitespace;
+            platform
+        }
+
+        fn counts(whitespace: WhitespaceMode) -> (u32, u32) {
+            let counts = platform(OLD, REINDENTED, whitespace).line_counts();
+            (counts.removals, counts.insertions)
+        }
+
+        #[test]
+        fn exact() {
+            assert_eq!(
+                counts(WhitespaceMode::Exact),
+                (3, 4),
+                "all reindented lines and the blank line changed"
+            );
+        }
+
+        #[test]
+        fn ignore_all() {
+            assert_eq!(
+                counts(WhitespaceMode::IgnoreAll),
+                (0, 1),
+                "only the blank line was added"
+            );
+            assert_eq!(
+                platform(OLD, REINDENTED, WhitespaceMode::IgnoreAll).to_unified_patch(
+                    Some("f".into()),
+                    Some("f".into()),
+                    DEFAULT_CONTEXT_LEN
+                ),
+                "--- a/f\n+++ b/f\n@@ -1,3 +1,4 @@\n+\n fn f() {\n   a;\n   b  c;\n",
+                "unchanged lines are shown as they were"
+            );
+        }
+
+        #[test]
+        fn ignore_change() {
+            assert_eq!(
+                counts(WhitespaceMode::IgnoreChange),
+                (1, 2),
+                "indentation changed in amount, but it's new for the last line"
+            );
+            let diff = platform("a  b \n", "a\tb\n", WhitespaceMode::IgnoreChange);
+            assert_eq!(
+                diff.line_counts().insertions,
+                0,
+                "sequences of whitespace are the same, and trailing whitespace is ignored"
+            );
+        }
+
+        #[test]
+        fn ignore_blank_lines() {
+            assert_eq!(
+                counts(WhitespaceMode::IgnoreBlankLines),
+                (3, 3),
+                "the blank line is ignored, but not the reindented lines"
+            );
+            assert_eq!(
+                platform("a\nb\n", "a\n\n\nb\n\n", WhitespaceMode::IgnoreBlankLines).to_unified_patch(
+                    Some("f".into()),
+                    Some("f".into()),
+                    DEFAULT_CONTEXT_LEN
+                ),
+                "",
+                "there is nothing to show if only blank lines are added"
+            );
+            assert_eq!(
+                platform("a\nb\n", "a\n\nc\n", WhitespaceMode::IgnoreBlankLines).to_unified_patch(
+                    Some("f".into()),
+                    Some("f".into()),
+                    DEFAULT_CONTEXT_LEN
+                ),
+                "--- a/f\n+++ b/f\n@@ -1,2 +1,3 @@\n a\n-b\n+\n+c\n",
+                "blank lines are shown if they are part of other changes"
+            );
+        }
+
+        #[test]
+        fn changes_to_ignored_whitespace_only_have_nothing_to_show() {
+            for whitespace in [WhitespaceMode::IgnoreAll, WhitespaceMode::IgnoreChange] {
+                assert_eq!(
+                    platform("a\n  b\n", "a\n\tb\n", whitespace).to_unified_patch(
+                        Some("f".into()),
+                        Some("f".into()),
+                        DEFAULT_CONTEXT_LEN
+                    ),
+                    "",
+                    "{whitespace:?}"
+                );
+            }
+        }
+    }
+
+    mod conversion {
+        use gix::object::blob::diff::{Conversion, Platform};
+        use gix_diff::blob::Algorithm;
+
+        fn conversion(attributes: &str, autocrlf: bool) -> Conversion {
+            Conversion::from_attributes(
+                gix::attrs::parse::Iter::new(attributes.into()).map(|attr| attr.expect("valid")),
+                autocrlf,
+            )
+        }
+
+        #[test]
+        fn line_endings_are_normalized_only_if_requested() {
+            let (old, new) = (&b"a\nb\n"[..], &b"a\r\nb\r\n"[..]);
+            let diff = Platform::from_bytes(old, new, Algorithm::Myers);
+            assert_eq!(diff.line_counts().insertions, 2, "by default, data is diffed as it is");
+
+            let diff = Platform::from_bytes(old, new, Algorithm::Myers).with_conversion(Conversion::Text);
+            let counts = diff.line_counts();
+            assert_eq!((counts.removals, counts.insertions), (0, 0));
+            assert_eq!(
+                diff.to_unified_patch(Some("f".into()), Some("f".into()), 3),
+                "",
+                "there is nothing to show"
+            );
+        }
+
+        #[test]
+        fn auto_leaves_binary_data_alone() {
+            let binary = &b"\0a\r\n"[..];
+            assert_eq!(Conversion::TextAuto.convert(binary).as_ref(), binary);
+            assert_eq!(Conversion::Text.convert(binary).as_ref(), b"\0a\n");
+            assert_eq!(
+                Conversion::TextAuto.convert(b"a\r\nb\rc\r\n").as_ref(),
+                b"a\nb\rc\n",
+                "lone carriage returns are kept"
+            );
+            assert_eq!(Conversion::None.convert(b"a\r\n").as_ref(), b"a\r\n");
+        }
+
+        #[test]
+        fn from_attributes() {
+            assert_eq!(conversion("", false), Conversion::None);
+            assert_eq!(
+                conversion("", true),
+                Conversion::TextAuto,
+                "core.autocrlf is the fallback"
+            );
+            assert_eq!(conversion("text", false), Conversion::Text);
+            assert_eq!(conversion("text=auto", false), Conversion::TextAuto);
+            assert_eq!(
+                conversion("-text", true),
+                Conversion::None,
+                "attributes override autocrlf"
+            );
+            assert_eq!(conversion("binary", true), Conversion::None);
+            assert_eq!(conversion("eol=lf", false), Conversion::Text, "eol implies text");
+            assert_eq!(conversion("eol=crlf -text", false), Conversion::None);
+            assert_eq!(
+                conversion("diff", false),
+                Conversion::None,
+                "other attributes don't matter"
+            );
+        }
+
+        #[test]
+        fn from_attributes_and_config() -> crate::Result {
+            let no_attributes = std::iter::empty::<gix::attrs::AssignmentRef<'_>>;
+            for (value, expected) in [
+                (None, Conversion::None),
+                (Some("false"), Conversion::None),
+                (Some("true"), Conversion::TextAuto),
+                (Some("input"), Conversion::TextAuto),
+            ] {
+                let opts =
+                    crate::util::restricted().config_overrides(value.map(|value| format!("core.autocrlf={value}")));
+                let repo = crate::util::repo_opts("make_basic_repo.sh", opts)?.to_thread_local();
+                assert_eq!(
+                    Conversion::from_attributes_and_config(no_attributes(), &repo)?,
+                    expected,
+                    "{value:?}"
+                );
+            }
+
+            let opts = crate::util::restricted().config_overrides(["core.autocrlf=invalid"]);
+            let repo =
+                crate::util::repo_opts("make_basic_repo.sh", opts.clone().strict_config(true))?.to_thread_local();
+            assert!(Conversion::from_attributes_and_config(no_attributes(), &repo).is_err());
+            let repo = crate::util::repo_opts("make_basic_repo.sh", opts)?.to_thread_local();
+            assert_eq!(
+                Conversion::from_attributes_and_config(no_attributes(), &repo)?,
+                Conversion::None,
+                "invalid values are ignored if the configuration is lenient"
+            );
+            Ok(())
+        }
+    }
+
+    #[cfg(unix)]
+    mod worktree {
+        use std::os::unix::fs::symlink;
+
+        use gix::object::blob::diff::{
+            worktree::{read, Error, Options},
+            Platform,
+        };
+        use gix_testtools::tempfile;
+
+        const FOLLOW: Options = Options { follow_symlinks: true };
+
+        fn worktree() -> crate::Result<(tempfile::TempDir, std::path::PathBuf)> {
+            let dir = tempfile::tempdir()?;
+            let root = dir.path().join("worktree");
+            std::fs::create_dir_all(root.join("dir"))?;
+            std::fs::write(root.join("file"), b"content\n")?;
+            std::fs::write(dir.path().join("outside"), b"secret\n")?;
+            Ok((dir, root))
+        }
+
+        #[test]
+        fn symlinks_are_read_as_their_target_unless_followed() -> crate::Result {
+            let (_dir, root) = worktree()?;
+            symlink("file", root.join("link"))?;
+            symlink("../link", root.join("dir").join("link-to-link"))?;
+
+            assert_eq!(read(&root, "file".into(), Options::default())?, b"content\n");
+            assert_eq!(read(&root, "file".into(), FOLLOW)?, b"content\n");
+            assert_eq!(
+                read(&root, "link".into(), Options::default())?,
+                b"file",
+                "like git, the link target is compared by default"
+            );
+            assert_eq!(read(&root, "link".into(), FOLLOW)?, b"content\n");
+            assert_eq!(
+                read(&root, "dir/link-to-link".into(), FOLLOW)?,
+                b"content\n",
+                "chains of relative links are resolved relative to the directory of each link"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn dangling_symlinks_can_only_be_read_as_their_target() -> crate::Result {
+            let (_dir, root) = worktree()?;
+            symlink("missing", root.join("dangling"))?;
+            assert_eq!(read(&root, "dangling".into(), Options::default())?, b"missing");
+            assert!(matches!(
+                read(&root, "dangling".into(), FOLLOW),
+                Err(Error::DanglingSymlink { target, .. }) if target == root.join("missing")
+            ));
+            assert!(
+                matches!(read(&root, "missing".into(), FOLLOW), Err(Error::Io { .. })),
+                "missing files are plain IO errors"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn symlink_loops_are_detected() -> crate::Result {
+            let (_dir, root) = worktree()?;
+            symlink("b", root.join("a"))?;
+            symlink("a", root.join("b"))?;
+            symlink("self", root.join("self"))?;
+            assert_eq!(read(&root, "a".into(), Options::default())?, b"b");
+            for name in ["a", "b", "self"] {
+                assert!(matches!(
+                    read(&root, name.into(), FOLLOW),
+                    Err(Error::SymlinkLoop { .. })
+                ));
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn symlinks_pointing_outside_of_the_worktree_are_rejected() -> crate::Result {
+            let (dir, root) = worktree()?;
+            symlink("../outside", root.join("relative"))?;
+            symlink(dir.path().join("outside"), root.join("absolute"))?;
+            symlink("dir/../../worktree/file", root.join("back-inside"))?;
+            for name in ["relative", "absolute"] {
+                assert!(matches!(
+                    read(&root, name.into(), FOLLOW),
+                    Err(Error::OutsideWorktree { .. })
+                ));
+            }
+            assert_eq!(
+                read(&root, "relative".into(), Options::default())?,
+                b"../outside",
+                "the target itself can always be read"
+            );
+            assert_eq!(
+                read(&root, "back-inside".into(), FOLLOW)?,
+                b"content\n",
+                "only the location that is eventually pointed to matters"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn paths_must_stay_within_the_worktree() -> crate::Result {
+            let (_dir, root) = worktree()?;
+            for rela_path in ["../outside", "dir/../file", "./file", "/file", "dir//file", "", "dir/"] {
+                for options in [Options::default(), FOLLOW] {
+                    assert!(
+                        matches!(read(&root, rela_path.into(), options), Err(Error::InvalidPath { .. })),
+                        "{rela_path:?}"
+                    );
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn symlinks_in_leading_directories_are_rejected() -> crate::Result {
+            let (dir, root) = worktree()?;
+            std::fs::write(root.join("dir").join("file"), b"inside\n")?;
+            symlink("dir", root.join("dir-link"))?;
+            symlink(dir.path(), root.join("escape"))?;
+            for rela_path in ["dir-link/file", "escape/outside"] {
+                for options in [Options::default(), FOLLOW] {
+                    assert!(matches!(
+                        read(&root, rela_path.into(), options),
+                        Err(Error::SymlinkInLeadingPath { path }) if path == root.join(rela_path.split('/').next().unwrap())
+                    ));
+                }
+            }
+            assert_eq!(read(&root, "dir/file".into(), Options::default())?, b"inside\n");
+            Ok(())
+        }
+
+        #[test]
+        fn blob_against_worktree_file() -> crate::Result {
+            let repo = crate::named_repo("make_diff_repo.sh")?;
+            let blob = repo.rev_parse_single("@^{/c2}:a")?;
+            let (_dir, root) = worktree()?;
+            std::fs::write(root.join("a"), b"a\na1\n")?;
+            symlink("a", root.join("link"))?;
+
+            let diff = Platform::from_id_and_worktree_file(&blob, &root, "a".into(), Options::default())?;
+            assert_eq!(diff.old.as_bytes(), b"a\n");
+            assert_eq!(diff.old.id(), Some(blob), "the blob is kept along with its id");
+            assert_eq!(diff.new.id(), None, "files in the worktree have no id");
+            assert_eq!(diff.line_counts().insertions, 1);
+
+            let diff = Platform::from_id_and_worktree_file(&blob, &root, "link".into(), FOLLOW)?;
+            assert_eq!(diff.new.as_bytes(), b"a\na1\n", "the link was followed");
+            Ok(())
+        }
+    }
+}
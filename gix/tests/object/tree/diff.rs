@@ -2,7 +2,10 @@ use std::convert::Infallible;
 
 use gix_object::{bstr::ByteSlice, tree::EntryMode};
 
-use gix::object::{blob::diff::line::Change, tree::diff::change::Event};
+use gix::object::{
+    blob::diff::line,
+    tree::diff::{change::Event, Change},
+};
 
 use crate::named_repo;
 
@@ -25,30 +28,217 @@ fn changes_against_tree_modified() -> crate::Result {
                     assert_eq!(entry_mode, EntryMode::Blob);
                     assert_eq!(previous_id.object().unwrap().data.as_bstr(), "a\n");
                     assert_eq!(id.object().unwrap().data.as_bstr(), "a\na1\n");
+                    let (previous, new) = change.event.blobs().expect("blob modification").expect("both exist");
+                    assert_eq!(previous.data.as_bstr(), "a\n");
+                    assert_eq!(new.data.as_bstr(), "a\na1\n");
                 }
-                Event::Copy { .. } | Event::Rename { .. } | Event::Deletion { .. } | Event::Addition { .. } => {
+                Event::Copy { .. }
+                | Event::Rename { .. }
+                | Event::Deletion { .. }
+                | Event::Addition { .. }
+                | Event::DirectoryRename { .. } => {
                     unreachable!("only modification is expected")
                 }
             };
 
             let diff = change.event.diff().expect("changed file").expect("objects available");
+            assert!(!diff.is_binary(), "both versions are text");
             let count = diff.line_counts();
             assert_eq!(count.insertions, 1);
             assert_eq!(count.removals, 0);
+            let mut insertions = 0;
             diff.lines(|hunk| {
                 match hunk {
-                    Change::Deletion { .. } => unreachable!("there was no deletion"),
-                    Change::Addition { lines } => assert_eq!(lines, vec!["a1".as_bytes().as_bstr()]),
-                    Change::Modification { .. } => unreachable!("there was no modification"),
+                    line::Change::Deletion { .. } => unreachable!("there was no deletion"),
+                    line::Change::Addition { lines } => {
+                        assert_eq!(lines, vec!["a1".as_bytes().as_bstr()]);
+                        insertions += lines.len() as u32;
+                    }
+                    line::Change::Modification { .. } => unreachable!("there was no modification"),
                 };
                 Ok::<_, Infallible>(())
             })
             .expect("infallible");
+            assert_eq!(
+                count.insertions, insertions,
+                "counting lines yields the same as counting the lines of materialized hunks"
+            );
+            assert_eq!(
+                diff.line_byte_ranges(),
+                vec![gix::diff::blob::byte_ranges::Hunk {
+                    before: 2..2,
+                    after: 2..5
+                }],
+                "'a1\\n' was inserted after 'a\\n'"
+            );
+
+            let chars = diff.char_tokens().expect("small enough");
+            let count = gix::diff::blob::diff(diff.algo, &chars, gix::diff::blob::sink::Counter::default());
+            assert_eq!(count.insertions, 3, "'a1\\n' was added");
+            assert_eq!(count.removals, 0);
             Ok(Default::default())
         })?;
     Ok(())
 }
 
+#[test]
+fn committed_blob_against_bytes_in_memory() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let blob = repo.rev_parse_single("@^{/c3}:a")?.object()?;
+    assert_eq!(blob.data.as_bstr(), "a\na1\n");
+
+    let diff = gix::object::blob::diff::Platform::from_bytes(
+        &blob.data,
+        b"a\na2\na3\n",
+        gix::diff::blob::Algorithm::Histogram,
+    );
+    assert_eq!(
+        diff.old.id(),
+        None,
+        "bytes have no id even if they are the data of a blob"
+    );
+    let count = diff.line_counts();
+    assert_eq!(count.insertions, 2, "a2 and a3 were added");
+    assert_eq!(count.removals, 1, "a1 was removed");
+    assert_eq!(
+        diff.line_byte_ranges(),
+        vec![gix::diff::blob::byte_ranges::Hunk {
+            before: 2..5,
+            after: 2..8
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_final_newline_is_a_change() -> crate::Result {
+    use gix::object::blob::diff::Platform;
+    let algo = gix::diff::blob::Algorithm::Histogram;
+
+    let diff = Platform::from_bytes(b"a\nb", b"a\nb\n", algo);
+    assert!(diff.old_missing_final_newline());
+    assert!(!diff.new_missing_final_newline());
+    let count = diff.line_counts();
+    assert_eq!((count.insertions, count.removals), (1, 1), "the last line changed");
+    let mut hunks = Vec::new();
+    diff.lines(|hunk| {
+        if let line::Change::Modification {
+            lines_before,
+            lines_after,
+        } = hunk
+        {
+            hunks.push((
+                lines_before.iter().map(|line| line.to_string()).collect::<Vec<_>>(),
+                lines_after.iter().map(|line| line.to_string()).collect::<Vec<_>>(),
+            ));
+        }
+        Ok::<_, Infallible>(())
+    })?;
+    assert_eq!(
+        hunks,
+        vec![(vec!["b".to_string()], vec!["b".to_string()])],
+        "lines are passed without terminator even though it was compared"
+    );
+
+    for (old, new) in [(&b"a\nb"[..], &b"a\nb"[..]), (b"a\nb\n", b"a\nb\n")] {
+        let diff = Platform::from_bytes(old, new, algo);
+        assert_eq!(diff.old_missing_final_newline(), diff.new_missing_final_newline());
+        let count = diff.line_counts();
+        assert_eq!((count.insertions, count.removals), (0, 0), "both sides are the same");
+    }
+
+    let diff = Platform::from_bytes(b"", b"a", algo);
+    assert!(
+        !diff.old_missing_final_newline(),
+        "empty blobs have no line to be missing a newline"
+    );
+    assert!(diff.new_missing_final_newline());
+    Ok(())
+}
+
+#[test]
+fn blobs_of_modification_report_the_missing_side() -> crate::Result {
+    use gix::prelude::ObjectIdExt;
+
+    let repo = named_repo("make_diff_repo.sh")?;
+    let existing = repo.rev_parse_single("@^{/c3}:a")?;
+    let missing = gix::hash::ObjectId::null(repo.object_hash());
+    let modification = |previous_id, id| Event::Modification {
+        previous_entry_mode: EntryMode::Blob,
+        previous_id,
+        entry_mode: EntryMode::Blob,
+        id,
+    };
+
+    let err = modification(missing.attach(&repo), existing)
+        .blobs()
+        .expect("blob modification")
+        .unwrap_err();
+    assert!(matches!(err, gix::object::tree::diff::change::blobs::Error::FindPrevious { id, .. } if id == missing));
+    let err = modification(existing, missing.attach(&repo))
+        .blobs()
+        .expect("blob modification")
+        .unwrap_err();
+    assert!(matches!(err, gix::object::tree::diff::change::blobs::Error::FindNew { id, .. } if id == missing));
+
+    let tree = Event::Modification {
+        previous_entry_mode: EntryMode::Tree,
+        previous_id: existing,
+        entry_mode: EntryMode::Tree,
+        id: existing,
+    };
+    assert!(tree.blobs().is_none(), "only blobs are obtained");
+    Ok(())
+}
+
+#[test]
+fn mode_only_changes() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let blob = repo.write_blob("content\n")?.detach();
+    let other_blob = repo.write_blob("other content\n")?.detach();
+    let tree = |entries: &[(&str, EntryMode, gix::hash::ObjectId)]| -> crate::Result<gix::Tree<'_>> {
+        let tree = gix::objs::Tree {
+            entries: entries
+                .iter()
+                .map(|(filename, mode, oid)| gix::objs::tree::Entry {
+                    mode: *mode,
+                    filename: (*filename).into(),
+                    oid: *oid,
+                })
+                .collect(),
+        };
+        Ok(repo.write_object(&tree)?.object()?.into_tree())
+    };
+    let from = tree(&[
+        ("content-change", EntryMode::Blob, blob),
+        ("mode-and-content-change", EntryMode::Blob, blob),
+        ("mode-change", EntryMode::Blob, blob),
+    ])?;
+    let to = tree(&[
+        ("content-change", EntryMode::Blob, other_blob),
+        ("mode-and-content-change", EntryMode::BlobExecutable, other_blob),
+        ("mode-change", EntryMode::BlobExecutable, blob),
+    ])?;
+
+    let mut actual = Vec::new();
+    from.changes()?
+        .track_path()
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            assert!(matches!(change.event, Event::Modification { .. }));
+            actual.push((change.location.to_owned(), change.event.is_mode_change_only()));
+            Ok(Default::default())
+        })?;
+    assert_eq!(
+        actual,
+        vec![
+            ("content-change".into(), false),
+            ("mode-and-content-change".into(), false),
+            ("mode-change".into(), true),
+        ]
+    );
+    Ok(())
+}
+
 #[test]
 fn changes_against_tree_with_filename_tracking() -> crate::Result {
     let repo = named_repo("make_diff_repo.sh")?;
@@ -88,25 +278,345 @@ fn changes_against_tree_with_filename_tracking() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn changes_against_tree_with_path_relative_to_directory() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let from = repo.empty_tree();
+    let to = tree_named(&repo, ":/c1");
+
+    let mut actual = Vec::new();
+    from.changes()?
+        .track_path_relative_to("dir/")
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            actual.push(change.location.to_owned());
+            Ok(Default::default())
+        })?;
+    actual.sort();
+    assert_eq!(
+        actual,
+        vec!["", "a", "b", "c", "d"],
+        "the directory itself is empty, paths within are relative and paths outside are passed through"
+    );
+
+    let from = tree_named(&repo, "@^{/r1-identity}~1");
+    let to = tree_named(&repo, ":/r1-identity");
+    let mut actual = Vec::new();
+    from.changes()?
+        .track_path_relative_to("dir")
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            if let Event::Rename { source_location, .. } = change.event {
+                actual.push((source_location.to_owned(), change.location.to_owned()));
+            }
+            Ok(Default::default())
+        })?;
+    assert_eq!(
+        actual,
+        vec![("a".into(), "a-moved".into())],
+        "the destination is relative while the source outside of the directory is passed through"
+    );
+    Ok(())
+}
+
+#[test]
+fn following_a_path_ends_at_the_root_commit_which_added_it() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let mut introduced_by = None;
+    for commit_id in repo.head_id()?.ancestors().all()? {
+        let commit = commit_id?.object()?.into_commit();
+        let previous_tree = match commit.parent_ids().next() {
+            Some(parent_id) => parent_id.object()?.into_commit().tree()?,
+            None => repo.empty_tree(),
+        };
+        previous_tree.changes()?.track_path().for_each_to_obtain_tree(
+            &commit.tree()?,
+            |change| -> Result<_, Infallible> {
+                if change.location == "dir/c" {
+                    if let Event::Addition { .. } = change.event {
+                        introduced_by = Some(commit.id);
+                    }
+                }
+                Ok(Default::default())
+            },
+        )?;
+    }
+    let introduced_by = repo
+        .find_object(introduced_by.expect("addition was seen"))?
+        .into_commit();
+    assert_eq!(
+        introduced_by.message()?.summary().as_ref(),
+        "c1",
+        "the root commit is diffed against the empty tree, so it reports the addition"
+    );
+    assert_eq!(introduced_by.parent_ids().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn old_and_new_location_of_all_event_kinds() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let id = repo.empty_tree().id();
+    let mode = EntryMode::Blob;
+    let location = "new".into();
+    let source_location = "old".into();
+    for (event, expected_old, expected_new) in [
+        (Event::Addition { entry_mode: mode, id }, None, Some("new")),
+        (Event::Deletion { entry_mode: mode, id }, Some("new"), None),
+        (
+            Event::Modification {
+                previous_entry_mode: mode,
+                previous_id: id,
+                entry_mode: mode,
+                id,
+            },
+            Some("new"),
+            Some("new"),
+        ),
+        (
+            Event::Rename {
+                source_location,
+                source_entry_mode: mode,
+                source_id: id,
+                entry_mode: mode,
+                id,
+            },
+            Some("old"),
+            Some("new"),
+        ),
+        (
+            Event::Copy {
+                source_location,
+                source_entry_mode: mode,
+                source_id: id,
+                entry_mode: mode,
+                id,
+            },
+            Some("old"),
+            Some("new"),
+        ),
+    ] {
+        let change = Change { location, event };
+        assert_eq!(change.old_location(), expected_old.map(Into::into), "{event:?}");
+        assert_eq!(change.new_location(), expected_new.map(Into::into), "{event:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn depth_of_changes_at_various_nesting_levels() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let to = tree_named(&repo, ":/c1");
+    let mut actual = Vec::new();
+    repo.empty_tree()
+        .changes()?
+        .track_path()
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            actual.push((change.location.to_owned(), change.depth()));
+            Ok(Default::default())
+        })?;
+    actual.sort();
+    assert_eq!(
+        actual,
+        vec![
+            ("a".into(), 1),
+            ("b".into(), 1),
+            ("d".into(), 1),
+            ("dir".into(), 1),
+            ("dir/c".into(), 2)
+        ]
+    );
+
+    repo.empty_tree()
+        .changes()?
+        .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+            assert_eq!(change.depth(), 0, "without tracked paths, there is no depth");
+            Ok(Default::default())
+        })?;
+    Ok(())
+}
+
+#[test]
+fn changes_of_subtree() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let from = tree_named(&repo, "@^{/r3-subtree}~1");
+    let to = tree_named(&repo, ":/r3-subtree");
+
+    let mut actual = Vec::new();
+    from.changes()?.track_path().for_each_to_obtain_subtree(
+        &to,
+        "dir".into(),
+        true,
+        |change| -> Result<_, Infallible> {
+            assert!(
+                matches!(change.event, Event::Modification { .. }),
+                "only modifications are expected"
+            );
+            actual.push(change.location.to_owned());
+            Ok(Default::default())
+        },
+    )?;
+    assert_eq!(actual, vec!["dir/c"], "locations are relative to the root of the trees");
+
+    let err = from
+        .changes()?
+        .for_each_to_obtain_subtree(&to, "lib".into(), true, |_change| -> Result<_, Infallible> {
+            unreachable!("the subtree is unchanged")
+        })
+        .unwrap_err();
+    assert!(
+        matches!(err, gix::object::tree::diff::for_each::Error::SubtreeOutsideDiffers { ref location } if location.is_empty()),
+        "the 'dir' tree differs at the root: {err:?}"
+    );
+    from.changes()?
+        .for_each_to_obtain_subtree(&to, "lib".into(), false, |_change| -> Result<_, Infallible> {
+            unreachable!("the subtree is unchanged, and changes elsewhere go unnoticed without verification")
+        })?;
+    Ok(())
+}
+
+#[test]
+fn changes_sort_in_the_order_git_lists_them() {
+    use gix::object::tree::diff::{change::EventDetached, ChangeDetached};
+    let change = |location: &str, entry_mode: EntryMode| ChangeDetached {
+        location: location.into(),
+        event: EventDetached::Addition {
+            entry_mode,
+            id: gix::ObjectId::null(gix::hash::Kind::Sha1),
+        },
+    };
+    let expected = vec![
+        change("a", EntryMode::Blob),
+        change("a-c", EntryMode::Blob),
+        change("a.txt", EntryMode::BlobExecutable),
+        change("a", EntryMode::Tree),
+        change("a/b", EntryMode::Link),
+        change("a/b/c", EntryMode::Blob),
+        change("a/bc", EntryMode::Blob),
+        ChangeDetached {
+            location: "b".into(),
+            event: EventDetached::DirectoryRename {
+                source_location: "z".into(),
+            },
+        },
+        change("b/c", EntryMode::Commit),
+    ];
+    let mut actual: Vec<_> = [4, 8, 0, 6, 2, 7, 1, 3, 5]
+        .iter()
+        .map(|idx| expected[*idx].clone())
+        .collect();
+    actual.sort_by(|a, b| a.cmp_by_path(b));
+    assert_eq!(
+        actual, expected,
+        "trees sort as if they had a trailing slash, and before the entries within them"
+    );
+}
+
+#[test]
+fn diff_id_depends_on_trees_and_options() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let from = tree_named(&repo, ":/c1");
+    let to = tree_named(&repo, ":/c2");
+    let other = tree_named(&repo, ":/c3");
+
+    let id = from.changes()?.diff_id(&to)?;
+    assert_eq!(from.changes()?.diff_id(&to)?, id, "the same inputs produce the same id");
+    assert_eq!(
+        from.changes()?.rename_cache_size(0).diff_id(&to)?,
+        id,
+        "options which don't affect the outcome are ignored"
+    );
+
+    assert_ne!(from.changes()?.diff_id(&other)?, id, "trees are part of the id");
+    assert_ne!(to.changes()?.diff_id(&from)?, id, "the direction matters");
+    assert_ne!(from.changes()?.track_path().diff_id(&to)?, id);
+    assert_ne!(
+        from.changes()?.track_path_relative_to("dir").diff_id(&to)?,
+        from.changes()?.track_path().diff_id(&to)?
+    );
+    assert_ne!(from.changes()?.track_renames(None).diff_id(&to)?, id);
+    assert_ne!(from.changes()?.with_pathspecs(["dir/"])?.diff_id(&to)?, id);
+    let renames = gix::object::tree::diff::Renames::default();
+    assert_ne!(
+        from.changes()?
+            .track_renames(Some(gix::object::tree::diff::Renames {
+                percentage: Some(0.9),
+                ..renames
+            }))
+            .diff_id(&to)?,
+        id
+    );
+    let with_percentage = |percentage| -> crate::Result<gix::ObjectId> {
+        Ok(from
+            .changes()?
+            .track_renames(Some(gix::object::tree::diff::Renames { percentage, ..renames }))
+            .diff_id(&to)?)
+    };
+    assert_eq!(
+        with_percentage(Some(1.0))?,
+        with_percentage(Some(1.5))?,
+        "percentages are normalized"
+    );
+    assert_ne!(
+        with_percentage(None)?,
+        with_percentage(Some(1.0))?,
+        "a full percentage still compares content if whitespace is ignored, which no percentage never does"
+    );
+    assert_ne!(
+        from.changes()?
+            .track_renames(Some(gix::object::tree::diff::Renames {
+                missing_blob: gix::object::tree::diff::renames::MissingBlob::SkipSimilarity,
+                ..renames
+            }))
+            .diff_id(&to)?,
+        id,
+        "the handling of missing blobs affects the outcome"
+    );
+    assert_ne!(
+        from.changes()?
+            .track_renames(Some(gix::object::tree::diff::Renames {
+                rename_empty: false,
+                ..renames
+            }))
+            .diff_id(&to)?,
+        id,
+        "the matching of empty blobs affects the outcome"
+    );
+    assert_eq!(
+        with_percentage(Some(-0.0))?,
+        with_percentage(Some(0.0))?,
+        "negative zero is the same as zero"
+    );
+    assert_eq!(
+        with_percentage(Some(f32::NAN))?,
+        with_percentage(Some(-f32::NAN))?,
+        "all NaNs are the same"
+    );
+    assert_ne!(with_percentage(Some(f32::NAN))?, with_percentage(Some(1.0))?);
+    let with_directory_renames = |directory_renames| -> crate::Result<gix::ObjectId> {
+        Ok(from
+            .changes()?
+            .track_renames(Some(gix::object::tree::diff::Renames {
+                directory_renames,
+                ..renames
+            }))
+            .diff_id(&to)?)
+    };
+    assert_eq!(with_directory_renames(Some(-0.0))?, with_directory_renames(Some(0.0))?);
+    assert_ne!(with_directory_renames(Some(0.5))?, with_directory_renames(None)?);
+    Ok(())
+}
+
 fn tree_named<'repo>(repo: &'repo gix::Repository, rev_spec: &str) -> gix::Tree<'repo> {
-    repo.rev_parse_single(rev_spec)
-        .unwrap()
-        .object()
-        .unwrap()
-        .peel_to_kind(gix::object::Kind::Tree)
-        .unwrap()
-        .into_tree()
+    repo.rev_parse_tree(rev_spec).unwrap()
 }
 
 mod renames {
     use crate::object::tree::diff::tree_named;
     use crate::util::named_repo;
     use gix::object::tree::diff::change::Event;
-    use gix_ref::bstr::BStr;
+    use gix_ref::bstr::{BStr, BString};
     use std::convert::Infallible;
 
     #[test]
-    #[ignore = "needs a second round PR to finish it"]
     fn identity() -> crate::Result {
         let repo = named_repo("make_diff_repo.sh")?;
         let from = tree_named(&repo, "@^{/r1-identity}~1");
@@ -116,7 +626,7 @@ mod renames {
         from.changes()?
             .track_path()
             .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
-                if !change.event.entry_mode().is_tree() {
+                if !change.is_tree_change() {
                     if let Event::Rename { source_location, .. } = change.event {
                         actual.push(source_location.to_owned());
                         actual.push(change.location.to_owned());
@@ -127,4 +637,778 @@ mod renames {
         assert_eq!(actual, vec![BStr::new("a"), "dir/a-moved".into()]);
         Ok(())
     }
+
+    #[test]
+    fn blob_similarity() {
+        use gix::{diff::blob::Algorithm, object::tree::diff::blob_similarity};
+        for algorithm in [Algorithm::Histogram, Algorithm::Myers, Algorithm::MyersMinimal] {
+            assert_eq!(blob_similarity(b"a\nb\n", b"a\nb\n", algorithm), 1.0, "identical");
+            assert_eq!(blob_similarity(b"a\nb\n", b"c\nd\n", algorithm), 0.0, "disjoint");
+            assert_eq!(
+                blob_similarity(b"a\nb\nc\nd\n", b"a\nx\nc\ny\n", algorithm),
+                0.5,
+                "half of the lines are shared"
+            );
+            assert_eq!(
+                blob_similarity(b"a\nb\n", b"a\nb\nc\nd\n", algorithm),
+                blob_similarity(b"a\nb\nc\nd\n", b"a\nb\n", algorithm),
+                "it's symmetric"
+            );
+            assert_eq!(
+                blob_similarity(b"a\nb\n", b"a\nb\nc\nd\n", algorithm),
+                0.5,
+                "added lines count against the similarity"
+            );
+            assert_eq!(blob_similarity(b"", b"", algorithm), 1.0, "empty blobs are identical");
+            assert_eq!(blob_similarity(b"", b"a\n", algorithm), 0.0);
+            assert_eq!(
+                blob_similarity(b"a\n", b"  a\n", algorithm),
+                0.0,
+                "whitespace is compared exactly"
+            );
+        }
+    }
+
+    #[test]
+    fn blob_similarity_streaming() -> crate::Result {
+        use gix::{
+            diff::blob::Algorithm,
+            object::tree::diff::{blob_similarity, blob_similarity_streaming},
+        };
+        use std::io::{BufReader, Read};
+
+        /// Generate `num_lines` lines on the fly, with the ones in the `changed` range being different.
+        struct Lines {
+            line: usize,
+            num_lines: usize,
+            changed: std::ops::Range<usize>,
+            pending: Vec<u8>,
+            pos: usize,
+        }
+
+        impl Lines {
+            fn new(num_lines: usize, changed: std::ops::Range<usize>) -> Self {
+                Lines {
+                    line: 0,
+                    num_lines,
+                    changed,
+                    pending: Vec::new(),
+                    pos: 0,
+                }
+            }
+        }
+
+        impl Read for Lines {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.pos == self.pending.len() {
+                    if self.line == self.num_lines {
+                        return Ok(0);
+                    }
+                    self.pending.clear();
+                    self.pos = 0;
+                    let prefix = if self.changed.contains(&self.line) {
+                        "changed"
+                    } else {
+                        "line"
+                    };
+                    self.pending
+                        .extend_from_slice(format!("{prefix} {}\n", self.line).as_bytes());
+                    self.line += 1;
+                }
+                let len = buf.len().min(self.pending.len() - self.pos);
+                buf[..len].copy_from_slice(&self.pending[self.pos..][..len]);
+                self.pos += len;
+                Ok(len)
+            }
+        }
+
+        let num_lines = 200_000;
+        let unchanged = || Lines::new(num_lines, 0..0);
+        let similarity = |old: Lines, new: Lines| {
+            blob_similarity_streaming(
+                BufReader::with_capacity(7, old),
+                BufReader::new(new),
+                Algorithm::Histogram,
+            )
+        };
+        assert_eq!(similarity(unchanged(), unchanged())?, 1.0, "identical");
+        assert_eq!(
+            similarity(unchanged(), Lines::new(num_lines, 0..num_lines))?,
+            0.0,
+            "disjoint"
+        );
+        assert_eq!(
+            similarity(unchanged(), Lines::new(num_lines, num_lines / 4..num_lines * 3 / 4))?,
+            0.5,
+            "half of the lines were changed"
+        );
+        assert_eq!(
+            similarity(unchanged(), Lines::new(num_lines / 4, 0..0))?,
+            0.25,
+            "truncated"
+        );
+
+        let read_all = |mut lines: Lines| -> std::io::Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            lines.read_to_end(&mut buf)?;
+            Ok(buf)
+        };
+        let (old, new) = (
+            read_all(Lines::new(10_000, 0..0))?,
+            read_all(Lines::new(10_000, 2_000..3_000))?,
+        );
+        for (old, new) in [(&old[..], &new[..]), (b"a\nb", b"a\nb\n"), (b"", b"a")] {
+            assert_eq!(
+                blob_similarity_streaming(old, new, Algorithm::Histogram)?,
+                blob_similarity(old, new, Algorithm::Histogram),
+                "the result is the same as if the blobs were in memory"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn max_file_size_from_configuration() -> crate::Result {
+        use gix::object::tree::diff::{renames::DEFAULT_MAX_FILE_SIZE, Renames};
+        let renames = |config: &'static str| -> crate::Result<Option<Renames>> {
+            let config = gix::config::File::try_from(config)?;
+            Ok(Renames::try_from_config(&config, false)?)
+        };
+        assert_eq!(
+            renames("[diff]\nrenames = true")?.expect("enabled").max_file_size,
+            Some(DEFAULT_MAX_FILE_SIZE)
+        );
+        assert_eq!(
+            renames("[diff]\nrenames = true\n[core]\nbigFileThreshold = 1k")?
+                .expect("enabled")
+                .max_file_size,
+            Some(1024)
+        );
+        assert_eq!(Renames::default().max_file_size, Some(DEFAULT_MAX_FILE_SIZE));
+        Ok(())
+    }
+
+    #[test]
+    fn limit_from_configuration() -> crate::Result {
+        use gix::object::tree::diff::Renames;
+        let limit = |config: &'static str| -> crate::Result<usize> {
+            let config = gix::config::File::try_from(config)?;
+            Ok(Renames::try_from_config(&config, false)?.expect("enabled").limit)
+        };
+        assert_eq!(limit("[diff]\nrenames = true")?, 1000, "the default is the one of git");
+        assert_eq!(limit("[diff]\nrenames = true\nrenameLimit = 5")?, 5);
+        assert_eq!(
+            limit("[diff]\nrenames = true\nrenameLimit = 0")?,
+            0,
+            "0 is passed on as is, which means there is no limit"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tracked_by_default_unless_disabled_by_configuration() -> crate::Result {
+        use gix::object::tree::diff::Renames;
+        let renames = |config: &'static str| -> crate::Result<Option<Renames>> {
+            let config = gix::config::File::try_from(config)?;
+            Ok(Renames::try_from_config(&config, false)?)
+        };
+        assert_eq!(
+            renames("")?,
+            Some(Renames::default()),
+            "like git, renames are tracked by default"
+        );
+        assert_eq!(
+            renames("[diff]\nrenameLimit = 5")?.expect("enabled").limit,
+            5,
+            "other values are used even if `diff.renames` isn't set"
+        );
+        assert_eq!(renames("[diff]\nrenames = false")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn copies_from_configuration() -> crate::Result {
+        use gix::object::tree::diff::Renames;
+        let renames = |config: &'static str| -> crate::Result<Renames> {
+            let config = gix::config::File::try_from(config)?;
+            Ok(Renames::try_from_config(&config, false)?.expect("enabled"))
+        };
+        assert_eq!(renames("[diff]\nrenames = true")?.copies, None);
+        let renames = renames("[diff]\nrenames = copies")?;
+        let copies = renames.copies.expect("copies are enabled");
+        assert_eq!(copies.percentage, None);
+        assert!(
+            copies.use_rename_threshold,
+            "like git, copies need the same similarity as renames"
+        );
+        assert_eq!(renames.copy_percentage(), renames.percentage);
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_by_configuration() -> crate::Result {
+        let repo = crate::util::repo_opts(
+            "make_diff_repo.sh",
+            crate::util::restricted().config_overrides(["diff.renames=false"]),
+        )?
+        .to_thread_local();
+        let from = tree_named(&repo, "@^{/r1-identity}~1");
+        let to = tree_named(&repo, ":/r1-identity");
+
+        let mut renames = 0;
+        from.changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                renames += usize::from(matches!(change.event, Event::Rename { .. }));
+                Ok(Default::default())
+            })?;
+        assert_eq!(renames, 0, "renames aren't tracked if they are turned off");
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_despite_configuration() -> crate::Result {
+        let repo = crate::util::repo_opts(
+            "make_diff_repo.sh",
+            crate::util::restricted().config_overrides(["diff.renames=copies"]),
+        )?
+        .to_thread_local();
+        let from = tree_named(&repo, "@^{/r1-identity}~1");
+        let to = tree_named(&repo, ":/r1-identity");
+
+        let mut actual = Vec::new();
+        from.changes()?
+            .track_path()
+            .track_renames(None)
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                if !change.is_tree_change() {
+                    let kind = match change.event {
+                        Event::Addition { .. } => "addition",
+                        Event::Deletion { .. } => "deletion",
+                        _ => "other",
+                    };
+                    actual.push((kind, change.location.to_owned()));
+                }
+                Ok(Default::default())
+            })?;
+        assert_eq!(
+            actual,
+            vec![
+                ("deletion", BStr::new("a").to_owned()),
+                ("addition", "dir/a-moved".into())
+            ],
+            "the rename is passed as separate deletion and addition"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn platform_can_be_reused_for_multiple_diffs() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let from = tree_named(&repo, "@^{/r1-identity}~1");
+        let to = tree_named(&repo, ":/r1-identity");
+        let unrelated = tree_named(&repo, ":/c1");
+
+        let mut platform = from.changes()?;
+        platform.track_path();
+        let mut renames_to = |other: &gix::Tree<'_>| -> crate::Result<_> {
+            let mut actual = Vec::new();
+            platform.for_each_to_obtain_tree(other, |change| -> Result<_, Infallible> {
+                if let Event::Rename { source_location, .. } = change.event {
+                    actual.push((source_location.to_owned(), change.location.to_owned()));
+                }
+                Ok(Default::default())
+            })?;
+            Ok(actual)
+        };
+        let expected = vec![(BStr::new("a").to_owned(), "dir/a-moved".into())];
+        assert_eq!(renames_to(&to)?, expected);
+        assert_eq!(
+            renames_to(&unrelated)?,
+            vec![],
+            "nothing is left from the previous diff"
+        );
+        assert_eq!(renames_to(&to)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn token_cache_is_passed_from_diff_to_diff() -> crate::Result {
+        use gix::object::tree::diff::renames::TokenCache;
+        let (repo, _tmp) = crate::repo_rw("make_diff_repo.sh")?;
+        let renames = |repo: &gix::Repository,
+                       from: &str,
+                       to: &str,
+                       cache: Option<TokenCache>|
+         -> crate::Result<(Vec<_>, Option<TokenCache>)> {
+            let (from, to) = (tree_named(repo, from), tree_named(repo, to));
+            let mut actual = Vec::new();
+            let mut platform = from.changes()?;
+            if let Some(cache) = cache {
+                platform.rename_token_cache(cache);
+            }
+            platform
+                .track_path()
+                .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                    if let Event::Rename { source_location, .. } = change.event {
+                        actual.push((source_location.to_owned(), change.location.to_owned()));
+                    }
+                    Ok(Default::default())
+                })?;
+            Ok((actual, platform.take_rename_token_cache()))
+        };
+        let (before, after) = ("@^{/r4-similarity}~1", "@^{/r4-similarity}");
+
+        let (expected, no_cache) = renames(&repo, before, after, None)?;
+        assert!(no_cache.is_none());
+        assert_eq!(
+            expected,
+            [("similar/text".into(), "similar/text-moved".into())],
+            "the blobs have to be compared to find this rename"
+        );
+        let (expected_reversed, _) = renames(&repo, after, before, None)?;
+
+        let (actual, cache) = renames(&repo, before, after, Some(TokenCache::new(1000, 2)))?;
+        assert_eq!(actual, expected, "the cache doesn't affect the outcome");
+        assert!(cache.is_some(), "the cache is handed back after each diff");
+
+        for path in ["similar/text", "similar/binary"] {
+            for rev in [before, after] {
+                let blob = match repo.rev_parse_single(format!("{rev}:{path}").as_str()) {
+                    Ok(id) => id.detach(),
+                    Err(_) => repo.rev_parse_single(format!("{rev}:{path}-moved").as_str())?.detach(),
+                };
+                let hex = blob.to_hex().to_string();
+                std::fs::remove_file(repo.objects.store_ref().path().join(&hex[..2]).join(&hex[2..]))?;
+            }
+        }
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        let (actual, _) = renames(&repo, after, before, cache)?;
+        assert_eq!(
+            actual, expected_reversed,
+            "the second diff finds no blob as all of them were removed, and only knows their lines from the cache"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn directories() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let from = tree_named(&repo, "@^{/r2-directory}~1");
+        let to = tree_named(&repo, ":/r2-directory");
+
+        let directory_renames = |fraction: Option<f32>| -> crate::Result<_> {
+            let mut actual = Vec::new();
+            from.changes()?
+                .track_path()
+                .track_renames(Some(gix::object::tree::diff::Renames {
+                    directory_renames: fraction,
+                    ..Default::default()
+                }))
+                .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                    if let Event::DirectoryRename { source_location } = change.event {
+                        actual.push((source_location.to_owned(), change.location.to_owned()));
+                    }
+                    Ok(Default::default())
+                })?;
+            Ok(actual)
+        };
+
+        assert_eq!(
+            directory_renames(Some(0.5))?,
+            vec![(BStr::new("src").to_owned(), "lib".into())],
+            "two of three removed files were renamed into the same directory"
+        );
+        assert_eq!(
+            directory_renames(Some(1.0))?,
+            vec![],
+            "one file was deleted instead of being renamed"
+        );
+        assert_eq!(directory_renames(None)?, vec![], "directory renames are off by default");
+        Ok(())
+    }
+
+    /// Return the `(source, destination)` pairs of renames with `renames` between the commits `r4-prep` and
+    /// `r4-similarity` of `repo`, which renamed and edited a text and a binary file.
+    fn similarity_renames(
+        repo: &gix::Repository,
+        renames: gix::object::tree::diff::Renames,
+    ) -> crate::Result<Vec<(BString, BString)>> {
+        let from = tree_named(repo, ":/r4-prep");
+        let to = tree_named(repo, ":/r4-similarity");
+
+        let mut actual = Vec::new();
+        from.changes()?
+            .track_path()
+            .track_renames(Some(renames))
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                if let Event::Rename { source_location, .. } = change.event {
+                    actual.push((source_location.to_owned(), change.location.to_owned()));
+                }
+                Ok(Default::default())
+            })?;
+        Ok(actual)
+    }
+
+    fn text_rename() -> Vec<(BString, BString)> {
+        vec![("similar/text".into(), "similar/text-moved".into())]
+    }
+
+    #[test]
+    fn similar_text_is_renamed_while_binaries_are_only_matched_by_identity() -> crate::Result {
+        use gix::object::tree::diff::Renames;
+        let repo = named_repo("make_diff_repo.sh")?;
+        assert_eq!(
+            similarity_renames(&repo, Renames::default())?,
+            text_rename(),
+            "the binary file was edited just like the text file, but binary files have no similarity"
+        );
+        assert_eq!(
+            similarity_renames(
+                &repo,
+                Renames {
+                    percentage: None,
+                    ..Default::default()
+                }
+            )?,
+            vec![],
+            "edited files are never renamed if only identity is considered"
+        );
+        assert_eq!(
+            similarity_renames(
+                &repo,
+                Renames {
+                    max_file_size: Some(50),
+                    ..Default::default()
+                }
+            )?,
+            vec![],
+            "the text files have more than 50 bytes, so they aren't compared"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn a_limit_of_zero_means_unlimited() -> crate::Result {
+        use gix::object::tree::diff::Renames;
+        let repo = named_repo("make_diff_repo.sh")?;
+        for (limit, expected) in [
+            (1, vec![]),
+            (2, text_rename()),
+            (0, text_rename()),
+            (usize::MAX, text_rename()),
+        ] {
+            assert_eq!(
+                similarity_renames(
+                    &repo,
+                    Renames {
+                        limit,
+                        ..Default::default()
+                    }
+                )?,
+                expected,
+                "with limit {limit}, 2 sources and destinations need 4 comparisons, which exceeds a limit of 1 but not 0"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn missing_blobs() -> crate::Result {
+        use gix::object::tree::diff::{renames::MissingBlob, Renames};
+        let (repo, _tmp) = crate::util::repo_rw("make_diff_repo.sh")?;
+        let moved_text = tree_named(&repo, ":/r4-similarity")
+            .lookup_entry_by_path("similar/text-moved")?
+            .expect("present")
+            .oid()
+            .to_hex()
+            .to_string();
+        std::fs::remove_file(
+            repo.objects
+                .store_ref()
+                .path()
+                .join(&moved_text[..2])
+                .join(&moved_text[2..]),
+        )?;
+
+        assert!(
+            similarity_renames(&repo, Renames::default()).is_err(),
+            "by default, missing blobs are an error"
+        );
+        assert_eq!(
+            similarity_renames(
+                &repo,
+                Renames {
+                    missing_blob: MissingBlob::SkipSimilarity,
+                    ..Default::default()
+                }
+            )?,
+            vec![],
+            "the missing destination can't be similar to anything"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn analysis() -> crate::Result {
+        use gix::object::tree::diff::{
+            renames::{Analysis, Copies},
+            Renames,
+        };
+        let repo = named_repo("make_diff_repo.sh")?;
+        let analyze = |from: &str, to: &str, renames: Option<Renames>| -> crate::Result<_> {
+            let (from, to) = (tree_named(&repo, from), tree_named(&repo, to));
+            Ok(from.changes()?.track_renames(renames).analyze_renames(&to)?)
+        };
+
+        assert_eq!(
+            analyze(":/c1", ":/c1", Some(Renames::default()))?,
+            Some(Analysis::default()),
+            "nothing changed"
+        );
+        let analyze_c1 = |renames: Renames| -> crate::Result<_> {
+            Ok(repo
+                .empty_tree()
+                .changes()?
+                .track_renames(Some(renames))
+                .analyze_renames(&tree_named(&repo, ":/c1"))?)
+        };
+        assert_eq!(
+            analyze_c1(Renames::default())?,
+            Some(Analysis {
+                additions: 4,
+                ..Default::default()
+            }),
+            "all files added in c1 are empty, and like in git, empty files are candidates by default"
+        );
+        assert_eq!(
+            analyze_c1(Renames {
+                rename_empty: false,
+                ..Default::default()
+            })?,
+            Some(Analysis::default()),
+            "unless they are excluded"
+        );
+        assert_eq!(analyze(":/c3", ":/r2-directory", None)?, None, "renames are disabled");
+
+        assert_eq!(
+            analyze(":/c3", ":/r2-directory", Some(Renames::default()))?,
+            Some(Analysis {
+                additions: 3,
+                deletions: 1,
+                modifications: 0,
+                would_skip_similarity: false,
+            }),
+            "'a' was renamed and 'src/one' and 'src/two' were added as 'lib/one' and 'lib/two'"
+        );
+        assert_eq!(
+            analyze(
+                ":/c3",
+                ":/r2-directory",
+                Some(Renames {
+                    limit: 1,
+                    ..Default::default()
+                })
+            )?,
+            Some(Analysis {
+                additions: 3,
+                deletions: 1,
+                modifications: 0,
+                would_skip_similarity: true,
+            }),
+            "3 pairs of additions and deletions exceed a limit of 1"
+        );
+        assert!(
+            !analyze(
+                ":/c3",
+                ":/r2-directory",
+                Some(Renames {
+                    limit: 1,
+                    percentage: None,
+                    ..Default::default()
+                })
+            )?
+            .expect("enabled")
+            .would_skip_similarity,
+            "without similarity checks, nothing is skipped"
+        );
+
+        let copies = Renames {
+            copies: Some(Copies::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            analyze(":/r2-directory", ":/r3-subtree", Some(copies))?,
+            Some(Analysis {
+                modifications: 1,
+                ..Default::default()
+            }),
+            "modifications are only candidates if copies are tracked"
+        );
+        assert_eq!(
+            analyze(":/r2-directory", ":/r3-subtree", Some(Renames::default()))?,
+            Some(Analysis::default())
+        );
+        Ok(())
+    }
+}
+
+mod pathspecs {
+    use std::convert::Infallible;
+
+    use gix::{bstr::BString, object::tree::diff::change::Event};
+
+    use crate::{named_repo, object::tree::diff::tree_named};
+
+    fn changes(specs: &[&str]) -> crate::Result<Vec<(BString, &'static str)>> {
+        changes_in(&named_repo("make_diff_repo.sh")?, specs)
+    }
+
+    fn changes_in(repo: &gix::Repository, specs: &[&str]) -> crate::Result<Vec<(BString, &'static str)>> {
+        let from = tree_named(repo, "@^{/r2-directory}~1");
+        let to = tree_named(repo, ":/r2-directory");
+
+        let mut actual = Vec::new();
+        from.changes()?
+            .track_path()
+            .with_pathspecs(specs)?
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                let kind = match change.event {
+                    Event::Addition { .. } => "addition",
+                    Event::Deletion { .. } => "deletion",
+                    Event::Modification { .. } => "modification",
+                    Event::Rename { .. } => "rename",
+                    Event::Copy { .. } => "copy",
+                    Event::DirectoryRename { .. } => "directory rename",
+                };
+                actual.push((change.location.to_owned(), kind));
+                Ok(Default::default())
+            })?;
+        actual.sort();
+        Ok(actual)
+    }
+
+    #[test]
+    fn include() -> crate::Result {
+        assert_eq!(
+            changes(&["lib/"])?,
+            vec![
+                ("lib".into(), "addition"),
+                ("lib/one".into(), "addition"),
+                ("lib/two".into(), "addition")
+            ],
+            "the deleted files in 'src' don't match, so they can't be rename sources"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn case_is_ignored_if_configured() -> crate::Result {
+        assert_eq!(changes(&["LIB/"])?, vec![], "paths are case-sensitive by default");
+        let repo = crate::util::repo_opts(
+            "make_diff_repo.sh",
+            crate::util::restricted().config_overrides(["core.ignoreCase=true"]),
+        )?
+        .to_thread_local();
+        assert_eq!(changes_in(&repo, &["LIB/"])?, changes(&["lib/"])?);
+        Ok(())
+    }
+
+    #[test]
+    fn exclude() -> crate::Result {
+        assert_eq!(
+            changes(&[":(exclude)src/three"])?,
+            vec![
+                ("lib".into(), "addition"),
+                ("lib/one".into(), "rename"),
+                ("lib/two".into(), "rename"),
+                ("src".into(), "deletion"),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn glob() -> crate::Result {
+        assert_eq!(
+            changes(&["*/t*", ":(exclude)*three"])?,
+            vec![("lib/two".into(), "rename")],
+            "renames are found among the matching changes"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_pathspecs_are_rejected() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let tree = tree_named(&repo, ":/r2-directory");
+        assert!(tree.changes()?.with_pathspecs([""]).is_err());
+        Ok(())
+    }
+}
+
+mod stats {
+    use gix::{
+        bstr::BStr,
+        object::tree::diff::{stats::LineCounts, Stats},
+    };
+
+    use crate::{object::tree::diff::tree_named, util::named_repo};
+
+    fn diff_stats(repo: &gix::Repository, from: &str, to: &str) -> crate::Result<Stats> {
+        let from = tree_named(repo, from);
+        let to = tree_named(repo, to);
+        let mut stats = Stats::default();
+        from.changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&to, |change| stats.record(change))?;
+        Ok(stats)
+    }
+
+    fn counts(insertions: u32, removals: u32) -> LineCounts {
+        LineCounts { insertions, removals }
+    }
+
+    #[test]
+    fn modifications() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let stats = diff_stats(&repo, ":/c1", ":/c3")?;
+        assert_eq!(stats.files().len(), 4);
+        assert_eq!(stats.files()[BStr::new("a")], counts(2, 0));
+        assert_eq!(stats.files()[BStr::new("b")], counts(1, 0));
+        assert_eq!(stats.files()[BStr::new("dir/c")], counts(1, 0));
+        assert_eq!(stats.files()[BStr::new("d")], counts(1, 0));
+        assert_eq!(stats.total_insertions(), 5);
+        assert_eq!(stats.total_removals(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn renames_are_counted_once() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let stats = diff_stats(&repo, "@^{/r1-identity}~1", ":/r1-identity")?;
+        assert_eq!(
+            stats.files().keys().collect::<Vec<_>>(),
+            ["dir/a-moved"],
+            "the rename is recorded under its destination only"
+        );
+        assert_eq!(
+            stats.files()[BStr::new("dir/a-moved")],
+            counts(0, 0),
+            "the content didn't change"
+        );
+
+        let stats = diff_stats(&repo, ":/r2-prep", ":/r2-directory")?;
+        assert_eq!(stats.files().len(), 3);
+        assert_eq!(stats.files()[BStr::new("lib/one")], counts(0, 0));
+        assert_eq!(stats.files()[BStr::new("lib/two")], counts(0, 0));
+        assert_eq!(
+            stats.files()[BStr::new("src/three")],
+            counts(0, 1),
+            "all lines of deletions are removed"
+        );
+        assert_eq!(stats.total_insertions(), 0);
+        assert_eq!(stats.total_removals(), 1);
+        Ok(())
+    }
 }
@@ -2,6 +2,56 @@ mod config_snapshot;
 mod identity;
 mod remote;
 
+mod diff_algorithm {
+    use gix::diff::blob::Algorithm;
+
+    fn repo(algorithm: Option<&str>, strict: bool) -> crate::Result<gix::Repository> {
+        let opts = crate::util::restricted()
+            .config_overrides(algorithm.map(|name| format!("diff.algorithm={name}")))
+            .strict_config(strict);
+        Ok(crate::util::repo_opts("make_basic_repo.sh", opts)?.to_thread_local())
+    }
+
+    #[test]
+    fn myers_is_the_default() -> crate::Result {
+        for strict in [false, true] {
+            assert_eq!(repo(None, strict)?.diff_algorithm()?, Algorithm::Myers);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn valid_values() -> crate::Result {
+        for strict in [false, true] {
+            for (name, expected) in [
+                ("myers", Algorithm::Myers),
+                ("default", Algorithm::Myers),
+                ("minimal", Algorithm::MyersMinimal),
+                ("histogram", Algorithm::Histogram),
+                ("patience", Algorithm::Patience),
+            ] {
+                assert_eq!(repo(Some(name), strict)?.diff_algorithm()?, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_values() -> crate::Result {
+        assert_eq!(
+            repo(Some("foo"), false)?.diff_algorithm()?,
+            Algorithm::Myers,
+            "lenient configuration falls back to the default"
+        );
+
+        assert!(matches!(
+            repo(Some("foo"), true)?.diff_algorithm().unwrap_err(),
+            gix::config::diff::algorithm::Error::Unknown { name } if name == "foo"
+        ));
+        Ok(())
+    }
+}
+
 #[cfg(feature = "blocking-network-client")]
 mod ssh_options {
     use std::ffi::OsStr;
@@ -0,0 +1,135 @@
+use gix::object::tree::diff::{change::EventDetached, ChangeDetached, ChangesByKind};
+use gix_object::tree::EntryMode;
+
+use crate::util::{hex_to_id, named_repo};
+
+#[test]
+fn tree_to_tree() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let changes = repo.diff_tree_to_tree("@^{/r1-identity}~1", ":/r1-identity")?;
+    let blob = hex_to_id("b4f17b61de71d9b2e54ac9e62b1629ae2d97a6a7");
+    assert_eq!(
+        changes,
+        vec![
+            ChangeDetached {
+                location: "dir".into(),
+                event: EventDetached::Modification {
+                    previous_entry_mode: EntryMode::Tree,
+                    previous_id: hex_to_id("e5c63aefe4327cb1c780c71966b678ce8e4225da"),
+                    entry_mode: EntryMode::Tree,
+                    id: hex_to_id("9fc8c9e65a9c48c678f6d5b4cfeffa81b962501c"),
+                },
+            },
+            ChangeDetached {
+                location: "dir/a-moved".into(),
+                event: EventDetached::Rename {
+                    source_location: "a".into(),
+                    source_entry_mode: EntryMode::Blob,
+                    source_id: blob,
+                    entry_mode: EntryMode::Blob,
+                    id: blob,
+                },
+            },
+        ],
+        "commits are peeled to their trees, paths are tracked and renames are found as configured"
+    );
+
+    let old = repo.rev_parse_single("@^{/r1-identity}~1")?;
+    let new = repo.rev_parse_single(":/r1-identity")?.object()?.peel_to_tree()?.id;
+    assert_eq!(
+        repo.diff_tree_to_tree_by_id(old, new)?,
+        changes,
+        "ids of commits and trees can be passed as well"
+    );
+    assert!(repo.diff_tree_to_tree_by_id(old, old)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn tree_to_tree_by_kind() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let changes = repo.diff_tree_to_tree("@^{/r1-identity}~1", ":/r1-identity")?;
+    assert_eq!(
+        changes.iter().map(ChangeDetached::is_tree_change).collect::<Vec<_>>(),
+        [true, false]
+    );
+
+    let by_kind: ChangesByKind = changes.iter().cloned().collect();
+    assert_eq!(
+        by_kind,
+        ChangesByKind {
+            tree_changes: vec![changes[0].clone()],
+            blob_changes: vec![changes[1].clone()],
+        },
+        "changes are separated by kind"
+    );
+
+    let mut all_twice = by_kind.clone();
+    all_twice.extend(changes.iter().cloned());
+    assert_eq!(
+        all_twice.tree_changes.len() + all_twice.blob_changes.len(),
+        4,
+        "more changes can be added later"
+    );
+    assert_eq!(
+        all_twice.blob_changes,
+        vec![changes[1].clone(), changes[1].clone()],
+        "the order of changes is retained"
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_to_tree_with_invalid_specs() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    assert!(matches!(
+        repo.diff_tree_to_tree("does-not-exist", "HEAD"),
+        Err(gix::object::tree::diff::tree_to_tree::Error::RevParse(_))
+    ));
+    let blob = repo.rev_parse_single("HEAD:b")?;
+    assert!(
+        matches!(
+            repo.diff_tree_to_tree_by_id(blob, blob),
+            Err(gix::object::tree::diff::tree_to_tree::Error::PeelToTree(_))
+        ),
+        "blobs can't be peeled to trees"
+    );
+    Ok(())
+}
+
+#[test]
+fn blobs() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let old = repo.rev_parse_blob("@^{/c2}:a")?.id;
+    let new = repo.rev_parse_blob("@^{/c3}:a")?.id;
+
+    let platform = repo.diff_blobs(old, new)?;
+    assert_eq!(platform.old.as_bytes(), b"a\n");
+    assert_eq!(platform.new.as_bytes(), b"a\na1\n");
+    assert_eq!(platform.new.id().map(gix::Id::detach), Some(new));
+    let counts = platform.line_counts();
+    assert_eq!((counts.insertions, counts.removals), (1, 0));
+
+    let counts = repo.diff_blobs(new, old)?.line_counts();
+    assert_eq!((counts.insertions, counts.removals), (0, 1), "the direction matters");
+
+    let missing = gix::ObjectId::from_hex(b"0000000000000000000000000000000000000001")?;
+    assert!(
+        matches!(
+            repo.diff_blobs(old, missing),
+            Err(gix::object::blob::diff::init::Error::FindExisting(_))
+        ),
+        "both blobs must exist"
+    );
+
+    let tree = repo.rev_parse_single("@^{/c3}^{tree}")?.detach();
+    match repo.diff_blobs(old, tree) {
+        Err(gix::object::blob::diff::init::Error::ObjectKind(err)) => {
+            assert_eq!(err.id, tree);
+            assert_eq!(err.actual, gix_object::Kind::Tree);
+            assert_eq!(err.expected, gix_object::Kind::Blob);
+        }
+        res => unreachable!("only blobs can be diffed, got {:?}", res.map(|_| ())),
+    }
+    Ok(())
+}
@@ -1,10 +1,12 @@
 use gix::Repository;
 
 mod config;
+mod diff;
 mod object;
 mod open;
 mod reference;
 mod remote;
+mod revision;
 mod state;
 mod worktree;
 
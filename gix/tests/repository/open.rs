@@ -191,6 +191,7 @@ mod with_overrides {
             .set("EMAIL", "user email")
             .set("GITOXIDE_PACK_CACHE_MEMORY", "0")
             .set("GITOXIDE_OBJECT_CACHE_MEMORY", "5m")
+            .set("GITOXIDE_PACK_CACHE_ENTRIES", "32")
             .set("GIT_SSL_CAINFO", "./env.pem")
             .set("GIT_SSL_VERSION", "tlsv1.3")
             .set("GIT_SSH_VARIANT", "ssh-variant-env")
@@ -365,6 +366,7 @@ mod with_overrides {
             ("gitoxide.user.emailFallback", "user email"),
             ("core.deltaBaseCacheLimit", "0"),
             ("gitoxide.objects.cacheLimit", "5m"),
+            ("gitoxide.objects.deltaBaseCacheEntries", "32"),
         ] {
             assert_eq!(
                 config
@@ -0,0 +1,73 @@
+use gix::revision::spec::parse::peel;
+
+#[test]
+fn rev_parse_typed_peels_tags_and_commits() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    let head = repo.head_commit()?;
+    let tag_ref = repo.tag(
+        "v1.0.0",
+        head.id,
+        gix_object::Kind::Commit,
+        Some(repo.committer().expect("present")?),
+        "a message",
+        gix_ref::transaction::PreviousValue::MustNotExist,
+    )?;
+    assert_ne!(tag_ref.id(), head.id, "the tag points to a tag object");
+
+    assert_eq!(
+        repo.rev_parse_commit("v1.0.0")?.id,
+        head.id,
+        "the tag object is followed to the commit"
+    );
+    assert_eq!(
+        repo.rev_parse_tree("v1.0.0")?.id,
+        head.tree_id()?,
+        "…and the commit to its tree"
+    );
+    assert_eq!(
+        repo.rev_parse_tree("HEAD^{tree}")?.id,
+        head.tree_id()?,
+        "trees stay trees"
+    );
+
+    let blob = repo.rev_parse_blob("v1.0.0:this")?;
+    assert_eq!(blob.kind, gix_object::Kind::Blob);
+    assert_eq!(
+        blob.data, b"hello\n",
+        "tags can be used to access blobs in their commit as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn rev_parse_typed_fails_if_the_object_cannot_be_peeled() -> crate::Result {
+    let repo = crate::named_repo("make_basic_repo.sh")?;
+    assert!(
+        matches!(
+            repo.rev_parse_blob("HEAD"),
+            Err(peel::Error::PeelToKind(gix::object::peel::to_kind::Error::NotFound {
+                actual: gix_object::Kind::Tree,
+                expected: gix_object::Kind::Blob,
+                ..
+            }))
+        ),
+        "commits peel to trees, which can't be peeled further"
+    );
+    let tree_id = repo.head_commit()?.tree_id()?.to_string();
+    assert!(
+        matches!(
+            repo.rev_parse_commit(tree_id.as_str()),
+            Err(peel::Error::PeelToKind(gix::object::peel::to_kind::Error::NotFound {
+                actual: gix_object::Kind::Tree,
+                expected: gix_object::Kind::Commit,
+                ..
+            }))
+        ),
+        "trees can't be peeled to commits"
+    );
+    assert!(
+        matches!(repo.rev_parse_tree("does-not-exist"), Err(peel::Error::Single(_))),
+        "specs that don't parse are reported as well"
+    );
+    Ok(())
+}
@@ -1 +1,2 @@
+mod path_history;
 mod spec;
@@ -0,0 +1,106 @@
+use gix::bstr::{BString, ByteSlice};
+
+use crate::util::named_repo;
+
+/// Return the summary of each commit that changed `path` along with the location of the file in it and its source location.
+fn history(repo: &gix::Repository, path: &str) -> crate::Result<Vec<(BString, BString, Option<BString>)>> {
+    let mut out = Vec::new();
+    for item in repo.path_history(path)? {
+        let item = item?;
+        let summary = item
+            .id
+            .object()?
+            .into_commit()
+            .message()?
+            .summary()
+            .as_bstr()
+            .to_owned();
+        out.push((summary, item.location, item.source_location));
+    }
+    Ok(out)
+}
+
+#[test]
+fn following_a_file_across_a_rename() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    assert_eq!(
+        history(&repo, "dir/a-moved")?,
+        vec![
+            ("r1-identity".into(), "dir/a-moved".into(), Some("a".into())),
+            ("c3".into(), "a".into(), None),
+            ("c2".into(), "a".into(), None),
+            ("c1".into(), "a".into(), None),
+        ],
+        "the commit that renamed it is followed by the history of its source"
+    );
+    Ok(())
+}
+
+#[test]
+fn following_a_file_to_the_root_commit_that_added_it() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    let items = repo.path_history("dir/a-moved")?.collect::<Result<Vec<_>, _>>()?;
+    let root = items.last().expect("non-empty history");
+    assert_eq!(
+        root.id.object()?.into_commit().parent_ids().count(),
+        0,
+        "the file was added in the root commit"
+    );
+    assert_eq!(root.location, "a");
+    assert!(root.added, "the root commit is reported to add the file");
+    assert!(
+        items[..items.len() - 1].iter().all(|item| !item.added),
+        "all other commits, including the one renaming the file, changed it"
+    );
+    Ok(())
+}
+
+#[test]
+fn renames_are_not_followed_if_disabled_by_configuration() -> crate::Result {
+    let repo = crate::util::repo_opts(
+        "make_diff_repo.sh",
+        crate::util::restricted().config_overrides(["diff.renames=false"]),
+    )?
+    .to_thread_local();
+    assert_eq!(
+        history(&repo, "dir/a-moved")?,
+        vec![("r1-identity".into(), "dir/a-moved".into(), None)],
+        "the file appears to be added by the commit that renamed it"
+    );
+    assert!(
+        repo.path_history("dir/a-moved")?.next().expect("one item")?.added,
+        "and it's reported as such"
+    );
+    Ok(())
+}
+
+#[test]
+fn following_a_file_across_a_directory_rename() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    assert_eq!(
+        history(&repo, "lib/one")?,
+        vec![
+            ("r2-directory".into(), "lib/one".into(), Some("src/one".into())),
+            ("r2-prep".into(), "src/one".into(), None),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn only_commits_changing_the_file_are_returned() -> crate::Result {
+    let repo = named_repo("make_diff_repo.sh")?;
+    assert_eq!(
+        history(&repo, "dir/c")?,
+        vec![
+            ("r3-subtree".into(), "dir/c".into(), None),
+            ("c2".into(), "dir/c".into(), None),
+            ("c1".into(), "dir/c".into(), None),
+        ]
+    );
+    assert!(
+        history(&repo, "does-not-exist")?.is_empty(),
+        "files that don't exist in HEAD have no history"
+    );
+    Ok(())
+}
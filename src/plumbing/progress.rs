@@ -116,10 +116,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.packedGitLimit",
         usage: NotApplicable { reason: "we target 32bit systems only and don't use a windowing mechanism" }
     },
-    Record {
-        config: "core.bigFileThreshold",
-        usage: Planned { note: Some("unfortunately we can't stream packed files yet, even if not delta-compressed, but respecting the threshold for other operations is definitely a must") }
-    },
     Record {
         config: "core.compression",
         usage: Planned { note: Some("Allow to remove similar hardcoded value - passing it through will be some effort") },